@@ -8,6 +8,8 @@ use tokio::task;
 use tracing_subscriber::prelude::*;
 
 use anyhow::Error;
+use futures::channel::mpsc;
+use futures::prelude::*;
 use std::time::Duration;
 use tokio::fs;
 use tokio::net::TcpListener;
@@ -26,6 +28,10 @@ struct Args {
     /// Port to listen on
     #[clap(short, long, default_value_t = 8443)]
     port: u16,
+    /// Port to also listen on for HTTP long-poll clients, for networks
+    /// where WebSockets are blocked
+    #[clap(long)]
+    http_port: Option<u16>,
     /// TLS certificate to use
     #[clap(short, long)]
     cert: Option<String>,
@@ -53,6 +59,54 @@ fn initialize_logging(envvar_name: &str) -> Result<(), Error> {
     Ok(())
 }
 
+// Serves the HTTP transport on the connections accepted by @listener, the TLS
+// handshakes being done concurrently
+async fn accept_http(
+    listener: TcpListener,
+    acceptor: Option<tokio_native_tls::TlsAcceptor>,
+    server: Server,
+) {
+    match acceptor {
+        Some(acceptor) => {
+            let (tx, rx) = mpsc::unbounded();
+            task::spawn(server.serve_http(rx.map(Ok::<_, std::io::Error>)));
+
+            while let Ok((stream, address)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                let tx = tx.clone();
+                task::spawn(async move {
+                    match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await
+                    {
+                        Ok(Ok(stream)) => {
+                            let _ = tx.unbounded_send(stream);
+                        }
+                        Ok(Err(err)) => {
+                            warn!("Failed to accept TLS connection from {}: {}", address, err)
+                        }
+                        Err(elapsed) => {
+                            warn!("TLS connection timed out {} after {}", address, elapsed)
+                        }
+                    }
+                });
+            }
+        }
+        None => {
+            let incoming = stream::unfold(listener, |listener| async move {
+                loop {
+                    match listener.accept().await {
+                        Ok((stream, _)) => {
+                            return Some((Ok::<_, std::io::Error>(stream), listener))
+                        }
+                        Err(err) => warn!("Failed to accept HTTP connection: {}", err),
+                    }
+                }
+            });
+
+            server.serve_http(incoming).await;
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Error> {
     let args = Args::parse();
@@ -82,6 +136,14 @@ async fn main() -> Result<(), Error> {
         None => None,
     };
 
+    if let Some(http_port) = args.http_port {
+        let http_addr = format!("{}:{}", args.host, http_port);
+        let http_listener = TcpListener::bind(&http_addr).await?;
+        info!("Listening for HTTP clients on: {}", http_addr);
+
+        task::spawn(accept_http(http_listener, acceptor.clone(), server.clone()));
+    }
+
     info!("Listening on: {}", addr);
 
     while let Ok((stream, address)) = listener.accept().await {
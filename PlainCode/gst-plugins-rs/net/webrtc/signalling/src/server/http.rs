@@ -0,0 +1,406 @@
+// SPDX-License-Identifier: MPL-2.0
+
+// Transport of the signalling protocol over plain HTTP requests, for
+// networks where WebSockets are blocked:
+//
+// * `POST /peers` creates a peer, whose ID is returned in the `peerId`
+//   field of the JSON response
+// * `POST /peers/{id}/messages` sends a message to the server
+// * `GET /peers/{id}/messages` long-polls the messages to the peer, as a
+//   JSON array, which is empty if none arrived within 30 seconds
+// * `DELETE /peers/{id}` removes the peer
+//
+// Peers not polling their messages for a minute are removed.
+
+use super::{Connection, Peer, Server};
+use futures::channel::mpsc;
+use futures::prelude::*;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::task;
+use tracing::{info, instrument, trace, warn};
+use warp::http::StatusCode;
+use warp::hyper::body::Bytes;
+use warp::reply::Response;
+use warp::{Filter, Reply};
+
+// How long a long-poll request waits for messages
+const POLL_TIMEOUT: Duration = Duration::from_secs(30);
+// Peers not fetching their messages for that long are removed
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+const MAX_MESSAGE_SIZE: u64 = 64 * 1024;
+
+struct QueueInner {
+    // None while a request is reading the messages
+    receiver: Option<mpsc::Receiver<String>>,
+    last_activity: Instant,
+}
+
+/// The messages to an HTTP peer, waiting to be fetched
+#[derive(Clone)]
+pub(super) struct Queue(Arc<Mutex<QueueInner>>);
+
+impl Queue {
+    fn new(receiver: mpsc::Receiver<String>) -> Self {
+        Self(Arc::new(Mutex::new(QueueInner {
+            receiver: Some(receiver),
+            last_activity: Instant::now(),
+        })))
+    }
+
+    // Exclusive access to the messages, None if a request already reads them
+    fn reader(&self) -> Option<Reader> {
+        let receiver = self.0.lock().unwrap().receiver.take()?;
+
+        Some(Reader {
+            queue: self.clone(),
+            receiver: Some(receiver),
+        })
+    }
+
+    fn touch(&self) {
+        self.0.lock().unwrap().last_activity = Instant::now();
+    }
+
+    fn is_idle(&self) -> bool {
+        let inner = self.0.lock().unwrap();
+
+        inner.receiver.is_some() && inner.last_activity.elapsed() > IDLE_TIMEOUT
+    }
+}
+
+// Gives the messages back to the queue when dropped
+struct Reader {
+    queue: Queue,
+    receiver: Option<mpsc::Receiver<String>>,
+}
+
+impl Drop for Reader {
+    fn drop(&mut self) {
+        let mut inner = self.queue.0.lock().unwrap();
+        inner.receiver = self.receiver.take();
+        inner.last_activity = Instant::now();
+    }
+}
+
+impl Stream for Reader {
+    type Item = String;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<String>> {
+        match self.receiver {
+            Some(ref mut receiver) => receiver.poll_next_unpin(cx),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+impl Server {
+    /// Serves the signalling protocol over HTTP on the @incoming connections,
+    /// e.g. the ones of a second listener next to the one of the WebSocket
+    /// connections given to [`Server::accept_async`]
+    #[instrument(level = "debug", skip(self, incoming))]
+    pub async fn serve_http<I>(self, incoming: I)
+    where
+        I: TryStream + Send,
+        I::Ok: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+        I::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let idle_task_handle = task::spawn(self.clone().remove_idle_peers());
+
+        warp::serve(self.routes()).run_incoming(incoming).await;
+
+        idle_task_handle.abort();
+    }
+
+    fn routes(
+        &self,
+    ) -> impl Filter<Extract = (Response,), Error = warp::Rejection> + Clone + Send + Sync + 'static
+    {
+        let server = self.clone();
+        let with_server = warp::any().map(move || server.clone());
+
+        let create = warp::path!("peers")
+            .and(warp::post())
+            .and(with_server.clone())
+            .then(|server: Server| async move { server.add_http_peer().await });
+
+        let send = warp::path!("peers" / String / "messages")
+            .and(warp::post())
+            .and(warp::body::content_length_limit(MAX_MESSAGE_SIZE))
+            .and(warp::body::bytes())
+            .and(with_server.clone())
+            .then(|peer_id: String, body: Bytes, server: Server| async move {
+                server.receive_message(peer_id, body).await
+            });
+
+        let poll = warp::path!("peers" / String / "messages")
+            .and(warp::get())
+            .and(with_server.clone())
+            .then(|peer_id: String, server: Server| async move { server.poll(peer_id).await });
+
+        let delete = warp::path!("peers" / String)
+            .and(warp::delete())
+            .and(with_server)
+            .then(|peer_id: String, server: Server| async move {
+                server.delete_http_peer(peer_id).await
+            });
+
+        create.or(send).unify().or(poll).unify().or(delete).unify()
+    }
+
+    fn http_queue(&self, peer_id: &str) -> Option<Queue> {
+        match self.state.lock().unwrap().peers.get(peer_id)?.connection {
+            Connection::Http(ref queue) => Some(queue.clone()),
+            Connection::WebSocket { .. } => None,
+        }
+    }
+
+    async fn add_http_peer(&self) -> Response {
+        let peer_id = uuid::Uuid::new_v4().to_string();
+        info!(peer_id = %peer_id, "New HTTP peer");
+
+        // 1000 is completely arbitrary, as for the WebSocket peers
+        let (sender, receiver) = mpsc::channel::<String>(1000);
+        let tx = {
+            let mut state = self.state.lock().unwrap();
+            state.peers.insert(
+                peer_id.clone(),
+                Peer {
+                    connection: Connection::Http(Queue::new(receiver)),
+                    sender,
+                },
+            );
+            state.tx.clone()
+        };
+
+        if let Some(mut tx) = tx {
+            let msg = serde_json::json!({
+                "type": "newPeer",
+            })
+            .to_string();
+
+            if let Err(err) = tx.send((peer_id.clone(), Some(msg))).await {
+                warn!(peer_id = %peer_id, "Error handling message: {:?}", err);
+            }
+        }
+
+        let reply = warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({ "peerId": peer_id })),
+            StatusCode::CREATED,
+        );
+
+        warp::reply::with_header(reply, "location", format!("/peers/{peer_id}")).into_response()
+    }
+
+    async fn receive_message(&self, peer_id: String, body: Bytes) -> Response {
+        let Some(queue) = self.http_queue(&peer_id) else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        queue.touch();
+
+        let Ok(msg) = String::from_utf8(body.to_vec()) else {
+            return StatusCode::BAD_REQUEST.into_response();
+        };
+        trace!(peer_id = %peer_id, "Received message {}", msg);
+
+        let tx = self.state.lock().unwrap().tx.clone();
+        if let Some(mut tx) = tx {
+            if let Err(err) = tx.send((peer_id.clone(), Some(msg))).await {
+                warn!(peer_id = %peer_id, "Error handling message: {:?}", err);
+                return StatusCode::SERVICE_UNAVAILABLE.into_response();
+            }
+        }
+
+        StatusCode::NO_CONTENT.into_response()
+    }
+
+    async fn poll(&self, peer_id: String) -> Response {
+        let Some(queue) = self.http_queue(&peer_id) else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        let Some(mut reader) = queue.reader() else {
+            return StatusCode::CONFLICT.into_response();
+        };
+
+        let mut messages = vec![];
+        match tokio::time::timeout(POLL_TIMEOUT, reader.next()).await {
+            Ok(Some(msg)) => messages.push(msg),
+            // Removed in the meantime
+            Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+            Err(_) => (),
+        }
+
+        // Along with the ones queued behind it
+        while let Some(Some(msg)) = reader.next().now_or_never() {
+            messages.push(msg);
+        }
+
+        trace!(peer_id = %peer_id, "Sending {} messages", messages.len());
+
+        warp::reply::with_header(
+            format!("[{}]", messages.join(",")),
+            "content-type",
+            "application/json",
+        )
+        .into_response()
+    }
+
+    async fn delete_http_peer(&self, peer_id: String) -> Response {
+        if self.http_queue(&peer_id).is_none() {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+
+        info!(peer_id = %peer_id, "HTTP peer left");
+        self.remove_http_peer(&peer_id).await;
+
+        StatusCode::NO_CONTENT.into_response()
+    }
+
+    async fn remove_http_peer(&self, peer_id: &str) {
+        let tx = self.state.lock().unwrap().tx.clone();
+        if let Some(mut tx) = tx {
+            let _ = tx.send((peer_id.to_string(), None)).await;
+        }
+
+        Self::remove_peer(self.state.clone(), peer_id);
+    }
+
+    async fn remove_idle_peers(self) {
+        let mut interval = tokio::time::interval(IDLE_TIMEOUT / 4);
+
+        loop {
+            interval.tick().await;
+
+            let idle_peers = self
+                .state
+                .lock()
+                .unwrap()
+                .peers
+                .iter()
+                .filter_map(|(peer_id, peer)| match peer.connection {
+                    Connection::Http(ref queue) if queue.is_idle() => Some(peer_id.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            for peer_id in idle_peers {
+                info!(peer_id = %peer_id, "Removing idle HTTP peer");
+                self.remove_http_peer(&peer_id).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::Handler;
+    use gst_plugin_webrtc_protocol as p;
+
+    async fn add_peer(
+        routes: &(impl Filter<Extract = (Response,), Error = warp::Rejection> + 'static),
+    ) -> String {
+        let res = warp::test::request()
+            .method("POST")
+            .path("/peers")
+            .reply(routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let body = serde_json::from_slice::<serde_json::Value>(res.body()).unwrap();
+        let peer_id = body["peerId"].as_str().unwrap().to_string();
+        assert_eq!(
+            res.headers()["location"],
+            format!("/peers/{peer_id}").as_str()
+        );
+
+        peer_id
+    }
+
+    async fn send(
+        routes: &(impl Filter<Extract = (Response,), Error = warp::Rejection> + 'static),
+        peer_id: &str,
+        msg: p::IncomingMessage,
+    ) -> StatusCode {
+        warp::test::request()
+            .method("POST")
+            .path(&format!("/peers/{peer_id}/messages"))
+            .body(serde_json::to_string(&msg).unwrap())
+            .reply(routes)
+            .await
+            .status()
+    }
+
+    async fn poll(
+        routes: &(impl Filter<Extract = (Response,), Error = warp::Rejection> + 'static),
+        peer_id: &str,
+    ) -> Vec<p::OutgoingMessage> {
+        let res = warp::test::request()
+            .path(&format!("/peers/{peer_id}/messages"))
+            .reply(routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        serde_json::from_slice(res.body()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_http_peers() {
+        let server = Server::spawn(Handler::new);
+        let routes = server.routes();
+
+        let producer = add_peer(&routes).await;
+        assert_eq!(
+            poll(&routes, &producer).await,
+            vec![p::OutgoingMessage::Welcome {
+                peer_id: producer.clone()
+            }]
+        );
+        assert_eq!(
+            send(
+                &routes,
+                &producer,
+                p::IncomingMessage::SetPeerStatus(p::PeerStatus {
+                    roles: vec![p::PeerRole::Producer],
+                    meta: None,
+                    peer_id: None,
+                })
+            )
+            .await,
+            StatusCode::NO_CONTENT
+        );
+
+        let consumer = add_peer(&routes).await;
+        assert_eq!(
+            poll(&routes, &consumer).await,
+            vec![p::OutgoingMessage::Welcome {
+                peer_id: consumer.clone()
+            }]
+        );
+        send(&routes, &consumer, p::IncomingMessage::List).await;
+        assert_eq!(
+            poll(&routes, &consumer).await,
+            vec![p::OutgoingMessage::List {
+                producers: vec![p::Peer {
+                    id: producer.clone(),
+                    meta: None,
+                }]
+            }]
+        );
+
+        let res = warp::test::request()
+            .method("DELETE")
+            .path(&format!("/peers/{producer}"))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+        assert_eq!(
+            send(&routes, &producer, p::IncomingMessage::List).await,
+            StatusCode::NOT_FOUND
+        );
+    }
+}
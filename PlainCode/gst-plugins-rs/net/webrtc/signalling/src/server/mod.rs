@@ -12,9 +12,18 @@ use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::task;
 use tracing::{debug, error, info, instrument, trace, warn};
 
+mod http;
+
+enum Connection {
+    WebSocket {
+        receive_task_handle: task::JoinHandle<()>,
+        send_task_handle: task::JoinHandle<Result<(), Error>>,
+    },
+    Http(http::Queue),
+}
+
 struct Peer {
-    receive_task_handle: task::JoinHandle<()>,
-    send_task_handle: task::JoinHandle<Result<(), Error>>,
+    connection: Connection,
     sender: mpsc::Sender<String>,
 }
 
@@ -101,16 +110,23 @@ impl Server {
     fn remove_peer(state: Arc<Mutex<State>>, peer_id: &str) {
         if let Some(mut peer) = state.lock().unwrap().peers.remove(peer_id) {
             let peer_id = peer_id.to_string();
-            task::spawn(async move {
-                peer.sender.close_channel();
-                if let Err(err) = peer.send_task_handle.await {
-                    trace!(peer_id = %peer_id, "Error while joining send task: {}", err);
-                }
+            peer.sender.close_channel();
 
-                if let Err(err) = peer.receive_task_handle.await {
-                    trace!(peer_id = %peer_id, "Error while joining receive task: {}", err);
-                }
-            });
+            if let Connection::WebSocket {
+                receive_task_handle,
+                send_task_handle,
+            } = peer.connection
+            {
+                task::spawn(async move {
+                    if let Err(err) = send_task_handle.await {
+                        trace!(peer_id = %peer_id, "Error while joining send task: {}", err);
+                    }
+
+                    if let Err(err) = receive_task_handle.await {
+                        trace!(peer_id = %peer_id, "Error while joining receive task: {}", err);
+                    }
+                });
+            }
         }
     }
 
@@ -226,8 +242,10 @@ impl Server {
         self.state.lock().unwrap().peers.insert(
             this_id.clone(),
             Peer {
-                receive_task_handle,
-                send_task_handle,
+                connection: Connection::WebSocket {
+                    receive_task_handle,
+                    send_task_handle,
+                },
                 sender: websocket_sender,
             },
         );
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use crate::signaller::Signallable;
+use crate::signaller::{Signallable, WebRTCSignallerRole};
 use gst::glib;
 
 mod imp;
@@ -10,6 +10,20 @@ glib::wrapper! {
     pub struct AwsKvsSignaller(ObjectSubclass<imp::Signaller>) @implements Signallable;
 }
 
+impl AwsKvsSignaller {
+    fn new(role: WebRTCSignallerRole) -> Self {
+        glib::Object::builder().property("role", role).build()
+    }
+
+    pub fn new_consumer() -> Self {
+        Self::new(WebRTCSignallerRole::Consumer)
+    }
+
+    pub fn new_producer() -> Self {
+        Self::new(WebRTCSignallerRole::Producer)
+    }
+}
+
 impl Default for AwsKvsSignaller {
     fn default() -> Self {
         glib::Object::new()
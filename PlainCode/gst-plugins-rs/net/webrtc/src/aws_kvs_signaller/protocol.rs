@@ -24,8 +24,21 @@ pub struct IceCandidate {
 #[serde(rename_all = "camelCase")]
 pub struct IncomingMessage {
     pub message_type: String,
+    #[serde(default)]
     pub message_payload: String,
+    // Not set for the messages of the master to viewers
+    #[serde(default)]
     pub sender_client_id: String,
+    pub status_response: Option<StatusResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusResponse {
+    pub correlation_id: Option<String>,
+    pub error_type: Option<String>,
+    pub status_code: Option<String>,
+    pub description: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -49,5 +62,7 @@ pub struct OutgoingIceCandidate {
 pub struct OutgoingMessage {
     pub action: String,
     pub message_payload: String,
-    pub recipient_client_id: String,
+    // Viewers can only send messages to the master
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient_client_id: Option<String>,
 }
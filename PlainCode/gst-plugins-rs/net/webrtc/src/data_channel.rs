@@ -0,0 +1,156 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use gst::glib;
+use gst::prelude::*;
+use gst_webrtc::WebRTCDataChannel;
+use once_cell::sync::Lazy;
+
+static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
+    gst::DebugCategory::new(
+        "webrtc-data-channel",
+        gst::DebugColorFlags::empty(),
+        Some("WebRTC application data channels"),
+    )
+});
+
+/// Name of the custom meta holding the `label`, `session-id` and `is-string`
+/// fields of the message carried by buffers output on `data` pads
+pub const DATA_CHANNEL_META_NAME: &str = "GstWebRTCDataChannelMeta";
+
+pub static DATA_CAPS: Lazy<gst::Caps> =
+    Lazy::new(|| gst::Caps::new_empty_simple("application/x-webrtc-data"));
+
+/// Label of the channel used for navigation events, it is handled separately
+pub const NAVIGATION_CHANNEL_LABEL: &str = "input";
+
+static META_REGISTRATION: Lazy<()> = Lazy::new(|| {
+    gst::meta::CustomMeta::register(DATA_CHANNEL_META_NAME, &[]);
+});
+
+#[derive(Debug, Clone)]
+pub enum DataChannelMessage {
+    String(String),
+    Data(glib::Bytes),
+}
+
+impl DataChannelMessage {
+    /// Wraps the message in a buffer for a `data` pad
+    pub fn to_buffer(&self, session_id: Option<&str>, label: &str) -> gst::Buffer {
+        Lazy::force(&META_REGISTRATION);
+
+        let mut buffer = match self {
+            DataChannelMessage::String(s) => gst::Buffer::from_slice(s.clone().into_bytes()),
+            DataChannelMessage::Data(data) => gst::Buffer::from_slice(data.clone()),
+        };
+
+        {
+            let buffer = buffer.get_mut().unwrap();
+            match gst::meta::CustomMeta::add(buffer, DATA_CHANNEL_META_NAME) {
+                Ok(mut meta) => {
+                    let s = meta.mut_structure();
+                    s.set("label", label);
+                    s.set("session-id", session_id);
+                    s.set("is-string", matches!(self, DataChannelMessage::String(_)));
+                }
+                Err(err) => gst::warning!(CAT, "Failed to add data channel meta: {err}"),
+            }
+        }
+
+        buffer
+    }
+
+    /// Sends the message on `channel`, returns false if it is not open
+    pub fn send(&self, channel: &WebRTCDataChannel) -> bool {
+        let ready_state = channel.property::<gst_webrtc::WebRTCDataChannelState>("ready-state");
+        if ready_state != gst_webrtc::WebRTCDataChannelState::Open {
+            gst::debug!(
+                CAT,
+                "Not sending on {} in state {ready_state:?}",
+                label(channel)
+            );
+            return false;
+        }
+
+        match self {
+            DataChannelMessage::String(s) => channel.send_string(Some(s.as_str())),
+            DataChannelMessage::Data(data) => channel.send_data(Some(data)),
+        }
+
+        true
+    }
+}
+
+pub fn label(channel: &WebRTCDataChannel) -> String {
+    channel
+        .property::<Option<String>>("label")
+        .unwrap_or_default()
+}
+
+/// Creates the channels declared in a `data-channels` property, the name of
+/// each structure being the label of the channel and its fields the options
+/// passed to `webrtcbin::create-data-channel` (`ordered`, `max-retransmits`,
+/// `max-packet-lifetime`, `protocol`, `negotiated`, `id`, `priority`)
+pub fn create_data_channels(
+    webrtcbin: &gst::Element,
+    declarations: &[gst::Structure],
+) -> Vec<WebRTCDataChannel> {
+    declarations
+        .iter()
+        .filter_map(|declaration| {
+            let label = declaration.name().to_string();
+            let mut config = declaration.clone();
+            config.set_name("config");
+
+            let channel = webrtcbin.emit_by_name::<Option<WebRTCDataChannel>>(
+                "create-data-channel",
+                &[&label, &config],
+            );
+
+            match channel {
+                Some(channel) => {
+                    gst::debug!(CAT, "Created data channel {label} with {config:?}");
+                    Some(channel)
+                }
+                None => {
+                    gst::warning!(CAT, "Failed to create data channel {label}");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Calls `f` with the label of `channel` and each message received on it
+pub fn connect_messages<F>(channel: &WebRTCDataChannel, f: F)
+where
+    F: Fn(&str, DataChannelMessage) + Send + Sync + Clone + 'static,
+{
+    let on_string = f.clone();
+    channel.connect("on-message-string", false, move |values| {
+        let channel = values[0].get::<WebRTCDataChannel>().unwrap();
+        if let Ok(Some(msg)) = values[1].get::<Option<String>>() {
+            on_string(&label(&channel), DataChannelMessage::String(msg));
+        }
+
+        None
+    });
+
+    channel.connect("on-message-data", false, move |values| {
+        let channel = values[0].get::<WebRTCDataChannel>().unwrap();
+        if let Ok(Some(data)) = values[1].get::<Option<glib::Bytes>>() {
+            f(&label(&channel), DataChannelMessage::Data(data));
+        }
+
+        None
+    });
+}
+
+/// Builds the source of a `data` pad
+pub fn make_data_src() -> gst_app::AppSrc {
+    gst_app::AppSrc::builder()
+        .caps(&DATA_CAPS)
+        .format(gst::Format::Time)
+        .is_live(true)
+        .do_timestamp(true)
+        .build()
+}
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use crate::signaller::{Signallable, SignallableImpl};
+use crate::signaller::{Signallable, SignallableImpl, WebRTCSignallerRole};
 use crate::RUNTIME;
 
 use anyhow::{anyhow, Error};
@@ -30,6 +30,39 @@ static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
     )
 });
 
+const DEFAULT_KEEPALIVE_INTERVAL: u32 = 10;
+// Error code of the Video Room plugin when creating a room that exists
+const ROOM_EXISTS_ERROR: i32 = 427;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, glib::Enum, Default)]
+#[repr(u32)]
+#[enum_type(name = "GstJanusVRPlugin")]
+pub enum JanusVRPlugin {
+    #[default]
+    #[enum_value(
+        name = "Video Room: publish to or subscribe to a room",
+        nick = "videoroom"
+    )]
+    VideoRoom,
+    #[enum_value(name = "Streaming: watch a mountpoint", nick = "streaming")]
+    Streaming,
+    #[enum_value(
+        name = "Audio Bridge: send audio to a conference and receive the mix",
+        nick = "audiobridge"
+    )]
+    AudioBridge,
+}
+
+impl JanusVRPlugin {
+    fn package(&self) -> &'static str {
+        match self {
+            JanusVRPlugin::VideoRoom => "janus.plugin.videoroom",
+            JanusVRPlugin::Streaming => "janus.plugin.streaming",
+            JanusVRPlugin::AudioBridge => "janus.plugin.audiobridge",
+        }
+    }
+}
+
 fn transaction_id() -> String {
     thread_rng()
         .sample_iter(&rand::distributions::Alphanumeric)
@@ -75,6 +108,14 @@ struct CreateSessionMsg {
     apisecret: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct DestroySessionMsg {
+    janus: String,
+    transaction: String,
+    session_id: u64,
+    apisecret: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 struct AttachPluginMsg {
     janus: String,
@@ -92,6 +133,8 @@ struct RoomRequestBody {
     id: JanusId,
     #[serde(skip_serializing_if = "Option::is_none")]
     display: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pin: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -104,6 +147,32 @@ struct RoomRequestMsg {
     body: RoomRequestBody,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct CreateRoomBody {
+    request: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    room: Option<JanusId>,
+    permanent: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bitrate: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    videocodec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    audiocodec: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct CreateRoomMsg {
+    janus: String,
+    transaction: String,
+    session_id: u64,
+    handle_id: u64,
+    apisecret: Option<String>,
+    body: CreateRoomBody,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 struct PublishBody {
     request: String,
@@ -127,6 +196,128 @@ struct PublishMsg {
     jsep: Jsep,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+struct SubscribeStream {
+    feed: JanusId,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct SubscriberJoinBody {
+    request: String,
+    ptype: String,
+    room: JanusId,
+    streams: Vec<SubscribeStream>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct SubscriberJoinMsg {
+    janus: String,
+    transaction: String,
+    session_id: u64,
+    handle_id: u64,
+    apisecret: Option<String>,
+    body: SubscriberJoinBody,
+}
+
+/// Body of the `subscribe` requests of subscribers
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct SubscriptionBody {
+    request: String,
+    streams: Vec<SubscribeStream>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct SubscriptionMsg {
+    janus: String,
+    transaction: String,
+    session_id: u64,
+    handle_id: u64,
+    apisecret: Option<String>,
+    body: SubscriptionBody,
+}
+
+/// Body of the requests without parameters, e.g. `start` and `leave`
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct RequestBody {
+    request: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct RequestMsg {
+    janus: String,
+    transaction: String,
+    session_id: u64,
+    handle_id: u64,
+    apisecret: Option<String>,
+    body: RequestBody,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    jsep: Option<Jsep>,
+}
+
+/// Body of the `watch` request of the Streaming plugin
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct WatchBody {
+    request: String,
+    id: JanusId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pin: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct WatchMsg {
+    janus: String,
+    transaction: String,
+    session_id: u64,
+    handle_id: u64,
+    apisecret: Option<String>,
+    body: WatchBody,
+}
+
+/// Body of the `join` request of the AudioBridge plugin
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct AudioBridgeJoinBody {
+    request: String,
+    room: JanusId,
+    id: JanusId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pin: Option<String>,
+    muted: bool,
+    // Janus sends the offer, so that the mix can be received without
+    // sending any audio
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    generate_offer: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct AudioBridgeJoinMsg {
+    janus: String,
+    transaction: String,
+    session_id: u64,
+    handle_id: u64,
+    apisecret: Option<String>,
+    body: AudioBridgeJoinBody,
+}
+
+/// Body of the `configure` request of the AudioBridge plugin
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct AudioBridgeConfigureBody {
+    request: String,
+    muted: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct AudioBridgeConfigureMsg {
+    janus: String,
+    transaction: String,
+    session_id: u64,
+    handle_id: u64,
+    apisecret: Option<String>,
+    body: AudioBridgeConfigureBody,
+    jsep: Jsep,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 struct Candidate {
     candidate: String,
@@ -149,10 +340,18 @@ struct TrickleMsg {
 enum OutgoingMessage {
     KeepAlive(KeepAliveMsg),
     CreateSession(CreateSessionMsg),
+    DestroySession(DestroySessionMsg),
+    CreateRoom(CreateRoomMsg),
     AttachPlugin(AttachPluginMsg),
     RoomRequest(RoomRequestMsg),
     Publish(PublishMsg),
     Trickle(TrickleMsg),
+    SubscriberJoin(SubscriberJoinMsg),
+    Subscription(SubscriptionMsg),
+    Request(RequestMsg),
+    Watch(WatchMsg),
+    AudioBridgeJoin(AudioBridgeJoinMsg),
+    AudioBridgeConfigure(AudioBridgeConfigureMsg),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -168,9 +367,17 @@ struct InnerHangup {
     reason: String,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+struct Publisher {
+    id: JanusId,
+    display: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct RoomJoined {
     room: JanusId,
+    #[serde(default)]
+    publishers: Vec<Publisher>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -178,6 +385,25 @@ struct RoomEvent {
     room: Option<JanusId>,
     error_code: Option<i32>,
     error: Option<String>,
+    #[serde(default)]
+    publishers: Vec<Publisher>,
+    unpublished: Option<JanusId>,
+    leaving: Option<JanusId>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct RoomCreated {
+    room: JanusId,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct RoomAttached {
+    room: JanusId,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct RoomUpdated {
+    room: Option<JanusId>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -191,9 +417,60 @@ struct RoomDestroyed {
 enum VideoRoomData {
     #[serde(rename = "joined")]
     Joined(RoomJoined),
+    #[serde(rename = "created")]
+    Created(RoomCreated),
     #[serde(rename = "event")]
     Event(RoomEvent),
     Destroyed(RoomDestroyed),
+    /// The subscriber handle joined, the offer is in the JSEP of the event
+    #[serde(rename = "attached")]
+    Attached(RoomAttached),
+    /// The subscriptions changed, a new offer is in the JSEP of the event
+    #[serde(rename = "updated")]
+    Updated(RoomUpdated),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct StreamingResult {
+    status: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct StreamingEvent {
+    error_code: Option<i32>,
+    error: Option<String>,
+    result: Option<StreamingResult>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "streaming", rename_all = "lowercase")]
+enum StreamingData {
+    Event(StreamingEvent),
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct AudioBridgeJoined {
+    room: JanusId,
+    id: JanusId,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct AudioBridgeEvent {
+    error_code: Option<i32>,
+    error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "audiobridge", rename_all = "lowercase")]
+enum AudioBridgeData {
+    Joined(AudioBridgeJoined),
+    Event(AudioBridgeEvent),
+    Destroyed(RoomDestroyed),
+    // Participants joining, leaving or talking
+    #[serde(other)]
+    Other,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -201,6 +478,10 @@ enum VideoRoomData {
 enum PluginData {
     #[serde(rename = "janus.plugin.videoroom")]
     VideoRoom { data: VideoRoomData },
+    #[serde(rename = "janus.plugin.streaming")]
+    Streaming { data: StreamingData },
+    #[serde(rename = "janus.plugin.audiobridge")]
+    AudioBridge { data: AudioBridgeData },
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -213,12 +494,15 @@ struct SuccessMsg {
     transaction: Option<String>,
     session_id: Option<u64>,
     data: Option<DataHolder>,
+    // Replies to synchronous plugin requests, e.g. `create`
+    plugindata: Option<PluginData>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 struct EventMsg {
     transaction: Option<String>,
     session_id: Option<u64>,
+    sender: Option<u64>,
     plugindata: Option<PluginData>,
     jsep: Option<Jsep>,
 }
@@ -234,6 +518,8 @@ enum JsonReply {
     Media,
     Error(InnerError),
     HangUp(InnerHangup),
+    /// The session expired, e.g. after missing keepalives
+    Timeout,
 }
 
 #[derive(Default)]
@@ -246,6 +532,15 @@ struct State {
     transaction_id: Option<String>,
     room_id: Option<JanusId>,
     feed_id: Option<JanusId>,
+    // The handle receiving the streams of the publishers in consumer role,
+    // the other one being used to follow the publishers of the room
+    subscriber_handle_id: Option<u64>,
+    subscriber_transaction_id: Option<String>,
+    subscriber_joined: bool,
+    // The publishers subscribed to, or to subscribe to once the subscriber
+    // handle is attached
+    feeds: Vec<JanusId>,
+    session_started: bool,
 }
 
 #[derive(Clone)]
@@ -256,6 +551,17 @@ struct Settings {
     display_name: Option<String>,
     secret_key: Option<String>,
     string_ids: bool,
+    role: WebRTCSignallerRole,
+    producer_peer_id: Option<String>,
+    create_room: bool,
+    room_secret: Option<String>,
+    room_bitrate: u32,
+    room_video_codecs: Option<String>,
+    room_audio_codecs: Option<String>,
+    rejoin: bool,
+    keepalive_interval: u32,
+    plugin: JanusVRPlugin,
+    pin: Option<String>,
 }
 
 impl Default for Settings {
@@ -267,6 +573,17 @@ impl Default for Settings {
             display_name: None,
             secret_key: None,
             string_ids: false,
+            role: WebRTCSignallerRole::Producer,
+            producer_peer_id: None,
+            create_room: false,
+            room_secret: None,
+            room_bitrate: 0,
+            room_video_codecs: None,
+            room_audio_codecs: None,
+            rejoin: false,
+            keepalive_interval: DEFAULT_KEEPALIVE_INTERVAL,
+            plugin: JanusVRPlugin::VideoRoom,
+            pin: None,
         }
     }
 }
@@ -276,20 +593,51 @@ impl Default for Settings {
 pub struct Signaller {
     state: Mutex<State>,
     #[property(name="janus-endpoint", get, set, type = String, member = janus_endpoint, blurb = "The Janus server endpoint to POST SDP offer to")]
-    #[property(name="room-id", get, set, type = String, member = room_id, blurb = "The Janus Room ID that will be joined to")]
+    #[property(name="room-id", get, set, type = String, member = room_id, blurb = "The Janus Room ID that will be joined to, or the ID of the mountpoint to watch with the Streaming plugin")]
     #[property(name="feed-id", get, set, type = String, member = feed_id, blurb = "The Janus Feed ID to identify where the track is coming from")]
     #[property(name="display-name", get, set, type = String, member = display_name, blurb = "The name of the publisher in the Janus Video Room")]
     #[property(name="secret-key", get, set, type = String, member = secret_key, blurb = "The secret API key to communicate with Janus server")]
     #[property(name="string-ids", get, set, type = bool, member = string_ids, blurb = "Force passing room-id and feed-id as string even if they can be parsed into an integer")]
+    #[property(name="role", get, set, type = WebRTCSignallerRole, member = role, blurb = "Whether to publish to the Video Room (Producer) or to subscribe to its publishers (Consumer)", builder(WebRTCSignallerRole::Producer))]
+    #[property(name="producer-peer-id", get, set, type = String, member = producer_peer_id, blurb = "In Consumer role, the feed ID or display name of the only publisher to subscribe to, all of them if unset")]
+    #[property(name="create-room", get, set, type = bool, member = create_room, blurb = "Create the room if it does not exist, with a random ID if room-id is unset")]
+    #[property(name="room-secret", get, set, type = String, member = room_secret, blurb = "The secret needed to edit or destroy the created room")]
+    #[property(name="room-bitrate", get, set, type = u32, member = room_bitrate, blurb = "The maximum bitrate of the publishers of the created room, in bits per second (0 = Janus default)")]
+    #[property(name="room-video-codecs", get, set, type = String, member = room_video_codecs, blurb = "Comma separated list of the video codecs allowed in the created room, e.g. vp8,h264")]
+    #[property(name="room-audio-codecs", get, set, type = String, member = room_audio_codecs, blurb = "Comma separated list of the audio codecs allowed in the created room, e.g. opus")]
+    #[property(name="rejoin", get, set, type = bool, member = rejoin, blurb = "Join the room again in a new session when the room is destroyed, the session expires or the peer connection is hung up, instead of ending the session")]
+    #[property(name="keepalive-interval", get, set, type = u32, member = keepalive_interval, minimum = 1, blurb = "Interval in seconds between the keepalives of the Janus session")]
+    #[property(name="plugin", get, set, type = JanusVRPlugin, member = plugin, blurb = "The Janus plugin to attach to, Streaming can only be used in Consumer role", builder(JanusVRPlugin::VideoRoom))]
+    #[property(name="pin", get, set, type = String, member = pin, blurb = "The PIN needed to join the room or to watch the mountpoint")]
     settings: Mutex<Settings>,
 }
 
+// The ids of a request sent on a plugin handle
+struct HandleIds {
+    transaction: String,
+    session_id: u64,
+    handle_id: u64,
+    apisecret: Option<String>,
+}
+
 impl Signaller {
     fn raise_error(&self, msg: String) {
         self.obj()
             .emit_by_name::<()>("error", &[&format!("Error: {msg}")]);
     }
 
+    fn is_consumer(&self) -> bool {
+        self.settings.lock().unwrap().role == WebRTCSignallerRole::Consumer
+    }
+
+    // Whether the media is received on a second handle, subscribed to the
+    // publishers of the Video Room
+    fn uses_subscriber_handle(&self) -> bool {
+        let settings = self.settings.lock().unwrap();
+        settings.role == WebRTCSignallerRole::Consumer
+            && settings.plugin == JanusVRPlugin::VideoRoom
+    }
+
     async fn connect(&self) -> Result<(), Error> {
         let settings = self.settings.lock().unwrap().clone();
         use tungstenite::client::IntoClientRequest;
@@ -312,6 +660,13 @@ impl Signaller {
         // Channel for asynchronously sending out websocket message
         let (mut ws_sink, mut ws_stream) = ws.split();
 
+        // Keepalives are sent at a fixed pace whatever the other requests
+        let keepalive_interval = Duration::from_secs(settings.keepalive_interval.into());
+        let mut keepalive = tokio::time::interval_at(
+            tokio::time::Instant::now() + keepalive_interval,
+            keepalive_interval,
+        );
+
         // 1000 is completely arbitrary, we simply don't want infinite piling
         // up of messages as with unbounded
         let (ws_sender, mut ws_receiver) = mpsc::channel::<OutgoingMessage>(1000);
@@ -329,23 +684,10 @@ impl Signaller {
                             },
                             None => break,
                         },
-                        _ = tokio::time::sleep(Duration::from_secs(10)) => {
-                            if let Some(ref this) = this {
-                                let (transaction, session_id, apisecret) = {
-                                    let state = this.state.lock().unwrap();
-                                    let settings = this.settings.lock().unwrap();
-                                    (
-                                        state.transaction_id.clone().unwrap(),
-                                        state.session_id.unwrap(),
-                                        settings.secret_key.clone(),
-                                    )
-                                };
-                                let msg = OutgoingMessage::KeepAlive(KeepAliveMsg {
-                                    janus: "keepalive".to_string(),
-                                    transaction,
-                                    session_id,
-                                    apisecret,
-                                });
+                        _ = keepalive.tick() => {
+                            // No session to keep alive until it is created
+                            if let Some(msg) = this.as_ref().and_then(|this| this.keepalive()) {
+                                gst::log!(CAT, "Sending keepalive {:?}", msg);
                                 res = ws_sink
                                     .send(WsMessage::Text(serde_json::to_string(&msg).unwrap()))
                                     .await;
@@ -429,62 +771,247 @@ impl Signaller {
                 gst::trace!(CAT, imp: self, "WebRTC streaming is working!");
             }
             JsonReply::Success(success) => {
-                if let Some(data) = success.data {
+                if let Some(PluginData::VideoRoom { data: plugindata }) = success.plugindata {
+                    match plugindata {
+                        VideoRoomData::Created(created) => {
+                            gst::trace!(CAT, imp: self, "Room {} was created successfully", created.room);
+                            self.state.lock().unwrap().room_id = Some(created.room);
+                            self.join_room();
+                        }
+                        VideoRoomData::Event(RoomEvent {
+                            error_code: Some(ROOM_EXISTS_ERROR),
+                            ..
+                        }) => {
+                            gst::debug!(CAT, imp: self, "Room already exists, joining it");
+                            self.join_room();
+                        }
+                        VideoRoomData::Event(RoomEvent {
+                            error_code: Some(code),
+                            error,
+                            ..
+                        }) => {
+                            self.raise_error(format!(
+                                "code: {code}, reason: {}",
+                                error.unwrap_or_default()
+                            ));
+                        }
+                        plugindata => {
+                            gst::trace!(CAT, imp: self, "Ignoring plugin reply {plugindata:?}");
+                        }
+                    }
+                } else if let Some(data) = success.data {
+                    let subscriber_transaction_id =
+                        self.state.lock().unwrap().subscriber_transaction_id.clone();
                     if success.session_id.is_none() {
                         gst::trace!(CAT, imp: self, "Janus session {} was created successfully", data.id);
                         self.set_session_id(data.id);
                         self.attach_plugin();
+                        if self.uses_subscriber_handle() {
+                            self.attach_subscriber();
+                        }
+                    } else if success.transaction.is_some()
+                        && success.transaction == subscriber_transaction_id
+                    {
+                        gst::trace!(CAT, imp: self, "Attached subscriber to Janus Video Room plugin successfully, handle: {}", data.id);
+                        self.state.lock().unwrap().subscriber_handle_id = Some(data.id);
+                        self.subscribe(vec![]);
                     } else {
-                        gst::trace!(CAT, imp: self, "Attached to Janus Video Room plugin successfully, handle: {}", data.id);
+                        let (plugin, create_room) = {
+                            let settings = self.settings.lock().unwrap();
+                            (settings.plugin, settings.create_room)
+                        };
+                        gst::trace!(CAT, imp: self, "Attached to Janus {plugin:?} plugin successfully, handle: {}", data.id);
                         self.set_handle_id(data.id);
-                        self.join_room();
+                        match plugin {
+                            JanusVRPlugin::VideoRoom if create_room => self.create_room(),
+                            JanusVRPlugin::VideoRoom => self.join_room(),
+                            JanusVRPlugin::Streaming => self.watch(),
+                            JanusVRPlugin::AudioBridge => self.join_audio_bridge(),
+                        }
                     }
                 }
             }
-            JsonReply::Event(event) => {
-                if let Some(PluginData::VideoRoom { data: plugindata }) = event.plugindata {
-                    match plugindata {
-                        VideoRoomData::Joined(joined) => {
-                            gst::trace!(CAT, imp: self, "Joined room {:?} successfully", joined.room);
+            JsonReply::Event(event) => match event.plugindata {
+                Some(PluginData::VideoRoom { data: plugindata }) => match plugindata {
+                    VideoRoomData::Joined(joined) => {
+                        gst::trace!(CAT, imp: self, "Joined room {:?} successfully", joined.room);
+                        if self.is_consumer() {
+                            self.on_publishers(joined.publishers);
+                        } else {
                             self.session_requested();
                         }
-                        VideoRoomData::Event(room_event) => {
-                            if room_event.error_code.is_some() && room_event.error.is_some() {
-                                self.raise_error(format!(
-                                    "code: {}, reason: {}",
-                                    room_event.error_code.unwrap(),
-                                    room_event.error.unwrap(),
-                                ));
-                                return;
-                            }
+                    }
+                    VideoRoomData::Event(room_event) => {
+                        if let Some(code) = room_event.error_code {
+                            self.raise_error(format!(
+                                "code: {code}, reason: {}",
+                                room_event.error.unwrap_or_default(),
+                            ));
+                            return;
+                        }
 
-                            if let Some(jsep) = event.jsep {
-                                if jsep.r#type == "answer" {
-                                    gst::trace!(CAT, imp: self, "Session requested successfully");
-                                    self.handle_answer(jsep.sdp);
-                                }
+                        if let Some(jsep) = event.jsep {
+                            if jsep.r#type == "answer" {
+                                gst::trace!(CAT, imp: self, "Session requested successfully");
+                                self.handle_answer(jsep.sdp);
                             }
                         }
-                        VideoRoomData::Destroyed(room_destroyed) => {
-                            gst::trace!(CAT, imp: self, "Room {} has been destroyed", room_destroyed.room);
 
-                            self.raise_error(format!(
-                                "room {} has been destroyed",
-                                room_destroyed.room
-                            ));
+                        if self.is_consumer() {
+                            self.on_publishers(room_event.publishers);
+                            for id in [room_event.unpublished, room_event.leaving]
+                                .into_iter()
+                                .flatten()
+                            {
+                                self.on_publisher_left(id);
+                            }
+                        }
+                    }
+                    VideoRoomData::Attached(_) | VideoRoomData::Updated(_) => match event.jsep {
+                        Some(jsep) if jsep.r#type == "offer" => {
+                            gst::trace!(CAT, imp: self, "Received subscriber offer from handle {:?}", event.sender);
+                            self.handle_offer(jsep.sdp);
+                        }
+                        _ => {
+                            gst::trace!(CAT, imp: self, "Subscriptions updated without a new offer")
                         }
+                    },
+                    VideoRoomData::Destroyed(room_destroyed) => {
+                        gst::trace!(CAT, imp: self, "Room {} has been destroyed", room_destroyed.room);
+
+                        self.on_session_lost(
+                            &format!("room {} has been destroyed", room_destroyed.room),
+                            true,
+                        );
                     }
+                    VideoRoomData::Created(created) => {
+                        gst::trace!(CAT, imp: self, "Room {} was created", created.room);
+                    }
+                },
+                Some(PluginData::Streaming { data }) => self.on_streaming_event(data, event.jsep),
+                Some(PluginData::AudioBridge { data }) => {
+                    self.on_audio_bridge_event(data, event.jsep)
                 }
-            }
+                None => (),
+            },
             JsonReply::Error(error) => {
                 self.raise_error(format!("code: {}, reason: {}", error.code, error.reason))
             }
-            JsonReply::HangUp(hangup) => self.raise_error(format!("hangup: {}", hangup.reason)),
+            JsonReply::HangUp(hangup) => {
+                self.on_session_lost(&format!("hangup: {}", hangup.reason), true)
+            }
+            JsonReply::Timeout => self.on_session_lost("session timed out", false),
             // ignore for now
             JsonReply::Ack | JsonReply::Media => {}
         }
     }
 
+    fn on_streaming_event(&self, data: StreamingData, jsep: Option<Jsep>) {
+        let StreamingData::Event(event) = data else {
+            return;
+        };
+
+        if let Some(code) = event.error_code {
+            self.raise_error(format!(
+                "code: {code}, reason: {}",
+                event.error.unwrap_or_default(),
+            ));
+            return;
+        }
+
+        if let Some(result) = event.result {
+            gst::trace!(CAT, imp: self, "Mountpoint status: {}", result.status);
+        }
+
+        match jsep {
+            Some(jsep) if jsep.r#type == "offer" => self.handle_offer(jsep.sdp),
+            _ => (),
+        }
+    }
+
+    fn on_audio_bridge_event(&self, data: AudioBridgeData, jsep: Option<Jsep>) {
+        match data {
+            AudioBridgeData::Joined(joined) => {
+                gst::trace!(CAT, imp: self, "Joined audio bridge {} as {}", joined.room, joined.id);
+                // In Consumer role, the offer generated by Janus comes along
+                if !self.is_consumer() {
+                    self.session_requested();
+                }
+            }
+            AudioBridgeData::Event(AudioBridgeEvent {
+                error_code: Some(code),
+                error,
+            }) => {
+                self.raise_error(format!(
+                    "code: {code}, reason: {}",
+                    error.unwrap_or_default()
+                ));
+                return;
+            }
+            AudioBridgeData::Destroyed(room_destroyed) => {
+                self.on_session_lost(
+                    &format!("audio bridge {} has been destroyed", room_destroyed.room),
+                    true,
+                );
+                return;
+            }
+            AudioBridgeData::Event(_) | AudioBridgeData::Other => (),
+        }
+
+        match jsep {
+            Some(jsep) if jsep.r#type == "offer" => self.handle_offer(jsep.sdp),
+            Some(jsep) if jsep.r#type == "answer" => self.handle_answer(jsep.sdp),
+            _ => (),
+        }
+    }
+
+    // Our participation to the room is over, either joins it again in a
+    // new Janus session or ends the media session. The current Janus session
+    // is destroyed if @destroy, it is already gone otherwise.
+    fn on_session_lost(&self, reason: &str, destroy: bool) {
+        let (rejoin, apisecret) = {
+            let settings = self.settings.lock().unwrap();
+            (settings.rejoin, settings.secret_key.clone())
+        };
+
+        let (session_id, session_started) = {
+            let mut state = self.state.lock().unwrap();
+            state.handle_id = None;
+            state.subscriber_handle_id = None;
+            state.subscriber_transaction_id = None;
+            state.subscriber_joined = false;
+            state.feeds.clear();
+
+            (
+                state.session_id.take(),
+                std::mem::take(&mut state.session_started),
+            )
+        };
+
+        if let (Some(session_id), true) = (session_id, destroy) {
+            self.send(OutgoingMessage::DestroySession(DestroySessionMsg {
+                janus: "destroy".to_string(),
+                transaction: transaction_id(),
+                session_id,
+                apisecret,
+            }));
+        }
+
+        if session_started {
+            self.obj()
+                .emit_by_name::<bool>("session-ended", &[&"unique"]);
+        }
+
+        if rejoin {
+            gst::info!(CAT, imp: self, "{reason}, joining the room again");
+            self.create_session();
+        } else if session_started {
+            gst::info!(CAT, imp: self, "{reason}, ending the session");
+        } else {
+            self.raise_error(reason.to_string());
+        }
+    }
+
     fn send(&self, msg: OutgoingMessage) {
         let state = self.state.lock().unwrap();
         if let Some(mut sender) = state.ws_sender.clone() {
@@ -510,12 +1037,86 @@ impl Signaller {
         }
     }
 
+    fn keepalive(&self) -> Option<OutgoingMessage> {
+        let session_id = self.state.lock().unwrap().session_id?;
+
+        Some(OutgoingMessage::KeepAlive(KeepAliveMsg {
+            janus: "keepalive".to_string(),
+            transaction: transaction_id(),
+            session_id,
+            apisecret: self.settings.lock().unwrap().secret_key.clone(),
+        }))
+    }
+
+    // The ids of a request on the subscriber handle if @subscriber, on the
+    // handle joined as publisher otherwise
+    fn handle_ids(&self, subscriber: bool) -> Option<HandleIds> {
+        let state = self.state.lock().unwrap();
+        let settings = self.settings.lock().unwrap();
+        let handle_id = if subscriber {
+            state.subscriber_handle_id
+        } else {
+            state.handle_id
+        };
+
+        match (&state.transaction_id, state.session_id, handle_id) {
+            (Some(transaction), Some(session_id), Some(handle_id)) => Some(HandleIds {
+                transaction: transaction.clone(),
+                session_id,
+                handle_id,
+                apisecret: settings.secret_key.clone(),
+            }),
+            _ => {
+                gst::debug!(CAT, imp: self, "Not attached to the Video Room plugin");
+                None
+            }
+        }
+    }
+
     fn set_transaction_id(&self, transaction: String) {
         self.state.lock().unwrap().transaction_id = Some(transaction);
     }
 
-    fn create_session(&self) {
-        let transaction = transaction_id();
+    /* room_id and feed_id can be either a string or integer depending
+     * on server configuration. The property is always a string, if we
+     * can parse it to integer then assume that's what the server expects,
+     * unless string-ids=true is set to force usage of strings.
+     * Save parsed value in state to not have to parse it again for future
+     * API calls.
+     */
+    fn resolve_ids(&self) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        let settings = self.settings.lock().unwrap();
+
+        let room_id = match settings.room_id {
+            Some(ref room_id) if !settings.string_ids => Some(
+                room_id
+                    .parse()
+                    .map_or_else(|_| JanusId::Str(room_id.clone()), JanusId::Num),
+            ),
+            Some(ref room_id) => Some(JanusId::Str(room_id.clone())),
+            // Janus picks the ID of the room
+            None if settings.create_room => None,
+            None => return Err("Janus Room ID must be set".to_string()),
+        };
+
+        state.feed_id = Some(match room_id {
+            Some(JanusId::Str(_)) => JanusId::Str(settings.feed_id.clone()),
+            _ if settings.string_ids => JanusId::Str(settings.feed_id.clone()),
+            _ => JanusId::Num(settings.feed_id.parse().map_err(|_| {
+                format!(
+                    "Janus Feed ID {} must be an integer as the Room ID is",
+                    settings.feed_id
+                )
+            })?),
+        });
+        state.room_id = room_id;
+
+        Ok(())
+    }
+
+    fn create_session(&self) {
+        let transaction = transaction_id();
         self.set_transaction_id(transaction.clone());
         let settings = self.settings.lock().unwrap();
         let apisecret = settings.secret_key.clone();
@@ -535,179 +1136,423 @@ impl Signaller {
     }
 
     fn attach_plugin(&self) {
-        let (transaction, session_id, apisecret) = {
+        let (transaction, session_id, plugin, apisecret) = {
             let state = self.state.lock().unwrap();
             let settings = self.settings.lock().unwrap();
+            let (Some(transaction), Some(session_id)) = (&state.transaction_id, state.session_id)
+            else {
+                return;
+            };
 
             (
-                state.transaction_id.clone().unwrap(),
-                state.session_id.unwrap(),
+                transaction.clone(),
+                session_id,
+                settings.plugin,
                 settings.secret_key.clone(),
             )
         };
         self.send(OutgoingMessage::AttachPlugin(AttachPluginMsg {
             janus: "attach".to_string(),
             transaction,
-            plugin: "janus.plugin.videoroom".to_string(),
+            plugin: plugin.package().to_string(),
             session_id,
             apisecret,
         }));
     }
 
-    fn join_room(&self) {
-        let (transaction, session_id, handle_id, room, feed_id, display, apisecret) = {
+    // Attaches the handle receiving the streams in consumer role
+    fn attach_subscriber(&self) {
+        let transaction = transaction_id();
+        let (session_id, apisecret) = {
             let mut state = self.state.lock().unwrap();
             let settings = self.settings.lock().unwrap();
+            let Some(session_id) = state.session_id else {
+                return;
+            };
+            state.subscriber_transaction_id = Some(transaction.clone());
 
-            if settings.room_id.is_none() {
-                self.raise_error("Janus Room ID must be set".to_string());
+            (session_id, settings.secret_key.clone())
+        };
+        self.send(OutgoingMessage::AttachPlugin(AttachPluginMsg {
+            janus: "attach".to_string(),
+            transaction,
+            plugin: "janus.plugin.videoroom".to_string(),
+            session_id,
+            apisecret,
+        }));
+    }
+
+    // Subscribes to the publishers of the room matching `producer-peer-id`
+    fn on_publishers(&self, publishers: Vec<Publisher>) {
+        let producer_peer_id = self.settings.lock().unwrap().producer_peer_id.clone();
+
+        let mut feeds = vec![];
+        for publisher in publishers {
+            if let Some(ref peer_id) = producer_peer_id {
+                if publisher.id.to_string() != *peer_id
+                    && publisher.display.as_ref() != Some(peer_id)
+                {
+                    continue;
+                }
+            }
+
+            {
+                let mut state = self.state.lock().unwrap();
+                if state.feeds.contains(&publisher.id) {
+                    continue;
+                }
+                state.feeds.push(publisher.id.clone());
+            }
+
+            gst::info!(CAT, imp: self, "Subscribing to publisher {}", publisher.id);
+            let meta = publisher.display.map(|display| {
+                gst::Structure::builder("meta")
+                    .field("display", display)
+                    .build()
+            });
+            self.obj().emit_by_name::<()>(
+                "producer-added",
+                &[&publisher.id.to_string(), &meta, &false],
+            );
+            feeds.push(publisher.id);
+        }
+
+        self.subscribe(feeds);
+    }
+
+    fn on_publisher_left(&self, id: JanusId) {
+        {
+            let mut state = self.state.lock().unwrap();
+            let Some(idx) = state.feeds.iter().position(|feed| *feed == id) else {
+                return;
+            };
+            state.feeds.remove(idx);
+        }
+
+        // Janus removes its streams from the subscriber handle by itself
+        gst::info!(CAT, imp: self, "Publisher {id} left");
+        self.obj().emit_by_name::<()>(
+            "producer-removed",
+            &[&id.to_string(), &None::<gst::Structure>],
+        );
+    }
+
+    // Joins the room with the subscriber handle once attached, subscribing
+    // to all the known feeds, or adds @feeds to its subscriptions
+    fn subscribe(&self, feeds: Vec<JanusId>) {
+        let Some(ids) = self.handle_ids(true) else {
+            return;
+        };
+
+        let (room, streams) = {
+            let mut state = self.state.lock().unwrap();
+            if state.subscriber_joined {
+                drop(state);
+                self.send_subscribe(ids, feeds);
                 return;
             }
+            let Some(room) = state.room_id.clone() else {
+                return;
+            };
+            if state.feeds.is_empty() {
+                return;
+            }
+            state.subscriber_joined = true;
 
-            /* room_id and feed_id can be either a string or integer depending
-             * on server configuration. The property is always a string, if we
-             * can parse it to integer then assume that's what the server expects,
-             * unless string-ids=true is set to force usage of strings.
-             * Save parsed value in state to not have to parse it again for future
-             * API calls.
-             */
-            if settings.string_ids {
-                state.room_id = Some(JanusId::Str(settings.room_id.clone().unwrap()));
-                state.feed_id = Some(JanusId::Str(settings.feed_id.clone()));
-            } else {
-                let room_id_str = settings.room_id.as_ref().unwrap();
-                match room_id_str.parse() {
-                    Ok(n) => {
-                        state.room_id = Some(JanusId::Num(n));
-                        state.feed_id = Some(JanusId::Num(settings.feed_id.parse().unwrap()));
-                    }
-                    Err(_) => {
-                        state.room_id = Some(JanusId::Str(room_id_str.clone()));
-                        state.feed_id = Some(JanusId::Str(settings.feed_id.clone()));
-                    }
-                };
+            let streams = state
+                .feeds
+                .iter()
+                .map(|feed| SubscribeStream { feed: feed.clone() })
+                .collect();
+
+            (room, streams)
+        };
+
+        self.send(OutgoingMessage::SubscriberJoin(SubscriberJoinMsg {
+            janus: "message".to_string(),
+            transaction: ids.transaction,
+            session_id: ids.session_id,
+            handle_id: ids.handle_id,
+            apisecret: ids.apisecret,
+            body: SubscriberJoinBody {
+                request: "join".to_string(),
+                ptype: "subscriber".to_string(),
+                room,
+                streams,
+            },
+        }));
+    }
+
+    fn send_subscribe(&self, ids: HandleIds, feeds: Vec<JanusId>) {
+        if feeds.is_empty() {
+            return;
+        }
+
+        self.send(OutgoingMessage::Subscription(SubscriptionMsg {
+            janus: "message".to_string(),
+            transaction: ids.transaction,
+            session_id: ids.session_id,
+            handle_id: ids.handle_id,
+            apisecret: ids.apisecret,
+            body: SubscriptionBody {
+                request: "subscribe".to_string(),
+                streams: feeds
+                    .into_iter()
+                    .map(|feed| SubscribeStream { feed })
+                    .collect(),
+            },
+        }));
+    }
+
+    // Sends a request on the handle carrying the media, along with our answer
+    fn media_request(&self, request: &str, jsep: Option<Jsep>, blocking: bool) {
+        let Some(ids) = self.handle_ids(self.uses_subscriber_handle()) else {
+            return;
+        };
+
+        let msg = OutgoingMessage::Request(RequestMsg {
+            janus: "message".to_string(),
+            transaction: ids.transaction,
+            session_id: ids.session_id,
+            handle_id: ids.handle_id,
+            apisecret: ids.apisecret,
+            body: RequestBody {
+                request: request.to_string(),
+            },
+            jsep,
+        });
+
+        if blocking {
+            self.send_blocking(msg);
+        } else {
+            self.send(msg);
+        }
+    }
+
+    fn create_room(&self) {
+        let Some(ids) = self.handle_ids(false) else {
+            return;
+        };
+
+        let body = {
+            let state = self.state.lock().unwrap();
+            let settings = self.settings.lock().unwrap();
+
+            CreateRoomBody {
+                request: "create".to_string(),
+                room: state.room_id.clone(),
+                permanent: false,
+                secret: settings.room_secret.clone(),
+                bitrate: Some(settings.room_bitrate).filter(|bitrate| *bitrate > 0),
+                videocodec: settings.room_video_codecs.clone(),
+                audiocodec: settings.room_audio_codecs.clone(),
             }
+        };
+
+        self.send(OutgoingMessage::CreateRoom(CreateRoomMsg {
+            janus: "message".to_string(),
+            transaction: ids.transaction,
+            session_id: ids.session_id,
+            handle_id: ids.handle_id,
+            apisecret: ids.apisecret,
+            body,
+        }));
+    }
+
+    fn join_room(&self) {
+        let Some(ids) = self.handle_ids(false) else {
+            return;
+        };
+
+        let (room, feed_id, display, pin) = {
+            let state = self.state.lock().unwrap();
+            let settings = self.settings.lock().unwrap();
+            let (Some(room), Some(feed_id)) = (state.room_id.clone(), state.feed_id.clone()) else {
+                drop(settings);
+                drop(state);
+                self.raise_error("Janus Room ID must be set".to_string());
+                return;
+            };
 
             (
-                state.transaction_id.clone().unwrap(),
-                state.session_id.unwrap(),
-                state.handle_id.unwrap(),
-                state.room_id.clone().unwrap(),
-                state.feed_id.clone().unwrap(),
+                room,
+                feed_id,
                 settings.display_name.clone(),
-                settings.secret_key.clone(),
+                settings.pin.clone(),
             )
         };
+
         self.send(OutgoingMessage::RoomRequest(RoomRequestMsg {
             janus: "message".to_string(),
-            transaction,
-            session_id,
-            handle_id,
-            apisecret,
+            transaction: ids.transaction,
+            session_id: ids.session_id,
+            handle_id: ids.handle_id,
+            apisecret: ids.apisecret,
             body: RoomRequestBody {
                 request: "join".to_string(),
                 ptype: "publisher".to_string(),
                 room,
                 id: feed_id,
                 display,
+                pin,
             },
         }));
     }
 
-    fn leave_room(&self) {
-        let (transaction, session_id, handle_id, room, feed_id, display, apisecret) = {
+    // Asks the Streaming plugin for the offer of the mountpoint
+    fn watch(&self) {
+        let Some(ids) = self.handle_ids(false) else {
+            return;
+        };
+
+        let (id, pin) = {
             let state = self.state.lock().unwrap();
             let settings = self.settings.lock().unwrap();
+            let Some(id) = state.room_id.clone() else {
+                return;
+            };
 
-            if settings.room_id.is_none() {
-                self.raise_error("Janus Room ID must be set".to_string());
+            (id, settings.pin.clone())
+        };
+
+        self.send(OutgoingMessage::Watch(WatchMsg {
+            janus: "message".to_string(),
+            transaction: ids.transaction,
+            session_id: ids.session_id,
+            handle_id: ids.handle_id,
+            apisecret: ids.apisecret,
+            body: WatchBody {
+                request: "watch".to_string(),
+                id,
+                pin,
+            },
+        }));
+    }
+
+    // In Consumer role, joins muted and lets Janus offer the mix
+    fn join_audio_bridge(&self) {
+        let Some(ids) = self.handle_ids(false) else {
+            return;
+        };
+
+        let consumer = self.is_consumer();
+        let (room, id, display, pin) = {
+            let state = self.state.lock().unwrap();
+            let settings = self.settings.lock().unwrap();
+            let (Some(room), Some(id)) = (state.room_id.clone(), state.feed_id.clone()) else {
                 return;
-            }
+            };
 
             (
-                state.transaction_id.clone().unwrap(),
-                state.session_id.unwrap(),
-                state.handle_id.unwrap(),
-                state.room_id.clone().unwrap(),
-                state.feed_id.clone().unwrap(),
+                room,
+                id,
                 settings.display_name.clone(),
-                settings.secret_key.clone(),
+                settings.pin.clone(),
             )
         };
+
+        self.send(OutgoingMessage::AudioBridgeJoin(AudioBridgeJoinMsg {
+            janus: "message".to_string(),
+            transaction: ids.transaction,
+            session_id: ids.session_id,
+            handle_id: ids.handle_id,
+            apisecret: ids.apisecret,
+            body: AudioBridgeJoinBody {
+                request: "join".to_string(),
+                room,
+                id,
+                display,
+                pin,
+                muted: consumer,
+                generate_offer: consumer,
+            },
+        }));
+    }
+
+    // Sends our offer, or our answer in Consumer role, to the audio bridge
+    fn configure_audio_bridge(&self, jsep: Jsep) {
+        let Some(ids) = self.handle_ids(false) else {
+            return;
+        };
+
+        self.send(OutgoingMessage::AudioBridgeConfigure(
+            AudioBridgeConfigureMsg {
+                janus: "message".to_string(),
+                transaction: ids.transaction,
+                session_id: ids.session_id,
+                handle_id: ids.handle_id,
+                apisecret: ids.apisecret,
+                body: AudioBridgeConfigureBody {
+                    request: "configure".to_string(),
+                    muted: self.is_consumer(),
+                },
+                jsep,
+            },
+        ));
+    }
+
+    fn leave_room(&self) {
+        let Some(ids) = self.handle_ids(false) else {
+            return;
+        };
+
+        let (room, feed_id, display) = {
+            let state = self.state.lock().unwrap();
+            let settings = self.settings.lock().unwrap();
+            let (Some(room), Some(feed_id)) = (state.room_id.clone(), state.feed_id.clone()) else {
+                return;
+            };
+
+            (room, feed_id, settings.display_name.clone())
+        };
+
         self.send_blocking(OutgoingMessage::RoomRequest(RoomRequestMsg {
             janus: "message".to_string(),
-            transaction,
-            session_id,
-            handle_id,
-            apisecret,
+            transaction: ids.transaction,
+            session_id: ids.session_id,
+            handle_id: ids.handle_id,
+            apisecret: ids.apisecret,
             body: RoomRequestBody {
                 request: "leave".to_string(),
                 ptype: "publisher".to_string(),
                 room,
                 id: feed_id,
                 display,
+                pin: None,
             },
         }));
     }
 
-    fn publish(&self, offer: &gst_webrtc::WebRTCSessionDescription) {
-        let (transaction, session_id, handle_id, apisecret) = {
-            let state = self.state.lock().unwrap();
-            let settings = self.settings.lock().unwrap();
-
-            if settings.room_id.is_none() {
-                self.raise_error("Janus Room ID must be set".to_string());
-                return;
-            }
-
-            (
-                state.transaction_id.clone().unwrap(),
-                state.session_id.unwrap(),
-                state.handle_id.unwrap(),
-                settings.secret_key.clone(),
-            )
+    fn publish(&self, jsep: Jsep) {
+        let Some(ids) = self.handle_ids(false) else {
+            return;
         };
-        let sdp_data = offer.sdp().as_text().unwrap();
+
         self.send(OutgoingMessage::Publish(PublishMsg {
             janus: "message".to_string(),
-            transaction,
-            session_id,
-            handle_id,
-            apisecret,
+            transaction: ids.transaction,
+            session_id: ids.session_id,
+            handle_id: ids.handle_id,
+            apisecret: ids.apisecret,
             body: PublishBody {
                 request: "publish".to_string(),
             },
-            jsep: Jsep {
-                sdp: sdp_data,
-                trickle: Some(true),
-                r#type: "offer".to_string(),
-            },
+            jsep,
         }));
     }
 
     fn trickle(&self, candidate: &str, sdp_m_line_index: u32) {
-        let (transaction, session_id, handle_id, apisecret) = {
-            let state = self.state.lock().unwrap();
-            let settings = self.settings.lock().unwrap();
-
-            if settings.room_id.is_none() {
-                self.raise_error("Janus Room ID must be set".to_string());
-                return;
-            }
-
-            (
-                state.transaction_id.clone().unwrap(),
-                state.session_id.unwrap(),
-                state.handle_id.unwrap(),
-                settings.secret_key.clone(),
-            )
+        // The subscriber handle carries the media in consumer role
+        let Some(ids) = self.handle_ids(self.uses_subscriber_handle()) else {
+            return;
         };
+
         self.send(OutgoingMessage::Trickle(TrickleMsg {
             janus: "trickle".to_string(),
-            transaction,
-            session_id,
-            handle_id,
-            apisecret,
+            transaction: ids.transaction,
+            session_id: ids.session_id,
+            handle_id: ids.handle_id,
+            apisecret: ids.apisecret,
             candidate: Candidate {
                 candidate: candidate.to_string(),
                 sdp_m_line_index,
@@ -716,6 +1561,7 @@ impl Signaller {
     }
 
     fn session_requested(&self) {
+        self.state.lock().unwrap().session_started = true;
         self.obj().emit_by_name::<()>(
             "session-requested",
             &[
@@ -726,6 +1572,27 @@ impl Signaller {
         );
     }
 
+    fn handle_offer(&self, sdp: String) {
+        let offer_sdp = match gst_sdp::SDPMessage::parse_buffer(sdp.as_bytes()) {
+            Ok(offer_sdp) => offer_sdp,
+            Err(err) => {
+                self.raise_error(format!("Could not parse offer SDP: {err}"));
+                return;
+            }
+        };
+
+        let start = !std::mem::replace(&mut self.state.lock().unwrap().session_started, true);
+        if start {
+            self.obj()
+                .emit_by_name::<()>("session-started", &[&"unique", &"unique"]);
+        }
+
+        let offer =
+            gst_webrtc::WebRTCSessionDescription::new(gst_webrtc::WebRTCSDPType::Offer, offer_sdp);
+        self.obj()
+            .emit_by_name::<()>("session-description", &[&"unique", &offer]);
+    }
+
     fn handle_answer(&self, sdp: String) {
         match gst_sdp::SDPMessage::parse_buffer(sdp.as_bytes()) {
             Ok(ans_sdp) => {
@@ -745,6 +1612,16 @@ impl Signaller {
 
 impl SignallableImpl for Signaller {
     fn start(&self) {
+        if self.settings.lock().unwrap().plugin == JanusVRPlugin::Streaming && !self.is_consumer() {
+            self.raise_error("The Streaming plugin can only be used in Consumer role".to_string());
+            return;
+        }
+
+        if let Err(err) = self.resolve_ids() {
+            self.raise_error(err);
+            return;
+        }
+
         let this = self.obj().clone();
         let imp = self.downgrade();
         RUNTIME.spawn(async move {
@@ -758,10 +1635,32 @@ impl SignallableImpl for Signaller {
         });
     }
 
-    fn send_sdp(&self, _session_id: &str, offer: &gst_webrtc::WebRTCSessionDescription) {
-        gst::info!(CAT, imp: self, "sending SDP offer to peer: {:?}", offer.sdp().as_text());
+    fn send_sdp(&self, _session_id: &str, sessdesc: &gst_webrtc::WebRTCSessionDescription) {
+        let sdp_type = match sessdesc.type_() {
+            gst_webrtc::WebRTCSDPType::Offer => "offer",
+            _ => "answer",
+        };
+        gst::info!(CAT, imp: self, "sending SDP {sdp_type} to peer: {:?}", sessdesc.sdp().as_text());
 
-        self.publish(offer);
+        let sdp = match sessdesc.sdp().as_text() {
+            Ok(sdp) => sdp,
+            Err(err) => {
+                self.raise_error(format!("Could not serialize {sdp_type} SDP: {err}"));
+                return;
+            }
+        };
+        let jsep = Jsep {
+            sdp,
+            trickle: Some(true),
+            r#type: sdp_type.to_string(),
+        };
+
+        let plugin = self.settings.lock().unwrap().plugin;
+        match plugin {
+            JanusVRPlugin::AudioBridge => self.configure_audio_bridge(jsep),
+            _ if self.is_consumer() => self.media_request("start", Some(jsep), false),
+            _ => self.publish(jsep),
+        }
     }
 
     fn add_ice(
@@ -801,10 +1700,25 @@ impl SignallableImpl for Signaller {
         state.session_id = None;
         state.handle_id = None;
         state.transaction_id = None;
+        state.subscriber_handle_id = None;
+        state.subscriber_transaction_id = None;
+        state.subscriber_joined = false;
+        state.feeds.clear();
+        state.session_started = false;
     }
 
     fn end_session(&self, _session_id: &str) {
-        self.leave_room();
+        let plugin = self.settings.lock().unwrap().plugin;
+        match plugin {
+            JanusVRPlugin::VideoRoom => {
+                if self.is_consumer() {
+                    self.media_request("leave", None, true);
+                }
+                self.leave_room();
+            }
+            JanusVRPlugin::Streaming => self.media_request("stop", None, true),
+            JanusVRPlugin::AudioBridge => self.media_request("leave", None, true),
+        }
     }
 }
 
@@ -818,3 +1732,608 @@ impl ObjectSubclass for Signaller {
 
 #[glib::derived_properties]
 impl ObjectImpl for Signaller {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::janusvr_signaller::JanusVRSignaller;
+    use crate::signaller::test_utils::{self, expect, next, session_description, Signals, SDP};
+    use crate::signaller::SignallableExt;
+    use serde_json::{json, Value};
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc};
+    use warp::Filter;
+
+    // What the mock server does once the first client joined the room
+    #[derive(Clone, Copy)]
+    enum Script {
+        Nothing,
+        Destroyed,
+        Timeout,
+        HangUp,
+        // A publisher joins once the subscriber started, and the first one
+        // leaves once the subscriptions were updated
+        Publishers,
+    }
+
+    fn reply(value: Value) -> warp::ws::Message {
+        warp::ws::Message::text(value.to_string())
+    }
+
+    // Replies of the mock Janus server to @request, for the room @room
+    fn replies(request: &Value, room: &mut Option<Value>, handles: &AtomicU64) -> Vec<Value> {
+        let transaction = request["transaction"].clone();
+        let session_id = request["session_id"].clone();
+
+        match request["janus"].as_str().unwrap_or_default() {
+            "create" => vec![json!({
+                "janus": "success",
+                "transaction": transaction,
+                "data": { "id": 1 },
+            })],
+            "attach" => vec![json!({
+                "janus": "success",
+                "transaction": transaction,
+                "session_id": session_id,
+                "data": { "id": handles.fetch_add(1, Ordering::SeqCst) },
+            })],
+            "message" => {
+                let body = &request["body"];
+                match body["request"].as_str().unwrap_or_default() {
+                    "create" => {
+                        let created = if room.is_some() {
+                            json!({
+                                "videoroom": "event",
+                                "error_code": ROOM_EXISTS_ERROR,
+                                "error": "Room already exists",
+                            })
+                        } else {
+                            let id = match body["room"] {
+                                Value::Null => json!(4242),
+                                ref id => id.clone(),
+                            };
+                            *room = Some(id.clone());
+                            json!({ "videoroom": "created", "room": id })
+                        };
+
+                        vec![json!({
+                            "janus": "success",
+                            "transaction": transaction,
+                            "session_id": session_id,
+                            "plugindata": {
+                                "plugin": "janus.plugin.videoroom",
+                                "data": created,
+                            },
+                        })]
+                    }
+                    // AudioBridge participants have no type
+                    "join" if body["ptype"].is_null() => vec![
+                        json!({ "janus": "ack", "transaction": transaction }),
+                        json!({
+                            "janus": "event",
+                            "transaction": transaction,
+                            "session_id": session_id,
+                            "sender": request["handle_id"],
+                            "plugindata": {
+                                "plugin": "janus.plugin.audiobridge",
+                                "data": {
+                                    "audiobridge": "joined",
+                                    "room": body["room"],
+                                    "id": body["id"],
+                                    "participants": [],
+                                },
+                            },
+                            "jsep": if body["generate_offer"] == true {
+                                json!({ "type": "offer", "sdp": SDP })
+                            } else {
+                                Value::Null
+                            },
+                        }),
+                    ],
+                    "configure" => vec![
+                        json!({ "janus": "ack", "transaction": transaction }),
+                        json!({
+                            "janus": "event",
+                            "transaction": transaction,
+                            "session_id": session_id,
+                            "sender": request["handle_id"],
+                            "plugindata": {
+                                "plugin": "janus.plugin.audiobridge",
+                                "data": { "audiobridge": "event", "result": "ok" },
+                            },
+                            "jsep": if request["jsep"]["type"] == "offer" {
+                                json!({ "type": "answer", "sdp": SDP })
+                            } else {
+                                Value::Null
+                            },
+                        }),
+                    ],
+                    "join" if body["ptype"] == "subscriber" => vec![
+                        json!({ "janus": "ack", "transaction": transaction }),
+                        json!({
+                            "janus": "event",
+                            "transaction": transaction,
+                            "session_id": session_id,
+                            "sender": request["handle_id"],
+                            "plugindata": {
+                                "plugin": "janus.plugin.videoroom",
+                                "data": { "videoroom": "attached", "room": body["room"] },
+                            },
+                            "jsep": { "type": "offer", "sdp": SDP },
+                        }),
+                    ],
+                    "subscribe" => vec![
+                        json!({ "janus": "ack", "transaction": transaction }),
+                        json!({
+                            "janus": "event",
+                            "transaction": transaction,
+                            "session_id": session_id,
+                            "sender": request["handle_id"],
+                            "plugindata": {
+                                "plugin": "janus.plugin.videoroom",
+                                "data": { "videoroom": "updated" },
+                            },
+                            "jsep": { "type": "offer", "sdp": SDP },
+                        }),
+                    ],
+                    "watch" => vec![
+                        json!({ "janus": "ack", "transaction": transaction }),
+                        json!({
+                            "janus": "event",
+                            "transaction": transaction,
+                            "session_id": session_id,
+                            "sender": request["handle_id"],
+                            "plugindata": {
+                                "plugin": "janus.plugin.streaming",
+                                "data": {
+                                    "streaming": "event",
+                                    "result": { "status": "preparing" },
+                                },
+                            },
+                            "jsep": { "type": "offer", "sdp": SDP },
+                        }),
+                    ],
+                    "join" => vec![
+                        json!({ "janus": "ack", "transaction": transaction }),
+                        json!({
+                            "janus": "event",
+                            "transaction": transaction,
+                            "session_id": session_id,
+                            "sender": request["handle_id"],
+                            "plugindata": {
+                                "plugin": "janus.plugin.videoroom",
+                                "data": {
+                                    "videoroom": "joined",
+                                    "room": body["room"],
+                                    "publishers": [{ "id": 1, "display": "alice" }],
+                                },
+                            },
+                        }),
+                    ],
+                    _ => vec![json!({ "janus": "ack", "transaction": transaction })],
+                }
+            }
+            _ => vec![json!({ "janus": "ack", "transaction": transaction })],
+        }
+    }
+
+    // Mock of the Janus API, running @script once the first
+    // session joined the room and sending every request to @requests
+    fn serve(script: Script, requests: mpsc::Sender<Value>) -> SocketAddr {
+        let requests = Arc::new(Mutex::new(requests));
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let janus = warp::ws().map(move |ws: warp::ws::Ws| {
+            let first = count.fetch_add(1, Ordering::SeqCst) == 0;
+            let requests = requests.clone();
+
+            let reply = ws.on_upgrade(move |mut socket| async move {
+                let handles = AtomicU64::new(10);
+                let mut room = None;
+                let mut joined = !first;
+
+                while let Some(Ok(msg)) = socket.next().await {
+                    let Ok(request) = serde_json::from_slice::<Value>(msg.as_bytes()) else {
+                        continue;
+                    };
+                    let _ = requests.lock().unwrap().send(request.clone());
+
+                    for value in replies(&request, &mut room, &handles) {
+                        if socket.send(reply(value)).await.is_err() {
+                            return;
+                        }
+                    }
+
+                    if let Script::Publishers = script {
+                        let data = match request["body"]["request"].as_str() {
+                            Some("start") => json!({
+                                "videoroom": "event",
+                                "publishers": [{ "id": 2, "display": "bob" }],
+                            }),
+                            Some("subscribe") => json!({ "videoroom": "event", "leaving": 1 }),
+                            _ => continue,
+                        };
+                        let event = json!({
+                            "janus": "event",
+                            "session_id": 1,
+                            "plugindata": { "plugin": "janus.plugin.videoroom", "data": data },
+                        });
+                        if socket.send(reply(event)).await.is_err() {
+                            return;
+                        }
+                        continue;
+                    }
+
+                    if joined || request["body"]["request"] != "join" {
+                        continue;
+                    }
+                    joined = true;
+
+                    let lost = match script {
+                        Script::Nothing => continue,
+                        Script::Destroyed => json!({
+                            "janus": "event",
+                            "session_id": 1,
+                            "sender": request["handle_id"],
+                            "plugindata": {
+                                "plugin": "janus.plugin.videoroom",
+                                "data": {
+                                    "videoroom": "destroyed",
+                                    "room": request["body"]["room"],
+                                },
+                            },
+                        }),
+                        Script::Timeout => json!({ "janus": "timeout", "session_id": 1 }),
+                        Script::HangUp => json!({
+                            "janus": "hangup",
+                            "session_id": 1,
+                            "sender": request["handle_id"],
+                            "reason": "DTLS alert",
+                        }),
+                    };
+                    if socket.send(reply(lost)).await.is_err() {
+                        return;
+                    }
+                }
+            });
+
+            warp::reply::with_header(reply, "sec-websocket-protocol", "janus-protocol")
+        });
+
+        let _guard = RUNTIME.enter();
+        let (addr, server) = warp::serve(janus).bind_ephemeral(([127, 0, 0, 1], 0));
+        RUNTIME.spawn(server);
+
+        addr
+    }
+
+    // Starts a signaller configured with @properties, its signals are sent
+    // by name to the returned receiver
+    fn start_signaller(
+        addr: SocketAddr,
+        properties: &[(&str, &dyn ToValue)],
+    ) -> (JanusVRSignaller, Signals) {
+        let signaller = glib::Object::builder::<JanusVRSignaller>()
+            .property("janus-endpoint", format!("ws://{addr}"))
+            .build();
+        for (name, value) in properties {
+            signaller.set_property_from_value(name, &value.to_value());
+        }
+
+        let rx = test_utils::start_signaller(
+            &signaller,
+            &[
+                "producer-added",
+                "producer-removed",
+                "session-requested",
+                "session-started",
+                "session-description",
+                "session-ended",
+                "error",
+            ],
+        );
+
+        (signaller, rx)
+    }
+
+    // The next request named @name sent by the client, plugin messages are
+    // named by their body request
+    fn next_request(requests: &mpsc::Receiver<Value>, name: &str) -> Value {
+        loop {
+            let request = requests.recv_timeout(Duration::from_secs(10)).unwrap();
+            let body = &request["body"];
+            if (body.is_null() && request["janus"] == name) || body["request"] == name {
+                return request;
+            }
+        }
+    }
+
+    #[test]
+    fn test_create_room() {
+        gst::init().unwrap();
+
+        let (tx, requests) = mpsc::channel();
+        let addr = serve(Script::Nothing, tx);
+        let (signaller, rx) = start_signaller(
+            addr,
+            &[
+                ("create-room", &true),
+                ("room-secret", &"secret"),
+                ("room-bitrate", &512_000u32),
+                ("room-video-codecs", &"vp8,h264"),
+                ("feed-id", &"1234"),
+            ],
+        );
+
+        expect(&rx, &["session-requested"]);
+
+        let create = next_request(&requests, "create");
+        assert_eq!(create["body"]["room"], Value::Null);
+        assert_eq!(create["body"]["permanent"], false);
+        assert_eq!(create["body"]["secret"], "secret");
+        assert_eq!(create["body"]["bitrate"], 512_000);
+        assert_eq!(create["body"]["videocodec"], "vp8,h264");
+        assert_eq!(create["body"]["audiocodec"], Value::Null);
+
+        // The room picked by the server is joined
+        let join = next_request(&requests, "join");
+        assert_eq!(join["body"]["room"], 4242);
+        assert_eq!(join["body"]["id"], 1234);
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_invalid_feed_id() {
+        gst::init().unwrap();
+
+        let (tx, _requests) = mpsc::channel();
+        let addr = serve(Script::Nothing, tx);
+        let (signaller, rx) = start_signaller(addr, &[("room-id", &"1234"), ("feed-id", &"feed")]);
+
+        expect(&rx, &["error"]);
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_room_destroyed() {
+        gst::init().unwrap();
+
+        for script in [Script::Destroyed, Script::HangUp] {
+            let (tx, requests) = mpsc::channel();
+            let addr = serve(script, tx);
+            let (signaller, rx) = start_signaller(addr, &[("room-id", &"1234")]);
+
+            expect(&rx, &["session-requested", "session-ended"]);
+
+            // The lost session is destroyed
+            next_request(&requests, "destroy");
+
+            signaller.stop();
+        }
+    }
+
+    #[test]
+    fn test_rejoin() {
+        gst::init().unwrap();
+
+        for script in [Script::Destroyed, Script::Timeout] {
+            let (tx, requests) = mpsc::channel();
+            let addr = serve(script, tx);
+            let (signaller, rx) = start_signaller(
+                addr,
+                &[
+                    ("room-id", &"1234"),
+                    ("create-room", &true),
+                    ("rejoin", &true),
+                ],
+            );
+
+            // The room is created and joined again in a new session
+            expect(
+                &rx,
+                &["session-requested", "session-ended", "session-requested"],
+            );
+
+            for _ in 0..2 {
+                let create = next_request(&requests, "create");
+                assert_eq!(create["body"]["room"], 1234);
+                next_request(&requests, "join");
+            }
+
+            signaller.stop();
+        }
+    }
+
+    #[test]
+    fn test_keepalive() {
+        gst::init().unwrap();
+
+        let (tx, requests) = mpsc::channel();
+        let addr = serve(Script::Nothing, tx);
+        let (signaller, rx) =
+            start_signaller(addr, &[("room-id", &"1234"), ("keepalive-interval", &1u32)]);
+
+        expect(&rx, &["session-requested"]);
+
+        let keepalive = next_request(&requests, "keepalive");
+        assert_eq!(keepalive["session_id"], 1);
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_subscriber() {
+        gst::init().unwrap();
+
+        let (tx, requests) = mpsc::channel();
+        let addr = serve(Script::Publishers, tx);
+        let (signaller, rx) = start_signaller(
+            addr,
+            &[
+                ("role", &WebRTCSignallerRole::Consumer),
+                ("room-id", &"1234"),
+            ],
+        );
+
+        // The publishers of the room are subscribed to on a second handle
+        assert_eq!(next(&rx, "producer-added"), "1");
+        let join = next_request(&requests, "join");
+        assert_eq!(join["body"]["ptype"], "publisher");
+        let subscriber_join = next_request(&requests, "join");
+        assert_eq!(subscriber_join["body"]["ptype"], "subscriber");
+        assert_eq!(subscriber_join["body"]["streams"], json!([{ "feed": 1 }]));
+        assert_ne!(subscriber_join["handle_id"], join["handle_id"]);
+
+        // Whose offer is answered
+        expect(&rx, &["session-started", "session-description"]);
+        signaller.send_sdp(
+            "unique",
+            &session_description(gst_webrtc::WebRTCSDPType::Answer),
+        );
+        let start = next_request(&requests, "start");
+        assert_eq!(start["handle_id"], subscriber_join["handle_id"]);
+        assert_eq!(start["jsep"]["type"], "answer");
+
+        // New publishers are added to the subscriptions, renegotiating
+        assert_eq!(next(&rx, "producer-added"), "2");
+        let subscribe = next_request(&requests, "subscribe");
+        assert_eq!(subscribe["handle_id"], subscriber_join["handle_id"]);
+        assert_eq!(subscribe["body"]["streams"], json!([{ "feed": 2 }]));
+        expect(&rx, &["session-description"]);
+
+        // And the ones leaving are removed
+        assert_eq!(next(&rx, "producer-removed"), "1");
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_streaming() {
+        gst::init().unwrap();
+
+        let (tx, requests) = mpsc::channel();
+        let addr = serve(Script::Nothing, tx);
+        let (signaller, rx) = start_signaller(
+            addr,
+            &[
+                ("role", &WebRTCSignallerRole::Consumer),
+                ("plugin", &JanusVRPlugin::Streaming),
+                ("room-id", &"1"),
+                ("pin", &"secret"),
+            ],
+        );
+
+        let attach = next_request(&requests, "attach");
+        assert_eq!(attach["plugin"], "janus.plugin.streaming");
+
+        let watch = next_request(&requests, "watch");
+        assert_eq!(watch["body"]["id"], 1);
+        assert_eq!(watch["body"]["pin"], "secret");
+
+        // The offer of the mountpoint is answered
+        expect(&rx, &["session-started", "session-description"]);
+        signaller.send_sdp(
+            "unique",
+            &session_description(gst_webrtc::WebRTCSDPType::Answer),
+        );
+        let start = next_request(&requests, "start");
+        assert_eq!(start["handle_id"], watch["handle_id"]);
+        assert_eq!(start["jsep"]["type"], "answer");
+
+        signaller.end_session("unique");
+        next_request(&requests, "stop");
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_streaming_producer() {
+        gst::init().unwrap();
+
+        let (tx, _requests) = mpsc::channel();
+        let addr = serve(Script::Nothing, tx);
+        let (signaller, rx) = start_signaller(
+            addr,
+            &[("plugin", &JanusVRPlugin::Streaming), ("room-id", &"1")],
+        );
+
+        expect(&rx, &["error"]);
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_audio_bridge() {
+        gst::init().unwrap();
+
+        let (tx, requests) = mpsc::channel();
+        let addr = serve(Script::Nothing, tx);
+        let (signaller, rx) = start_signaller(
+            addr,
+            &[
+                ("plugin", &JanusVRPlugin::AudioBridge),
+                ("room-id", &"1234"),
+                ("display-name", &"ana"),
+            ],
+        );
+
+        let attach = next_request(&requests, "attach");
+        assert_eq!(attach["plugin"], "janus.plugin.audiobridge");
+
+        let join = next_request(&requests, "join");
+        assert_eq!(join["body"]["room"], 1234);
+        assert_eq!(join["body"]["display"], "ana");
+        assert_eq!(join["body"]["muted"], false);
+        assert_eq!(join["body"].get("generate_offer"), None);
+
+        // Our offer is sent with the configure request
+        expect(&rx, &["session-requested"]);
+        signaller.send_sdp(
+            "unique",
+            &session_description(gst_webrtc::WebRTCSDPType::Offer),
+        );
+        let configure = next_request(&requests, "configure");
+        assert_eq!(configure["jsep"]["type"], "offer");
+        expect(&rx, &["session-description"]);
+
+        signaller.end_session("unique");
+        next_request(&requests, "leave");
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_audio_bridge_consumer() {
+        gst::init().unwrap();
+
+        let (tx, requests) = mpsc::channel();
+        let addr = serve(Script::Nothing, tx);
+        let (signaller, rx) = start_signaller(
+            addr,
+            &[
+                ("role", &WebRTCSignallerRole::Consumer),
+                ("plugin", &JanusVRPlugin::AudioBridge),
+                ("room-id", &"1234"),
+            ],
+        );
+
+        let join = next_request(&requests, "join");
+        assert_eq!(join["body"]["muted"], true);
+        assert_eq!(join["body"]["generate_offer"], true);
+
+        // The offer of the mix is answered with the configure request
+        expect(&rx, &["session-started", "session-description"]);
+        signaller.send_sdp(
+            "unique",
+            &session_description(gst_webrtc::WebRTCSDPType::Answer),
+        );
+        let configure = next_request(&requests, "configure");
+        assert_eq!(configure["body"]["muted"], true);
+        assert_eq!(configure["jsep"]["type"], "answer");
+
+        signaller.stop();
+    }
+}
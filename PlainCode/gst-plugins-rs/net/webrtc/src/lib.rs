@@ -6,7 +6,7 @@
  * @title: Rust WebRTC elements
  * @short_description: A collection of high level WebRTC elements wrapping webrtcbin
  *
- * {{ net/webrtc/README.md[2:233] }}
+ * {{ net/webrtc/README.md[2:264] }}
  *
  * Since: plugins-rs-0.9
  */
@@ -15,12 +15,18 @@ use once_cell::sync::Lazy;
 use tokio::runtime;
 
 mod aws_kvs_signaller;
+mod data_channel;
 mod janusvr_signaller;
 mod livekit_signaller;
+pub mod local_signaller;
+mod manual_signaller;
+mod mqtt_signaller;
 pub mod signaller;
+mod sip_signaller;
 pub mod utils;
 pub mod webrtcsink;
 pub mod webrtcsrc;
+mod whep_signaller;
 mod whip_signaller;
 
 fn plugin_init(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use crate::data_channel::{self, DataChannelMessage};
 use crate::signaller::{Signallable, SignallableImpl, WebRTCSignallerRole};
 
 use crate::utils::{wait_async, WaitError};
@@ -11,10 +12,12 @@ use gst::glib;
 use gst::prelude::*;
 use gst::subclass::prelude::*;
 use once_cell::sync::Lazy;
+use prost::Message as _;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::oneshot;
 use tokio::task::JoinHandle;
 
@@ -31,6 +34,10 @@ static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
 });
 
 const DEFAULT_TRACK_PUBLISH_TIMEOUT: u32 = 10;
+const RELIABLE_CHANNEL_LABEL: &str = "_reliable";
+const LOSSY_CHANNEL_LABEL: &str = "_lossy";
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(500);
 
 #[derive(Clone)]
 struct Settings {
@@ -71,6 +78,11 @@ pub struct Signaller {
     connection: Mutex<Option<Connection>>,
     join_canceller: Mutex<Option<futures::future::AbortHandle>>,
     signal_task_canceller: Mutex<Option<futures::future::AbortHandle>>,
+    webrtcbin: Mutex<Option<glib::WeakRef<gst::Element>>>,
+    // The publishers we subscribe to, by SID
+    participants: Mutex<HashMap<String, proto::ParticipantInfo>>,
+    // Tracks the application unsubscribed from
+    unsubscribed_tracks: Mutex<HashSet<String>>,
 }
 
 struct Channels {
@@ -81,9 +93,26 @@ struct Channels {
 struct Connection {
     signal_client: Arc<signal_client::SignalClient>,
     pending_tracks: HashMap<String, oneshot::Sender<proto::TrackInfo>>,
-    signal_task: JoinHandle<()>,
+    // Spawned once the join response is handled
+    signal_task: Option<JoinHandle<()>>,
     early_candidates: Option<Vec<String>>,
     channels: Option<Channels>,
+    wsurl: String,
+    // Refreshed by the server, used to reconnect
+    auth_token: String,
+    participant_sid: Option<String>,
+}
+
+/// What the signal task does after handling a signal event
+enum SignalAction {
+    Continue,
+    /// Resume the signalling connection, keeping the media connection
+    Resume(String),
+    /// Join the room again with a new media connection
+    Reconnect(String),
+    /// The server does not want us back
+    Disconnect(String),
+    Stop,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -193,32 +222,324 @@ impl Signaller {
         }
     }
 
+    fn post_message(&self, structure: gst::Structure) {
+        self.obj()
+            .emit_by_name::<()>("element-message", &[&structure]);
+    }
+
     async fn signal_task(&self, mut signal_events: signal_client::SignalEvents) {
         loop {
-            match wait_async(&self.signal_task_canceller, signal_events.recv(), 0).await {
-                Ok(Some(signal)) => match signal {
-                    signal_client::SignalEvent::Message(signal) => {
-                        self.on_signal_event(*signal).await;
-                    }
-                    signal_client::SignalEvent::Close(reason) => {
-                        gst::debug!(CAT, imp: self, "Close: {reason}");
-                        self.raise_error("Server disconnected".to_string());
-                        break;
-                    }
+            let action =
+                match wait_async(&self.signal_task_canceller, signal_events.recv(), 0).await {
+                    Ok(Some(signal)) => match signal {
+                        signal_client::SignalEvent::Message(signal) => {
+                            self.on_signal_event(*signal).await
+                        }
+                        signal_client::SignalEvent::Close(reason) => {
+                            gst::debug!(CAT, imp: self, "Close: {reason}");
+                            SignalAction::Resume(format!("Server disconnected: {reason}"))
+                        }
+                    },
+                    Ok(None) => SignalAction::Resume("Signal client closed".to_string()),
+                    Err(err) => match err {
+                        WaitError::FutureAborted => SignalAction::Stop,
+                        WaitError::FutureError(err) => {
+                            self.raise_error(err.to_string());
+                            SignalAction::Continue
+                        }
+                    },
+                };
+
+            let action = match action {
+                SignalAction::Resume(reason) => self.resume(&reason, &mut signal_events).await,
+                action => action,
+            };
+
+            match action {
+                SignalAction::Continue | SignalAction::Resume(_) => (),
+                SignalAction::Reconnect(reason) => match self.reconnect(&reason).await {
+                    Some(new_signal_events) => signal_events = new_signal_events,
+                    None => break,
                 },
-                Ok(None) => {}
-                Err(err) => match err {
-                    WaitError::FutureAborted => {
-                        gst::debug!(CAT, imp: self, "Closing signal_task");
-                        break;
-                    }
-                    WaitError::FutureError(err) => self.raise_error(err.to_string()),
+                SignalAction::Disconnect(reason) => {
+                    self.disconnect(&reason);
+                    break;
+                }
+                SignalAction::Stop => {
+                    gst::debug!(CAT, imp: self, "Closing signal_task");
+                    break;
+                }
+            }
+        }
+    }
+
+    // Waits before the next reconnection attempt, returns false if stopped
+    async fn reconnect_delay(&self, attempt: u32) -> bool {
+        if attempt == 0 {
+            return true;
+        }
+
+        let delay = RECONNECT_BASE_DELAY * 2u32.pow(attempt - 1);
+        gst::debug!(CAT, imp: self, "Reconnecting in {delay:?}");
+
+        wait_async(&self.signal_task_canceller, tokio::time::sleep(delay), 0)
+            .await
+            .is_ok()
+    }
+
+    /// Resumes the signalling connection of the participant after it was
+    /// lost, restarting ICE on the publisher connection. Falls back to a full
+    /// reconnection if the server does not let us resume.
+    async fn resume(
+        &self,
+        reason: &str,
+        signal_events: &mut signal_client::SignalEvents,
+    ) -> SignalAction {
+        let Some(signal_client) = self.signal_client() else {
+            return SignalAction::Stop;
+        };
+
+        gst::warning!(CAT, imp: self, "{reason}, resuming");
+        self.post_message(
+            gst::Structure::builder("livekit-reconnecting")
+                .field("reason", reason)
+                .field("resume", true)
+                .build(),
+        );
+
+        let mut last_error = None;
+        for attempt in 0..MAX_RECONNECT_ATTEMPTS {
+            if !self.reconnect_delay(attempt).await {
+                return SignalAction::Stop;
+            }
+
+            match wait_async(&self.signal_task_canceller, signal_client.restart(), 0).await {
+                Ok(Ok(response)) => {
+                    gst::info!(CAT, imp: self, "Resumed: {response:?}");
+                    last_error = None;
+                    break;
+                }
+                Ok(Err(err)) => {
+                    gst::warning!(CAT, imp: self, "Failed to resume: {err}");
+                    last_error = Some(err.to_string());
+                }
+                Err(WaitError::FutureAborted) => return SignalAction::Stop,
+                Err(WaitError::FutureError(err)) => last_error = Some(err.to_string()),
+            }
+        }
+
+        if let Some(err) = last_error {
+            return SignalAction::Reconnect(format!("Failed to resume: {err}"));
+        }
+
+        // The close events of the previous connection were queued before
+        // restart() returned
+        let mut action = SignalAction::Continue;
+        while let Ok(event) = signal_events.try_recv() {
+            if let signal_client::SignalEvent::Message(signal) = event {
+                action = self.on_signal_event(*signal).await;
+                if !matches!(action, SignalAction::Continue) {
+                    break;
+                }
+            }
+        }
+
+        // The server restarts ICE on the subscriber connection itself
+        if !self.is_subscriber() {
+            self.restart_ice().await;
+        }
+
+        self.post_message(
+            gst::Structure::builder("livekit-reconnected")
+                .field("resume", true)
+                .build(),
+        );
+
+        action
+    }
+
+    async fn restart_ice(&self) {
+        let Some(webrtcbin) = self
+            .webrtcbin
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|webrtcbin| webrtcbin.upgrade())
+        else {
+            return;
+        };
+        let Some(signal_client) = self.signal_client() else {
+            return;
+        };
+
+        gst::info!(CAT, imp: self, "Restarting ICE");
+
+        let (tx, rx) = oneshot::channel();
+        let promise = gst::Promise::with_change_func(move |reply| {
+            let _ = tx.send(reply.ok().flatten().map(|reply| reply.to_owned()));
+        });
+        webrtcbin.emit_by_name::<()>(
+            "create-offer",
+            &[
+                &gst::Structure::builder("options")
+                    .field("ice-restart", true)
+                    .build(),
+                &promise,
+            ],
+        );
+
+        let Some(offer) = rx.await.ok().flatten().and_then(|reply| {
+            reply
+                .get::<gst_webrtc::WebRTCSessionDescription>("offer")
+                .ok()
+        }) else {
+            gst::warning!(CAT, imp: self, "Failed to create ICE restart offer");
+            return;
+        };
+
+        webrtcbin.emit_by_name::<()>("set-local-description", &[&offer, &None::<gst::Promise>]);
+
+        // The answer is handled like the initial one
+        signal_client
+            .send(proto::signal_request::Message::Offer(
+                proto::SessionDescription {
+                    r#type: "offer".to_string(),
+                    sdp: offer.sdp().to_string(),
                 },
+            ))
+            .await;
+    }
+
+    /// Joins the room again after the server asked us to leave, the media
+    /// session is ended and a new one is negotiated
+    async fn reconnect(&self, reason: &str) -> Option<signal_client::SignalEvents> {
+        let (old_signal_client, wsurl, auth_token) = {
+            let connection = self.connection.lock().unwrap();
+            let connection = connection.as_ref()?;
+            (
+                connection.signal_client.clone(),
+                connection.wsurl.clone(),
+                connection.auth_token.clone(),
+            )
+        };
+
+        gst::warning!(CAT, imp: self, "{reason}, reconnecting");
+        self.post_message(
+            gst::Structure::builder("livekit-reconnecting")
+                .field("reason", reason)
+                .field("resume", false)
+                .build(),
+        );
+
+        old_signal_client.close().await;
+        *self.webrtcbin.lock().unwrap() = None;
+        self.obj()
+            .emit_by_name::<bool>("session-ended", &[&"unique"]);
+
+        let mut last_error = None;
+        for attempt in 0..MAX_RECONNECT_ATTEMPTS {
+            if !self.reconnect_delay(attempt).await {
+                return None;
+            }
+
+            let options = signal_client::SignalOptions {
+                auto_subscribe: self.auto_subscribe(),
+                ..Default::default()
+            };
+            let res = wait_async(
+                &self.signal_task_canceller,
+                signal_client::SignalClient::connect(&wsurl, &auth_token, options),
+                0,
+            )
+            .await;
+
+            let (signal_client, join_response, signal_events) = match res {
+                Ok(Ok(res)) => res,
+                Ok(Err(err)) => {
+                    gst::warning!(CAT, imp: self, "Failed to reconnect: {err}");
+                    last_error = Some(err.to_string());
+                    continue;
+                }
+                Err(WaitError::FutureAborted) => return None,
+                Err(WaitError::FutureError(err)) => {
+                    last_error = Some(err.to_string());
+                    continue;
+                }
+            };
+
+            gst::info!(
+                CAT,
+                imp: self,
+                "Reconnected with JoinResponse: {:?}",
+                join_response
+            );
+
+            {
+                let mut connection = self.connection.lock().unwrap();
+                // Stopped meanwhile
+                let connection = connection.as_mut()?;
+                connection.signal_client = Arc::new(signal_client);
+                connection.pending_tracks.clear();
+                connection.early_candidates = Some(Vec::new());
+                connection.channels = None;
+                connection.participant_sid =
+                    join_response.participant.as_ref().map(|p| p.sid.clone());
             }
+
+            self.on_joined(&join_response);
+            self.post_message(
+                gst::Structure::builder("livekit-reconnected")
+                    .field("resume", false)
+                    .build(),
+            );
+
+            return Some(signal_events);
         }
+
+        self.raise_error(format!(
+            "Failed to reconnect: {}",
+            last_error.unwrap_or_default()
+        ));
+
+        None
+    }
+
+    fn disconnect(&self, reason: &str) {
+        gst::warning!(CAT, imp: self, "Disconnected by the server: {reason}");
+        self.post_message(
+            gst::Structure::builder("livekit-disconnected")
+                .field("reason", reason)
+                .build(),
+        );
+
+        *self.webrtcbin.lock().unwrap() = None;
+        self.obj()
+            .emit_by_name::<bool>("session-ended", &[&"unique"]);
+        self.raise_error(format!("Disconnected by the server: {reason}"));
     }
 
-    async fn on_signal_event(&self, event: proto::signal_response::Message) {
+    // Starts the media session once the room is joined
+    fn on_joined(&self, join_response: &proto::JoinResponse) {
+        if self.is_subscriber() {
+            self.obj()
+                .emit_by_name::<()>("session-started", &[&"unique", &"unique"]);
+            for participant in &join_response.other_participants {
+                self.on_participant(participant, false)
+            }
+        } else {
+            // Subscribers get offers from the server instead
+            self.obj().emit_by_name::<()>(
+                "session-requested",
+                &[
+                    &"unique",
+                    &"unique",
+                    &None::<gst_webrtc::WebRTCSessionDescription>,
+                ],
+            );
+        }
+    }
+
+    async fn on_signal_event(&self, event: proto::signal_response::Message) -> SignalAction {
         match event {
             proto::signal_response::Message::Answer(answer) => {
                 gst::debug!(CAT, imp: self, "Received publisher answer: {:?}", answer);
@@ -226,7 +547,7 @@ impl Signaller {
                     Ok(sdp) => sdp,
                     Err(_) => {
                         self.raise_error("Couldn't parse Answer SDP".to_string());
-                        return;
+                        return SignalAction::Continue;
                     }
                 };
                 let answer = gst_webrtc::WebRTCSessionDescription::new(
@@ -240,14 +561,14 @@ impl Signaller {
             proto::signal_response::Message::Offer(offer) => {
                 if !self.is_subscriber() {
                     gst::warning!(CAT, imp: self, "Ignoring subscriber offer in non-subscriber mode: {:?}", offer);
-                    return;
+                    return SignalAction::Continue;
                 }
                 gst::debug!(CAT, imp: self, "Received subscriber offer: {:?}", offer);
                 let sdp = match gst_sdp::SDPMessage::parse_buffer(offer.sdp.as_bytes()) {
                     Ok(sdp) => sdp,
                     Err(_) => {
                         self.raise_error("Couldn't parse Offer SDP".to_string());
-                        return;
+                        return SignalAction::Continue;
                     }
                 };
                 let offer = gst_webrtc::WebRTCSessionDescription::new(
@@ -262,7 +583,7 @@ impl Signaller {
                 gst::debug!(CAT, imp: self, "Received ice_candidate {:?}", trickle);
 
                 let Some(target) = self.signal_target() else {
-                    return;
+                    return SignalAction::Continue;
                 };
 
                 if target == trickle.target() {
@@ -280,6 +601,30 @@ impl Signaller {
 
             proto::signal_response::Message::ConnectionQuality(quality) => {
                 gst::debug!(CAT, imp: self, "Connection quality: {:?}", quality);
+
+                let local_sid = self
+                    .connection
+                    .lock()
+                    .unwrap()
+                    .as_ref()
+                    .and_then(|connection| connection.participant_sid.clone());
+                for info in &quality.updates {
+                    self.post_message(
+                        gst::Structure::builder("livekit-connection-quality")
+                            .field("participant-sid", &info.participant_sid)
+                            .field("local", local_sid.as_ref() == Some(&info.participant_sid))
+                            .field("quality", format!("{:?}", info.quality()).to_lowercase())
+                            .field("score", info.score)
+                            .build(),
+                    );
+                }
+            }
+
+            proto::signal_response::Message::RefreshToken(token) => {
+                gst::debug!(CAT, imp: self, "Refreshed token");
+                if let Some(connection) = &mut *self.connection.lock().unwrap() {
+                    connection.auth_token = token;
+                }
             }
 
             proto::signal_response::Message::TrackPublished(publish_res) => {
@@ -294,7 +639,7 @@ impl Signaller {
             proto::signal_response::Message::Update(update) => {
                 if !self.is_subscriber() {
                     gst::trace!(CAT, imp: self, "Ignoring update in non-subscriber mode: {:?}", update);
-                    return;
+                    return SignalAction::Continue;
                 }
                 gst::debug!(CAT, imp: self, "Update: {:?}", update);
                 for participant in update.participants {
@@ -304,10 +649,21 @@ impl Signaller {
 
             proto::signal_response::Message::Leave(leave) => {
                 gst::debug!(CAT, imp: self, "Leave: {:?}", leave);
+
+                // Servers shutting down or migrating participants let them
+                // join again
+                let reason = format!("Server asked to leave ({:?})", leave.reason());
+                return if leave.can_reconnect {
+                    SignalAction::Reconnect(reason)
+                } else {
+                    SignalAction::Disconnect(reason)
+                };
             }
 
             _ => {}
         }
+
+        SignalAction::Continue
     }
 
     fn send_sdp_answer(&self, _session_id: &str, sessdesc: &gst_webrtc::WebRTCSessionDescription) {
@@ -482,12 +838,19 @@ impl Signaller {
             .and_then(|meta| gst::Structure::from_str(meta).ok());
         match participant.state {
             x if x == proto::participant_info::State::Active as i32 => {
+                self.participants
+                    .lock()
+                    .unwrap()
+                    .insert(peer_sid.clone(), participant.clone());
+
+                let unsubscribed_tracks = self.unsubscribed_tracks.lock().unwrap();
                 let track_sids = participant
                     .tracks
                     .iter()
-                    .filter(|t| !t.muted)
+                    .filter(|t| !t.muted && !unsubscribed_tracks.contains(&t.sid))
                     .map(|t| t.sid.clone())
                     .collect::<Vec<_>>();
+                drop(unsubscribed_tracks);
                 let update = proto::UpdateSubscription {
                     track_sids: track_sids.clone(),
                     subscribe: true,
@@ -511,12 +874,184 @@ impl Signaller {
                 });
             }
             _ => {
+                self.participants.lock().unwrap().remove(peer_sid);
                 self.obj()
                     .emit_by_name::<()>("producer-removed", &[&peer_sid, &meta]);
             }
         }
     }
 
+    // Posts the user packets received on @channel as `livekit-data` messages
+    fn connect_data_channel(&self, channel: &gst_webrtc::WebRTCDataChannel) {
+        let label = data_channel::label(channel);
+        if label != RELIABLE_CHANNEL_LABEL && label != LOSSY_CHANNEL_LABEL {
+            return;
+        }
+
+        let weak_imp = self.downgrade();
+        data_channel::connect_messages(channel, move |label, msg| {
+            let DataChannelMessage::Data(data) = msg else {
+                return;
+            };
+            let Some(imp) = weak_imp.upgrade() else {
+                return;
+            };
+
+            match proto::DataPacket::decode(data.as_ref()) {
+                Ok(packet) => {
+                    if let Some(structure) = user_packet_message(packet, label) {
+                        imp.post_message(structure);
+                    }
+                }
+                Err(err) => {
+                    gst::warning!(CAT, imp: imp, "Invalid data packet on {label}: {err}");
+                }
+            }
+        });
+    }
+
+    /// Sends @payload to the participants of the room, or only to those of
+    /// @destination_identities if not empty
+    pub fn send_data(
+        &self,
+        payload: &[u8],
+        reliable: bool,
+        topic: Option<&str>,
+        destination_identities: Vec<String>,
+    ) -> bool {
+        let channel = {
+            let connection = self.connection.lock().unwrap();
+            let Some(channels) = connection.as_ref().and_then(|c| c.channels.as_ref()) else {
+                gst::warning!(CAT, imp: self, "No data channels, dropping data");
+                return false;
+            };
+
+            if reliable {
+                channels.reliable_channel.clone()
+            } else {
+                channels.lossy_channel.clone()
+            }
+        };
+
+        let packet = user_packet(payload, reliable, topic, destination_identities);
+
+        DataChannelMessage::Data(glib::Bytes::from_owned(packet.encode_to_vec())).send(&channel)
+    }
+
+    /// Describes the remote track sent with the media stream identifier
+    /// @msid, which LiveKit builds from the participant and track SIDs
+    pub fn track_info(&self, msid: &str) -> Option<gst::Structure> {
+        let participants = self.participants.lock().unwrap();
+        let (participant, track) = match msid.split_once('|') {
+            Some((participant_sid, track_sid)) => {
+                let participant = participants.get(participant_sid)?;
+                let track = participant.tracks.iter().find(|t| t.sid == track_sid)?;
+                (participant, track)
+            }
+            None => participants.values().find_map(|participant| {
+                participant
+                    .tracks
+                    .iter()
+                    .find(|t| t.sid == msid)
+                    .map(|track| (participant, track))
+            })?,
+        };
+
+        Some(
+            gst::Structure::builder("livekit-track")
+                .field("participant-sid", &participant.sid)
+                .field("participant-identity", &participant.identity)
+                .field("participant-name", &participant.name)
+                .field("track-sid", &track.sid)
+                .field("track-name", &track.name)
+                .field("source", format!("{:?}", track.source()).to_lowercase())
+                .field("simulcast", track.simulcast)
+                .build(),
+        )
+    }
+
+    fn track_participant_sid(&self, track_sid: &str) -> Option<String> {
+        self.participants
+            .lock()
+            .unwrap()
+            .values()
+            .find(|participant| participant.tracks.iter().any(|t| t.sid == track_sid))
+            .map(|participant| participant.sid.clone())
+    }
+
+    fn send_request(&self, request: proto::signal_request::Message) -> bool {
+        let Some(signal_client) = self.signal_client() else {
+            gst::warning!(CAT, imp: self, "Not connected, dropping {request:?}");
+            return false;
+        };
+
+        RUNTIME.spawn(async move { signal_client.send(request).await });
+
+        true
+    }
+
+    // Subscribes to or unsubscribes from a track of a remote participant,
+    // the server sending a new offer with the track added or removed
+    fn set_track_subscription(&self, track_sid: &str, subscribe: bool) -> bool {
+        let Some(participant_sid) = self.track_participant_sid(track_sid) else {
+            gst::warning!(CAT, imp: self, "Unknown track {track_sid}");
+            return false;
+        };
+
+        if subscribe {
+            self.unsubscribed_tracks.lock().unwrap().remove(track_sid);
+        } else {
+            self.unsubscribed_tracks
+                .lock()
+                .unwrap()
+                .insert(track_sid.to_string());
+        }
+
+        gst::info!(
+            CAT,
+            imp: self,
+            "{} track {track_sid}",
+            if subscribe { "Subscribing to" } else { "Unsubscribing from" }
+        );
+        self.send_request(proto::signal_request::Message::Subscription(
+            proto::UpdateSubscription {
+                track_sids: vec![track_sid.to_string()],
+                subscribe,
+                participant_tracks: vec![proto::ParticipantTracks {
+                    participant_sid,
+                    track_sids: vec![track_sid.to_string()],
+                }],
+            },
+        ))
+    }
+
+    // Selects the simulcast layer the server forwards for a video track
+    fn set_track_quality(&self, track_sid: &str, quality: &str) -> bool {
+        let quality = match quality {
+            "low" => proto::VideoQuality::Low,
+            "medium" => proto::VideoQuality::Medium,
+            "high" => proto::VideoQuality::High,
+            _ => {
+                gst::warning!(CAT, imp: self, "Invalid video quality {quality:?}");
+                return false;
+            }
+        };
+
+        if self.track_participant_sid(track_sid).is_none() {
+            gst::warning!(CAT, imp: self, "Unknown track {track_sid}");
+            return false;
+        }
+
+        gst::info!(CAT, imp: self, "Setting quality of {track_sid} to {quality:?}");
+        self.send_request(proto::signal_request::Message::TrackSetting(
+            proto::UpdateTrackSettings {
+                track_sids: vec![track_sid.to_string()],
+                quality: quality as i32,
+                ..Default::default()
+            },
+        ))
+    }
+
     async fn close_signal_client(signal_client: &signal_client::SignalClient) {
         signal_client
             .send(proto::signal_request::Message::Leave(proto::LeaveRequest {
@@ -619,21 +1154,6 @@ impl SignallableImpl for Signaller {
                 join_response
             );
 
-            let weak_imp = imp.downgrade();
-            let signal_task = RUNTIME.spawn(async move {
-                if let Some(imp) = weak_imp.upgrade() {
-                    imp.signal_task(signal_events).await;
-                }
-            });
-
-            if imp.is_subscriber() {
-                imp.obj()
-                    .emit_by_name::<()>("session-started", &[&"unique", &"unique"]);
-                for participant in &join_response.other_participants {
-                    imp.on_participant(participant, false)
-                }
-            }
-
             let weak_imp = imp.downgrade();
             imp.obj().connect_closure(
                 "webrtcbin-ready",
@@ -641,11 +1161,15 @@ impl SignallableImpl for Signaller {
                 glib::closure!(|_signaller: &super::LiveKitSignaller,
                                 _consumer_identifier: &str,
                                 webrtcbin: &gst::Element| {
+                    if let Some(imp) = weak_imp.upgrade() {
+                        *imp.webrtcbin.lock().unwrap() = Some(webrtcbin.downgrade());
+                    }
+
                     gst::info!(CAT, "Adding data channels");
                     let reliable_channel = webrtcbin.emit_by_name::<gst_webrtc::WebRTCDataChannel>(
                         "create-data-channel",
                         &[
-                            &"_reliable",
+                            &RELIABLE_CHANNEL_LABEL,
                             &gst::Structure::builder("config")
                                 .field("ordered", true)
                                 .build(),
@@ -654,7 +1178,7 @@ impl SignallableImpl for Signaller {
                     let lossy_channel = webrtcbin.emit_by_name::<gst_webrtc::WebRTCDataChannel>(
                         "create-data-channel",
                         &[
-                            &"_lossy",
+                            &LOSSY_CHANNEL_LABEL,
                             &gst::Structure::builder("config")
                                 .field("ordered", true)
                                 .field("max-retransmits", 0)
@@ -663,6 +1187,21 @@ impl SignallableImpl for Signaller {
                     );
 
                     if let Some(imp) = weak_imp.upgrade() {
+                        imp.connect_data_channel(&reliable_channel);
+                        imp.connect_data_channel(&lossy_channel);
+
+                        // The server sends the data of the room on its own
+                        // channels of the subscriber connection
+                        let weak_imp = weak_imp.clone();
+                        webrtcbin.connect("on-data-channel", false, move |args| {
+                            let channel = args[1].get::<gst_webrtc::WebRTCDataChannel>().unwrap();
+                            if let Some(imp) = weak_imp.upgrade() {
+                                imp.connect_data_channel(&channel);
+                            }
+
+                            None
+                        });
+
                         let mut connection = imp.connection.lock().unwrap();
                         if let Some(connection) = connection.as_mut() {
                             connection.channels = Some(Channels {
@@ -676,24 +1215,31 @@ impl SignallableImpl for Signaller {
 
             let connection = Connection {
                 signal_client,
-                signal_task,
+                signal_task: None,
                 pending_tracks: Default::default(),
                 early_candidates: Some(Vec::new()),
                 channels: None,
+                wsurl,
+                auth_token,
+                participant_sid: join_response.participant.as_ref().map(|p| p.sid.clone()),
             };
 
             if let Ok(mut sc) = imp.connection.lock() {
                 *sc = Some(connection);
             }
 
-            imp.obj().emit_by_name::<()>(
-                "session-requested",
-                &[
-                    &"unique",
-                    &"unique",
-                    &None::<gst_webrtc::WebRTCSessionDescription>,
-                ],
-            );
+            imp.on_joined(&join_response);
+
+            // Signal events are queued until then
+            let weak_imp = imp.downgrade();
+            let signal_task = RUNTIME.spawn(async move {
+                if let Some(imp) = weak_imp.upgrade() {
+                    imp.signal_task(signal_events).await;
+                }
+            });
+            if let Some(connection) = &mut *imp.connection.lock().unwrap() {
+                connection.signal_task = Some(signal_task);
+            }
         });
     }
 
@@ -755,9 +1301,14 @@ impl SignallableImpl for Signaller {
         }
 
         if let Some(connection) = self.connection.lock().unwrap().take() {
-            block_on(connection.signal_task).unwrap();
+            if let Some(signal_task) = connection.signal_task {
+                block_on(signal_task).unwrap();
+            }
             block_on(Self::close_signal_client(&connection.signal_client));
         }
+        *self.webrtcbin.lock().unwrap() = None;
+        self.participants.lock().unwrap().clear();
+        self.unsubscribed_tracks.lock().unwrap().clear();
     }
 
     fn end_session(&self, session_id: &str) {
@@ -774,6 +1325,114 @@ impl ObjectSubclass for Signaller {
 }
 
 impl ObjectImpl for Signaller {
+    fn signals() -> &'static [glib::subclass::Signal] {
+        static SIGNALS: Lazy<Vec<glib::subclass::Signal>> = Lazy::new(|| {
+            vec![
+                /**
+                 * GstLiveKitWebRTCSinkSignaller::element-message:
+                 * @structure: The content of the message
+                 *
+                 * Emitted with the connection quality updates of the server
+                 * (`livekit-connection-quality`), when the connection to the
+                 * server is lost and restored (`livekit-reconnecting`,
+                 * `livekit-reconnected` and `livekit-disconnected`) and with the
+                 * user data received from other participants (`livekit-data`).
+                 * The LiveKit elements post them as element messages.
+                 */
+                glib::subclass::Signal::builder("element-message")
+                    .param_types([gst::Structure::static_type()])
+                    .build(),
+                /**
+                 * GstLiveKitWebRTCSinkSignaller::send-data:
+                 * @payload: The data to send
+                 * @reliable: Whether to send it on the reliable or the lossy channel
+                 * @topic: (nullable): The topic of the data
+                 * @destination-identities: The identities of the participants
+                 *   to send the data to, an empty list sending it to all of them
+                 *
+                 * Sends user data to the participants of the room.
+                 *
+                 * Returns: whether the data was sent
+                 */
+                glib::subclass::Signal::builder("send-data")
+                    .action()
+                    .param_types([
+                        glib::Bytes::static_type(),
+                        bool::static_type(),
+                        String::static_type(),
+                        Vec::<String>::static_type(),
+                    ])
+                    .return_type::<bool>()
+                    .class_handler(|_token, args| {
+                        let this = args[0].get::<super::LiveKitSignaller>().unwrap();
+                        let payload = args[1].get::<glib::Bytes>().unwrap();
+                        let reliable = args[2].get::<bool>().unwrap();
+                        let topic = args[3].get::<Option<&str>>().unwrap();
+                        let destination_identities =
+                            args[4].get::<Vec<String>>().unwrap_or_default();
+
+                        Some(
+                            this.imp()
+                                .send_data(&payload, reliable, topic, destination_identities)
+                                .to_value(),
+                        )
+                    })
+                    .build(),
+                /**
+                 * GstLiveKitWebRTCSinkSignaller::set-track-subscription:
+                 * @track-sid: The SID of a track of a remote participant
+                 * @subscribe: Whether to subscribe to the track
+                 *
+                 * Subscribes to or unsubscribes from a track in consumer
+                 * role, the source pad of the track being added or removed
+                 * once the server renegotiated the session.
+                 *
+                 * Returns: whether the request was sent
+                 */
+                glib::subclass::Signal::builder("set-track-subscription")
+                    .action()
+                    .param_types([String::static_type(), bool::static_type()])
+                    .return_type::<bool>()
+                    .class_handler(|_token, args| {
+                        let this = args[0].get::<super::LiveKitSignaller>().unwrap();
+                        let track_sid = args[1].get::<&str>().unwrap();
+                        let subscribe = args[2].get::<bool>().unwrap();
+
+                        Some(
+                            this.imp()
+                                .set_track_subscription(track_sid, subscribe)
+                                .to_value(),
+                        )
+                    })
+                    .build(),
+                /**
+                 * GstLiveKitWebRTCSinkSignaller::set-track-quality:
+                 * @track-sid: The SID of a video track of a remote participant
+                 * @quality: `low`, `medium` or `high`
+                 *
+                 * Selects the simulcast layer the server sends for a video
+                 * track in consumer role.
+                 *
+                 * Returns: whether the request was sent
+                 */
+                glib::subclass::Signal::builder("set-track-quality")
+                    .action()
+                    .param_types([String::static_type(), String::static_type()])
+                    .return_type::<bool>()
+                    .class_handler(|_token, args| {
+                        let this = args[0].get::<super::LiveKitSignaller>().unwrap();
+                        let track_sid = args[1].get::<&str>().unwrap();
+                        let quality = args[2].get::<&str>().unwrap();
+
+                        Some(this.imp().set_track_quality(track_sid, quality).to_value())
+                    })
+                    .build(),
+            ]
+        });
+
+        SIGNALS.as_ref()
+    }
+
     fn properties() -> &'static [glib::ParamSpec] {
         static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
             vec![
@@ -930,3 +1589,488 @@ impl ObjectImpl for Signaller {
         }
     }
 }
+
+// The `livekit-data` message of a user packet received on the channel
+// labelled @label
+// The server forwards user packets without destination identities to all
+// the participants
+fn user_packet(
+    payload: &[u8],
+    reliable: bool,
+    topic: Option<&str>,
+    destination_identities: Vec<String>,
+) -> proto::DataPacket {
+    let kind = if reliable {
+        proto::data_packet::Kind::Reliable
+    } else {
+        proto::data_packet::Kind::Lossy
+    };
+
+    proto::DataPacket {
+        kind: kind as i32,
+        value: Some(proto::data_packet::Value::User(proto::UserPacket {
+            payload: payload.to_vec(),
+            topic: topic.map(String::from),
+            destination_identities,
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+}
+
+fn user_packet_message(packet: proto::DataPacket, label: &str) -> Option<gst::Structure> {
+    let Some(proto::data_packet::Value::User(user)) = packet.value else {
+        return None;
+    };
+
+    Some(
+        gst::Structure::builder("livekit-data")
+            .field("participant-sid", user.participant_sid)
+            .field("participant-identity", user.participant_identity)
+            .field("topic", user.topic)
+            .field("reliable", label == RELIABLE_CHANNEL_LABEL)
+            .field("payload", glib::Bytes::from_owned(user.payload))
+            .build(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::livekit_signaller::LiveKitSignaller;
+    use crate::signaller::test_utils::{connect_signals, expect, signal_channel, Signals};
+    use crate::signaller::SignallableExt;
+    use futures::{SinkExt, StreamExt};
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use warp::Filter;
+
+    // What the mock server does once the first client joined
+    #[derive(Clone, Copy)]
+    enum Script {
+        Leave { can_reconnect: bool },
+        Close,
+        ConnectionQuality,
+        // A remote publisher is in the room, requests are forwarded
+        Room,
+    }
+
+    fn response(message: proto::signal_response::Message) -> warp::ws::Message {
+        warp::ws::Message::binary(
+            proto::SignalResponse {
+                message: Some(message),
+            }
+            .encode_to_vec(),
+        )
+    }
+
+    // Mock of the LiveKit signal protocol, running @script on the first
+    // connection and sending the query parameters of every connection to
+    // @connections
+    fn serve(
+        script: Script,
+        connections: mpsc::Sender<HashMap<String, String>>,
+        requests: mpsc::Sender<proto::signal_request::Message>,
+    ) -> SocketAddr {
+        let connections = Arc::new(Mutex::new(connections));
+        let requests = Arc::new(Mutex::new(requests));
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let rtc = warp::path("rtc")
+            .and(warp::query::<HashMap<String, String>>())
+            .and(warp::ws())
+            .map(move |query: HashMap<String, String>, ws: warp::ws::Ws| {
+                let first = count.fetch_add(1, Ordering::SeqCst) == 0;
+                let connections = connections.clone();
+                let requests = requests.clone();
+
+                ws.on_upgrade(move |mut socket| async move {
+                    let resume = query.get("reconnect").map(String::as_str) == Some("1");
+                    let _ = connections.lock().unwrap().send(query);
+
+                    let hello = if resume {
+                        proto::signal_response::Message::Reconnect(Default::default())
+                    } else {
+                        proto::signal_response::Message::Join(proto::JoinResponse {
+                            participant: Some(proto::ParticipantInfo {
+                                sid: "PA_local".to_string(),
+                                ..Default::default()
+                            }),
+                            other_participants: match script {
+                                Script::Room => vec![remote_participant()],
+                                _ => vec![],
+                            },
+                            ping_interval: 30,
+                            ping_timeout: 60,
+                            ..Default::default()
+                        })
+                    };
+                    if socket.send(response(hello)).await.is_err() || !first {
+                        while let Some(Ok(_)) = socket.next().await {}
+                        return;
+                    }
+
+                    let message = match script {
+                        Script::Leave { can_reconnect } => {
+                            proto::signal_response::Message::Leave(proto::LeaveRequest {
+                                can_reconnect,
+                                reason: proto::DisconnectReason::ServerShutdown as i32,
+                                ..Default::default()
+                            })
+                        }
+                        Script::Close => {
+                            let _ = socket.close().await;
+                            return;
+                        }
+                        Script::ConnectionQuality => {
+                            proto::signal_response::Message::ConnectionQuality(
+                                proto::ConnectionQualityUpdate {
+                                    updates: vec![proto::ConnectionQualityInfo {
+                                        participant_sid: "PA_local".to_string(),
+                                        quality: proto::ConnectionQuality::Poor as i32,
+                                        score: 1.5,
+                                    }],
+                                },
+                            )
+                        }
+                        Script::Room => {
+                            while let Some(Ok(msg)) = socket.next().await {
+                                let request = proto::SignalRequest::decode(msg.as_bytes())
+                                    .ok()
+                                    .and_then(|request| request.message);
+                                if let Some(request) = request {
+                                    let _ = requests.lock().unwrap().send(request);
+                                }
+                            }
+                            return;
+                        }
+                    };
+                    let _ = socket.send(response(message)).await;
+
+                    // Keep the connection open until the client closes it
+                    while let Some(Ok(_)) = socket.next().await {}
+                })
+            });
+
+        let _guard = RUNTIME.enter();
+        let (addr, server) = warp::serve(rtc).bind_ephemeral(([127, 0, 0, 1], 0));
+        RUNTIME.spawn(server);
+
+        addr
+    }
+
+    fn remote_participant() -> proto::ParticipantInfo {
+        proto::ParticipantInfo {
+            sid: "PA_remote".to_string(),
+            identity: "remote".to_string(),
+            name: "Remote".to_string(),
+            state: proto::participant_info::State::Active as i32,
+            is_publisher: true,
+            tracks: vec![
+                proto::TrackInfo {
+                    sid: "TR_video".to_string(),
+                    name: "camera".to_string(),
+                    r#type: proto::TrackType::Video as i32,
+                    source: proto::TrackSource::Camera as i32,
+                    simulcast: true,
+                    ..Default::default()
+                },
+                proto::TrackInfo {
+                    sid: "TR_audio".to_string(),
+                    name: "microphone".to_string(),
+                    r#type: proto::TrackType::Audio as i32,
+                    source: proto::TrackSource::Microphone as i32,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    // Starts a signaller with @role, its signals and element messages are
+    // sent by name to the returned receiver
+    fn start_signaller(
+        addr: SocketAddr,
+        role: WebRTCSignallerRole,
+        messages: Arc<Mutex<Vec<gst::Structure>>>,
+    ) -> (LiveKitSignaller, Signals) {
+        let signaller = glib::Object::builder::<LiveKitSignaller>()
+            .property("role", role)
+            .property("ws-url", format!("ws://{addr}"))
+            .property("auth-token", "token")
+            .build();
+
+        let (tx, rx) = signal_channel();
+        connect_signals(
+            &signaller,
+            &[
+                "session-requested",
+                "session-started",
+                "session-ended",
+                "producer-added",
+                "error",
+            ],
+            &tx,
+        );
+        signaller.connect("element-message", false, move |args| {
+            let structure = args[1].get::<gst::Structure>().unwrap();
+            let _ = tx
+                .lock()
+                .unwrap()
+                .send((structure.name().to_string(), String::new()));
+            messages.lock().unwrap().push(structure);
+            None
+        });
+
+        signaller.start();
+
+        (signaller, rx)
+    }
+
+    // The next subscription or track settings request sent by the client
+    fn next_request(
+        requests: &mpsc::Receiver<proto::signal_request::Message>,
+    ) -> proto::signal_request::Message {
+        loop {
+            let request = requests.recv_timeout(Duration::from_secs(10)).unwrap();
+            if matches!(
+                request,
+                proto::signal_request::Message::Subscription(_)
+                    | proto::signal_request::Message::TrackSetting(_)
+            ) {
+                return request;
+            }
+        }
+    }
+
+    #[test]
+    fn test_leave_reconnect() {
+        gst::init().unwrap();
+
+        let (tx, connections) = mpsc::channel();
+        let addr = serve(
+            Script::Leave {
+                can_reconnect: true,
+            },
+            tx,
+            mpsc::channel().0,
+        );
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let (signaller, rx) =
+            start_signaller(addr, WebRTCSignallerRole::Producer, messages.clone());
+
+        // The room is joined again with a new media session
+        expect(
+            &rx,
+            &[
+                "session-requested",
+                "livekit-reconnecting",
+                "session-ended",
+                "session-requested",
+                "livekit-reconnected",
+            ],
+        );
+
+        for _ in 0..2 {
+            let query = connections.recv_timeout(Duration::from_secs(10)).unwrap();
+            assert!(!query.contains_key("reconnect"));
+        }
+        assert!(!messages.lock().unwrap()[0].get::<bool>("resume").unwrap());
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_leave_disconnect() {
+        gst::init().unwrap();
+
+        let (tx, _connections) = mpsc::channel();
+        let addr = serve(
+            Script::Leave {
+                can_reconnect: false,
+            },
+            tx,
+            mpsc::channel().0,
+        );
+        let (signaller, rx) =
+            start_signaller(addr, WebRTCSignallerRole::Producer, Default::default());
+
+        expect(
+            &rx,
+            &[
+                "session-requested",
+                "livekit-disconnected",
+                "session-ended",
+                "error",
+            ],
+        );
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_resume() {
+        gst::init().unwrap();
+
+        let (tx, connections) = mpsc::channel();
+        let addr = serve(Script::Close, tx, mpsc::channel().0);
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let (signaller, rx) =
+            start_signaller(addr, WebRTCSignallerRole::Producer, messages.clone());
+
+        // The media session is kept
+        expect(
+            &rx,
+            &[
+                "session-requested",
+                "livekit-reconnecting",
+                "livekit-reconnected",
+            ],
+        );
+
+        let query = connections.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert!(!query.contains_key("reconnect"));
+        let query = connections.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(query.get("reconnect").map(String::as_str), Some("1"));
+        assert_eq!(query.get("sid").map(String::as_str), Some("PA_local"));
+        assert!(messages.lock().unwrap()[1].get::<bool>("resume").unwrap());
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_connection_quality() {
+        gst::init().unwrap();
+
+        let (tx, _connections) = mpsc::channel();
+        let addr = serve(Script::ConnectionQuality, tx, mpsc::channel().0);
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let (signaller, rx) =
+            start_signaller(addr, WebRTCSignallerRole::Producer, messages.clone());
+
+        expect(&rx, &["session-requested", "livekit-connection-quality"]);
+
+        let quality = messages.lock().unwrap()[0].clone();
+        assert_eq!(quality.get::<&str>("participant-sid").unwrap(), "PA_local");
+        assert!(quality.get::<bool>("local").unwrap());
+        assert_eq!(quality.get::<&str>("quality").unwrap(), "poor");
+        assert_eq!(quality.get::<f32>("score").unwrap(), 1.5);
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_track_subscription() {
+        gst::init().unwrap();
+
+        let (tx, _connections) = mpsc::channel();
+        let (requests_tx, requests) = mpsc::channel();
+        let addr = serve(Script::Room, tx, requests_tx);
+        let (signaller, rx) =
+            start_signaller(addr, WebRTCSignallerRole::Consumer, Default::default());
+
+        expect(&rx, &["session-started", "producer-added"]);
+        let proto::signal_request::Message::Subscription(subscription) = next_request(&requests)
+        else {
+            panic!("expected a subscription");
+        };
+        assert!(subscription.subscribe);
+        assert_eq!(subscription.track_sids, ["TR_video", "TR_audio"]);
+
+        let info = signaller.track_info("PA_remote|TR_video").unwrap();
+        assert_eq!(info.get::<&str>("participant-identity").unwrap(), "remote");
+        assert_eq!(info.get::<&str>("track-sid").unwrap(), "TR_video");
+        assert_eq!(info.get::<&str>("source").unwrap(), "camera");
+        assert!(info.get::<bool>("simulcast").unwrap());
+        assert!(signaller.track_info("PA_remote|TR_unknown").is_none());
+
+        assert!(signaller.emit_by_name::<bool>("set-track-subscription", &[&"TR_audio", &false]));
+        let proto::signal_request::Message::Subscription(subscription) = next_request(&requests)
+        else {
+            panic!("expected a subscription");
+        };
+        assert!(!subscription.subscribe);
+        assert_eq!(subscription.track_sids, ["TR_audio"]);
+        assert_eq!(
+            subscription.participant_tracks[0].participant_sid,
+            "PA_remote"
+        );
+
+        assert!(signaller.emit_by_name::<bool>("set-track-quality", &[&"TR_video", &"low"]));
+        let proto::signal_request::Message::TrackSetting(settings) = next_request(&requests) else {
+            panic!("expected track settings");
+        };
+        assert_eq!(settings.track_sids, ["TR_video"]);
+        assert_eq!(settings.quality(), proto::VideoQuality::Low);
+
+        assert!(!signaller.emit_by_name::<bool>("set-track-quality", &[&"TR_video", &"best"]));
+        assert!(!signaller.emit_by_name::<bool>("set-track-subscription", &[&"TR_unknown", &true]));
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_data_packet() {
+        gst::init().unwrap();
+
+        let packet = proto::DataPacket {
+            kind: proto::data_packet::Kind::Reliable as i32,
+            value: Some(proto::data_packet::Value::User(proto::UserPacket {
+                participant_sid: "PA_remote".to_string(),
+                participant_identity: "remote".to_string(),
+                payload: b"hello".to_vec(),
+                topic: Some("chat".to_string()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let packet = proto::DataPacket::decode(packet.encode_to_vec().as_slice()).unwrap();
+
+        let message = user_packet_message(packet, RELIABLE_CHANNEL_LABEL).unwrap();
+        assert_eq!(message.name(), "livekit-data");
+        assert_eq!(message.get::<&str>("participant-sid").unwrap(), "PA_remote");
+        assert_eq!(
+            message.get::<&str>("participant-identity").unwrap(),
+            "remote"
+        );
+        assert_eq!(message.get::<Option<&str>>("topic").unwrap(), Some("chat"));
+        assert!(message.get::<bool>("reliable").unwrap());
+        assert_eq!(
+            message.get::<glib::Bytes>("payload").unwrap().as_ref(),
+            b"hello"
+        );
+
+        // Only user packets are posted
+        let speakers = proto::DataPacket {
+            value: Some(proto::data_packet::Value::Speaker(Default::default())),
+            ..Default::default()
+        };
+        assert!(user_packet_message(speakers, LOSSY_CHANNEL_LABEL).is_none());
+
+        // An empty list of identities sends to all the participants
+        let packet = user_packet(b"hello", false, None, vec![]);
+        assert_eq!(packet.kind, proto::data_packet::Kind::Lossy as i32);
+        let Some(proto::data_packet::Value::User(user)) = packet.value else {
+            panic!("not a user packet");
+        };
+        assert!(user.destination_identities.is_empty());
+        let packet = user_packet(b"hello", true, None, vec!["remote".to_string()]);
+        let Some(proto::data_packet::Value::User(user)) = packet.value else {
+            panic!("not a user packet");
+        };
+        assert_eq!(user.destination_identities, ["remote"]);
+
+        // Nothing is sent before joining the room
+        let signaller = LiveKitSignaller::new_producer();
+        assert!(!signaller.emit_by_name::<bool>(
+            "send-data",
+            &[
+                &glib::Bytes::from_static(b"hello"),
+                &true,
+                &None::<String>,
+                &Vec::<String>::new(),
+            ],
+        ));
+    }
+}
@@ -2,6 +2,8 @@
 
 use crate::signaller::{Signallable, WebRTCSignallerRole};
 use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
 
 mod imp;
 
@@ -21,6 +23,52 @@ impl LiveKitSignaller {
     pub fn new_producer() -> Self {
         Self::new(WebRTCSignallerRole::Producer)
     }
+
+    /// Posts the `element-message` structures of the signaller as element
+    /// messages of `element`
+    pub fn forward_messages(&self, element: &gst::Element) {
+        let element = element.downgrade();
+        self.connect("element-message", false, move |args| {
+            let structure = args[1].get::<gst::Structure>().unwrap();
+            if let Some(element) = element.upgrade() {
+                let _ = element.post_message(
+                    gst::message::Element::builder(structure)
+                        .src(&element)
+                        .build(),
+                );
+            }
+
+            None
+        });
+    }
+
+    /// Describes the remote participant track sent with the media stream
+    /// identifier `msid`, if known
+    pub fn track_info(&self, msid: &str) -> Option<gst::Structure> {
+        self.imp().track_info(msid)
+    }
+}
+
+/// The `send-data` action signal of the LiveKit elements, forwarded to the
+/// one of their signaller, an empty list of destination identities sends the
+/// data to all the participants
+pub fn send_data_signal() -> glib::subclass::Signal {
+    glib::subclass::Signal::builder("send-data")
+        .action()
+        .param_types([
+            glib::Bytes::static_type(),
+            bool::static_type(),
+            String::static_type(),
+            Vec::<String>::static_type(),
+        ])
+        .return_type::<bool>()
+        .class_handler(|_token, args| {
+            let element = args[0].get::<gst::Element>().unwrap();
+            let signaller = element.property::<Signallable>("signaller");
+
+            signaller.emit_by_name_with_values("send-data", &args[1..])
+        })
+        .build()
 }
 
 impl Default for LiveKitSignaller {
@@ -0,0 +1,671 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::signaller::{Signallable, SignallableImpl, WebRTCSignallerRole};
+use crate::RUNTIME;
+use futures::channel::mpsc;
+use futures::prelude::*;
+use gst::glib;
+use gst::glib::prelude::*;
+use gst::subclass::prelude::*;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::task;
+
+static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
+    gst::DebugCategory::new(
+        "webrtc-local-signaller",
+        gst::DebugColorFlags::empty(),
+        Some("WebRTC in-process signaller"),
+    )
+});
+
+const DEFAULT_CHANNEL_NAME: &str = "default";
+
+// The channels of all the signallers of the process, by name
+static CHANNELS: Lazy<Mutex<HashMap<String, Channel>>> = Lazy::new(Default::default);
+
+// What a signaller has to emit, in order, from its dispatch task
+#[derive(Debug)]
+enum Event {
+    ProducerAdded(String),
+    ProducerRemoved(String),
+    SessionRequested {
+        session_id: String,
+        peer_id: String,
+    },
+    SessionStarted {
+        session_id: String,
+        peer_id: String,
+    },
+    Description {
+        session_id: String,
+        sdp: gst_webrtc::WebRTCSessionDescription,
+    },
+    Ice {
+        session_id: String,
+        candidate: String,
+        sdp_m_line_index: u32,
+        sdp_mid: Option<String>,
+    },
+    SessionEnded(String),
+    Error(String),
+}
+
+struct Peer {
+    id: String,
+    events: mpsc::UnboundedSender<Event>,
+}
+
+impl Peer {
+    fn send(&self, event: Event) {
+        // The receiver is only gone once the peer is stopping
+        let _ = self.events.unbounded_send(event);
+    }
+}
+
+struct Session {
+    producer_id: String,
+    consumer_id: String,
+}
+
+impl Session {
+    fn other(&self, peer_id: &str) -> &str {
+        if self.producer_id == peer_id {
+            &self.consumer_id
+        } else {
+            &self.producer_id
+        }
+    }
+}
+
+#[derive(Default)]
+struct Channel {
+    producer: Option<Peer>,
+    consumers: Vec<Peer>,
+    // Only told about the producer coming and going
+    listeners: Vec<Peer>,
+    sessions: HashMap<String, Session>,
+}
+
+impl Channel {
+    fn peer(&self, peer_id: &str) -> Option<&Peer> {
+        self.producer
+            .iter()
+            .chain(self.consumers.iter())
+            .find(|peer| peer.id == peer_id)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.producer.is_none() && self.consumers.is_empty() && self.listeners.is_empty()
+    }
+
+    fn notify_consumers(&self, event: impl Fn() -> Event) {
+        for peer in self.consumers.iter().chain(self.listeners.iter()) {
+            peer.send(event());
+        }
+    }
+
+    fn start_session(&mut self, consumer_id: &str) {
+        let (Some(producer), Some(consumer)) = (
+            self.producer.as_ref(),
+            self.consumers.iter().find(|peer| peer.id == consumer_id),
+        ) else {
+            return;
+        };
+
+        let session_id = uuid::Uuid::new_v4().to_string();
+
+        // The consumer is told first so that it is ready for the offer
+        consumer.send(Event::SessionStarted {
+            session_id: session_id.clone(),
+            peer_id: producer.id.clone(),
+        });
+        producer.send(Event::SessionRequested {
+            session_id: session_id.clone(),
+            peer_id: consumer.id.clone(),
+        });
+
+        self.sessions.insert(
+            session_id,
+            Session {
+                producer_id: producer.id.clone(),
+                consumer_id: consumer.id.clone(),
+            },
+        );
+    }
+
+    // Removes the session, notifying the peer other than @peer_id
+    fn end_session(&mut self, session_id: &str, peer_id: &str) -> bool {
+        let Some(session) = self.sessions.remove(session_id) else {
+            return false;
+        };
+
+        if let Some(other) = self.peer(session.other(peer_id)) {
+            other.send(Event::SessionEnded(session_id.to_string()));
+        }
+
+        true
+    }
+
+    // Routes @event of the session to the peer other than @peer_id
+    fn forward(&self, session_id: &str, peer_id: &str, event: Event) -> bool {
+        let Some(session) = self.sessions.get(session_id) else {
+            return false;
+        };
+
+        if let Some(other) = self.peer(session.other(peer_id)) {
+            other.send(event);
+        }
+
+        true
+    }
+}
+
+#[derive(Clone)]
+struct Settings {
+    channel_name: String,
+    role: WebRTCSignallerRole,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            channel_name: DEFAULT_CHANNEL_NAME.to_string(),
+            role: WebRTCSignallerRole::Consumer,
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    // The channel and peer ID the signaller is registered with
+    registration: Option<(String, String)>,
+    dispatch_task_handle: Option<task::JoinHandle<()>>,
+}
+
+#[derive(Default)]
+pub struct Signaller {
+    state: Mutex<State>,
+    settings: Mutex<Settings>,
+}
+
+impl Signaller {
+    fn handle_event(&self, event: Event) {
+        gst::trace!(CAT, imp: self, "Handling {event:?}");
+
+        let obj = self.obj();
+        match event {
+            Event::ProducerAdded(peer_id) => {
+                obj.emit_by_name::<()>(
+                    "producer-added",
+                    &[&peer_id, &None::<gst::Structure>, &true],
+                );
+            }
+            Event::ProducerRemoved(peer_id) => {
+                obj.emit_by_name::<()>("producer-removed", &[&peer_id, &None::<gst::Structure>]);
+            }
+            Event::SessionRequested {
+                session_id,
+                peer_id,
+            } => {
+                obj.emit_by_name::<()>(
+                    "session-requested",
+                    &[
+                        &session_id,
+                        &peer_id,
+                        &None::<gst_webrtc::WebRTCSessionDescription>,
+                    ],
+                );
+            }
+            Event::SessionStarted {
+                session_id,
+                peer_id,
+            } => {
+                obj.emit_by_name::<()>("session-started", &[&session_id, &peer_id]);
+            }
+            Event::Description { session_id, sdp } => {
+                obj.emit_by_name::<()>("session-description", &[&session_id, &sdp]);
+            }
+            Event::Ice {
+                session_id,
+                candidate,
+                sdp_m_line_index,
+                sdp_mid,
+            } => {
+                obj.emit_by_name::<()>(
+                    "handle-ice",
+                    &[&session_id, &sdp_m_line_index, &sdp_mid, &candidate],
+                );
+            }
+            Event::SessionEnded(session_id) => {
+                obj.emit_by_name::<bool>("session-ended", &[&session_id]);
+            }
+            Event::Error(error) => {
+                obj.emit_by_name::<()>("error", &[&error]);
+            }
+        }
+    }
+
+    // Calls @func on the channel the signaller is registered with, along with
+    // its peer ID
+    fn with_channel<T>(&self, func: impl FnOnce(&mut Channel, &str) -> T) -> Option<T> {
+        let (channel_name, peer_id) = self.state.lock().unwrap().registration.clone()?;
+        let mut channels = CHANNELS.lock().unwrap();
+        let channel = channels.get_mut(&channel_name)?;
+
+        Some(func(channel, &peer_id))
+    }
+
+    // Adds @peer to @channel, returns false if it was refused
+    fn register(channel: &mut Channel, peer: Peer, role: WebRTCSignallerRole) -> bool {
+        match role {
+            WebRTCSignallerRole::Producer => {
+                if let Some(ref producer) = channel.producer {
+                    peer.send(Event::Error(format!(
+                        "Channel already has producer {}",
+                        producer.id
+                    )));
+                    return false;
+                }
+
+                channel.notify_consumers(|| Event::ProducerAdded(peer.id.clone()));
+
+                channel.producer = Some(peer);
+                let consumer_ids = channel
+                    .consumers
+                    .iter()
+                    .map(|consumer| consumer.id.clone())
+                    .collect::<Vec<_>>();
+                for consumer_id in consumer_ids {
+                    channel.start_session(&consumer_id);
+                }
+            }
+            WebRTCSignallerRole::Consumer => {
+                let consumer_id = peer.id.clone();
+                if let Some(ref producer) = channel.producer {
+                    peer.send(Event::ProducerAdded(producer.id.clone()));
+                }
+
+                channel.consumers.push(peer);
+                channel.start_session(&consumer_id);
+            }
+            WebRTCSignallerRole::Listener => {
+                if let Some(ref producer) = channel.producer {
+                    peer.send(Event::ProducerAdded(producer.id.clone()));
+                }
+
+                channel.listeners.push(peer);
+            }
+        }
+
+        true
+    }
+
+    fn unregister(channel: &mut Channel, peer_id: &str) {
+        let session_ids = channel
+            .sessions
+            .iter()
+            .filter(|(_, session)| session.producer_id == peer_id || session.consumer_id == peer_id)
+            .map(|(session_id, _)| session_id.clone())
+            .collect::<Vec<_>>();
+        for session_id in session_ids {
+            channel.end_session(&session_id, peer_id);
+        }
+
+        if channel
+            .producer
+            .as_ref()
+            .is_some_and(|producer| producer.id == peer_id)
+        {
+            channel.producer = None;
+            channel.notify_consumers(|| Event::ProducerRemoved(peer_id.to_string()));
+        } else {
+            channel.consumers.retain(|consumer| consumer.id != peer_id);
+            channel.listeners.retain(|listener| listener.id != peer_id);
+        }
+    }
+}
+
+impl SignallableImpl for Signaller {
+    fn start(&self) {
+        let settings = self.settings.lock().unwrap().clone();
+        let mut state = self.state.lock().unwrap();
+        if state.registration.is_some() {
+            gst::debug!(CAT, imp: self, "Already started");
+            return;
+        }
+
+        let peer_id = uuid::Uuid::new_v4().to_string();
+        gst::info!(
+            CAT,
+            imp: self,
+            "Joining channel {} as {:?} {peer_id}",
+            settings.channel_name,
+            settings.role
+        );
+
+        let (events, mut receiver) = mpsc::unbounded();
+        let weak = self.obj().downgrade();
+        state.dispatch_task_handle = Some(RUNTIME.spawn(async move {
+            while let Some(event) = receiver.next().await {
+                let Some(obj) = weak.upgrade() else {
+                    break;
+                };
+                obj.imp().handle_event(event);
+            }
+        }));
+
+        let mut channels = CHANNELS.lock().unwrap();
+        let channel = channels.entry(settings.channel_name.clone()).or_default();
+        let registered = Self::register(
+            channel,
+            Peer {
+                id: peer_id.clone(),
+                events,
+            },
+            settings.role,
+        );
+
+        if registered {
+            state.registration = Some((settings.channel_name, peer_id));
+        }
+    }
+
+    fn stop(&self) {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some((channel_name, peer_id)) = state.registration.take() {
+            gst::info!(CAT, imp: self, "Leaving channel {channel_name}");
+
+            let mut channels = CHANNELS.lock().unwrap();
+            if let Some(channel) = channels.get_mut(&channel_name) {
+                Self::unregister(channel, &peer_id);
+                if channel.is_empty() {
+                    channels.remove(&channel_name);
+                }
+            }
+        }
+
+        if let Some(handle) = state.dispatch_task_handle.take() {
+            handle.abort();
+        }
+    }
+
+    fn send_sdp(&self, session_id: &str, sdp: &gst_webrtc::WebRTCSessionDescription) {
+        gst::debug!(CAT, imp: self, "Sending SDP {:?} for {session_id}", sdp.type_());
+
+        let event = Event::Description {
+            session_id: session_id.to_string(),
+            sdp: sdp.clone(),
+        };
+        if self.with_channel(|channel, peer_id| channel.forward(session_id, peer_id, event))
+            != Some(true)
+        {
+            gst::warning!(CAT, imp: self, "Can't send SDP for unknown session {session_id}");
+        }
+    }
+
+    fn add_ice(
+        &self,
+        session_id: &str,
+        candidate: &str,
+        sdp_m_line_index: u32,
+        sdp_mid: Option<String>,
+    ) {
+        let event = Event::Ice {
+            session_id: session_id.to_string(),
+            candidate: candidate.to_string(),
+            sdp_m_line_index,
+            sdp_mid,
+        };
+        if self.with_channel(|channel, peer_id| channel.forward(session_id, peer_id, event))
+            != Some(true)
+        {
+            gst::debug!(
+                CAT,
+                imp: self,
+                "Dropping candidate of unknown session {session_id}"
+            );
+        }
+    }
+
+    fn end_session(&self, session_id: &str) {
+        gst::debug!(CAT, imp: self, "Ending session {session_id}");
+
+        self.with_channel(|channel, peer_id| channel.end_session(session_id, peer_id));
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for Signaller {
+    const NAME: &'static str = "GstLocalSignaller";
+    type Type = super::LocalSignaller;
+    type ParentType = glib::Object;
+    type Interfaces = (Signallable,);
+}
+
+impl ObjectImpl for Signaller {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+            vec![
+                glib::ParamSpecString::builder("channel-name")
+                    .nick("Channel name")
+                    .blurb("Name of the channel the producer and its consumers meet in")
+                    .default_value(DEFAULT_CHANNEL_NAME)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecEnum::builder_with_default("role", WebRTCSignallerRole::Consumer)
+                    .nick("Role")
+                    .blurb("Whether the signaller is the producer or a consumer of the channel")
+                    .mutable_ready()
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let mut settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "channel-name" => {
+                settings.channel_name = value
+                    .get::<Option<String>>()
+                    .unwrap()
+                    .unwrap_or_else(|| DEFAULT_CHANNEL_NAME.to_string());
+            }
+            "role" => {
+                settings.role = value.get().unwrap();
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "channel-name" => settings.channel_name.to_value(),
+            "role" => settings.role.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local_signaller::LocalSignaller;
+    use crate::signaller::test_utils::{self, next, session_description, Signals};
+    use crate::signaller::SignallableExt;
+    use gst::prelude::*;
+    use std::sync::mpsc as sync_mpsc;
+    use std::time::Duration;
+
+    const SIGNALS: &[&str] = &[
+        "producer-added",
+        "producer-removed",
+        "session-requested",
+        "session-started",
+        "session-description",
+        "session-ended",
+        "handle-ice",
+        "error",
+    ];
+
+    fn start_signaller(signaller: &LocalSignaller) -> Signals {
+        test_utils::start_signaller(signaller, SIGNALS)
+    }
+
+    #[test]
+    fn test_negotiation() {
+        gst::init().unwrap();
+
+        let consumer = LocalSignaller::new_consumer("test-negotiation");
+        let consumer_rx = start_signaller(&consumer);
+        let producer = LocalSignaller::new_producer("test-negotiation");
+        let producer_rx = start_signaller(&producer);
+
+        next(&consumer_rx, "producer-added");
+        let session_id = next(&consumer_rx, "session-started");
+        assert_eq!(next(&producer_rx, "session-requested"), session_id);
+
+        producer.send_sdp(
+            &session_id,
+            &session_description(gst_webrtc::WebRTCSDPType::Offer),
+        );
+        producer.add_ice(&session_id, "candidate:1", 0, Some("0".to_string()));
+        assert_eq!(next(&consumer_rx, "session-description"), session_id);
+        assert_eq!(next(&consumer_rx, "handle-ice"), session_id);
+
+        consumer.send_sdp(
+            &session_id,
+            &session_description(gst_webrtc::WebRTCSDPType::Answer),
+        );
+        assert_eq!(next(&producer_rx, "session-description"), session_id);
+
+        consumer.end_session(&session_id);
+        assert_eq!(next(&producer_rx, "session-ended"), session_id);
+
+        consumer.stop();
+        producer.stop();
+        assert!(!CHANNELS.lock().unwrap().contains_key("test-negotiation"));
+    }
+
+    #[test]
+    fn test_producer_stopped() {
+        gst::init().unwrap();
+
+        let producer = LocalSignaller::new_producer("test-producer-stopped");
+        let producer_rx = start_signaller(&producer);
+        let consumers = (0..2)
+            .map(|_| {
+                let consumer = LocalSignaller::new_consumer("test-producer-stopped");
+                let rx = start_signaller(&consumer);
+                (consumer, rx)
+            })
+            .collect::<Vec<_>>();
+
+        let mut session_ids = vec![];
+        for (_, rx) in &consumers {
+            next(rx, "producer-added");
+            session_ids.push(next(rx, "session-started"));
+        }
+        for session_id in &session_ids {
+            assert_eq!(&next(&producer_rx, "session-requested"), session_id);
+        }
+
+        producer.stop();
+        for ((_, rx), session_id) in consumers.iter().zip(&session_ids) {
+            assert_eq!(&next(rx, "session-ended"), session_id);
+            next(rx, "producer-removed");
+        }
+
+        // A new producer serves the consumers that are still waiting
+        let producer = LocalSignaller::new_producer("test-producer-stopped");
+        let producer_rx = start_signaller(&producer);
+        for (_, rx) in &consumers {
+            next(rx, "producer-added");
+            let session_id = next(rx, "session-started");
+            assert!(!session_ids.contains(&session_id));
+        }
+        next(&producer_rx, "session-requested");
+        next(&producer_rx, "session-requested");
+
+        producer.stop();
+        for (consumer, _) in consumers {
+            consumer.stop();
+        }
+    }
+
+    #[test]
+    fn test_webrtcsink_to_webrtcsrc() {
+        gst::init().unwrap();
+        crate::webrtcsrc::register(None).unwrap();
+
+        let sink = crate::webrtcsink::BaseWebRTCSink::with_signaller(
+            LocalSignaller::new_producer("test-webrtcsink-to-webrtcsrc").upcast(),
+        );
+        let src = gst::ElementFactory::make("webrtcsrc")
+            .property(
+                "signaller",
+                LocalSignaller::new_consumer("test-webrtcsink-to-webrtcsrc"),
+            )
+            .build()
+            .unwrap();
+
+        let producer = gst::Pipeline::new();
+        let videotestsrc = gst::ElementFactory::make("videotestsrc")
+            .property("is-live", true)
+            .build()
+            .unwrap();
+        producer
+            .add_many([&videotestsrc, sink.upcast_ref::<gst::Element>()])
+            .unwrap();
+        videotestsrc.link(&sink).unwrap();
+
+        let (tx, rx) = sync_mpsc::channel();
+        let tx = Mutex::new(tx);
+        let consumer = gst::Pipeline::new();
+        consumer.add(&src).unwrap();
+        let weak_consumer = consumer.downgrade();
+        src.connect_pad_added(move |_, pad| {
+            let Some(consumer) = weak_consumer.upgrade() else {
+                return;
+            };
+            let fakesink = gst::ElementFactory::make("fakesink").build().unwrap();
+            consumer.add(&fakesink).unwrap();
+            fakesink.sync_state_with_parent().unwrap();
+            pad.link(&fakesink.static_pad("sink").unwrap()).unwrap();
+
+            let tx = tx.lock().unwrap().clone();
+            pad.add_probe(gst::PadProbeType::BUFFER, move |_, _| {
+                let _ = tx.send(());
+                gst::PadProbeReturn::Remove
+            });
+        });
+
+        consumer.set_state(gst::State::Playing).unwrap();
+        producer.set_state(gst::State::Playing).unwrap();
+
+        rx.recv_timeout(Duration::from_secs(30)).unwrap();
+
+        producer.set_state(gst::State::Null).unwrap();
+        consumer.set_state(gst::State::Null).unwrap();
+    }
+
+    #[test]
+    fn test_second_producer() {
+        gst::init().unwrap();
+
+        let producer = LocalSignaller::new_producer("test-second-producer");
+        let _producer_rx = start_signaller(&producer);
+        let second = LocalSignaller::new_producer("test-second-producer");
+        let second_rx = start_signaller(&second);
+
+        next(&second_rx, "error");
+
+        second.stop();
+        producer.stop();
+    }
+}
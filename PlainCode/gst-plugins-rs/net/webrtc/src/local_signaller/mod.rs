@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::signaller::{Signallable, WebRTCSignallerRole};
+use gst::glib;
+
+mod imp;
+
+glib::wrapper! {
+    /// Signaller connecting the producer and consumers of a channel living in
+    /// the same process, without any signalling server.
+    ///
+    /// ```no_run
+    /// use gst::prelude::*;
+    /// use gstrswebrtc::local_signaller::LocalSignaller;
+    /// use gstrswebrtc::webrtcsink::BaseWebRTCSink;
+    ///
+    /// let sink = BaseWebRTCSink::with_signaller(LocalSignaller::new_producer("demo").upcast());
+    /// let src = gst::ElementFactory::make("webrtcsrc")
+    ///     .property("signaller", LocalSignaller::new_consumer("demo"))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub struct LocalSignaller(ObjectSubclass<imp::Signaller>) @implements Signallable;
+}
+
+impl LocalSignaller {
+    fn new(channel_name: &str, role: WebRTCSignallerRole) -> Self {
+        glib::Object::builder()
+            .property("channel-name", channel_name)
+            .property("role", role)
+            .build()
+    }
+
+    pub fn new_consumer(channel_name: &str) -> Self {
+        Self::new(channel_name, WebRTCSignallerRole::Consumer)
+    }
+
+    pub fn new_producer(channel_name: &str) -> Self {
+        Self::new(channel_name, WebRTCSignallerRole::Producer)
+    }
+}
+
+impl Default for LocalSignaller {
+    fn default() -> Self {
+        glib::Object::new()
+    }
+}
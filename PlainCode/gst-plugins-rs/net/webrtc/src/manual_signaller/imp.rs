@@ -0,0 +1,690 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::signaller::{Signallable, SignallableImpl, WebRTCSignallerRole};
+use crate::RUNTIME;
+use anyhow::{Context, Error};
+use futures::channel::mpsc;
+use futures::prelude::*;
+use futures::stream;
+use gst::glib;
+use gst::glib::prelude::*;
+use gst::subclass::prelude::*;
+use gst_plugin_webrtc_protocol as p;
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::task;
+
+static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
+    gst::DebugCategory::new(
+        "webrtc-manual-signaller",
+        gst::DebugColorFlags::empty(),
+        Some("WebRTC manual signaller"),
+    )
+});
+
+// Path standing for the standard input or output
+const STDIO_PATH: &str = "-";
+// Peer ID of the other side of sessions started by an offer
+const REMOTE_PEER_ID: &str = "remote";
+
+#[derive(Clone, Default)]
+struct Settings {
+    input: Option<String>,
+    output: Option<String>,
+    role: WebRTCSignallerRole,
+    session_id: Option<String>,
+}
+
+#[derive(Default)]
+struct State {
+    sender: Option<mpsc::UnboundedSender<p::IncomingMessage>>,
+    read_task_handle: Option<task::JoinHandle<()>>,
+    write_task_handle: Option<task::JoinHandle<()>>,
+    sessions: HashSet<String>,
+}
+
+#[derive(Default)]
+pub struct Signaller {
+    state: Mutex<State>,
+    settings: Mutex<Settings>,
+}
+
+impl Signaller {
+    fn raise_error(&self, msg: String) {
+        gst::error!(CAT, imp: self, "{msg}");
+        self.obj().emit_by_name::<()>("error", &[&msg]);
+    }
+
+    fn send(&self, msg: p::IncomingMessage) {
+        let state = self.state.lock().unwrap();
+        if let Some(sender) = state.sender.as_ref() {
+            let _ = sender.unbounded_send(msg);
+        } else {
+            gst::warning!(CAT, imp: self, "Not started, dropping {msg:?}");
+        }
+    }
+
+    async fn read_input(
+        weak: glib::WeakRef<super::ManualSignaller>,
+        input: Option<String>,
+    ) -> Result<(), Error> {
+        let reader: Box<dyn AsyncRead + Send + Unpin> = match input.as_deref() {
+            None | Some(STDIO_PATH) => Box::new(tokio::io::stdin()),
+            Some(path) => Box::new(
+                tokio::fs::File::open(path)
+                    .await
+                    .with_context(|| format!("Failed to open input {path}"))?,
+            ),
+        };
+
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            let Some(obj) = weak.upgrade() else {
+                break;
+            };
+
+            obj.imp().handle_line(line.trim());
+        }
+
+        Ok(())
+    }
+
+    async fn write_output(
+        output: Option<String>,
+        mut receiver: mpsc::UnboundedReceiver<p::IncomingMessage>,
+    ) -> Result<(), Error> {
+        // Opening a named pipe blocks until it has a reader, so only do it
+        // once there is something to write
+        let Some(msg) = receiver.next().await else {
+            return Ok(());
+        };
+
+        let mut writer: Box<dyn AsyncWrite + Send + Unpin> = match output.as_deref() {
+            None | Some(STDIO_PATH) => Box::new(tokio::io::stdout()),
+            Some(path) => Box::new(
+                tokio::fs::File::create(path)
+                    .await
+                    .with_context(|| format!("Failed to open output {path}"))?,
+            ),
+        };
+
+        let mut messages = stream::once(async { msg }).chain(receiver);
+        while let Some(msg) = messages.next().await {
+            let mut line = serde_json::to_string(&msg)?;
+            line.push('\n');
+            writer.write_all(line.as_bytes()).await?;
+            writer.flush().await?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_line(&self, line: &str) {
+        // Blank lines and comments make hand written inputs easier to read
+        if line.is_empty() || line.starts_with('#') {
+            return;
+        }
+
+        match serde_json::from_str::<p::OutgoingMessage>(line) {
+            Ok(msg) => self.handle_message(msg),
+            Err(err) => gst::warning!(CAT, imp: self, "Ignoring invalid message {line}: {err}"),
+        }
+    }
+
+    fn handle_message(&self, msg: p::OutgoingMessage) {
+        gst::debug!(CAT, imp: self, "Received {msg:?}");
+
+        let role = self.settings.lock().unwrap().role;
+        match msg {
+            p::OutgoingMessage::StartSession {
+                session_id,
+                peer_id,
+            } => {
+                if role != WebRTCSignallerRole::Producer {
+                    gst::warning!(CAT, imp: self, "Ignoring session request as {role:?}");
+                    return;
+                }
+
+                self.state
+                    .lock()
+                    .unwrap()
+                    .sessions
+                    .insert(session_id.clone());
+                self.obj().emit_by_name::<()>(
+                    "session-requested",
+                    &[
+                        &session_id,
+                        &peer_id,
+                        &None::<gst_webrtc::WebRTCSessionDescription>,
+                    ],
+                );
+            }
+            p::OutgoingMessage::SessionStarted {
+                peer_id,
+                session_id,
+            } => {
+                if self
+                    .state
+                    .lock()
+                    .unwrap()
+                    .sessions
+                    .insert(session_id.clone())
+                {
+                    self.obj()
+                        .emit_by_name::<()>("session-started", &[&session_id, &peer_id]);
+                }
+            }
+            p::OutgoingMessage::EndSession(p::EndSessionMessage { session_id }) => {
+                if self.state.lock().unwrap().sessions.remove(&session_id) {
+                    gst::info!(CAT, imp: self, "Session {session_id} ended");
+                    self.obj()
+                        .emit_by_name::<bool>("session-ended", &[&session_id]);
+                }
+            }
+            p::OutgoingMessage::Peer(p::PeerMessage {
+                session_id,
+                peer_message,
+            }) => self.handle_peer_message(session_id, peer_message, role),
+            p::OutgoingMessage::Error { details } => {
+                self.raise_error(format!("Error message from input: {details}"));
+            }
+            p::OutgoingMessage::Welcome { .. }
+            | p::OutgoingMessage::PeerStatusChanged(_)
+            | p::OutgoingMessage::List { .. } => {
+                gst::debug!(CAT, imp: self, "Ignoring server message");
+            }
+        }
+    }
+
+    fn handle_peer_message(
+        &self,
+        session_id: String,
+        peer_message: p::PeerMessageInner,
+        role: WebRTCSignallerRole,
+    ) {
+        let known = self.state.lock().unwrap().sessions.contains(&session_id);
+
+        match peer_message {
+            p::PeerMessageInner::Sdp(sdp) => {
+                let (sdp_type, sdp) = match sdp {
+                    p::SdpMessage::Offer { sdp } => (gst_webrtc::WebRTCSDPType::Offer, sdp),
+                    p::SdpMessage::Answer { sdp } => (gst_webrtc::WebRTCSDPType::Answer, sdp),
+                };
+                let sdp = match gst_sdp::SDPMessage::parse_buffer(sdp.as_bytes()) {
+                    Ok(sdp) => sdp,
+                    Err(err) => {
+                        self.raise_error(format!("Error parsing SDP: {sdp} {err:?}"));
+                        return;
+                    }
+                };
+                let desc = gst_webrtc::WebRTCSessionDescription::new(sdp_type, sdp);
+
+                if known {
+                    self.obj()
+                        .emit_by_name::<()>("session-description", &[&session_id, &desc]);
+                    return;
+                }
+
+                if sdp_type != gst_webrtc::WebRTCSDPType::Offer {
+                    gst::warning!(CAT, imp: self, "Ignoring answer for unknown session {session_id}");
+                    return;
+                }
+
+                // An offer for an unknown session starts it
+                self.state
+                    .lock()
+                    .unwrap()
+                    .sessions
+                    .insert(session_id.clone());
+                if role == WebRTCSignallerRole::Producer {
+                    self.obj().emit_by_name::<()>(
+                        "session-requested",
+                        &[&session_id, &REMOTE_PEER_ID, &desc],
+                    );
+                } else {
+                    self.obj()
+                        .emit_by_name::<()>("session-started", &[&session_id, &REMOTE_PEER_ID]);
+                    self.obj()
+                        .emit_by_name::<()>("session-description", &[&session_id, &desc]);
+                }
+            }
+            p::PeerMessageInner::Ice {
+                candidate,
+                sdp_m_line_index,
+            } => {
+                if !known {
+                    gst::warning!(
+                        CAT,
+                        imp: self,
+                        "Ignoring candidate for unknown session {session_id}"
+                    );
+                    return;
+                }
+
+                let sdp_mid: Option<String> = None;
+                self.obj().emit_by_name::<()>(
+                    "handle-ice",
+                    &[&session_id, &sdp_m_line_index, &sdp_mid, &candidate],
+                );
+            }
+        }
+    }
+}
+
+impl SignallableImpl for Signaller {
+    fn start(&self) {
+        let settings = self.settings.lock().unwrap().clone();
+        let mut state = self.state.lock().unwrap();
+        if state.sender.is_some() {
+            gst::debug!(CAT, imp: self, "Already started");
+            return;
+        }
+
+        gst::info!(
+            CAT,
+            imp: self,
+            "Starting as {:?}, reading from {} and writing to {}",
+            settings.role,
+            settings.input.as_deref().unwrap_or(STDIO_PATH),
+            settings.output.as_deref().unwrap_or(STDIO_PATH),
+        );
+
+        let (sender, receiver) = mpsc::unbounded();
+        state.sender = Some(sender);
+
+        let weak = self.obj().downgrade();
+        state.write_task_handle = Some(RUNTIME.spawn(async move {
+            if let Err(err) = Self::write_output(settings.output, receiver).await {
+                if let Some(obj) = weak.upgrade() {
+                    obj.imp().raise_error(format!("Error writing: {err:?}"));
+                }
+            }
+        }));
+
+        let weak = self.obj().downgrade();
+        let session_id = settings
+            .session_id
+            .filter(|_| settings.role == WebRTCSignallerRole::Producer);
+        state.read_task_handle = Some(RUNTIME.spawn(async move {
+            if let Some(session_id) = session_id {
+                let Some(obj) = weak.upgrade() else {
+                    return;
+                };
+                obj.imp().handle_message(p::OutgoingMessage::StartSession {
+                    session_id,
+                    peer_id: REMOTE_PEER_ID.to_string(),
+                });
+            }
+
+            if let Err(err) = Self::read_input(weak.clone(), settings.input).await {
+                if let Some(obj) = weak.upgrade() {
+                    obj.imp().raise_error(format!("Error reading: {err:?}"));
+                }
+            }
+        }));
+    }
+
+    fn stop(&self) {
+        gst::info!(CAT, imp: self, "Stopping now");
+
+        let mut state = self.state.lock().unwrap();
+        let write_task_handle = state.write_task_handle.take();
+        if let Some(handle) = state.read_task_handle.take() {
+            handle.abort();
+        }
+        state.sessions.clear();
+
+        // Let the pending messages be written before returning
+        if let Some(sender) = state.sender.take() {
+            sender.close_channel();
+            if let Some(handle) = write_task_handle {
+                RUNTIME.block_on(async move {
+                    if let Err(err) = handle.await {
+                        gst::warning!(CAT, imp: self, "Error while joining write task: {err}");
+                    }
+                });
+            }
+        }
+    }
+
+    fn send_sdp(&self, session_id: &str, sdp: &gst_webrtc::WebRTCSessionDescription) {
+        gst::debug!(CAT, imp: self, "Sending SDP {:?} for {session_id}", sdp.type_());
+
+        let text = match sdp.sdp().as_text() {
+            Ok(text) => text,
+            Err(err) => {
+                self.raise_error(format!("Failed to serialize SDP: {err:?}"));
+                return;
+            }
+        };
+        let sdp = match sdp.type_() {
+            gst_webrtc::WebRTCSDPType::Offer => p::SdpMessage::Offer { sdp: text },
+            gst_webrtc::WebRTCSDPType::Answer => p::SdpMessage::Answer { sdp: text },
+            sdp_type => {
+                self.raise_error(format!("Unsupported SDP type {sdp_type:?}"));
+                return;
+            }
+        };
+
+        self.state
+            .lock()
+            .unwrap()
+            .sessions
+            .insert(session_id.to_string());
+        self.send(p::IncomingMessage::Peer(p::PeerMessage {
+            session_id: session_id.to_string(),
+            peer_message: p::PeerMessageInner::Sdp(sdp),
+        }));
+    }
+
+    fn add_ice(
+        &self,
+        session_id: &str,
+        candidate: &str,
+        sdp_m_line_index: u32,
+        _sdp_mid: Option<String>,
+    ) {
+        self.send(p::IncomingMessage::Peer(p::PeerMessage {
+            session_id: session_id.to_string(),
+            peer_message: p::PeerMessageInner::Ice {
+                candidate: candidate.to_string(),
+                sdp_m_line_index,
+            },
+        }));
+    }
+
+    fn end_session(&self, session_id: &str) {
+        gst::debug!(CAT, imp: self, "Ending session {session_id}");
+
+        if self.state.lock().unwrap().sessions.remove(session_id) {
+            self.send(p::IncomingMessage::EndSession(p::EndSessionMessage {
+                session_id: session_id.to_string(),
+            }));
+        }
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for Signaller {
+    const NAME: &'static str = "GstManualSignaller";
+    type Type = super::ManualSignaller;
+    type ParentType = glib::Object;
+    type Interfaces = (Signallable,);
+}
+
+impl ObjectImpl for Signaller {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+            vec![
+                glib::ParamSpecString::builder("input")
+                    .nick("Input")
+                    .blurb("File or named pipe the messages are read from, the standard input if unset or \"-\"")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("output")
+                    .nick("Output")
+                    .blurb("File or named pipe the messages are written to, the standard output if unset or \"-\"")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecEnum::builder_with_default("role", WebRTCSignallerRole::default())
+                    .nick("Role")
+                    .blurb("Whether the signaller produces or consumes streams")
+                    .mutable_ready()
+                    .build(),
+                /**
+                 * GstManualSignaller:session-id:
+                 *
+                 * In the producer role, the ID of a session requested as
+                 * soon as the signaller starts, as if a `startSession`
+                 * message had been read.
+                 */
+                glib::ParamSpecString::builder("session-id")
+                    .nick("Session ID")
+                    .blurb("ID of the session the producer starts right away")
+                    .mutable_ready()
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let mut settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "input" => {
+                settings.input = value.get().expect("type checked upstream");
+            }
+            "output" => {
+                settings.output = value.get().expect("type checked upstream");
+            }
+            "role" => {
+                settings.role = value.get().expect("type checked upstream");
+            }
+            "session-id" => {
+                settings.session_id = value.get().expect("type checked upstream");
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "input" => settings.input.to_value(),
+            "output" => settings.output.to_value(),
+            "role" => settings.role.to_value(),
+            "session-id" => settings.session_id.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manual_signaller::ManualSignaller;
+    use crate::signaller::test_utils::{self, expect_args, session_description, Signals, SDP};
+    use crate::signaller::SignallableExt;
+    use serde_json::{json, Value};
+    use std::path::PathBuf;
+    use std::sync::mpsc as sync_mpsc;
+    use std::time::Duration;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{name}-{}.jsonl", uuid::Uuid::new_v4()))
+    }
+
+    // Starts @signaller reading @lines, its signals are sent to the returned
+    // receiver along with their session ID
+    fn start_signaller(signaller: &ManualSignaller, lines: &[Value], output: &PathBuf) -> Signals {
+        let input = temp_path("input");
+        let mut contents = "# recorded negotiation\n\n".to_string();
+        for line in lines {
+            contents.push_str(&format!("{line}\n"));
+        }
+        std::fs::write(&input, contents).unwrap();
+
+        signaller.set_property("input", input.to_str().unwrap());
+        signaller.set_property("output", output.to_str().unwrap());
+
+        test_utils::start_signaller(
+            signaller,
+            &[
+                "session-requested",
+                "session-started",
+                "session-description",
+                "session-ended",
+                "handle-ice",
+                "error",
+            ],
+        )
+    }
+
+    fn read_output(output: &PathBuf) -> Vec<Value> {
+        std::fs::read_to_string(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn candidate(session_id: &str) -> Value {
+        json!({
+            "type": "peer",
+            "sessionId": session_id,
+            "ice": { "candidate": "candidate:1 1 UDP 2122252543 127.0.0.1 5000 typ host", "sdpMLineIndex": 0 },
+        })
+    }
+
+    #[test]
+    fn test_producer() {
+        gst::init().unwrap();
+
+        let output = temp_path("output");
+        let signaller = ManualSignaller::new_producer();
+        let rx = start_signaller(
+            &signaller,
+            &[
+                json!({ "type": "startSession", "peerId": "consumer", "sessionId": "session1" }),
+                json!({ "type": "peer", "sessionId": "session1", "sdp": { "type": "answer", "sdp": SDP } }),
+                candidate("session1"),
+                candidate("unknown"),
+                json!({ "type": "peer", "sessionId": "unknown", "sdp": { "type": "answer", "sdp": SDP } }),
+                json!({ "type": "endSession", "sessionId": "session1" }),
+            ],
+            &output,
+        );
+
+        expect_args(
+            &rx,
+            &[
+                ("session-requested", "session1"),
+                ("session-description", "session1"),
+                ("handle-ice", "session1"),
+                ("session-ended", "session1"),
+            ],
+        );
+
+        signaller.send_sdp(
+            "session2",
+            &session_description(gst_webrtc::WebRTCSDPType::Offer),
+        );
+        signaller.add_ice("session2", "candidate:1", 1, None);
+        signaller.end_session("session2");
+        signaller.stop();
+
+        assert_eq!(
+            read_output(&output),
+            [
+                json!({ "type": "peer", "sessionId": "session2", "sdp": { "type": "offer", "sdp": SDP } }),
+                json!({ "type": "peer", "sessionId": "session2", "ice": { "candidate": "candidate:1", "sdpMLineIndex": 1 } }),
+                json!({ "type": "endSession", "sessionId": "session2" }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_producer_session_id() {
+        gst::init().unwrap();
+
+        let output = temp_path("output");
+        let signaller = ManualSignaller::new_producer();
+        signaller.set_property("session-id", "session1");
+        let rx = start_signaller(&signaller, &[candidate("session1")], &output);
+
+        expect_args(
+            &rx,
+            &[
+                ("session-requested", "session1"),
+                ("handle-ice", "session1"),
+            ],
+        );
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_consumer() {
+        gst::init().unwrap();
+
+        let output = temp_path("output");
+        let signaller = ManualSignaller::new_consumer();
+        let rx = start_signaller(
+            &signaller,
+            &[
+                json!({ "type": "peer", "sessionId": "session1", "sdp": { "type": "offer", "sdp": SDP } }),
+                candidate("session1"),
+                json!({ "type": "list", "producers": [] }),
+                json!({ "type": "endSession", "sessionId": "session1" }),
+                json!({ "type": "error", "details": "bye" }),
+            ],
+            &output,
+        );
+
+        expect_args(
+            &rx,
+            &[
+                ("session-started", "session1"),
+                ("session-description", "session1"),
+                ("handle-ice", "session1"),
+                ("session-ended", "session1"),
+                ("error", "Error message from input: bye"),
+            ],
+        );
+
+        signaller.send_sdp(
+            "session1",
+            &session_description(gst_webrtc::WebRTCSDPType::Answer),
+        );
+        signaller.stop();
+
+        assert_eq!(
+            read_output(&output),
+            [
+                json!({ "type": "peer", "sessionId": "session1", "sdp": { "type": "answer", "sdp": SDP } }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        gst::init().unwrap();
+
+        let output = temp_path("output");
+        let signaller = ManualSignaller::new_consumer();
+        signaller.set_property("input", "/nonexistent/input.jsonl");
+        signaller.set_property("output", output.to_str().unwrap());
+
+        let (tx, rx) = sync_mpsc::channel();
+        let tx = Mutex::new(tx);
+        signaller.connect("error", false, move |args| {
+            let _ = tx.lock().unwrap().send(args[1].get::<String>().unwrap());
+            None
+        });
+        signaller.start();
+
+        let error = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert!(error.contains("/nonexistent/input.jsonl"), "{error}");
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_message_format() {
+        // The messages written by a peer are read as is by the other one
+        let msg = p::IncomingMessage::Peer(p::PeerMessage {
+            session_id: "session1".to_string(),
+            peer_message: p::PeerMessageInner::Sdp(p::SdpMessage::Offer {
+                sdp: SDP.to_string(),
+            }),
+        });
+
+        let read =
+            serde_json::from_str::<p::OutgoingMessage>(&serde_json::to_string(&msg).unwrap())
+                .unwrap();
+        assert!(matches!(read, p::OutgoingMessage::Peer(_)));
+    }
+}
@@ -0,0 +1,1009 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::signaller::{Signallable, SignallableImpl, WebRTCSignallerRole};
+use crate::utils::{gvalue_to_json, serialize_json_object};
+use crate::RUNTIME;
+use anyhow::{anyhow, Context, Error};
+use gst::glib;
+use gst::glib::prelude::*;
+use gst::subclass::prelude::*;
+use gst_plugin_webrtc_protocol as p;
+use once_cell::sync::Lazy;
+use rumqttc::{
+    AsyncClient, Event, EventLoop, LastWill, MqttOptions, Outgoing, Packet, Publish, QoS,
+    TlsConfiguration, Transport,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::task;
+use url::Url;
+
+static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
+    gst::DebugCategory::new(
+        "webrtc-mqtt-signaller",
+        gst::DebugColorFlags::empty(),
+        Some("WebRTC MQTT signaller"),
+    )
+});
+
+// Placeholder of the topic patterns replaced by the ID of a peer
+const PEER_ID_PLACEHOLDER: &str = "{peer-id}";
+
+const DEFAULT_BROKER_URI: &str = "mqtt://127.0.0.1:1883";
+const DEFAULT_PEER_TOPIC_PATTERN: &str = "gstreamer/webrtc/peers/{peer-id}";
+const DEFAULT_PRESENCE_TOPIC_PATTERN: &str = "gstreamer/webrtc/producers/{peer-id}";
+const DEFAULT_QOS: u32 = 1;
+const DEFAULT_KEEP_ALIVE: u32 = 30;
+// How long pending messages are given to be delivered when stopping
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+// How long to wait before connecting again to the broker
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Clone)]
+struct Settings {
+    broker_uri: String,
+    role: WebRTCSignallerRole,
+    peer_id: Option<String>,
+    producer_peer_id: Option<String>,
+    peer_topic_pattern: String,
+    presence_topic_pattern: String,
+    qos: u32,
+    keep_alive: u32,
+    username: Option<String>,
+    password: Option<String>,
+    cafile: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            broker_uri: DEFAULT_BROKER_URI.to_string(),
+            role: WebRTCSignallerRole::default(),
+            peer_id: None,
+            producer_peer_id: None,
+            peer_topic_pattern: DEFAULT_PEER_TOPIC_PATTERN.to_string(),
+            presence_topic_pattern: DEFAULT_PRESENCE_TOPIC_PATTERN.to_string(),
+            qos: DEFAULT_QOS,
+            keep_alive: DEFAULT_KEEP_ALIVE,
+            username: None,
+            password: None,
+            cafile: None,
+        }
+    }
+}
+
+impl Settings {
+    fn qos(&self) -> QoS {
+        match self.qos {
+            0 => QoS::AtMostOnce,
+            1 => QoS::AtLeastOnce,
+            _ => QoS::ExactlyOnce,
+        }
+    }
+
+    fn peer_topic(&self, peer_id: &str) -> String {
+        topic(&self.peer_topic_pattern, peer_id)
+    }
+
+    fn presence_topic(&self, peer_id: &str) -> String {
+        topic(&self.presence_topic_pattern, peer_id)
+    }
+
+    fn options(&self, peer_id: &str) -> Result<MqttOptions, Error> {
+        for pattern in [&self.peer_topic_pattern, &self.presence_topic_pattern] {
+            if !pattern.contains(PEER_ID_PLACEHOLDER) {
+                return Err(anyhow!(
+                    "Topic pattern {pattern} doesn't contain {PEER_ID_PLACEHOLDER}"
+                ));
+            }
+        }
+
+        let uri = Url::parse(&self.broker_uri)
+            .with_context(|| format!("Invalid broker URI {}", self.broker_uri))?;
+        let host = uri
+            .host_str()
+            .ok_or_else(|| anyhow!("Broker URI {uri} has no host"))?;
+        let tls = match uri.scheme() {
+            "mqtt" | "tcp" => false,
+            "mqtts" | "ssl" => true,
+            scheme => return Err(anyhow!("Unsupported broker URI scheme {scheme}")),
+        };
+        let port = uri.port().unwrap_or(if tls { 8883 } else { 1883 });
+
+        let mut options = MqttOptions::new(peer_id, host, port);
+        options.set_keep_alive(Duration::from_secs(self.keep_alive.into()));
+        if let Some(ref username) = self.username {
+            options.set_credentials(username, self.password.as_deref().unwrap_or_default());
+        }
+
+        if tls {
+            let tls_config = match self.cafile {
+                Some(ref cafile) => TlsConfiguration::SimpleNative {
+                    ca: std::fs::read(cafile)
+                        .with_context(|| format!("Failed to read CA file {cafile}"))?,
+                    client_auth: None,
+                },
+                None => TlsConfiguration::Native,
+            };
+            options.set_transport(Transport::tls_with_config(tls_config));
+        }
+
+        // The presence of a producer is cleared by the broker if it is lost
+        if self.role == WebRTCSignallerRole::Producer {
+            options.set_last_will(LastWill::new(
+                self.presence_topic(peer_id),
+                Vec::<u8>::new(),
+                self.qos(),
+                true,
+            ));
+        }
+
+        Ok(options)
+    }
+}
+
+fn topic(pattern: &str, peer_id: &str) -> String {
+    pattern.replace(PEER_ID_PLACEHOLDER, peer_id)
+}
+
+// Extracts the ID of the peer @topic was built for from @pattern
+fn peer_id_from_topic<'a>(pattern: &str, topic: &'a str) -> Option<&'a str> {
+    let (prefix, suffix) = pattern.split_once(PEER_ID_PLACEHOLDER)?;
+    let peer_id = topic.strip_prefix(prefix)?.strip_suffix(suffix)?;
+
+    (!peer_id.is_empty() && !peer_id.contains('/')).then_some(peer_id)
+}
+
+#[derive(Default)]
+struct State {
+    client: Option<AsyncClient>,
+    event_loop_handle: Option<task::JoinHandle<()>>,
+    peer_id: Option<String>,
+    // The peer of each session, by ID
+    sessions: HashMap<String, String>,
+    producers: HashSet<String>,
+}
+
+#[derive(Default)]
+pub struct Signaller {
+    state: Mutex<State>,
+    settings: Mutex<Settings>,
+}
+
+impl Signaller {
+    fn raise_error(&self, msg: String) {
+        gst::error!(CAT, imp: self, "{msg}");
+        self.obj().emit_by_name::<()>("error", &[&msg]);
+    }
+
+    fn publish(&self, topic: String, retain: bool, payload: Vec<u8>) {
+        let qos = self.settings.lock().unwrap().qos();
+        let state = self.state.lock().unwrap();
+        let Some(ref client) = state.client else {
+            gst::warning!(CAT, imp: self, "Not connected, dropping message to {topic}");
+            return;
+        };
+
+        gst::trace!(CAT, imp: self, "Publishing to {topic}");
+        if let Err(err) = client.try_publish(topic, qos, retain, payload) {
+            drop(state);
+            self.raise_error(format!("Failed to publish: {err}"));
+        }
+    }
+
+    // Sends @msg to the inbox of @peer_id
+    fn send(&self, peer_id: &str, msg: p::OutgoingMessage) {
+        let topic = self.settings.lock().unwrap().peer_topic(peer_id);
+        match serde_json::to_vec(&msg) {
+            Ok(payload) => self.publish(topic, false, payload),
+            Err(err) => self.raise_error(format!("Failed to serialize {msg:?}: {err}")),
+        }
+    }
+
+    fn session_peer(&self, session_id: &str) -> Option<String> {
+        self.state.lock().unwrap().sessions.get(session_id).cloned()
+    }
+
+    async fn run_event_loop(weak: glib::WeakRef<super::MqttSignaller>, mut event_loop: EventLoop) {
+        let mut connected = false;
+
+        loop {
+            let event = event_loop.poll().await;
+            let Some(obj) = weak.upgrade() else {
+                break;
+            };
+
+            match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    gst::info!(CAT, obj: obj, "Connected to the broker");
+                    connected = true;
+                    obj.imp().on_connected();
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    obj.imp().handle_publish(publish);
+                }
+                Ok(Event::Outgoing(Outgoing::Disconnect)) => {
+                    gst::debug!(CAT, obj: obj, "Disconnected from the broker");
+                    break;
+                }
+                Ok(_) => (),
+                Err(err) => {
+                    // The event loop connects again on the next poll, subscriptions
+                    // are renewed once it is connected
+                    if !connected {
+                        obj.imp()
+                            .raise_error(format!("Failed to connect to the broker: {err}"));
+                        break;
+                    }
+
+                    gst::warning!(CAT, obj: obj, "Connection to the broker lost: {err}");
+                    drop(obj);
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        }
+    }
+
+    fn on_connected(&self) {
+        let settings = self.settings.lock().unwrap().clone();
+        let state = self.state.lock().unwrap();
+        let (Some(client), Some(peer_id)) = (state.client.clone(), state.peer_id.clone()) else {
+            return;
+        };
+        drop(state);
+
+        let qos = settings.qos();
+        let mut subscriptions = vec![];
+        if settings.role != WebRTCSignallerRole::Listener {
+            subscriptions.push(settings.peer_topic(&peer_id));
+        }
+        if settings.role != WebRTCSignallerRole::Producer {
+            subscriptions.push(settings.presence_topic("+"));
+        }
+
+        for topic in subscriptions {
+            gst::debug!(CAT, imp: self, "Subscribing to {topic}");
+            if let Err(err) = client.try_subscribe(topic, qos) {
+                self.raise_error(format!("Failed to subscribe: {err}"));
+                return;
+            }
+        }
+
+        if settings.role == WebRTCSignallerRole::Producer {
+            let meta = self
+                .obj()
+                .emit_by_name::<Option<gst::Structure>>("request-meta", &[])
+                .and_then(|meta| gvalue_to_json(&meta.to_value()));
+            let status = p::PeerStatus {
+                roles: vec![p::PeerRole::Producer],
+                meta,
+                peer_id: Some(peer_id.clone()),
+            };
+
+            match serde_json::to_vec(&status) {
+                Ok(payload) => self.publish(settings.presence_topic(&peer_id), true, payload),
+                Err(err) => self.raise_error(format!("Failed to serialize presence: {err}")),
+            }
+        }
+    }
+
+    fn handle_publish(&self, publish: Publish) {
+        let settings = self.settings.lock().unwrap().clone();
+
+        if let Some(producer_id) =
+            peer_id_from_topic(&settings.presence_topic_pattern, &publish.topic)
+        {
+            self.handle_presence(producer_id, &publish.payload, !publish.retain, &settings);
+            return;
+        }
+
+        match serde_json::from_slice::<p::OutgoingMessage>(&publish.payload) {
+            Ok(msg) => self.handle_message(msg),
+            Err(err) => {
+                gst::warning!(
+                    CAT,
+                    imp: self,
+                    "Ignoring invalid message on {}: {err}",
+                    publish.topic
+                );
+            }
+        }
+    }
+
+    fn handle_presence(
+        &self,
+        producer_id: &str,
+        payload: &[u8],
+        new_connection: bool,
+        settings: &Settings,
+    ) {
+        // Empty retained messages clear the presence of a producer
+        if payload.is_empty() {
+            let mut state = self.state.lock().unwrap();
+            if !state.producers.remove(producer_id) {
+                return;
+            }
+            let session_ids = state
+                .sessions
+                .iter()
+                .filter(|(_, peer_id)| *peer_id == producer_id)
+                .map(|(session_id, _)| session_id.clone())
+                .collect::<Vec<_>>();
+            for session_id in &session_ids {
+                state.sessions.remove(session_id);
+            }
+            drop(state);
+
+            gst::info!(CAT, imp: self, "Producer {producer_id} left");
+            for session_id in session_ids {
+                self.obj()
+                    .emit_by_name::<bool>("session-ended", &[&session_id]);
+            }
+            self.obj()
+                .emit_by_name::<()>("producer-removed", &[&producer_id, &None::<gst::Structure>]);
+
+            return;
+        }
+
+        let status = match serde_json::from_slice::<p::PeerStatus>(payload) {
+            Ok(status) => status,
+            Err(err) => {
+                gst::warning!(CAT, imp: self, "Ignoring invalid presence of {producer_id}: {err}");
+                return;
+            }
+        };
+        if !status.producing() {
+            return;
+        }
+
+        if !self
+            .state
+            .lock()
+            .unwrap()
+            .producers
+            .insert(producer_id.to_string())
+        {
+            return;
+        }
+
+        gst::info!(CAT, imp: self, "Producer {producer_id} is available");
+        let meta = status.meta.and_then(|meta| match meta {
+            serde_json::Value::Object(v) => Some(serialize_json_object(&v)),
+            _ => {
+                gst::error!(CAT, imp: self, "Invalid json value: {meta:?}");
+                None
+            }
+        });
+        self.obj()
+            .emit_by_name::<()>("producer-added", &[&producer_id, &meta, &new_connection]);
+
+        if settings.role == WebRTCSignallerRole::Consumer
+            && settings
+                .producer_peer_id
+                .as_deref()
+                .map_or(true, |id| id == producer_id)
+        {
+            self.start_session(producer_id);
+        }
+    }
+
+    fn start_session(&self, producer_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        // A consumer has a single session
+        let Some(peer_id) = state.peer_id.clone().filter(|_| state.sessions.is_empty()) else {
+            return;
+        };
+
+        let session_id = uuid::Uuid::new_v4().to_string();
+        state
+            .sessions
+            .insert(session_id.clone(), producer_id.to_string());
+        drop(state);
+
+        gst::info!(
+            CAT,
+            imp: self,
+            "Starting session {session_id} with producer {producer_id}"
+        );
+        self.send(
+            producer_id,
+            p::OutgoingMessage::StartSession {
+                peer_id,
+                session_id: session_id.clone(),
+            },
+        );
+        self.obj()
+            .emit_by_name::<()>("session-started", &[&session_id, &producer_id]);
+    }
+
+    fn handle_message(&self, msg: p::OutgoingMessage) {
+        gst::debug!(CAT, imp: self, "Received {msg:?}");
+
+        match msg {
+            p::OutgoingMessage::StartSession {
+                peer_id,
+                session_id,
+            } => {
+                if self.settings.lock().unwrap().role != WebRTCSignallerRole::Producer {
+                    gst::warning!(CAT, imp: self, "Ignoring session request from {peer_id}");
+                    return;
+                }
+
+                self.state
+                    .lock()
+                    .unwrap()
+                    .sessions
+                    .insert(session_id.clone(), peer_id.clone());
+                self.obj().emit_by_name::<()>(
+                    "session-requested",
+                    &[
+                        &session_id,
+                        &peer_id,
+                        &None::<gst_webrtc::WebRTCSessionDescription>,
+                    ],
+                );
+            }
+            p::OutgoingMessage::EndSession(p::EndSessionMessage { session_id }) => {
+                if self
+                    .state
+                    .lock()
+                    .unwrap()
+                    .sessions
+                    .remove(&session_id)
+                    .is_some()
+                {
+                    gst::info!(CAT, imp: self, "Session {session_id} ended");
+                    self.obj()
+                        .emit_by_name::<bool>("session-ended", &[&session_id]);
+                }
+            }
+            p::OutgoingMessage::Peer(p::PeerMessage {
+                session_id,
+                peer_message,
+            }) => {
+                if self.session_peer(&session_id).is_none() {
+                    gst::warning!(CAT, imp: self, "Ignoring message of unknown session {session_id}");
+                    return;
+                }
+
+                match peer_message {
+                    p::PeerMessageInner::Sdp(sdp) => {
+                        let (sdp_type, sdp) = match sdp {
+                            p::SdpMessage::Offer { sdp } => (gst_webrtc::WebRTCSDPType::Offer, sdp),
+                            p::SdpMessage::Answer { sdp } => {
+                                (gst_webrtc::WebRTCSDPType::Answer, sdp)
+                            }
+                        };
+                        let sdp = match gst_sdp::SDPMessage::parse_buffer(sdp.as_bytes()) {
+                            Ok(sdp) => sdp,
+                            Err(err) => {
+                                self.raise_error(format!("Error parsing SDP: {sdp} {err:?}"));
+                                return;
+                            }
+                        };
+
+                        let desc = gst_webrtc::WebRTCSessionDescription::new(sdp_type, sdp);
+                        self.obj()
+                            .emit_by_name::<()>("session-description", &[&session_id, &desc]);
+                    }
+                    p::PeerMessageInner::Ice {
+                        candidate,
+                        sdp_m_line_index,
+                    } => {
+                        let sdp_mid: Option<String> = None;
+                        self.obj().emit_by_name::<()>(
+                            "handle-ice",
+                            &[&session_id, &sdp_m_line_index, &sdp_mid, &candidate],
+                        );
+                    }
+                }
+            }
+            p::OutgoingMessage::Error { details } => {
+                self.raise_error(format!("Error message from peer: {details}"));
+            }
+            p::OutgoingMessage::Welcome { .. }
+            | p::OutgoingMessage::SessionStarted { .. }
+            | p::OutgoingMessage::PeerStatusChanged(_)
+            | p::OutgoingMessage::List { .. } => {
+                gst::debug!(CAT, imp: self, "Ignoring unexpected message");
+            }
+        }
+    }
+}
+
+impl SignallableImpl for Signaller {
+    fn start(&self) {
+        let settings = self.settings.lock().unwrap().clone();
+        let mut state = self.state.lock().unwrap();
+        if state.client.is_some() {
+            gst::debug!(CAT, imp: self, "Already started");
+            return;
+        }
+
+        let peer_id = settings
+            .peer_id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let options = match settings.options(&peer_id) {
+            Ok(options) => options,
+            Err(err) => {
+                drop(state);
+                self.raise_error(format!("{err:?}"));
+                return;
+            }
+        };
+
+        gst::info!(
+            CAT,
+            imp: self,
+            "Connecting to {} as {:?} {peer_id}",
+            settings.broker_uri,
+            settings.role
+        );
+
+        let (client, event_loop) = AsyncClient::new(options, 64);
+        state.client = Some(client);
+        state.peer_id = Some(peer_id);
+
+        let weak = self.obj().downgrade();
+        state.event_loop_handle = Some(RUNTIME.spawn(Self::run_event_loop(weak, event_loop)));
+    }
+
+    fn stop(&self) {
+        gst::info!(CAT, imp: self, "Stopping now");
+
+        let settings = self.settings.lock().unwrap().clone();
+        let mut state = self.state.lock().unwrap();
+        let sessions = std::mem::take(&mut state.sessions);
+        state.producers.clear();
+        let peer_id = state.peer_id.take();
+        let event_loop_handle = state.event_loop_handle.take();
+        let Some(client) = state.client.clone() else {
+            return;
+        };
+        drop(state);
+
+        for (session_id, peer_id) in sessions {
+            self.send(
+                &peer_id,
+                p::OutgoingMessage::EndSession(p::EndSessionMessage { session_id }),
+            );
+        }
+
+        if let Some(peer_id) = peer_id.filter(|_| settings.role == WebRTCSignallerRole::Producer) {
+            self.publish(settings.presence_topic(&peer_id), true, Vec::new());
+        }
+
+        self.state.lock().unwrap().client = None;
+
+        // Let the pending messages be delivered before disconnecting
+        RUNTIME.block_on(async move {
+            let mut event_loop_handle = event_loop_handle;
+            let disconnect = async {
+                if let Err(err) = client.disconnect().await {
+                    gst::warning!(CAT, imp: self, "Failed to disconnect: {err}");
+                }
+
+                if let Some(ref mut handle) = event_loop_handle {
+                    let _ = handle.await;
+                }
+            };
+
+            if tokio::time::timeout(STOP_TIMEOUT, disconnect)
+                .await
+                .is_err()
+            {
+                gst::warning!(CAT, imp: self, "Timed out disconnecting from the broker");
+                if let Some(handle) = event_loop_handle {
+                    handle.abort();
+                }
+            }
+        });
+    }
+
+    fn send_sdp(&self, session_id: &str, sdp: &gst_webrtc::WebRTCSessionDescription) {
+        gst::debug!(CAT, imp: self, "Sending SDP {:?} for {session_id}", sdp.type_());
+
+        let Some(peer_id) = self.session_peer(session_id) else {
+            gst::warning!(CAT, imp: self, "Can't send SDP for unknown session {session_id}");
+            return;
+        };
+        let text = match sdp.sdp().as_text() {
+            Ok(text) => text,
+            Err(err) => {
+                self.raise_error(format!("Failed to serialize SDP: {err:?}"));
+                return;
+            }
+        };
+        let sdp = if sdp.type_() == gst_webrtc::WebRTCSDPType::Offer {
+            p::SdpMessage::Offer { sdp: text }
+        } else {
+            p::SdpMessage::Answer { sdp: text }
+        };
+
+        self.send(
+            &peer_id,
+            p::OutgoingMessage::Peer(p::PeerMessage {
+                session_id: session_id.to_string(),
+                peer_message: p::PeerMessageInner::Sdp(sdp),
+            }),
+        );
+    }
+
+    fn add_ice(
+        &self,
+        session_id: &str,
+        candidate: &str,
+        sdp_m_line_index: u32,
+        _sdp_mid: Option<String>,
+    ) {
+        let Some(peer_id) = self.session_peer(session_id) else {
+            gst::debug!(CAT, imp: self, "Dropping candidate of unknown session {session_id}");
+            return;
+        };
+
+        self.send(
+            &peer_id,
+            p::OutgoingMessage::Peer(p::PeerMessage {
+                session_id: session_id.to_string(),
+                peer_message: p::PeerMessageInner::Ice {
+                    candidate: candidate.to_string(),
+                    sdp_m_line_index,
+                },
+            }),
+        );
+    }
+
+    fn end_session(&self, session_id: &str) {
+        gst::debug!(CAT, imp: self, "Ending session {session_id}");
+
+        let peer_id = self.state.lock().unwrap().sessions.remove(session_id);
+        if let Some(peer_id) = peer_id {
+            self.send(
+                &peer_id,
+                p::OutgoingMessage::EndSession(p::EndSessionMessage {
+                    session_id: session_id.to_string(),
+                }),
+            );
+        }
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for Signaller {
+    const NAME: &'static str = "GstMqttSignaller";
+    type Type = super::MqttSignaller;
+    type ParentType = glib::Object;
+    type Interfaces = (Signallable,);
+}
+
+impl ObjectImpl for Signaller {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+            vec![
+                glib::ParamSpecString::builder("broker-uri")
+                    .nick("Broker URI")
+                    .blurb("URI of the MQTT broker, mqtts:// for TLS")
+                    .default_value(DEFAULT_BROKER_URI)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecEnum::builder_with_default("role", WebRTCSignallerRole::default())
+                    .nick("Role")
+                    .blurb("Whether the signaller is a producer, a consumer or a listener")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("peer-id")
+                    .nick("Peer ID")
+                    .blurb("ID of the peer, also used as MQTT client ID, random if unset")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("producer-peer-id")
+                    .nick("Producer peer ID")
+                    .blurb("In the consumer role, ID of the producer to start a session with, the first one available if unset")
+                    .mutable_ready()
+                    .build(),
+                /**
+                 * GstMqttSignaller:peer-topic-pattern:
+                 *
+                 * Topic the messages to a peer are published to, `{peer-id}`
+                 * being replaced by its ID. Each producer and consumer
+                 * subscribes to its own.
+                 */
+                glib::ParamSpecString::builder("peer-topic-pattern")
+                    .nick("Peer topic pattern")
+                    .blurb("Topic of the messages to a peer, {peer-id} being replaced by its ID")
+                    .default_value(DEFAULT_PEER_TOPIC_PATTERN)
+                    .mutable_ready()
+                    .build(),
+                /**
+                 * GstMqttSignaller:presence-topic-pattern:
+                 *
+                 * Topic the retained presence of a producer is published to,
+                 * `{peer-id}` being replaced by its ID. Consumers and
+                 * listeners subscribe to all of them to list the producers.
+                 */
+                glib::ParamSpecString::builder("presence-topic-pattern")
+                    .nick("Presence topic pattern")
+                    .blurb("Topic of the presence of a producer, {peer-id} being replaced by its ID")
+                    .default_value(DEFAULT_PRESENCE_TOPIC_PATTERN)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("qos")
+                    .nick("QoS")
+                    .blurb("Quality of service of the published messages and subscriptions")
+                    .maximum(2)
+                    .default_value(DEFAULT_QOS)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("keep-alive")
+                    .nick("Keep alive")
+                    .blurb("Interval in seconds of the keep alive pings to the broker")
+                    .minimum(5)
+                    .default_value(DEFAULT_KEEP_ALIVE)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("username")
+                    .nick("Username")
+                    .blurb("Username to authenticate with the broker")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("password")
+                    .nick("Password")
+                    .blurb("Password to authenticate with the broker")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("cafile")
+                    .nick("CA file")
+                    .blurb("Certificate authority of the broker, the system ones if unset")
+                    .mutable_ready()
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let mut settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "broker-uri" => {
+                settings.broker_uri = value
+                    .get::<Option<String>>()
+                    .expect("type checked upstream")
+                    .unwrap_or_else(|| DEFAULT_BROKER_URI.to_string());
+            }
+            "role" => settings.role = value.get().expect("type checked upstream"),
+            "peer-id" => settings.peer_id = value.get().expect("type checked upstream"),
+            "producer-peer-id" => {
+                settings.producer_peer_id = value.get().expect("type checked upstream");
+            }
+            "peer-topic-pattern" => {
+                settings.peer_topic_pattern = value
+                    .get::<Option<String>>()
+                    .expect("type checked upstream")
+                    .unwrap_or_else(|| DEFAULT_PEER_TOPIC_PATTERN.to_string());
+            }
+            "presence-topic-pattern" => {
+                settings.presence_topic_pattern = value
+                    .get::<Option<String>>()
+                    .expect("type checked upstream")
+                    .unwrap_or_else(|| DEFAULT_PRESENCE_TOPIC_PATTERN.to_string());
+            }
+            "qos" => settings.qos = value.get().expect("type checked upstream"),
+            "keep-alive" => settings.keep_alive = value.get().expect("type checked upstream"),
+            "username" => settings.username = value.get().expect("type checked upstream"),
+            "password" => settings.password = value.get().expect("type checked upstream"),
+            "cafile" => settings.cafile = value.get().expect("type checked upstream"),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "broker-uri" => settings.broker_uri.to_value(),
+            "role" => settings.role.to_value(),
+            "peer-id" => settings.peer_id.to_value(),
+            "producer-peer-id" => settings.producer_peer_id.to_value(),
+            "peer-topic-pattern" => settings.peer_topic_pattern.to_value(),
+            "presence-topic-pattern" => settings.presence_topic_pattern.to_value(),
+            "qos" => settings.qos.to_value(),
+            "keep-alive" => settings.keep_alive.to_value(),
+            "username" => settings.username.to_value(),
+            "password" => settings.password.to_value(),
+            "cafile" => settings.cafile.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mqtt_signaller::MqttSignaller;
+    use crate::signaller::test_utils::{self, next, session_description, Signals};
+    use crate::signaller::SignallableExt;
+    use std::process::{Child, Command};
+    use std::sync::mpsc as sync_mpsc;
+
+    struct Broker {
+        process: Child,
+        port: u16,
+    }
+
+    impl Drop for Broker {
+        fn drop(&mut self) {
+            let _ = self.process.kill();
+            let _ = self.process.wait();
+        }
+    }
+
+    // Runs a local mosquitto broker
+    fn broker() -> Broker {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let process = Command::new("mosquitto")
+            .args(["-p", &port.to_string()])
+            .spawn()
+            .expect("failed to run mosquitto");
+
+        // Wait for the broker to listen
+        for _ in 0..50 {
+            if std::net::TcpStream::connect(("127.0.0.1", port)).is_ok() {
+                return Broker { process, port };
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        panic!("mosquitto didn't start listening");
+    }
+
+    // Starts @signaller, its signals are sent to the returned receiver along
+    // with their first argument
+    fn start_signaller(broker: &Broker, signaller: &MqttSignaller) -> Signals {
+        signaller.set_property("broker-uri", format!("mqtt://127.0.0.1:{}", broker.port));
+
+        test_utils::start_signaller(
+            signaller,
+            &[
+                "producer-added",
+                "producer-removed",
+                "session-requested",
+                "session-started",
+                "session-description",
+                "session-ended",
+                "handle-ice",
+                "error",
+            ],
+        )
+    }
+
+    #[test]
+    fn test_topics() {
+        assert_eq!(
+            topic(DEFAULT_PEER_TOPIC_PATTERN, "peer1"),
+            "gstreamer/webrtc/peers/peer1"
+        );
+        assert_eq!(
+            peer_id_from_topic("cameras/{peer-id}/presence", "cameras/cam1/presence"),
+            Some("cam1")
+        );
+        assert_eq!(
+            peer_id_from_topic("cameras/{peer-id}/presence", "cameras/cam1/inbox"),
+            None
+        );
+        assert_eq!(
+            peer_id_from_topic("cameras/{peer-id}", "cameras/cam1/presence"),
+            None
+        );
+
+        let settings = Settings {
+            peer_topic_pattern: "cameras/inbox".to_string(),
+            ..Default::default()
+        };
+        assert!(settings.options("peer1").is_err());
+        let settings = Settings {
+            broker_uri: "http://127.0.0.1".to_string(),
+            ..Default::default()
+        };
+        assert!(settings.options("peer1").is_err());
+    }
+
+    #[test]
+    #[ignore = "needs mosquitto"]
+    fn test_negotiation() {
+        gst::init().unwrap();
+        let broker = broker();
+
+        let producer = MqttSignaller::new_producer();
+        producer.set_property("peer-id", "producer1");
+        producer.set_property("qos", 2u32);
+        let producer_rx = start_signaller(&broker, &producer);
+
+        // The presence of the producer is retained for late consumers
+        std::thread::sleep(Duration::from_millis(500));
+        let consumer = MqttSignaller::new_consumer();
+        consumer.set_property("producer-peer-id", "producer1");
+        let consumer_rx = start_signaller(&broker, &consumer);
+
+        assert_eq!(next(&consumer_rx, "producer-added"), "producer1");
+        let session_id = next(&consumer_rx, "session-started");
+        assert_eq!(next(&producer_rx, "session-requested"), session_id);
+
+        producer.send_sdp(
+            &session_id,
+            &session_description(gst_webrtc::WebRTCSDPType::Offer),
+        );
+        producer.add_ice(&session_id, "candidate:1", 0, None);
+        assert_eq!(next(&consumer_rx, "session-description"), session_id);
+        assert_eq!(next(&consumer_rx, "handle-ice"), session_id);
+
+        consumer.send_sdp(
+            &session_id,
+            &session_description(gst_webrtc::WebRTCSDPType::Answer),
+        );
+        assert_eq!(next(&producer_rx, "session-description"), session_id);
+
+        producer.stop();
+        assert_eq!(next(&consumer_rx, "session-ended"), session_id);
+        assert_eq!(next(&consumer_rx, "producer-removed"), "producer1");
+
+        consumer.stop();
+    }
+
+    #[test]
+    #[ignore = "needs mosquitto"]
+    fn test_listener() {
+        gst::init().unwrap();
+        let broker = broker();
+
+        let listener = MqttSignaller::default();
+        listener.set_property("role", WebRTCSignallerRole::Listener);
+        let listener_rx = start_signaller(&broker, &listener);
+        std::thread::sleep(Duration::from_millis(500));
+
+        let producer = MqttSignaller::new_producer();
+        producer.set_property("peer-id", "producer1");
+        let _producer_rx = start_signaller(&broker, &producer);
+        assert_eq!(next(&listener_rx, "producer-added"), "producer1");
+
+        producer.stop();
+        assert_eq!(next(&listener_rx, "producer-removed"), "producer1");
+
+        listener.stop();
+    }
+
+    #[test]
+    fn test_unreachable_broker() {
+        gst::init().unwrap();
+
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let signaller = MqttSignaller::new_producer();
+        signaller.set_property("broker-uri", format!("mqtt://127.0.0.1:{port}"));
+
+        let (tx, rx) = sync_mpsc::channel();
+        let tx = Mutex::new(tx);
+        signaller.connect("error", false, move |args| {
+            let _ = tx.lock().unwrap().send(args[1].get::<String>().unwrap());
+            None
+        });
+        signaller.start();
+
+        let error = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert!(
+            error.starts_with("Failed to connect to the broker"),
+            "{error}"
+        );
+
+        signaller.stop();
+    }
+}
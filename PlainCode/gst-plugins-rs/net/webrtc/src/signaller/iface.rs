@@ -397,6 +397,12 @@ unsafe impl prelude::ObjectInterface for Signallable {
                  *
                  * This signal can be used to tweak @webrtcbin, creating a data
                  * channel for example.
+                 *
+                 * It is emitted for each session, before its negotiation starts.
+                 * On the consumer side, the `webrtcbin` of a session is created
+                 * once the session is requested or started rather than when the
+                 * element goes to READY, and @peer_id is the id of the session
+                 * instead of `"none"`.
                  */
                 Signal::builder("webrtcbin-ready")
                     .param_types([String::static_type(), gst::Element::static_type()])
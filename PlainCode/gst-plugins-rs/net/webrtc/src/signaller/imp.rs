@@ -25,6 +25,9 @@ use url::Url;
 use super::CAT;
 
 const DEFAULT_INSECURE_TLS: bool = false;
+const DEFAULT_WAIT_FOR_PRODUCER: bool = false;
+// The server answers long-poll requests after 30 seconds without messages
+const HTTP_POLL_TIMEOUT: Duration = Duration::from_secs(60);
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, glib::Enum, Default)]
 #[repr(u32)]
@@ -43,6 +46,7 @@ pub struct Settings {
     role: WebRTCSignallerRole,
     headers: Option<gst::Structure>,
     insecure_tls: bool,
+    wait_for_producer: bool,
 }
 
 impl Default for Settings {
@@ -54,6 +58,7 @@ impl Default for Settings {
             role: Default::default(),
             headers: None,
             insecure_tls: DEFAULT_INSECURE_TLS,
+            wait_for_producer: DEFAULT_WAIT_FOR_PRODUCER,
         }
     }
 }
@@ -66,8 +71,8 @@ pub struct Signaller {
 
 #[derive(Default)]
 struct State {
-    /// Sender for the websocket messages
-    websocket_sender: Option<mpsc::Sender<p::IncomingMessage>>,
+    /// Sender for the messages to the server
+    sender: Option<mpsc::Sender<p::IncomingMessage>>,
     send_task_handle: Option<task::JoinHandle<Result<(), Error>>>,
     receive_task_handle: Option<task::JoinHandle<()>>,
     producers: HashSet<String>,
@@ -125,6 +130,13 @@ impl Signaller {
                 .ok_or_else(|| anyhow!("No target producer peer id set"))?;
         }
 
+        let mut uri = self.uri();
+        uri.set_query(None);
+
+        if matches!(uri.scheme(), "http" | "https") {
+            return self.connect_http(uri, cafile, insecure_tls).await;
+        }
+
         let mut connector_builder = tokio_native_tls::native_tls::TlsConnector::builder();
 
         if let Some(path) = cafile {
@@ -142,9 +154,6 @@ impl Signaller {
             connector_builder.build()?,
         ));
 
-        let mut uri = self.uri();
-        uri.set_query(None);
-
         gst::info!(CAT, imp: self, "connecting to {}", uri.to_string());
 
         let mut req = uri.into_client_request()?;
@@ -199,13 +208,7 @@ impl Signaller {
                 res.map_err(Into::into)
             }));
 
-        let obj = self.obj();
-        let meta =
-            if let Some(meta) = obj.emit_by_name::<Option<gst::Structure>>("request-meta", &[]) {
-                gvalue_to_json(&meta.to_value())
-            } else {
-                None
-            };
+        let meta = self.request_meta();
 
         let receive_task_handle =
             RUNTIME.spawn(glib::clone!(@weak-allow-none self as this => async move {
@@ -226,22 +229,183 @@ impl Signaller {
             }));
 
         let mut state = self.state.lock().unwrap();
-        state.websocket_sender = Some(websocket_sender);
+        state.sender = Some(websocket_sender);
         state.send_task_handle = Some(send_task_handle);
         state.receive_task_handle = Some(receive_task_handle);
 
         Ok(())
     }
 
+    // Exchanges the messages through HTTP requests instead, for networks
+    // where WebSockets are blocked: they are posted to the server, which is
+    // long-polled for the ones to this client
+    async fn connect_http(
+        &self,
+        uri: Url,
+        cafile: Option<String>,
+        insecure_tls: bool,
+    ) -> Result<(), Error> {
+        let mut builder = reqwest::Client::builder();
+
+        if let Some(path) = cafile {
+            let cert = tokio::fs::read(&path).await?;
+            builder = builder.add_root_certificate(reqwest::Certificate::from_pem(&cert)?);
+        }
+
+        if insecure_tls {
+            builder = builder.danger_accept_invalid_certs(true);
+            gst::warning!(CAT, imp: self, "insecure tls connections are allowed");
+        }
+
+        if let Some(headers) = self.headers() {
+            let mut header_map = reqwest::header::HeaderMap::new();
+            for (key, value) in headers {
+                header_map.insert(
+                    reqwest::header::HeaderName::from_bytes(key.as_bytes())?,
+                    reqwest::header::HeaderValue::from_str(&value)?,
+                );
+            }
+            builder = builder.default_headers(header_map);
+        }
+
+        let client = builder.build()?;
+        let base = uri.as_str().trim_end_matches('/');
+
+        gst::info!(CAT, imp: self, "connecting to {base} over HTTP");
+
+        let res = timeout(
+            // FIXME: Make the timeout configurable
+            Duration::from_secs(20),
+            client.post(format!("{base}/peers")).send(),
+        )
+        .await??
+        .error_for_status()?;
+        let body = serde_json::from_str::<serde_json::Value>(&res.text().await?)?;
+        let peer_id = body["peerId"]
+            .as_str()
+            .ok_or_else(|| anyhow!("No peer id in {body}"))?;
+        let peer_url = format!("{base}/peers/{peer_id}");
+        let messages_url = format!("{peer_url}/messages");
+
+        gst::info!(CAT, imp: self, "connected");
+
+        let (sender, mut receiver) = mpsc::channel::<p::IncomingMessage>(1000);
+        let send_client = client.clone();
+        let send_messages_url = messages_url.clone();
+        let send_task_handle =
+            RUNTIME.spawn(glib::clone!(@weak-allow-none self as this => async move {
+                let mut res = Ok(());
+                while let Some(msg) = receiver.next().await {
+                    gst::log!(CAT, "Sending HTTP message {:?}", msg);
+                    res = send_client
+                        .post(&send_messages_url)
+                        .header(reqwest::header::CONTENT_TYPE, "application/json")
+                        .body(serde_json::to_string(&msg).unwrap())
+                        .send()
+                        .await
+                        .and_then(reqwest::Response::error_for_status)
+                        .map(|_| ());
+
+                    if let Err(ref err) = res {
+                        this.as_ref().map_or_else(|| gst::error!(CAT, "Quitting send loop: {err}"),
+                            |this| gst::error!(CAT, imp: this, "Quitting send loop: {err}")
+                        );
+                        break;
+                    }
+                }
+
+                this.map_or_else(|| gst::debug!(CAT, "Done sending"),
+                    |this| gst::debug!(CAT, imp: this, "Done sending")
+                );
+
+                // Leave right away instead of waiting for the server to time out
+                let _ = send_client.delete(&peer_url).send().await;
+
+                res.map_err(Into::into)
+            }));
+
+        let meta = self.request_meta();
+
+        let receive_task_handle =
+            RUNTIME.spawn(glib::clone!(@weak-allow-none self as this => async move {
+                'poll: loop {
+                    let res = async {
+                        client
+                            .get(&messages_url)
+                            .timeout(HTTP_POLL_TIMEOUT)
+                            .send()
+                            .await?
+                            .error_for_status()?
+                            .text()
+                            .await
+                    }
+                    .await;
+
+                    let Some(ref this) = this else {
+                        break;
+                    };
+
+                    let messages = match res
+                        .map_err(Error::from)
+                        .and_then(|text| Ok(serde_json::from_str::<Vec<serde_json::Value>>(&text)?))
+                    {
+                        Ok(messages) => messages,
+                        Err(err) => {
+                            this.obj()
+                                .emit_by_name::<()>("error", &[&format!("Error receiving: {}", err)]);
+                            break;
+                        }
+                    };
+
+                    for msg in messages {
+                        gst::trace!(CAT, imp: this, "Received message {}", msg);
+
+                        match serde_json::from_value::<p::OutgoingMessage>(msg.clone()) {
+                            Ok(msg) => {
+                                if let ControlFlow::Break(_) = this.handle_server_message(msg, &meta) {
+                                    break 'poll;
+                                }
+                            }
+                            Err(_) => this.unknown_message(&msg.to_string()),
+                        }
+                    }
+                }
+
+                let msg = "Stopped HTTP polling";
+                this.map_or_else(|| gst::info!(CAT, "{msg}"),
+                    |this| gst::info!(CAT, imp: this, "{msg}")
+                );
+            }));
+
+        let mut state = self.state.lock().unwrap();
+        state.sender = Some(sender);
+        state.send_task_handle = Some(send_task_handle);
+        state.receive_task_handle = Some(receive_task_handle);
+
+        Ok(())
+    }
+
+    fn request_meta(&self) -> Option<serde_json::Value> {
+        self.obj()
+            .emit_by_name::<Option<gst::Structure>>("request-meta", &[])
+            .and_then(|meta| gvalue_to_json(&meta.to_value()))
+    }
+
     fn set_status(&self, meta: &Option<serde_json::Value>, peer_id: &str) {
         self.state.lock().unwrap().client_id = Some(peer_id.to_string());
 
         let role = self.settings.lock().unwrap().role;
+        let wait_for_producer = self.waits_for_producer();
         self.send(p::IncomingMessage::SetPeerStatus(match role {
             super::WebRTCSignallerRole::Consumer => p::PeerStatus {
                 meta: meta.clone(),
                 peer_id: Some(peer_id.to_string()),
-                roles: vec![],
+                // Listen to find out when the producer (re)appears
+                roles: if wait_for_producer {
+                    vec![p::PeerRole::Listener]
+                } else {
+                    vec![]
+                },
             },
             super::WebRTCSignallerRole::Producer => p::PeerStatus {
                 meta: meta.clone(),
@@ -255,11 +419,26 @@ impl Signaller {
             },
         }));
 
-        if matches!(role, super::WebRTCSignallerRole::Listener) {
+        if matches!(role, super::WebRTCSignallerRole::Listener) || wait_for_producer {
             self.send(p::IncomingMessage::List);
         }
     }
 
+    fn waits_for_producer(&self) -> bool {
+        let settings = self.settings.lock().unwrap();
+
+        matches!(settings.role, super::WebRTCSignallerRole::Consumer) && settings.wait_for_producer
+    }
+
+    // Starts a session when the producer we are waiting for shows up
+    fn on_producer_added(&self, producer_id: &str) {
+        if self.waits_for_producer() && self.producer_peer_id().as_deref() == Some(producer_id) {
+            gst::info!(CAT, imp: self, "Producer {producer_id} is available");
+
+            self.start_session();
+        }
+    }
+
     fn producer_peer_id(&self) -> Option<String> {
         let settings = self.settings.lock().unwrap();
 
@@ -296,7 +475,7 @@ impl Signaller {
 
     fn send(&self, msg: p::IncomingMessage) {
         let state = self.state.lock().unwrap();
-        if let Some(mut sender) = state.websocket_sender.clone() {
+        if let Some(mut sender) = state.sender.clone() {
             RUNTIME.spawn(glib::clone!(@weak self as this => async move {
                 if let Err(err) = sender.send(msg).await {
                     this.obj().emit_by_name::<()>("error", &[&format!("Error: {}", err)]);
@@ -332,167 +511,173 @@ impl Signaller {
                 gst::trace!(CAT, imp: self, "Received message {}", msg);
 
                 if let Ok(msg) = serde_json::from_str::<p::OutgoingMessage>(&msg) {
-                    match msg {
-                        p::OutgoingMessage::Welcome { peer_id } => {
-                            self.set_status(meta, &peer_id);
-                            self.start_session();
-                        }
-                        p::OutgoingMessage::PeerStatusChanged(p::PeerStatus {
-                            meta,
-                            roles,
-                            peer_id,
-                        }) => {
-                            let meta = meta.and_then(|m| match m {
-                                serde_json::Value::Object(v) => Some(serialize_json_object(&v)),
-                                _ => {
-                                    gst::error!(CAT, imp: self, "Invalid json value: {m:?}");
-                                    None
-                                }
-                            });
-
-                            let peer_id =
-                                peer_id.expect("Status changed should always contain a peer ID");
-                            let mut state = self.state.lock().unwrap();
-                            if roles.iter().any(|r| matches!(r, p::PeerRole::Producer)) {
-                                if !state.producers.contains(&peer_id) {
-                                    state.producers.insert(peer_id.clone());
-                                    drop(state);
-
-                                    self.obj().emit_by_name::<()>(
-                                        "producer-added",
-                                        &[&peer_id, &meta, &true],
-                                    );
-                                }
-                            } else if state.producers.remove(&peer_id) {
-                                drop(state);
+                    return self.handle_server_message(msg, meta);
+                }
 
-                                self.obj()
-                                    .emit_by_name::<()>("producer-removed", &[&peer_id, &meta]);
-                            }
-                        }
-                        p::OutgoingMessage::SessionStarted {
-                            peer_id,
-                            session_id,
-                        } => {
-                            self.obj()
-                                .emit_by_name::<()>("session-started", &[&session_id, &peer_id]);
-                        }
-                        p::OutgoingMessage::StartSession {
-                            session_id,
-                            peer_id,
-                        } => {
-                            assert!(matches!(
-                                self.obj().property::<WebRTCSignallerRole>("role"),
-                                super::WebRTCSignallerRole::Producer
-                            ));
+                self.unknown_message(&msg);
+            }
+            Ok(WsMessage::Close(reason)) => {
+                gst::info!(CAT, imp: self, "websocket connection closed: {:?}", reason);
+                return ControlFlow::Break(());
+            }
+            Ok(_) => (),
+            Err(err) => {
+                self.obj()
+                    .emit_by_name::<()>("error", &[&format!("Error receiving: {}", err)]);
+                return ControlFlow::Break(());
+            }
+        }
+        ControlFlow::Continue(())
+    }
 
-                            self.obj().emit_by_name::<()>(
-                                "session-requested",
-                                &[
-                                    &session_id,
-                                    &peer_id,
-                                    &None::<gst_webrtc::WebRTCSessionDescription>,
-                                ],
-                            );
-                        }
-                        p::OutgoingMessage::EndSession(p::EndSessionMessage { session_id }) => {
-                            gst::info!(CAT, imp: self, "Session {session_id} ended");
+    fn unknown_message(&self, msg: &str) {
+        gst::error!(CAT, imp: self, "Unknown message from server: {}", msg);
 
-                            self.obj()
-                                .emit_by_name::<bool>("session-ended", &[&session_id]);
-                        }
-                        p::OutgoingMessage::Peer(p::PeerMessage {
-                            session_id,
-                            peer_message,
-                        }) => match peer_message {
-                            p::PeerMessageInner::Sdp(reply) => {
-                                let (sdp, desc_type) = match reply {
-                                    p::SdpMessage::Answer { sdp } => {
-                                        (sdp, gst_webrtc::WebRTCSDPType::Answer)
-                                    }
-                                    p::SdpMessage::Offer { sdp } => {
-                                        (sdp, gst_webrtc::WebRTCSDPType::Offer)
-                                    }
-                                };
-                                let sdp = match gst_sdp::SDPMessage::parse_buffer(sdp.as_bytes()) {
-                                    Ok(sdp) => sdp,
-                                    Err(err) => {
-                                        self.obj().emit_by_name::<()>(
-                                            "error",
-                                            &[&format!("Error parsing SDP: {sdp} {err:?}")],
-                                        );
-
-                                        return ControlFlow::Break(());
-                                    }
-                                };
-
-                                let desc =
-                                    gst_webrtc::WebRTCSessionDescription::new(desc_type, sdp);
-                                self.obj().emit_by_name::<()>(
-                                    "session-description",
-                                    &[&session_id, &desc],
-                                );
-                            }
-                            p::PeerMessageInner::Ice {
-                                candidate,
-                                sdp_m_line_index,
-                            } => {
-                                let sdp_mid: Option<String> = None;
-                                self.obj().emit_by_name::<()>(
-                                    "handle-ice",
-                                    &[&session_id, &sdp_m_line_index, &sdp_mid, &candidate],
-                                );
-                            }
-                        },
-                        p::OutgoingMessage::List { producers } => {
-                            for producer in producers {
-                                let mut state = self.state.lock().unwrap();
-                                if !state.producers.contains(&producer.id) {
-                                    state.producers.insert(producer.id.clone());
-                                    drop(state);
-
-                                    let meta = producer.meta.and_then(|m| match m {
-                                        serde_json::Value::Object(v) => Some(serialize_json_object(&v)),
-                                        _ => {
-                                            gst::error!(CAT, imp: self, "Invalid json value: {m:?}");
-                                            None
-                                        }
-                                    });
-
-                                    self.obj().emit_by_name::<()>(
-                                        "producer-added",
-                                        &[&producer.id, &meta, &false],
-                                    );
-                                }
-                            }
-                        }
-                        p::OutgoingMessage::Error { details } => {
+        self.obj()
+            .emit_by_name::<()>("error", &[&format!("Unknown message from server: {}", msg)]);
+    }
+
+    fn handle_server_message(
+        &self,
+        msg: p::OutgoingMessage,
+        meta: &Option<serde_json::Value>,
+    ) -> ControlFlow<()> {
+        match msg {
+            p::OutgoingMessage::Welcome { peer_id } => {
+                self.set_status(meta, &peer_id);
+                if !self.waits_for_producer() {
+                    self.start_session();
+                }
+            }
+            p::OutgoingMessage::PeerStatusChanged(p::PeerStatus {
+                meta,
+                roles,
+                peer_id,
+            }) => {
+                let meta = meta.and_then(|m| match m {
+                    serde_json::Value::Object(v) => Some(serialize_json_object(&v)),
+                    _ => {
+                        gst::error!(CAT, imp: self, "Invalid json value: {m:?}");
+                        None
+                    }
+                });
+
+                let peer_id = peer_id.expect("Status changed should always contain a peer ID");
+                let mut state = self.state.lock().unwrap();
+                if roles.iter().any(|r| matches!(r, p::PeerRole::Producer)) {
+                    if !state.producers.contains(&peer_id) {
+                        state.producers.insert(peer_id.clone());
+                        drop(state);
+
+                        self.obj()
+                            .emit_by_name::<()>("producer-added", &[&peer_id, &meta, &true]);
+
+                        self.on_producer_added(&peer_id);
+                    }
+                } else if state.producers.remove(&peer_id) {
+                    drop(state);
+
+                    self.obj()
+                        .emit_by_name::<()>("producer-removed", &[&peer_id, &meta]);
+                }
+            }
+            p::OutgoingMessage::SessionStarted {
+                peer_id,
+                session_id,
+            } => {
+                self.obj()
+                    .emit_by_name::<()>("session-started", &[&session_id, &peer_id]);
+            }
+            p::OutgoingMessage::StartSession {
+                session_id,
+                peer_id,
+            } => {
+                assert!(matches!(
+                    self.obj().property::<WebRTCSignallerRole>("role"),
+                    super::WebRTCSignallerRole::Producer
+                ));
+
+                self.obj().emit_by_name::<()>(
+                    "session-requested",
+                    &[
+                        &session_id,
+                        &peer_id,
+                        &None::<gst_webrtc::WebRTCSessionDescription>,
+                    ],
+                );
+            }
+            p::OutgoingMessage::EndSession(p::EndSessionMessage { session_id }) => {
+                gst::info!(CAT, imp: self, "Session {session_id} ended");
+
+                self.obj()
+                    .emit_by_name::<bool>("session-ended", &[&session_id]);
+            }
+            p::OutgoingMessage::Peer(p::PeerMessage {
+                session_id,
+                peer_message,
+            }) => match peer_message {
+                p::PeerMessageInner::Sdp(reply) => {
+                    let (sdp, desc_type) = match reply {
+                        p::SdpMessage::Answer { sdp } => (sdp, gst_webrtc::WebRTCSDPType::Answer),
+                        p::SdpMessage::Offer { sdp } => (sdp, gst_webrtc::WebRTCSDPType::Offer),
+                    };
+                    let sdp = match gst_sdp::SDPMessage::parse_buffer(sdp.as_bytes()) {
+                        Ok(sdp) => sdp,
+                        Err(err) => {
                             self.obj().emit_by_name::<()>(
                                 "error",
-                                &[&format!("Error message from server: {details}")],
+                                &[&format!("Error parsing SDP: {sdp} {err:?}")],
                             );
+
+                            return ControlFlow::Break(());
                         }
-                    }
-                } else {
-                    gst::error!(CAT, imp: self, "Unknown message from server: {}", msg);
+                    };
 
+                    let desc = gst_webrtc::WebRTCSessionDescription::new(desc_type, sdp);
+                    self.obj()
+                        .emit_by_name::<()>("session-description", &[&session_id, &desc]);
+                }
+                p::PeerMessageInner::Ice {
+                    candidate,
+                    sdp_m_line_index,
+                } => {
+                    let sdp_mid: Option<String> = None;
                     self.obj().emit_by_name::<()>(
-                        "error",
-                        &[&format!("Unknown message from server: {}", msg)],
+                        "handle-ice",
+                        &[&session_id, &sdp_m_line_index, &sdp_mid, &candidate],
                     );
                 }
+            },
+            p::OutgoingMessage::List { producers } => {
+                for producer in producers {
+                    let mut state = self.state.lock().unwrap();
+                    if !state.producers.contains(&producer.id) {
+                        state.producers.insert(producer.id.clone());
+                        drop(state);
+
+                        let meta = producer.meta.and_then(|m| match m {
+                            serde_json::Value::Object(v) => Some(serialize_json_object(&v)),
+                            _ => {
+                                gst::error!(CAT, imp: self, "Invalid json value: {m:?}");
+                                None
+                            }
+                        });
+
+                        self.obj()
+                            .emit_by_name::<()>("producer-added", &[&producer.id, &meta, &false]);
+
+                        self.on_producer_added(&producer.id);
+                    }
+                }
             }
-            Ok(WsMessage::Close(reason)) => {
-                gst::info!(CAT, imp: self, "websocket connection closed: {:?}", reason);
-                return ControlFlow::Break(());
-            }
-            Ok(_) => (),
-            Err(err) => {
-                self.obj()
-                    .emit_by_name::<()>("error", &[&format!("Error receiving: {}", err)]);
-                return ControlFlow::Break(());
+            p::OutgoingMessage::Error { details } => {
+                self.obj().emit_by_name::<()>(
+                    "error",
+                    &[&format!("Error message from server: {details}")],
+                );
             }
         }
+
         ControlFlow::Continue(())
     }
 }
@@ -511,7 +696,7 @@ impl ObjectImpl for Signaller {
             vec![
                 glib::ParamSpecString::builder("uri")
                     .nick("Signaller URI")
-                    .blurb("URI for connecting to the signaller server")
+                    .blurb("URI for connecting to the signaller server, over HTTP long-polling for http(s) URIs")
                     .flags(glib::ParamFlags::READWRITE)
                     .build(),
                 glib::ParamSpecString::builder("producer-peer-id")
@@ -550,6 +735,19 @@ impl ObjectImpl for Signaller {
                     .default_value(DEFAULT_INSECURE_TLS)
                     .flags(glib::ParamFlags::READWRITE)
                     .build(),
+                /**
+                 * GstWebRTCSignaller::wait-for-producer:
+                 *
+                 * In the consumer role, only ask for a session once the
+                 * producer is registered on the server, and ask for a new one
+                 * every time it registers again after leaving.
+                 */
+                glib::ParamSpecBoolean::builder("wait-for-producer")
+                    .nick("Wait for producer")
+                    .blurb("Start a session whenever the producer becomes available")
+                    .default_value(DEFAULT_WAIT_FOR_PRODUCER)
+                    .flags(glib::ParamFlags::READWRITE)
+                    .build(),
             ]
         });
 
@@ -597,6 +795,10 @@ impl ObjectImpl for Signaller {
                 self.settings.lock().unwrap().insecure_tls =
                     value.get::<bool>().expect("type checked upstream")
             }
+            "wait-for-producer" => {
+                self.settings.lock().unwrap().wait_for_producer =
+                    value.get::<bool>().expect("type checked upstream")
+            }
             _ => unimplemented!(),
         }
     }
@@ -621,6 +823,7 @@ impl ObjectImpl for Signaller {
             "client-id" => self.state.lock().unwrap().client_id.to_value(),
             "headers" => settings.headers.to_value(),
             "insecure-tls" => settings.insecure_tls.to_value(),
+            "wait-for-producer" => settings.wait_for_producer.to_value(),
             _ => unimplemented!(),
         }
     }
@@ -642,7 +845,7 @@ impl SignallableImpl for Signaller {
         let mut state = self.state.lock().unwrap();
         let send_task_handle = state.send_task_handle.take();
         let receive_task_handle = state.receive_task_handle.take();
-        if let Some(mut sender) = state.websocket_sender.take() {
+        if let Some(mut sender) = state.sender.take() {
             RUNTIME.block_on(async move {
                 sender.close_channel();
 
@@ -712,7 +915,7 @@ impl SignallableImpl for Signaller {
 
         let state = self.state.lock().unwrap();
         let session_id = session_id.to_string();
-        if let Some(mut sender) = state.websocket_sender.clone() {
+        if let Some(mut sender) = state.sender.clone() {
             RUNTIME.spawn(glib::clone!(@weak self as this => async move {
                 if let Err(err) = sender
                     .send(p::IncomingMessage::EndSession(p::EndSessionMessage {
@@ -728,3 +931,93 @@ impl SignallableImpl for Signaller {
 }
 
 impl GstObjectImpl for Signaller {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signaller::test_utils::{self, next, session_description, Signals};
+    use crate::signaller::Signaller;
+    use gst_plugin_webrtc_signalling::handlers::Handler;
+    use gst_plugin_webrtc_signalling::server::Server;
+
+    // Serves the HTTP transport of a signalling server on an ephemeral port,
+    // returns its URI
+    fn serve_http() -> String {
+        let _guard = RUNTIME.enter();
+        let listener = RUNTIME
+            .block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+            .unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let incoming = stream::unfold(listener, |listener| async move {
+            let res = listener.accept().await.map(|(stream, _)| stream);
+            Some((res, listener))
+        });
+        RUNTIME.spawn(Server::spawn(Handler::new).serve_http(incoming));
+
+        format!("http://127.0.0.1:{port}")
+    }
+
+    fn start_signaller(uri: &str, signaller: &Signaller) -> Signals {
+        signaller.set_property("uri", uri);
+
+        test_utils::start_signaller(
+            signaller,
+            &[
+                "session-requested",
+                "session-started",
+                "session-description",
+                "session-ended",
+                "handle-ice",
+                "error",
+            ],
+        )
+    }
+
+    #[test]
+    fn test_http_negotiation() {
+        gst::init().unwrap();
+        let uri = serve_http();
+
+        let producer = Signaller::new(WebRTCSignallerRole::Producer);
+        let producer_rx = start_signaller(&uri, &producer);
+
+        // The server assigns the peer ID of the producer when it connects
+        let mut producer_id = None;
+        for _ in 0..100 {
+            producer_id = producer.property::<Option<String>>("client-id");
+            if producer_id.is_some() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        let producer_id = producer_id.expect("producer didn't connect");
+
+        let consumer = Signaller::new(WebRTCSignallerRole::Consumer);
+        consumer.set_property("producer-peer-id", &producer_id);
+        let consumer_rx = start_signaller(&uri, &consumer);
+
+        let session_id = next(&consumer_rx, "session-started");
+        assert_eq!(next(&producer_rx, "session-requested"), session_id);
+
+        producer.send_sdp(
+            &session_id,
+            &session_description(gst_webrtc::WebRTCSDPType::Offer),
+        );
+        producer.add_ice(&session_id, "candidate:1", 0, None);
+        assert_eq!(next(&consumer_rx, "session-description"), session_id);
+        assert_eq!(next(&consumer_rx, "handle-ice"), session_id);
+
+        consumer.send_sdp(
+            &session_id,
+            &session_description(gst_webrtc::WebRTCSDPType::Answer),
+        );
+        assert_eq!(next(&producer_rx, "session-description"), session_id);
+
+        // Stopping deletes the peer on the server right away
+        producer.stop();
+        assert_eq!(next(&consumer_rx, "session-ended"), session_id);
+
+        consumer.stop();
+    }
+}
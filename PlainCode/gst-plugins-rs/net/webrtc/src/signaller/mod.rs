@@ -1,5 +1,7 @@
 mod iface;
 mod imp;
+#[cfg(test)]
+pub(crate) mod test_utils;
 use gst::glib;
 
 /**
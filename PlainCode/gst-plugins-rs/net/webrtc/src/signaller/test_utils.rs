@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: MPL-2.0
+
+// Fixtures shared by the tests of the signallers
+
+use super::{prelude::*, Signallable};
+use gst::glib;
+use gst::prelude::*;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+pub const SDP: &str = "v=0\r\no=- 0 0 IN IP4 127.0.0.1\r\ns=-\r\nt=0 0\r\n";
+
+/// Sends the name of the received signals along with their first argument
+pub type SignalSender = Arc<Mutex<mpsc::Sender<(String, String)>>>;
+
+/// The signals received from a signaller, in order
+pub type Signals = mpsc::Receiver<(String, String)>;
+
+pub fn signal_channel() -> (SignalSender, Signals) {
+    let (tx, rx) = mpsc::channel();
+
+    (Arc::new(Mutex::new(tx)), rx)
+}
+
+// Forwards the @names signals of @signaller to @tx, they must have a string
+// as first argument
+pub fn connect_signals(
+    signaller: &impl IsA<glib::Object>,
+    names: &[&'static str],
+    tx: &SignalSender,
+) {
+    for &name in names {
+        let tx = tx.clone();
+        signaller.connect(name, false, move |args| {
+            let arg = args[1].get::<String>().unwrap();
+            let _ = tx.lock().unwrap().send((name.to_string(), arg));
+            (name == "session-ended").then(|| false.to_value())
+        });
+    }
+}
+
+// Starts @signaller, its @names signals are sent to the returned receiver
+pub fn start_signaller(signaller: &impl IsA<Signallable>, names: &[&'static str]) -> Signals {
+    let (tx, rx) = signal_channel();
+    connect_signals(signaller, names, &tx);
+
+    signaller.start();
+
+    rx
+}
+
+// Waits for the next signal, which must be @expected, and returns its argument
+pub fn next(rx: &Signals, expected: &str) -> String {
+    let (signal, arg) = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(signal, expected);
+
+    arg
+}
+
+// Waits for the @expected signals, in order, whatever their argument
+pub fn expect(rx: &Signals, expected: &[&str]) {
+    for name in expected {
+        let (signal, _) = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(signal, *name, "expected {expected:?}");
+    }
+}
+
+// Waits for the @expected signals, in order, along with their argument
+pub fn expect_args(rx: &Signals, expected: &[(&str, &str)]) {
+    for (name, arg) in expected {
+        assert_eq!(next(rx, name), *arg);
+    }
+}
+
+pub fn session_description(
+    sdp_type: gst_webrtc::WebRTCSDPType,
+) -> gst_webrtc::WebRTCSessionDescription {
+    gst_webrtc::WebRTCSessionDescription::new(
+        sdp_type,
+        gst_sdp::SDPMessage::parse_buffer(SDP.as_bytes()).unwrap(),
+    )
+}
@@ -0,0 +1,1902 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::message::{host_port, param, uri, user, DigestChallenge, Message};
+use super::sdp;
+use crate::signaller::{Signallable, SignallableImpl, WebRTCSignallerRole};
+use crate::RUNTIME;
+use anyhow::{anyhow, Context, Error};
+use async_tungstenite::tungstenite::client::IntoClientRequest;
+use async_tungstenite::tungstenite::http::HeaderValue;
+use async_tungstenite::tungstenite::Message as WsMessage;
+use futures::channel::mpsc;
+use futures::prelude::*;
+use gst::glib;
+use gst::glib::prelude::*;
+use gst::subclass::prelude::*;
+use gst_webrtc::{WebRTCICEGatheringState, WebRTCSDPType, WebRTCSessionDescription};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::task;
+
+static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
+    gst::DebugCategory::new(
+        "webrtc-sip-signaller",
+        gst::DebugColorFlags::empty(),
+        Some("WebRTC SIP signaller"),
+    )
+});
+
+const DEFAULT_REGISTER: bool = true;
+const DEFAULT_REGISTER_EXPIRES: u32 = 3600;
+const DEFAULT_LOCAL_PORT: u32 = 0;
+const DEFAULT_SDP_MUNGING: bool = true;
+const DEFAULT_SIP_PORT: u16 = 5060;
+const ALLOW: &str = "INVITE, ACK, CANCEL, BYE, OPTIONS";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(20);
+// Retransmission timers of the requests sent over UDP, RFC 3261 section 17
+const T1: Duration = Duration::from_millis(500);
+const T2: Duration = Duration::from_secs(4);
+const TRANSACTION_TIMEOUT: Duration = Duration::from_secs(32);
+// Registrations are refreshed halfway to their expiration, but not more often
+const MIN_REGISTER_REFRESH: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TransportKind {
+    Udp,
+    Tcp,
+    Ws,
+    Wss,
+}
+
+impl TransportKind {
+    // The transport to reach @uri with, a SIP URI or a WebSocket URL
+    fn for_uri(uri: &str) -> Result<Self, Error> {
+        if uri.starts_with("ws://") {
+            return Ok(Self::Ws);
+        } else if uri.starts_with("wss://") {
+            return Ok(Self::Wss);
+        }
+
+        match param(uri, "transport") {
+            None => Ok(Self::Udp),
+            Some(transport) if transport.eq_ignore_ascii_case("udp") => Ok(Self::Udp),
+            Some(transport) if transport.eq_ignore_ascii_case("tcp") => Ok(Self::Tcp),
+            Some(transport) => Err(anyhow!("Unsupported transport {transport}")),
+        }
+    }
+
+    fn via(self) -> &'static str {
+        match self {
+            Self::Udp => "UDP",
+            Self::Tcp => "TCP",
+            Self::Ws => "WS",
+            Self::Wss => "WSS",
+        }
+    }
+
+    fn contact_param(self) -> &'static str {
+        match self {
+            Self::Udp => "udp",
+            Self::Tcp => "tcp",
+            Self::Ws | Self::Wss => "ws",
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Settings {
+    role: WebRTCSignallerRole,
+    user_uri: Option<String>,
+    proxy_uri: Option<String>,
+    remote_uri: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    register: bool,
+    register_expires: u32,
+    local_port: u32,
+    local_address: Option<String>,
+    sdp_munging: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            role: WebRTCSignallerRole::default(),
+            user_uri: None,
+            proxy_uri: None,
+            remote_uri: None,
+            username: None,
+            password: None,
+            register: DEFAULT_REGISTER,
+            register_expires: DEFAULT_REGISTER_EXPIRES,
+            local_port: DEFAULT_LOCAL_PORT,
+            local_address: None,
+            sdp_munging: DEFAULT_SDP_MUNGING,
+        }
+    }
+}
+
+impl Settings {
+    // Where the requests are sent, the proxy if any or else the callee
+    fn outbound_uri(&self) -> Option<&str> {
+        self.proxy_uri.as_deref().or(self.remote_uri.as_deref())
+    }
+}
+
+struct Outgoing {
+    text: String,
+    destination: Option<SocketAddr>,
+}
+
+struct Connection {
+    sender: mpsc::UnboundedSender<Outgoing>,
+    task_handle: task::JoinHandle<()>,
+    transport: TransportKind,
+    // Host and port of the Via and Contact headers
+    sent_by: String,
+    // The address of record of the user
+    aor: String,
+}
+
+impl Connection {
+    fn contact(&self) -> String {
+        let user = user(&self.aor).unwrap_or("gst");
+
+        format!(
+            "<sip:{user}@{};transport={}>",
+            self.sent_by,
+            self.transport.contact_param()
+        )
+    }
+
+    fn request(&self, method: &str, uri: &str) -> Message {
+        let mut request = Message::request(method, uri);
+        request.add_header(
+            "Via",
+            format!(
+                "SIP/2.0/{} {};branch={};rport",
+                self.transport.via(),
+                self.sent_by,
+                new_branch()
+            ),
+        );
+        request.add_header("Max-Forwards", "70");
+
+        request
+    }
+}
+
+struct Registration {
+    call_id: String,
+    tag: String,
+    cseq: u32,
+    // The last REGISTER sent
+    request: Option<Message>,
+    registered: bool,
+    refresh_task_handle: Option<task::JoinHandle<()>>,
+}
+
+// A call, identified by its Call-ID
+#[derive(Default)]
+struct Session {
+    outgoing: bool,
+    // The URI of the peer, as given with session-requested
+    peer_id: String,
+    // Our From or To header, along with our tag
+    local: String,
+    // The header of the peer, along with its tag once known
+    remote: String,
+    // Request-URI of the requests within the dialog
+    target: String,
+    cseq: u32,
+    // The INVITE we sent or received
+    invite: Option<Message>,
+    // Where the INVITE of an incoming call came from
+    source: Option<SocketAddr>,
+    confirmed: bool,
+    // The INVITE didn't carry an offer, the answer comes with the ACK
+    late_offer: bool,
+    webrtcbin: Option<glib::WeakRef<gst::Element>>,
+    // The description is sent once all candidates are gathered
+    sdp_pending: bool,
+    offer_mids: Vec<String>,
+}
+
+#[derive(Default)]
+struct State {
+    connection: Option<Connection>,
+    registration: Option<Registration>,
+    sessions: HashMap<String, Session>,
+    retransmissions: HashMap<String, task::JoinHandle<()>>,
+}
+
+#[derive(Default)]
+pub struct Signaller {
+    state: Mutex<State>,
+    settings: Mutex<Settings>,
+}
+
+fn new_branch() -> String {
+    format!("z9hG4bK{}", uuid::Uuid::new_v4().simple())
+}
+
+fn new_tag() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..16].to_string()
+}
+
+// Key of the retransmissions of a request
+fn transaction_key(branch: &str, method: &str) -> String {
+    format!("{branch}:{method}")
+}
+
+// Key of the retransmissions of the final response to an incoming INVITE
+fn response_key(call_id: &str) -> String {
+    format!("{call_id}:response")
+}
+
+// The address of the host of the SIP URI @uri
+async fn resolve(uri: &str) -> Result<SocketAddr, Error> {
+    let (host, port) = host_port(uri).ok_or_else(|| anyhow!("Invalid SIP URI {uri}"))?;
+    let port = port.unwrap_or(DEFAULT_SIP_PORT);
+
+    tokio::net::lookup_host((host, port))
+        .await?
+        .next()
+        .ok_or_else(|| anyhow!("Failed to resolve {host}"))
+}
+
+// The local address packets to @destination are sent from
+fn local_ip_towards(destination: SocketAddr) -> Option<IpAddr> {
+    let bind = if destination.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let socket = std::net::UdpSocket::bind(bind).ok()?;
+    socket.connect(destination).ok()?;
+
+    Some(socket.local_addr().ok()?.ip())
+}
+
+// Gives @request a new branch, making it a new transaction
+fn renew_branch(request: &mut Message) {
+    let Some((_, via)) = request
+        .headers
+        .iter_mut()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Via"))
+    else {
+        return;
+    };
+
+    *via = via
+        .split(';')
+        .map(|param| {
+            if param.trim_start().starts_with("branch=") {
+                format!("branch={}", new_branch())
+            } else {
+                param.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(";");
+}
+
+// The ACK of the non 2xx final @response to @invite, part of the INVITE
+// transaction
+fn failure_ack(invite: &Message, response: &Message) -> Message {
+    let mut ack = Message::request("ACK", invite.request_uri().unwrap_or_default());
+    for name in ["Via", "From", "Call-ID"] {
+        if let Some(value) = invite.header(name) {
+            ack.add_header(name, value);
+        }
+    }
+    if let Some(to) = response.header("To") {
+        ack.add_header("To", to);
+    }
+    if let Some((cseq, _)) = invite.cseq() {
+        ack.add_header("CSeq", format!("{cseq} ACK"));
+    }
+    ack.add_header("Max-Forwards", "70");
+
+    ack
+}
+
+// The CANCEL of the pending @invite
+fn cancel(invite: &Message) -> Message {
+    let mut cancel = Message::request("CANCEL", invite.request_uri().unwrap_or_default());
+    for name in ["Via", "From", "To", "Call-ID"] {
+        if let Some(value) = invite.header(name) {
+            cancel.add_header(name, value);
+        }
+    }
+    if let Some((cseq, _)) = invite.cseq() {
+        cancel.add_header("CSeq", format!("{cseq} CANCEL"));
+    }
+    cancel.add_header("Max-Forwards", "70");
+
+    cancel
+}
+
+impl Signaller {
+    fn raise_error(&self, msg: String) {
+        gst::error!(CAT, imp: self, "{msg}");
+        self.obj().emit_by_name::<()>("error", &[&msg]);
+    }
+
+    async fn connect(&self, settings: &Settings) -> Result<(), Error> {
+        let outbound = settings.outbound_uri();
+        let transport = outbound.map_or(Ok(TransportKind::Udp), TransportKind::for_uri)?;
+        let (sender, receiver) = mpsc::unbounded();
+        let weak = self.obj().downgrade();
+
+        let (sent_by, task_handle) = match transport {
+            TransportKind::Udp => {
+                let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, settings.local_port as u16))
+                    .await
+                    .context("Failed to bind UDP socket")?;
+                let destination = match outbound {
+                    Some(uri) => Some(resolve(uri).await?),
+                    None => None,
+                };
+                let port = socket.local_addr()?.port();
+                let sent_by = match settings.local_address {
+                    Some(ref address) => format!("{address}:{port}"),
+                    None => {
+                        let ip = destination
+                            .and_then(local_ip_towards)
+                            .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+                        SocketAddr::new(ip, port).to_string()
+                    }
+                };
+
+                (
+                    sent_by,
+                    RUNTIME.spawn(Self::run_udp(weak, socket, destination, receiver)),
+                )
+            }
+            TransportKind::Tcp => {
+                let destination = resolve(outbound.unwrap_or_default()).await?;
+                let stream = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(destination))
+                    .await
+                    .context("Timed out connecting")??;
+
+                (
+                    stream.local_addr()?.to_string(),
+                    RUNTIME.spawn(Self::run_tcp(weak, stream, receiver)),
+                )
+            }
+            TransportKind::Ws | TransportKind::Wss => {
+                let mut request = outbound.unwrap_or_default().into_client_request()?;
+                request
+                    .headers_mut()
+                    .insert("Sec-WebSocket-Protocol", HeaderValue::from_static("sip"));
+                let (ws, _) = tokio::time::timeout(
+                    CONNECT_TIMEOUT,
+                    async_tungstenite::tokio::connect_async(request),
+                )
+                .await
+                .context("Timed out connecting")??;
+
+                // WebSocket clients can't be reached directly, RFC 7118 section 5.2
+                (
+                    format!("{}.invalid", new_tag()),
+                    RUNTIME.spawn(Self::run_websocket(weak, ws, receiver)),
+                )
+            }
+        };
+
+        let aor = settings
+            .user_uri
+            .clone()
+            .unwrap_or_else(|| format!("sip:gst@{sent_by}"));
+
+        gst::info!(
+            CAT,
+            imp: self,
+            "Connected over {transport:?} as {aor}, reachable at {sent_by}"
+        );
+
+        self.state.lock().unwrap().connection = Some(Connection {
+            sender,
+            task_handle,
+            transport,
+            sent_by,
+            aor,
+        });
+
+        Ok(())
+    }
+
+    async fn run_udp(
+        weak: glib::WeakRef<super::SipSignaller>,
+        socket: UdpSocket,
+        destination: Option<SocketAddr>,
+        mut receiver: mpsc::UnboundedReceiver<Outgoing>,
+    ) {
+        let mut buffer = vec![0u8; 65535];
+
+        loop {
+            tokio::select! {
+                res = socket.recv_from(&mut buffer) => match res {
+                    Ok((len, source)) => {
+                        let Some(obj) = weak.upgrade() else {
+                            break;
+                        };
+                        obj.imp().handle_data(&buffer[..len], Some(source));
+                    }
+                    // e.g. ICMP port unreachable reported on the socket
+                    Err(err) => gst::debug!(CAT, "Error receiving: {err}"),
+                },
+                msg = receiver.next() => match msg {
+                    Some(Outgoing { text, destination: to }) => {
+                        let Some(to) = to.or(destination) else {
+                            gst::warning!(CAT, "No destination for {text}");
+                            continue;
+                        };
+
+                        gst::trace!(CAT, "Sending to {to}: {text}");
+                        if let Err(err) = socket.send_to(text.as_bytes(), to).await {
+                            gst::warning!(CAT, "Failed to send to {to}: {err}");
+                        }
+                    }
+                    None => break,
+                },
+            }
+        }
+    }
+
+    async fn run_tcp(
+        weak: glib::WeakRef<super::SipSignaller>,
+        stream: TcpStream,
+        mut receiver: mpsc::UnboundedReceiver<Outgoing>,
+    ) {
+        let (mut reader, mut writer) = stream.into_split();
+        let mut buffer = Vec::new();
+        let mut chunk = vec![0u8; 4096];
+
+        let reason = loop {
+            tokio::select! {
+                res = reader.read(&mut chunk) => match res {
+                    Ok(0) => break "closed by peer".to_string(),
+                    Ok(len) => {
+                        let Some(obj) = weak.upgrade() else {
+                            return;
+                        };
+
+                        buffer.extend_from_slice(&chunk[..len]);
+                        loop {
+                            // Skip CRLF keep alives
+                            while buffer.starts_with(b"\r\n") {
+                                buffer.drain(..2);
+                            }
+
+                            let Some(len) = Message::framed_len(&buffer) else {
+                                break;
+                            };
+                            let data = buffer.drain(..len).collect::<Vec<_>>();
+                            obj.imp().handle_data(&data, None);
+                        }
+                    }
+                    Err(err) => break err.to_string(),
+                },
+                msg = receiver.next() => match msg {
+                    Some(Outgoing { text, .. }) => {
+                        gst::trace!(CAT, "Sending: {text}");
+                        if let Err(err) = writer.write_all(text.as_bytes()).await {
+                            break err.to_string();
+                        }
+                    }
+                    None => return,
+                },
+            }
+        };
+
+        if let Some(obj) = weak.upgrade() {
+            obj.imp()
+                .raise_error(format!("Connection to the SIP server lost: {reason}"));
+        }
+    }
+
+    async fn run_websocket(
+        weak: glib::WeakRef<super::SipSignaller>,
+        ws: async_tungstenite::WebSocketStream<async_tungstenite::tokio::ConnectStream>,
+        mut receiver: mpsc::UnboundedReceiver<Outgoing>,
+    ) {
+        let (mut sink, mut stream) = ws.split();
+
+        let reason = loop {
+            tokio::select! {
+                msg = stream.next() => match msg {
+                    Some(Ok(WsMessage::Text(text))) => {
+                        let Some(obj) = weak.upgrade() else {
+                            return;
+                        };
+                        obj.imp().handle_data(text.as_bytes(), None);
+                    }
+                    Some(Ok(WsMessage::Binary(data))) => {
+                        let Some(obj) = weak.upgrade() else {
+                            return;
+                        };
+                        obj.imp().handle_data(&data, None);
+                    }
+                    Some(Ok(WsMessage::Close(reason))) => break format!("closed: {reason:?}"),
+                    Some(Ok(_)) => (),
+                    Some(Err(err)) => break err.to_string(),
+                    None => break "closed".to_string(),
+                },
+                msg = receiver.next() => match msg {
+                    Some(Outgoing { text, .. }) => {
+                        gst::trace!(CAT, "Sending: {text}");
+                        if let Err(err) = sink.send(WsMessage::Text(text)).await {
+                            break err.to_string();
+                        }
+                    }
+                    None => {
+                        let _ = sink.close().await;
+                        return;
+                    }
+                },
+            }
+        };
+
+        if let Some(obj) = weak.upgrade() {
+            obj.imp()
+                .raise_error(format!("Connection to the SIP server lost: {reason}"));
+        }
+    }
+
+    fn transmit(&self, text: String, destination: Option<SocketAddr>) {
+        let state = self.state.lock().unwrap();
+        if let Some(ref connection) = state.connection {
+            let _ = connection
+                .sender
+                .unbounded_send(Outgoing { text, destination });
+        }
+    }
+
+    fn respond(&self, request: &Message, code: u16, reason: &str, destination: Option<SocketAddr>) {
+        self.transmit(
+            Message::response(request, code, reason).to_string(),
+            destination,
+        );
+    }
+
+    // Sends @request, retransmitting it over UDP until a response is received
+    fn send_request(&self, request: Message, destination: Option<SocketAddr>) {
+        let text = request.to_string();
+        let is_udp = self
+            .state
+            .lock()
+            .unwrap()
+            .connection
+            .as_ref()
+            .is_some_and(|connection| connection.transport == TransportKind::Udp);
+
+        if is_udp {
+            if let (Some(branch), Some(method)) = (request.branch(), request.method()) {
+                let key = transaction_key(branch, method);
+                self.retransmit(key, text.clone(), destination, method == "INVITE");
+            }
+        }
+
+        self.transmit(text, destination);
+    }
+
+    fn retransmit(&self, key: String, text: String, destination: Option<SocketAddr>, invite: bool) {
+        let weak = self.obj().downgrade();
+        let task_key = key.clone();
+        let handle = RUNTIME.spawn(async move {
+            let mut interval = T1;
+            let mut elapsed = Duration::ZERO;
+
+            loop {
+                tokio::time::sleep(interval).await;
+                elapsed += interval;
+
+                let Some(obj) = weak.upgrade() else {
+                    return;
+                };
+                if elapsed >= TRANSACTION_TIMEOUT {
+                    obj.imp().on_transaction_timeout(&task_key, &text);
+                    return;
+                }
+
+                gst::debug!(CAT, obj: obj, "Retransmitting {task_key}");
+                obj.imp().transmit(text.clone(), destination);
+                interval = if invite {
+                    interval * 2
+                } else {
+                    (interval * 2).min(T2)
+                };
+            }
+        });
+
+        if let Some(previous) = self
+            .state
+            .lock()
+            .unwrap()
+            .retransmissions
+            .insert(key, handle)
+        {
+            previous.abort();
+        }
+    }
+
+    fn stop_retransmission(&self, key: &str) {
+        if let Some(handle) = self.state.lock().unwrap().retransmissions.remove(key) {
+            handle.abort();
+        }
+    }
+
+    fn on_transaction_timeout(&self, key: &str, text: &str) {
+        self.state.lock().unwrap().retransmissions.remove(key);
+
+        let Ok(message) = Message::parse(text) else {
+            return;
+        };
+        let call_id = message.header("Call-ID").unwrap_or_default().to_string();
+
+        match message
+            .method()
+            .or_else(|| message.cseq().map(|(_, method)| method))
+        {
+            Some("REGISTER") => self.raise_error("Registration timed out".to_string()),
+            Some("INVITE") => {
+                gst::warning!(CAT, imp: self, "Call {call_id} timed out");
+                if self
+                    .state
+                    .lock()
+                    .unwrap()
+                    .sessions
+                    .remove(&call_id)
+                    .is_some()
+                {
+                    self.obj()
+                        .emit_by_name::<bool>("session-ended", &[&call_id]);
+                }
+            }
+            _ => gst::debug!(CAT, imp: self, "Transaction {key} timed out"),
+        }
+    }
+
+    // Answers the challenge of @response to @request, None if it can't or
+    // already failed to
+    fn authorize(&self, request: &Message, response: &Message) -> Option<Message> {
+        let (challenge_header, authorization_header) = match response.code() {
+            Some(401) => ("WWW-Authenticate", "Authorization"),
+            Some(407) => ("Proxy-Authenticate", "Proxy-Authorization"),
+            _ => return None,
+        };
+
+        if request.header(authorization_header).is_some() {
+            gst::warning!(CAT, imp: self, "Authentication failed");
+            return None;
+        }
+
+        let settings = self.settings.lock().unwrap().clone();
+        let password = settings.password?;
+        let aor = self.state.lock().unwrap().connection.as_ref()?.aor.clone();
+        let username = settings
+            .username
+            .or_else(|| user(&aor).map(str::to_string))?;
+
+        let challenge = match DigestChallenge::parse(response.header(challenge_header)?) {
+            Ok(challenge) => challenge,
+            Err(err) => {
+                gst::warning!(CAT, imp: self, "Can't answer challenge: {err}");
+                return None;
+            }
+        };
+
+        let (method, uri) = (request.method()?, request.request_uri()?);
+        let (cseq, _) = request.cseq()?;
+        let mut retry = request.clone();
+        retry.set_header(
+            authorization_header,
+            challenge.authorization(method, uri, &username, &password, &new_tag()),
+        );
+        retry.set_header("CSeq", format!("{} {method}", cseq + 1));
+        renew_branch(&mut retry);
+
+        Some(retry)
+    }
+
+    fn handle_data(&self, data: &[u8], source: Option<SocketAddr>) {
+        let Ok(text) = std::str::from_utf8(data) else {
+            gst::warning!(CAT, imp: self, "Ignoring non UTF-8 message");
+            return;
+        };
+
+        // Keep alives
+        if text.trim().is_empty() {
+            return;
+        }
+
+        gst::trace!(CAT, imp: self, "Received from {source:?}: {text}");
+        let message = match Message::parse(text) {
+            Ok(message) => message,
+            Err(err) => {
+                gst::warning!(CAT, imp: self, "Ignoring invalid message: {err}");
+                return;
+            }
+        };
+
+        if message.code().is_some() {
+            self.handle_response(message);
+        } else {
+            self.handle_request(message, source);
+        }
+    }
+
+    fn handle_response(&self, response: Message) {
+        let Some((_, method)) = response.cseq() else {
+            gst::warning!(CAT, imp: self, "Ignoring response without CSeq");
+            return;
+        };
+
+        if let Some(branch) = response.branch() {
+            self.stop_retransmission(&transaction_key(branch, method));
+        }
+
+        match method {
+            "REGISTER" => self.on_register_response(&response),
+            "INVITE" => self.on_invite_response(&response),
+            _ => gst::debug!(
+                CAT,
+                imp: self,
+                "{method} answered with {:?}",
+                response.start_line
+            ),
+        }
+    }
+
+    fn handle_request(&self, request: Message, source: Option<SocketAddr>) {
+        match request.method() {
+            Some("INVITE") => self.on_invite(request, source),
+            Some("ACK") => self.on_ack(&request),
+            Some("BYE") => self.on_bye(&request, source),
+            Some("CANCEL") => self.on_cancel(&request, source),
+            Some("OPTIONS") => {
+                let mut response = Message::response(&request, 200, "OK");
+                response.add_header("Allow", ALLOW);
+                self.transmit(response.to_string(), source);
+            }
+            method => {
+                gst::debug!(CAT, imp: self, "Unsupported request {method:?}");
+                self.respond(&request, 501, "Not Implemented", source);
+            }
+        }
+    }
+
+    fn send_register(&self, expires: u32) {
+        let mut state = self.state.lock().unwrap();
+        let State {
+            connection: Some(ref connection),
+            registration: Some(ref mut registration),
+            ..
+        } = *state
+        else {
+            return;
+        };
+
+        let Some((domain, port)) = host_port(&connection.aor) else {
+            drop(state);
+            self.raise_error("Invalid user URI".to_string());
+            return;
+        };
+        let registrar = match port {
+            Some(port) => format!("sip:{domain}:{port}"),
+            None => format!("sip:{domain}"),
+        };
+
+        registration.cseq += 1;
+        let mut request = connection.request("REGISTER", &registrar);
+        request.add_header(
+            "From",
+            format!("<{}>;tag={}", connection.aor, registration.tag),
+        );
+        request.add_header("To", format!("<{}>", connection.aor));
+        request.add_header("Call-ID", registration.call_id.clone());
+        request.add_header("CSeq", format!("{} REGISTER", registration.cseq));
+        request.add_header("Contact", connection.contact());
+        request.add_header("Expires", expires.to_string());
+        registration.request = Some(request.clone());
+        drop(state);
+
+        gst::info!(CAT, imp: self, "Registering for {expires}s");
+        self.send_request(request, None);
+    }
+
+    fn on_register_response(&self, response: &Message) {
+        let Some(code) = response.code() else {
+            return;
+        };
+        let requested = self.settings.lock().unwrap().register_expires;
+
+        let mut state = self.state.lock().unwrap();
+        let Some(ref mut registration) = state.registration else {
+            return;
+        };
+        let Some(request) = registration.request.clone() else {
+            return;
+        };
+        if response.cseq() != request.cseq() {
+            gst::debug!(CAT, imp: self, "Ignoring response to a previous REGISTER");
+            return;
+        }
+
+        match code {
+            100..=199 => (),
+            200..=299 => {
+                let expires = response
+                    .header("Contact")
+                    .and_then(|contact| param(contact, "expires"))
+                    .or_else(|| response.header("Expires"))
+                    .and_then(|expires| expires.parse::<u64>().ok())
+                    .unwrap_or(requested.into());
+
+                let first = !registration.registered;
+                registration.registered = true;
+
+                let refresh = Duration::from_secs(expires / 2).max(MIN_REGISTER_REFRESH);
+                let weak = self.obj().downgrade();
+                let refresh_task_handle = RUNTIME.spawn(async move {
+                    tokio::time::sleep(refresh).await;
+                    if let Some(obj) = weak.upgrade() {
+                        obj.imp().send_register(requested);
+                    }
+                });
+                if let Some(handle) = registration
+                    .refresh_task_handle
+                    .replace(refresh_task_handle)
+                {
+                    handle.abort();
+                }
+                drop(state);
+
+                gst::info!(CAT, imp: self, "Registered for {expires}s");
+                if first {
+                    self.start_call();
+                }
+            }
+            _ => {
+                drop(state);
+
+                if let Some(retry) = self.authorize(&request, response) {
+                    let mut state = self.state.lock().unwrap();
+                    if let Some(ref mut registration) = state.registration {
+                        registration.cseq = retry.cseq().map_or(0, |(cseq, _)| cseq);
+                        registration.request = Some(retry.clone());
+                        drop(state);
+
+                        self.send_request(retry, None);
+                    }
+                    return;
+                }
+
+                self.raise_error(format!(
+                    "Registration failed: {code} {}",
+                    response.reason().unwrap_or_default()
+                ));
+            }
+        }
+    }
+
+    // Calls the remote URI, if any, once connected and registered
+    fn start_call(&self) {
+        let Some(remote_uri) = self.settings.lock().unwrap().remote_uri.clone() else {
+            return;
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let Some(aor) = state.connection.as_ref().map(|c| c.aor.clone()) else {
+            return;
+        };
+        let call_id = uuid::Uuid::new_v4().to_string();
+        state.sessions.insert(
+            call_id.clone(),
+            Session {
+                outgoing: true,
+                peer_id: remote_uri.clone(),
+                local: format!("<{aor}>;tag={}", new_tag()),
+                remote: format!("<{remote_uri}>"),
+                target: remote_uri.clone(),
+                cseq: 1,
+                ..Default::default()
+            },
+        );
+        drop(state);
+
+        gst::info!(CAT, imp: self, "Calling {remote_uri}");
+        self.obj().emit_by_name::<()>(
+            "session-requested",
+            &[&call_id, &remote_uri, &None::<WebRTCSessionDescription>],
+        );
+    }
+
+    fn remote_description(
+        &self,
+        sdp_type: WebRTCSDPType,
+        body: &str,
+        offer_mids: &[String],
+    ) -> Result<WebRTCSessionDescription, Error> {
+        let text = if self.settings.lock().unwrap().sdp_munging {
+            sdp::munge_remote(body, offer_mids)
+        } else {
+            body.to_string()
+        };
+        let sdp = gst_sdp::SDPMessage::parse_buffer(text.as_bytes())
+            .map_err(|err| anyhow!("Invalid SDP: {err}"))?;
+
+        Ok(WebRTCSessionDescription::new(sdp_type, sdp))
+    }
+
+    fn on_invite_response(&self, response: &Message) {
+        let (Some(code), Some(call_id)) = (response.code(), response.header("Call-ID")) else {
+            return;
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let State {
+            connection: Some(ref connection),
+            ref mut sessions,
+            ..
+        } = *state
+        else {
+            return;
+        };
+        let Some(session) = sessions.get_mut(call_id) else {
+            return;
+        };
+        let Some(invite) = session.invite.clone() else {
+            return;
+        };
+        if response.cseq() != invite.cseq() {
+            gst::debug!(CAT, imp: self, "Ignoring response to a previous INVITE");
+            return;
+        }
+
+        match code {
+            100..=199 => {
+                gst::debug!(CAT, imp: self, "Call {call_id} progressing: {code}");
+            }
+            200..=299 => {
+                let first = !session.confirmed;
+                session.confirmed = true;
+                if let Some(to) = response.header("To") {
+                    session.remote = to.to_string();
+                }
+                if let Some(contact) = response.header("Contact") {
+                    session.target = uri(contact).to_string();
+                }
+
+                let mut ack = connection.request("ACK", &session.target);
+                ack.add_header("From", session.local.clone());
+                ack.add_header("To", session.remote.clone());
+                ack.add_header("Call-ID", call_id);
+                ack.add_header("CSeq", format!("{} ACK", session.cseq));
+                let offer_mids = session.offer_mids.clone();
+                drop(state);
+
+                self.transmit(ack.to_string(), None);
+                if !first {
+                    return;
+                }
+
+                gst::info!(CAT, imp: self, "Call {call_id} answered");
+                match self.remote_description(WebRTCSDPType::Answer, &response.body, &offer_mids) {
+                    Ok(answer) => {
+                        self.obj()
+                            .emit_by_name::<()>("session-description", &[&call_id, &answer]);
+                    }
+                    Err(err) => {
+                        self.raise_error(format!("Failed to handle answer of {call_id}: {err}"))
+                    }
+                }
+            }
+            _ => {
+                drop(state);
+                self.transmit(failure_ack(&invite, response).to_string(), None);
+
+                if matches!(code, 401 | 407) {
+                    if let Some(retry) = self.authorize(&invite, response) {
+                        let mut state = self.state.lock().unwrap();
+                        if let Some(session) = state.sessions.get_mut(call_id) {
+                            session.cseq = retry.cseq().map_or(0, |(cseq, _)| cseq);
+                            session.invite = Some(retry.clone());
+                            drop(state);
+                            self.send_request(retry, None);
+                            return;
+                        }
+                    }
+                }
+
+                gst::warning!(CAT, imp: self, "Call {call_id} failed: {code}");
+                if self
+                    .state
+                    .lock()
+                    .unwrap()
+                    .sessions
+                    .remove(call_id)
+                    .is_some()
+                {
+                    self.obj()
+                        .emit_by_name::<bool>("session-ended", &[&call_id]);
+                }
+            }
+        }
+    }
+
+    fn on_invite(&self, request: Message, source: Option<SocketAddr>) {
+        let Some(call_id) = request.header("Call-ID").map(str::to_string) else {
+            self.respond(&request, 400, "Bad Request", source);
+            return;
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if let Some(session) = state.sessions.get(&call_id) {
+            let renegotiation = session.outgoing || session.confirmed;
+            drop(state);
+
+            if renegotiation {
+                gst::warning!(CAT, imp: self, "Renegotiating call {call_id} is not supported");
+                self.respond(&request, 488, "Not Acceptable Here", source);
+            } else {
+                // Retransmission
+                self.respond(&request, 100, "Trying", source);
+            }
+            return;
+        }
+
+        if state.connection.is_none() {
+            return;
+        }
+        let (Some(from), Some(to)) = (request.header("From"), request.header("To")) else {
+            drop(state);
+            self.respond(&request, 400, "Bad Request", source);
+            return;
+        };
+        let from = from.to_string();
+        let session = Session {
+            outgoing: false,
+            peer_id: uri(&from).to_string(),
+            local: format!("{to};tag={}", new_tag()),
+            remote: from.clone(),
+            target: uri(request.header("Contact").unwrap_or(&from)).to_string(),
+            late_offer: request.body.is_empty(),
+            source,
+            invite: Some(request.clone()),
+            ..Default::default()
+        };
+        state.sessions.insert(call_id.clone(), session);
+        drop(state);
+
+        gst::info!(CAT, imp: self, "Incoming call {call_id} from {from}");
+        self.respond(&request, 100, "Trying", source);
+
+        let offer = if request.body.is_empty() {
+            None
+        } else {
+            match self.remote_description(WebRTCSDPType::Offer, &request.body, &[]) {
+                Ok(offer) => Some(offer),
+                Err(err) => {
+                    gst::warning!(CAT, imp: self, "Rejecting call {call_id}: {err}");
+                    self.state.lock().unwrap().sessions.remove(&call_id);
+                    self.respond(&request, 488, "Not Acceptable Here", source);
+                    return;
+                }
+            }
+        };
+
+        self.obj()
+            .emit_by_name::<()>("session-requested", &[&call_id, &uri(&from), &offer]);
+    }
+
+    fn on_ack(&self, request: &Message) {
+        let Some(call_id) = request.header("Call-ID") else {
+            return;
+        };
+        self.stop_retransmission(&response_key(call_id));
+
+        let mut state = self.state.lock().unwrap();
+        let Some(session) = state.sessions.get_mut(call_id) else {
+            return;
+        };
+        if session.confirmed || session.outgoing {
+            return;
+        }
+        session.confirmed = true;
+        let late_offer = session.late_offer;
+        let offer_mids = session.offer_mids.clone();
+        drop(state);
+
+        gst::info!(CAT, imp: self, "Call {call_id} established");
+        if !late_offer {
+            return;
+        }
+
+        match self.remote_description(WebRTCSDPType::Answer, &request.body, &offer_mids) {
+            Ok(answer) => {
+                self.obj()
+                    .emit_by_name::<()>("session-description", &[&call_id, &answer]);
+            }
+            Err(err) => self.raise_error(format!("Failed to handle answer of {call_id}: {err}")),
+        }
+    }
+
+    fn on_bye(&self, request: &Message, source: Option<SocketAddr>) {
+        let Some(call_id) = request.header("Call-ID") else {
+            return;
+        };
+
+        let removed = self
+            .state
+            .lock()
+            .unwrap()
+            .sessions
+            .remove(call_id)
+            .is_some();
+        if !removed {
+            self.respond(request, 481, "Call/Transaction Does Not Exist", source);
+            return;
+        }
+
+        self.stop_retransmission(&response_key(call_id));
+        self.respond(request, 200, "OK", source);
+
+        gst::info!(CAT, imp: self, "Call {call_id} hung up");
+        self.obj()
+            .emit_by_name::<bool>("session-ended", &[&call_id]);
+    }
+
+    fn on_cancel(&self, request: &Message, source: Option<SocketAddr>) {
+        let Some(call_id) = request.header("Call-ID") else {
+            return;
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let cancelled = match state.sessions.get(call_id) {
+            Some(session) if !session.outgoing && !session.confirmed => {
+                state.sessions.remove(call_id)
+            }
+            _ => None,
+        };
+        drop(state);
+
+        let Some(session) = cancelled else {
+            self.respond(request, 481, "Call/Transaction Does Not Exist", source);
+            return;
+        };
+
+        self.respond(request, 200, "OK", source);
+        self.stop_retransmission(&response_key(call_id));
+        if let Some(invite) = session.invite {
+            let mut response = Message::response(&invite, 487, "Request Terminated");
+            response.set_header("To", session.local);
+            self.transmit(response.to_string(), source);
+        }
+
+        gst::info!(CAT, imp: self, "Call {call_id} cancelled");
+        self.obj()
+            .emit_by_name::<bool>("session-ended", &[&call_id]);
+    }
+
+    // Sends our complete description, in the INVITE of an outgoing call or
+    // in the final response to an incoming one
+    fn send_local_description(&self, session_id: &str, desc: &WebRTCSessionDescription) {
+        let text = match desc.sdp().as_text() {
+            Ok(text) => text,
+            Err(err) => {
+                self.raise_error(format!("Failed to serialize SDP: {err:?}"));
+                return;
+            }
+        };
+        let text = if self.settings.lock().unwrap().sdp_munging {
+            sdp::munge_local(&text)
+        } else {
+            text
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let State {
+            connection: Some(ref connection),
+            ref mut sessions,
+            ..
+        } = *state
+        else {
+            return;
+        };
+        let contact = connection.contact();
+        let is_udp = connection.transport == TransportKind::Udp;
+        let Some(session) = sessions.get_mut(session_id) else {
+            gst::warning!(CAT, imp: self, "Can't send SDP for unknown session {session_id}");
+            return;
+        };
+
+        if desc.type_() == WebRTCSDPType::Offer {
+            session.offer_mids = sdp::mids(&text);
+        }
+
+        if session.outgoing && session.invite.is_none() {
+            let mut invite = connection.request("INVITE", &session.target);
+            invite.add_header("From", session.local.clone());
+            invite.add_header("To", session.remote.clone());
+            invite.add_header("Call-ID", session_id);
+            invite.add_header("CSeq", format!("{} INVITE", session.cseq));
+            invite.add_header("Contact", contact);
+            invite.add_header("Allow", ALLOW);
+            invite.set_body("application/sdp", text);
+            session.invite = Some(invite.clone());
+            drop(state);
+
+            self.send_request(invite, None);
+        } else if !session.outgoing && !session.confirmed {
+            let Some(ref request) = session.invite else {
+                return;
+            };
+            let mut response = Message::response(request, 200, "OK");
+            response.set_header("To", session.local.clone());
+            response.add_header("Contact", contact);
+            response.add_header("Allow", ALLOW);
+            response.set_body("application/sdp", text);
+            let source = session.source;
+            drop(state);
+
+            // The response is retransmitted until it is acknowledged
+            let text = response.to_string();
+            if is_udp {
+                self.retransmit(response_key(session_id), text.clone(), source, true);
+            }
+            self.transmit(text, source);
+        } else {
+            gst::warning!(
+                CAT,
+                imp: self,
+                "Renegotiating session {session_id} is not supported"
+            );
+        }
+    }
+
+    fn on_gathering_complete(&self, session_id: &str, webrtcbin: &gst::Element) {
+        let pending = self
+            .state
+            .lock()
+            .unwrap()
+            .sessions
+            .get_mut(session_id)
+            .map(|session| std::mem::take(&mut session.sdp_pending))
+            .unwrap_or(false);
+        if !pending {
+            return;
+        }
+
+        match webrtcbin.property::<Option<WebRTCSessionDescription>>("local-description") {
+            Some(desc) => self.send_local_description(session_id, &desc),
+            None => self.raise_error("Local description is not set".to_string()),
+        }
+    }
+}
+
+impl SignallableImpl for Signaller {
+    fn start(&self) {
+        let settings = self.settings.lock().unwrap().clone();
+        if self.state.lock().unwrap().connection.is_some() {
+            gst::debug!(CAT, imp: self, "Already started");
+            return;
+        }
+
+        if settings.role == WebRTCSignallerRole::Listener {
+            self.raise_error("The listener role is not supported".to_string());
+            return;
+        }
+
+        let weak = self.obj().downgrade();
+        RUNTIME.spawn(async move {
+            let Some(obj) = weak.upgrade() else {
+                return;
+            };
+            let imp = obj.imp();
+
+            if let Err(err) = imp.connect(&settings).await {
+                imp.raise_error(format!("Failed to connect: {err:?}"));
+                return;
+            }
+
+            if settings.register && settings.proxy_uri.is_some() {
+                imp.state.lock().unwrap().registration = Some(Registration {
+                    call_id: uuid::Uuid::new_v4().to_string(),
+                    tag: new_tag(),
+                    cseq: 0,
+                    request: None,
+                    registered: false,
+                    refresh_task_handle: None,
+                });
+                imp.send_register(settings.register_expires);
+            } else {
+                imp.start_call();
+            }
+        });
+    }
+
+    fn stop(&self) {
+        gst::info!(CAT, imp: self, "Stopping now");
+
+        let session_ids = self
+            .state
+            .lock()
+            .unwrap()
+            .sessions
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        for session_id in session_ids {
+            self.end_session(&session_id);
+        }
+
+        let registered = {
+            let mut state = self.state.lock().unwrap();
+            for (_, handle) in state.retransmissions.drain() {
+                handle.abort();
+            }
+            state.registration.as_mut().is_some_and(|registration| {
+                if let Some(handle) = registration.refresh_task_handle.take() {
+                    handle.abort();
+                }
+                registration.registered
+            })
+        };
+        if registered {
+            self.send_register(0);
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.registration = None;
+        let Some(connection) = state.connection.take() else {
+            return;
+        };
+        drop(state);
+
+        // Let the pending messages be sent before closing the connection
+        connection.sender.close_channel();
+        let mut task_handle = connection.task_handle;
+        RUNTIME.block_on(async {
+            if tokio::time::timeout(Duration::from_secs(2), &mut task_handle)
+                .await
+                .is_err()
+            {
+                task_handle.abort();
+            }
+        });
+        for (_, handle) in self.state.lock().unwrap().retransmissions.drain() {
+            handle.abort();
+        }
+    }
+
+    fn send_sdp(&self, session_id: &str, sdp: &WebRTCSessionDescription) {
+        gst::debug!(CAT, imp: self, "Sending SDP {:?} for {session_id}", sdp.type_());
+
+        // Candidates are not trickled, the description is sent once they
+        // are all gathered
+        let webrtcbin = self
+            .state
+            .lock()
+            .unwrap()
+            .sessions
+            .get(session_id)
+            .and_then(|session| session.webrtcbin.as_ref())
+            .and_then(|webrtcbin| webrtcbin.upgrade());
+
+        if let Some(webrtcbin) = webrtcbin {
+            if let Some(session) = self.state.lock().unwrap().sessions.get_mut(session_id) {
+                session.sdp_pending = true;
+            }
+
+            // Gathering may have completed in the meantime
+            if webrtcbin.property::<WebRTCICEGatheringState>("ice-gathering-state")
+                == WebRTCICEGatheringState::Complete
+            {
+                self.on_gathering_complete(session_id, &webrtcbin);
+            }
+            return;
+        }
+
+        self.send_local_description(session_id, sdp);
+    }
+
+    fn add_ice(
+        &self,
+        _session_id: &str,
+        _candidate: &str,
+        _sdp_m_line_index: u32,
+        _sdp_mid: Option<String>,
+    ) {
+        // Candidates are sent along with the description
+    }
+
+    fn end_session(&self, session_id: &str) {
+        gst::debug!(CAT, imp: self, "Ending session {session_id}");
+
+        let mut state = self.state.lock().unwrap();
+        let Some(mut session) = state.sessions.remove(session_id) else {
+            return;
+        };
+        let Some(connection) = state.connection.as_ref() else {
+            return;
+        };
+
+        if session.confirmed {
+            session.cseq += 1;
+            let mut bye = connection.request("BYE", &session.target);
+            bye.add_header("From", session.local.clone());
+            bye.add_header("To", session.remote.clone());
+            bye.add_header("Call-ID", session_id);
+            bye.add_header("CSeq", format!("{} BYE", session.cseq));
+            drop(state);
+
+            gst::info!(CAT, imp: self, "Hanging up {session_id}");
+            self.send_request(bye, session.source);
+        } else if let Some(invite) = session.invite {
+            drop(state);
+
+            if session.outgoing {
+                if let Some(branch) = invite.branch() {
+                    self.stop_retransmission(&transaction_key(branch, "INVITE"));
+                }
+
+                gst::info!(CAT, imp: self, "Cancelling {session_id}");
+                self.send_request(cancel(&invite), None);
+            } else {
+                self.stop_retransmission(&response_key(session_id));
+
+                let mut response = Message::response(&invite, 603, "Decline");
+                response.set_header("To", session.local);
+
+                gst::info!(CAT, imp: self, "Declining {session_id}");
+                self.transmit(response.to_string(), session.source);
+            }
+        }
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for Signaller {
+    const NAME: &'static str = "GstSipSignaller";
+    type Type = super::SipSignaller;
+    type ParentType = glib::Object;
+    type Interfaces = (Signallable,);
+}
+
+impl ObjectImpl for Signaller {
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        self.obj().connect_closure(
+            "webrtcbin-ready",
+            false,
+            glib::closure!(|signaller: &super::SipSignaller,
+                            peer_id: &str,
+                            webrtcbin: &gst::Element| {
+                // webrtcsrc gives the Call-ID, webrtcsink the URI of the
+                // peer, which identifies the call not bound to a webrtcbin yet
+                let session_id = {
+                    let mut state = signaller.imp().state.lock().unwrap();
+                    let session = if state.sessions.contains_key(peer_id) {
+                        state
+                            .sessions
+                            .get_mut(peer_id)
+                            .map(|session| (peer_id.to_string(), session))
+                    } else {
+                        state
+                            .sessions
+                            .iter_mut()
+                            .find(|(_, session)| {
+                                session.webrtcbin.is_none() && session.peer_id == peer_id
+                            })
+                            .map(|(call_id, session)| (call_id.clone(), session))
+                    };
+                    let Some((session_id, session)) = session else {
+                        gst::warning!(CAT, obj: signaller, "No call for {peer_id}");
+                        return;
+                    };
+                    session.webrtcbin = Some(webrtcbin.downgrade());
+
+                    session_id
+                };
+
+                let obj_weak = signaller.downgrade();
+                webrtcbin.connect_notify(Some("ice-gathering-state"), move |webrtcbin, _pspec| {
+                    let Some(obj) = obj_weak.upgrade() else {
+                        return;
+                    };
+
+                    if webrtcbin.property::<WebRTCICEGatheringState>("ice-gathering-state")
+                        == WebRTCICEGatheringState::Complete
+                    {
+                        gst::info!(CAT, obj: obj, "ICE gathering complete for {session_id}");
+                        obj.imp().on_gathering_complete(&session_id, webrtcbin);
+                    }
+                });
+            }),
+        );
+    }
+
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+            vec![
+                glib::ParamSpecEnum::builder_with_default("role", WebRTCSignallerRole::default())
+                    .nick("Role")
+                    .blurb("Whether the signaller produces or consumes streams")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("user-uri")
+                    .nick("User URI")
+                    .blurb("SIP address of record of the user, e.g. sip:alice@example.com")
+                    .mutable_ready()
+                    .build(),
+                /**
+                 * GstSipSignaller:proxy-uri:
+                 *
+                 * The outbound proxy and registrar all requests are sent to, e.g.
+                 * `sip:pbx.example.com;transport=tcp`, or a `ws://` or `wss://`
+                 * URL to connect to over WebSocket. Requests are sent to the
+                 * `remote-uri` over UDP or TCP if unset.
+                 */
+                glib::ParamSpecString::builder("proxy-uri")
+                    .nick("Proxy URI")
+                    .blurb("SIP URI or WebSocket URL of the outbound proxy and registrar")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("remote-uri")
+                    .nick("Remote URI")
+                    .blurb("SIP URI to call once started, calls are only received if unset")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("username")
+                    .nick("Username")
+                    .blurb("Digest authentication username, the user of the user URI if unset")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("password")
+                    .nick("Password")
+                    .blurb("Digest authentication password")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecBoolean::builder("register")
+                    .nick("Register")
+                    .blurb("Whether to register with the proxy")
+                    .default_value(DEFAULT_REGISTER)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("register-expires")
+                    .nick("Register expires")
+                    .blurb("Requested duration of the registration in seconds")
+                    .minimum(60)
+                    .default_value(DEFAULT_REGISTER_EXPIRES)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("local-port")
+                    .nick("Local port")
+                    .blurb("UDP port to listen on, 0 for a random one")
+                    .maximum(u16::MAX as u32)
+                    .default_value(DEFAULT_LOCAL_PORT)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("local-address")
+                    .nick("Local address")
+                    .blurb("Address advertised in the Via and Contact headers over UDP, detected if unset")
+                    .mutable_ready()
+                    .build(),
+                /**
+                 * GstSipSignaller:sdp-munging:
+                 *
+                 * Rewrites the descriptions for endpoints that don't implement
+                 * all of WebRTC: the media ports and addresses of the local
+                 * descriptions are the ones of their default candidate for
+                 * endpoints that don't do ICE, and the ICE and DTLS
+                 * parameters of the remote descriptions are copied from the
+                 * session level to each media, which also get an identifier if
+                 * missing.
+                 */
+                glib::ParamSpecBoolean::builder("sdp-munging")
+                    .nick("SDP munging")
+                    .blurb("Whether to rewrite the SDP for endpoints that don't implement all of WebRTC")
+                    .default_value(DEFAULT_SDP_MUNGING)
+                    .mutable_ready()
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let mut settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "role" => settings.role = value.get().expect("type checked upstream"),
+            "user-uri" => settings.user_uri = value.get().expect("type checked upstream"),
+            "proxy-uri" => settings.proxy_uri = value.get().expect("type checked upstream"),
+            "remote-uri" => settings.remote_uri = value.get().expect("type checked upstream"),
+            "username" => settings.username = value.get().expect("type checked upstream"),
+            "password" => settings.password = value.get().expect("type checked upstream"),
+            "register" => settings.register = value.get().expect("type checked upstream"),
+            "register-expires" => {
+                settings.register_expires = value.get().expect("type checked upstream")
+            }
+            "local-port" => settings.local_port = value.get().expect("type checked upstream"),
+            "local-address" => settings.local_address = value.get().expect("type checked upstream"),
+            "sdp-munging" => settings.sdp_munging = value.get().expect("type checked upstream"),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "role" => settings.role.to_value(),
+            "user-uri" => settings.user_uri.to_value(),
+            "proxy-uri" => settings.proxy_uri.to_value(),
+            "remote-uri" => settings.remote_uri.to_value(),
+            "username" => settings.username.to_value(),
+            "password" => settings.password.to_value(),
+            "register" => settings.register.to_value(),
+            "register-expires" => settings.register_expires.to_value(),
+            "local-port" => settings.local_port.to_value(),
+            "local-address" => settings.local_address.to_value(),
+            "sdp-munging" => settings.sdp_munging.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signaller::test_utils::{self, next, session_description, Signals, SDP};
+    use crate::signaller::SignallableExt;
+    use crate::sip_signaller::SipSignaller;
+    use gst::prelude::*;
+
+    // A user agent standing in for the proxy, the registrar and the callee
+    struct Peer {
+        socket: std::net::UdpSocket,
+    }
+
+    impl Peer {
+        fn new() -> Self {
+            let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+            socket
+                .set_read_timeout(Some(Duration::from_secs(10)))
+                .unwrap();
+
+            Self { socket }
+        }
+
+        fn uri(&self) -> String {
+            format!("sip:{}", self.socket.local_addr().unwrap())
+        }
+
+        fn recv(&self) -> (Message, SocketAddr) {
+            let mut buffer = vec![0u8; 65535];
+            let (len, source) = self.socket.recv_from(&mut buffer).unwrap();
+
+            (
+                Message::parse(std::str::from_utf8(&buffer[..len]).unwrap()).unwrap(),
+                source,
+            )
+        }
+
+        // Skips retransmissions and provisional responses
+        fn expect_request(&self, method: &str) -> (Message, SocketAddr) {
+            loop {
+                let (message, source) = self.recv();
+                if message.method() == Some(method) {
+                    return (message, source);
+                }
+            }
+        }
+
+        fn expect_response(&self, code: u16, method: &str) -> Message {
+            loop {
+                let (message, _) = self.recv();
+                if message.code() == Some(code) && message.cseq().is_some_and(|(_, m)| m == method)
+                {
+                    return message;
+                }
+            }
+        }
+
+        fn send(&self, message: &Message, destination: SocketAddr) {
+            self.socket
+                .send_to(message.to_string().as_bytes(), destination)
+                .unwrap();
+        }
+    }
+
+    // Starts @signaller, its signals are sent to the returned receiver along
+    // with their first argument
+    fn start_signaller(signaller: &SipSignaller) -> Signals {
+        test_utils::start_signaller(
+            signaller,
+            &[
+                "session-requested",
+                "session-started",
+                "session-description",
+                "session-ended",
+                "error",
+            ],
+        )
+    }
+
+    fn with_tag(response: &mut Message) {
+        let to = format!("{};tag=peer", response.header("To").unwrap());
+        response.set_header("To", to);
+    }
+
+    #[test]
+    fn test_register_and_call() {
+        gst::init().unwrap();
+
+        let peer = Peer::new();
+        let signaller = SipSignaller::new_producer();
+        signaller.set_property("proxy-uri", peer.uri());
+        signaller.set_property("user-uri", "sip:alice@127.0.0.1");
+        signaller.set_property("password", "secret");
+        signaller.set_property("remote-uri", "sip:bob@127.0.0.1");
+        let rx = start_signaller(&signaller);
+
+        let (register, source) = peer.expect_request("REGISTER");
+        assert_eq!(register.request_uri(), Some("sip:127.0.0.1"));
+        assert!(register.header("Authorization").is_none());
+        let mut challenge = Message::response(&register, 401, "Unauthorized");
+        challenge.add_header(
+            "WWW-Authenticate",
+            "Digest realm=\"test\", nonce=\"abc\", qop=\"auth\"",
+        );
+        peer.send(&challenge, source);
+
+        let (register, source) = peer.expect_request("REGISTER");
+        let authorization = register.header("Authorization").unwrap();
+        let cnonce = authorization
+            .split(", ")
+            .find_map(|field| field.strip_prefix("cnonce="))
+            .unwrap()
+            .trim_matches('"');
+        let expected = DigestChallenge::parse(challenge.header("WWW-Authenticate").unwrap())
+            .unwrap()
+            .authorization("REGISTER", "sip:127.0.0.1", "alice", "secret", cnonce);
+        assert_eq!(authorization, expected);
+        assert_eq!(register.cseq(), Some((2, "REGISTER")));
+        let mut ok = Message::response(&register, 200, "OK");
+        ok.add_header("Expires", "3600");
+        peer.send(&ok, source);
+
+        let call_id = next(&rx, "session-requested");
+        signaller.send_sdp(&call_id, &session_description(WebRTCSDPType::Offer));
+
+        let (invite, source) = peer.expect_request("INVITE");
+        assert_eq!(invite.request_uri(), Some("sip:bob@127.0.0.1"));
+        assert_eq!(invite.header("Call-ID"), Some(call_id.as_str()));
+        assert!(invite.body.starts_with("v=0\r\n"));
+        peer.send(&Message::response(&invite, 180, "Ringing"), source);
+        let mut ok = Message::response(&invite, 200, "OK");
+        with_tag(&mut ok);
+        ok.add_header("Contact", format!("<{}>", peer.uri()));
+        ok.set_body("application/sdp", SDP.to_string());
+        peer.send(&ok, source);
+
+        assert_eq!(next(&rx, "session-description"), call_id);
+        let (ack, _) = peer.expect_request("ACK");
+        assert_eq!(ack.request_uri(), Some(peer.uri().as_str()));
+        assert_eq!(ack.cseq(), Some((1, "ACK")));
+        assert_eq!(param(ack.header("To").unwrap(), "tag"), Some("peer"));
+
+        let mut bye = Message::request("BYE", "sip:alice@127.0.0.1");
+        bye.add_header("Via", "SIP/2.0/UDP 127.0.0.1;branch=z9hG4bKbye");
+        bye.add_header("From", ok.header("To").unwrap());
+        bye.add_header("To", ok.header("From").unwrap());
+        bye.add_header("Call-ID", call_id.clone());
+        bye.add_header("CSeq", "1 BYE");
+        peer.send(&bye, source);
+
+        peer.expect_response(200, "BYE");
+        assert_eq!(next(&rx, "session-ended"), call_id);
+
+        signaller.stop();
+        let (register, _) = peer.expect_request("REGISTER");
+        assert_eq!(register.header("Expires"), Some("0"));
+    }
+
+    #[test]
+    fn test_sink_call() {
+        gst::init().unwrap();
+
+        let peer = Peer::new();
+        let sink = glib::Object::new::<crate::webrtcsink::SipWebRTCSink>();
+        sink.set_property("stun-server", None::<String>);
+        let signaller = sink.property::<glib::Object>("signaller");
+        signaller.set_property("proxy-uri", peer.uri());
+        signaller.set_property("user-uri", "sip:alice@127.0.0.1");
+        signaller.set_property("remote-uri", "sip:bob@127.0.0.1");
+        signaller.set_property("register", false);
+
+        let pipeline = gst::Pipeline::new();
+        let videotestsrc = gst::ElementFactory::make("videotestsrc")
+            .property("is-live", true)
+            .build()
+            .unwrap();
+        pipeline
+            .add_many([&videotestsrc, sink.upcast_ref::<gst::Element>()])
+            .unwrap();
+        videotestsrc.link(&sink).unwrap();
+        pipeline.set_state(gst::State::Playing).unwrap();
+
+        // The offer is sent once the candidates are gathered
+        let (invite, _) = peer.expect_request("INVITE");
+        assert_eq!(invite.request_uri(), Some("sip:bob@127.0.0.1"));
+        assert!(invite.body.contains("a=candidate:"), "{}", invite.body);
+
+        pipeline.set_state(gst::State::Null).unwrap();
+    }
+
+    #[test]
+    fn test_incoming_call() {
+        gst::init().unwrap();
+
+        let peer = Peer::new();
+        let signaller = SipSignaller::new_consumer();
+        signaller.set_property("proxy-uri", peer.uri());
+        signaller.set_property("user-uri", "sip:alice@127.0.0.1");
+        let rx = start_signaller(&signaller);
+
+        let (register, source) = peer.expect_request("REGISTER");
+        peer.send(&Message::response(&register, 200, "OK"), source);
+
+        let mut invite = Message::request("INVITE", register.header("Contact").map(uri).unwrap());
+        invite.add_header("Via", "SIP/2.0/UDP 127.0.0.1;branch=z9hG4bKinvite");
+        invite.add_header("From", "<sip:bob@127.0.0.1>;tag=peer");
+        invite.add_header("To", "<sip:alice@127.0.0.1>");
+        invite.add_header("Call-ID", "call");
+        invite.add_header("CSeq", "1 INVITE");
+        invite.add_header("Contact", format!("<{}>", peer.uri()));
+        invite.set_body("application/sdp", SDP.to_string());
+        peer.send(&invite, source);
+
+        peer.expect_response(100, "INVITE");
+        assert_eq!(next(&rx, "session-requested"), "call");
+        signaller.send_sdp("call", &session_description(WebRTCSDPType::Answer));
+
+        let ok = peer.expect_response(200, "INVITE");
+        assert!(param(ok.header("To").unwrap(), "tag").is_some());
+        assert!(ok.header("Contact").is_some());
+        assert!(ok.body.starts_with("v=0\r\n"));
+
+        let mut ack = Message::request("ACK", uri(ok.header("Contact").unwrap()));
+        ack.add_header("Via", "SIP/2.0/UDP 127.0.0.1;branch=z9hG4bKack");
+        ack.add_header("From", ok.header("From").unwrap());
+        ack.add_header("To", ok.header("To").unwrap());
+        ack.add_header("Call-ID", "call");
+        ack.add_header("CSeq", "1 ACK");
+        peer.send(&ack, source);
+
+        // Wait for the ACK to be handled
+        let mut options = Message::request("OPTIONS", "sip:alice@127.0.0.1");
+        options.add_header("Via", "SIP/2.0/UDP 127.0.0.1;branch=z9hG4bKoptions");
+        options.add_header("Call-ID", "options");
+        options.add_header("CSeq", "1 OPTIONS");
+        peer.send(&options, source);
+        peer.expect_response(200, "OPTIONS");
+
+        signaller.end_session("call");
+        let (bye, source) = peer.expect_request("BYE");
+        assert_eq!(bye.request_uri(), Some(peer.uri().as_str()));
+        assert_eq!(bye.header("To"), Some("<sip:bob@127.0.0.1>;tag=peer"));
+        peer.send(&Message::response(&bye, 200, "OK"), source);
+
+        signaller.stop();
+    }
+}
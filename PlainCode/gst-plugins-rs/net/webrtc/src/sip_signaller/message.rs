@@ -0,0 +1,486 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use anyhow::{anyhow, Error};
+use data_encoding::HEXLOWER;
+use md5::{Digest, Md5};
+use std::fmt;
+
+// Compact forms of the header names, RFC 3261 section 7.3.3
+const COMPACT_HEADERS: &[(&str, &str)] = &[
+    ("v", "Via"),
+    ("f", "From"),
+    ("t", "To"),
+    ("i", "Call-ID"),
+    ("m", "Contact"),
+    ("l", "Content-Length"),
+    ("c", "Content-Type"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StartLine {
+    Request { method: String, uri: String },
+    Response { code: u16, reason: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub start_line: StartLine,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Message {
+    pub fn request(method: &str, uri: &str) -> Self {
+        Self {
+            start_line: StartLine::Request {
+                method: method.to_string(),
+                uri: uri.to_string(),
+            },
+            headers: vec![],
+            body: String::new(),
+        }
+    }
+
+    // Builds a response to @request, copying the headers identifying the
+    // transaction
+    pub fn response(request: &Message, code: u16, reason: &str) -> Self {
+        let mut response = Self {
+            start_line: StartLine::Response {
+                code,
+                reason: reason.to_string(),
+            },
+            headers: vec![],
+            body: String::new(),
+        };
+
+        for name in ["Via", "From", "To", "Call-ID", "CSeq"] {
+            for value in request.headers(name) {
+                response.add_header(name, value);
+            }
+        }
+
+        response
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let (head, body) = text
+            .split_once("\r\n\r\n")
+            .ok_or_else(|| anyhow!("Missing end of headers"))?;
+        let mut lines = head.split("\r\n");
+
+        let start_line = lines.next().ok_or_else(|| anyhow!("Empty message"))?;
+        let start_line = if let Some(status) = start_line.strip_prefix("SIP/2.0 ") {
+            let (code, reason) = status.split_once(' ').unwrap_or((status, ""));
+            StartLine::Response {
+                code: code
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid status code {code}"))?,
+                reason: reason.to_string(),
+            }
+        } else {
+            let mut parts = start_line.split(' ');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(method), Some(uri), Some("SIP/2.0")) => StartLine::Request {
+                    method: method.to_string(),
+                    uri: uri.to_string(),
+                },
+                _ => return Err(anyhow!("Invalid start line {start_line}")),
+            }
+        };
+
+        let mut headers: Vec<(String, String)> = vec![];
+        for line in lines {
+            // Folded header values continue on lines starting with whitespace
+            if line.starts_with([' ', '\t']) {
+                let (_, value) = headers
+                    .last_mut()
+                    .ok_or_else(|| anyhow!("Invalid header line {line}"))?;
+                value.push(' ');
+                value.push_str(line.trim());
+                continue;
+            }
+
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Invalid header line {line}"))?;
+            let name = name.trim();
+            let name = COMPACT_HEADERS
+                .iter()
+                .find(|(compact, _)| compact.eq_ignore_ascii_case(name))
+                .map_or(name, |(_, full)| full);
+            headers.push((name.to_string(), value.trim().to_string()));
+        }
+
+        let mut message = Self {
+            start_line,
+            headers,
+            body: String::new(),
+        };
+        let length = message
+            .header("Content-Length")
+            .map_or(Ok(body.len()), |length| length.parse())
+            .map_err(|_| anyhow!("Invalid Content-Length"))?;
+        message.body = body
+            .get(..length)
+            .ok_or_else(|| anyhow!("Truncated body"))?
+            .to_string();
+
+        Ok(message)
+    }
+
+    // Length of the first message of the @data stream, if complete
+    pub fn framed_len(data: &[u8]) -> Option<usize> {
+        let end = data.windows(4).position(|w| w == b"\r\n\r\n")? + 4;
+        let head = std::str::from_utf8(&data[..end]).ok()?;
+        let length = head
+            .split("\r\n")
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| {
+                let name = name.trim();
+                name.eq_ignore_ascii_case("Content-Length") || name.eq_ignore_ascii_case("l")
+            })
+            .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+
+        (data.len() >= end + length).then_some(end + length)
+    }
+
+    pub fn method(&self) -> Option<&str> {
+        match self.start_line {
+            StartLine::Request { ref method, .. } => Some(method),
+            StartLine::Response { .. } => None,
+        }
+    }
+
+    pub fn request_uri(&self) -> Option<&str> {
+        match self.start_line {
+            StartLine::Request { ref uri, .. } => Some(uri),
+            StartLine::Response { .. } => None,
+        }
+    }
+
+    pub fn code(&self) -> Option<u16> {
+        match self.start_line {
+            StartLine::Response { code, .. } => Some(code),
+            StartLine::Request { .. } => None,
+        }
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        match self.start_line {
+            StartLine::Response { ref reason, .. } => Some(reason),
+            StartLine::Request { .. } => None,
+        }
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers(name).next()
+    }
+
+    pub fn headers<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.headers
+            .iter()
+            .filter(move |(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn add_header(&mut self, name: &str, value: impl Into<String>) {
+        self.headers.push((name.to_string(), value.into()));
+    }
+
+    pub fn set_header(&mut self, name: &str, value: impl Into<String>) {
+        self.headers.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+        self.add_header(name, value);
+    }
+
+    pub fn set_body(&mut self, content_type: &str, body: String) {
+        self.set_header("Content-Type", content_type);
+        self.body = body;
+    }
+
+    // The sequence number and method of the CSeq header
+    pub fn cseq(&self) -> Option<(u32, &str)> {
+        let (number, method) = self.header("CSeq")?.split_once(' ')?;
+
+        Some((number.trim().parse().ok()?, method.trim()))
+    }
+
+    pub fn branch(&self) -> Option<&str> {
+        param(self.header("Via")?, "branch")
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.start_line {
+            StartLine::Request {
+                ref method,
+                ref uri,
+            } => write!(f, "{method} {uri} SIP/2.0\r\n")?,
+            StartLine::Response { code, ref reason } => write!(f, "SIP/2.0 {code} {reason}\r\n")?,
+        }
+
+        for (name, value) in &self.headers {
+            if !name.eq_ignore_ascii_case("Content-Length") {
+                write!(f, "{name}: {value}\r\n")?;
+            }
+        }
+
+        write!(
+            f,
+            "Content-Length: {}\r\n\r\n{}",
+            self.body.len(),
+            self.body
+        )
+    }
+}
+
+// The value of the @name parameter of a header value, e.g. the tag of a From
+// header
+pub fn param<'a>(value: &'a str, name: &str) -> Option<&'a str> {
+    // Parameters of the URI of a name-addr are not parameters of the header
+    let params = match value.rfind('>') {
+        Some(end) => &value[end + 1..],
+        None => value,
+    };
+
+    params.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=').unwrap_or((param, ""));
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+// The URI of a name-addr or addr-spec header value
+pub fn uri(value: &str) -> &str {
+    match (value.find('<'), value.find('>')) {
+        (Some(start), Some(end)) if start < end => &value[start + 1..end],
+        _ => value.split(';').next().unwrap_or(value).trim(),
+    }
+}
+
+// The host and port of a SIP URI
+pub fn host_port(uri: &str) -> Option<(&str, Option<u16>)> {
+    let rest = uri
+        .strip_prefix("sips:")
+        .or_else(|| uri.strip_prefix("sip:"))?;
+    let rest = rest.split([';', '?']).next()?;
+    let host_port = rest.rsplit_once('@').map_or(rest, |(_, host)| host);
+
+    // IPv6 references are enclosed in brackets
+    if let Some(host_port) = host_port.strip_prefix('[') {
+        let (host, port) = host_port.split_once(']')?;
+        let port = port.strip_prefix(':').and_then(|port| port.parse().ok());
+        return Some((host, port));
+    }
+
+    match host_port.split_once(':') {
+        Some((host, port)) => Some((host, Some(port.parse().ok()?))),
+        None => Some((host_port, None)),
+    }
+}
+
+// The user part of a SIP URI
+pub fn user(uri: &str) -> Option<&str> {
+    let rest = uri
+        .strip_prefix("sips:")
+        .or_else(|| uri.strip_prefix("sip:"))?;
+
+    rest.split_once('@').map(|(user, _)| user)
+}
+
+fn md5_hex(data: &str) -> String {
+    HEXLOWER.encode(&Md5::digest(data.as_bytes()))
+}
+
+// Digest authentication credentials answering a challenge, RFC 2617 and RFC 8760
+#[derive(Debug)]
+pub struct DigestChallenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    qop_auth: bool,
+}
+
+impl DigestChallenge {
+    // Parses the value of a WWW-Authenticate or Proxy-Authenticate header
+    pub fn parse(value: &str) -> Result<Self, Error> {
+        let params = value
+            .trim()
+            .strip_prefix("Digest")
+            .ok_or_else(|| anyhow!("Unsupported authentication scheme {value}"))?;
+
+        let mut realm = None;
+        let mut nonce = None;
+        let mut opaque = None;
+        let mut qop_auth = false;
+        for (key, value) in split_params(params) {
+            match key.to_ascii_lowercase().as_str() {
+                "realm" => realm = Some(value),
+                "nonce" => nonce = Some(value),
+                "opaque" => opaque = Some(value),
+                "qop" => qop_auth = value.split(',').any(|qop| qop.trim() == "auth"),
+                "algorithm" if !value.eq_ignore_ascii_case("MD5") => {
+                    return Err(anyhow!("Unsupported digest algorithm {value}"));
+                }
+                _ => (),
+            }
+        }
+
+        Ok(Self {
+            realm: realm.ok_or_else(|| anyhow!("Challenge without realm"))?,
+            nonce: nonce.ok_or_else(|| anyhow!("Challenge without nonce"))?,
+            opaque,
+            qop_auth,
+        })
+    }
+
+    // The value of the Authorization or Proxy-Authorization header of the
+    // @method request to @uri
+    pub fn authorization(
+        &self,
+        method: &str,
+        uri: &str,
+        username: &str,
+        password: &str,
+        cnonce: &str,
+    ) -> String {
+        let ha1 = md5_hex(&format!("{username}:{}:{password}", self.realm));
+        let ha2 = md5_hex(&format!("{method}:{uri}"));
+        let nc = "00000001";
+
+        let response = if self.qop_auth {
+            md5_hex(&format!("{ha1}:{}:{nc}:{cnonce}:auth:{ha2}", self.nonce))
+        } else {
+            md5_hex(&format!("{ha1}:{}:{ha2}", self.nonce))
+        };
+
+        let mut value = format!(
+            "Digest username=\"{username}\", realm=\"{}\", nonce=\"{}\", uri=\"{uri}\", response=\"{response}\", algorithm=MD5",
+            self.realm, self.nonce
+        );
+        if self.qop_auth {
+            value.push_str(&format!(", qop=auth, nc={nc}, cnonce=\"{cnonce}\""));
+        }
+        if let Some(ref opaque) = self.opaque {
+            value.push_str(&format!(", opaque=\"{opaque}\""));
+        }
+
+        value
+    }
+}
+
+// Splits comma separated key=value parameters, values may be quoted strings
+// containing commas
+fn split_params(params: &str) -> Vec<(String, String)> {
+    let mut result = vec![];
+    let mut rest = params.trim();
+
+    while !rest.is_empty() {
+        let Some((key, value)) = rest.split_once('=') else {
+            break;
+        };
+        let key = key.trim().trim_start_matches(',').trim().to_string();
+        let value = value.trim_start();
+
+        let (value, remaining) = if let Some(quoted) = value.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            (&quoted[..end], quoted.get(end + 1..).unwrap_or(""))
+        } else {
+            let end = value.find(',').unwrap_or(value.len());
+            (value[..end].trim(), &value[end..])
+        };
+
+        result.push((key, value.to_string()));
+        rest = remaining.trim_start().trim_start_matches(',').trim_start();
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let text = "INVITE sip:bob@example.com SIP/2.0\r\n\
+            v: SIP/2.0/UDP 10.0.0.1:5060;branch=z9hG4bK776asdhds\r\n\
+            From: Alice <sip:alice@example.com>;tag=1928301774\r\n\
+            To: <sip:bob@example.com>\r\n\
+            i: a84b4c76e66710\r\n\
+            CSeq: 314159 INVITE\r\n\
+            Subject: folded\r\n  header\r\n\
+            Content-Length: 4\r\n\r\nv=0\nextra";
+
+        let message = Message::parse(text).unwrap();
+        assert_eq!(message.method(), Some("INVITE"));
+        assert_eq!(message.header("call-id"), Some("a84b4c76e66710"));
+        assert_eq!(message.header("Subject"), Some("folded header"));
+        assert_eq!(message.branch(), Some("z9hG4bK776asdhds"));
+        assert_eq!(message.cseq(), Some((314159, "INVITE")));
+        assert_eq!(
+            param(message.header("From").unwrap(), "tag"),
+            Some("1928301774")
+        );
+        assert_eq!(message.body, "v=0\n");
+
+        let response = Message::response(&message, 180, "Ringing");
+        let parsed = Message::parse(&response.to_string()).unwrap();
+        assert_eq!(parsed.code(), Some(180));
+        assert_eq!(parsed.header("CSeq"), Some("314159 INVITE"));
+        assert_eq!(parsed.header("Content-Length"), Some("0"));
+    }
+
+    #[test]
+    fn test_framed_len() {
+        let message = "OPTIONS sip:a SIP/2.0\r\nl: 3\r\n\r\nabc";
+        assert_eq!(Message::framed_len(message.as_bytes()), Some(message.len()));
+        assert_eq!(
+            Message::framed_len(&message.as_bytes()[..message.len() - 1]),
+            None
+        );
+        assert_eq!(Message::framed_len(b"OPTIONS sip:a SIP/2.0\r\n"), None);
+    }
+
+    #[test]
+    fn test_uris() {
+        assert_eq!(
+            uri("\"Bob\" <sip:bob@example.com;transport=tcp>;tag=1"),
+            "sip:bob@example.com;transport=tcp"
+        );
+        assert_eq!(param("<sip:bob@example.com;tag=2>;tag=1", "tag"), Some("1"));
+        assert_eq!(
+            host_port("sip:bob@example.com:5070;transport=tcp"),
+            Some(("example.com", Some(5070)))
+        );
+        assert_eq!(host_port("sip:[::1]:5060"), Some(("::1", Some(5060))));
+        assert_eq!(host_port("sip:example.com"), Some(("example.com", None)));
+        assert_eq!(user("sip:bob@example.com"), Some("bob"));
+    }
+
+    #[test]
+    fn test_digest() {
+        // Example of RFC 2617 section 3.5
+        let challenge = DigestChallenge::parse(
+            "Digest realm=\"testrealm@host.com\", qop=\"auth,auth-int\", nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", opaque=\"5ccc069c403ebaf9f0171e9517f40e41\"",
+        )
+        .unwrap();
+
+        let authorization = challenge.authorization(
+            "GET",
+            "/dir/index.html",
+            "Mufasa",
+            "Circle Of Life",
+            "0a4f113b",
+        );
+        assert!(authorization.contains("response=\"6629fae49393a05397450978507c4ef1\""));
+        assert!(authorization.contains("opaque=\"5ccc069c403ebaf9f0171e9517f40e41\""));
+
+        assert!(
+            DigestChallenge::parse("Digest realm=\"a\", nonce=\"b\", algorithm=SHA-256").is_err()
+        );
+        assert!(DigestChallenge::parse("Basic realm=\"a\"").is_err());
+    }
+}
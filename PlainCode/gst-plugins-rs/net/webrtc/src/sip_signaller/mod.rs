@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::signaller::{Signallable, WebRTCSignallerRole};
+use gst::glib;
+
+mod imp;
+mod message;
+mod sdp;
+
+glib::wrapper! {
+    pub struct SipSignaller(ObjectSubclass<imp::Signaller>) @implements Signallable;
+}
+
+impl SipSignaller {
+    fn new(role: WebRTCSignallerRole) -> Self {
+        glib::Object::builder().property("role", role).build()
+    }
+
+    pub fn new_consumer() -> Self {
+        Self::new(WebRTCSignallerRole::Consumer)
+    }
+
+    pub fn new_producer() -> Self {
+        Self::new(WebRTCSignallerRole::Producer)
+    }
+}
+
+impl Default for SipSignaller {
+    fn default() -> Self {
+        glib::Object::new()
+    }
+}
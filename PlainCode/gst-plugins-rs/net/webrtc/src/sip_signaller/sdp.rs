@@ -0,0 +1,237 @@
+// SPDX-License-Identifier: MPL-2.0
+
+// Rewrites of the SDP exchanged with SIP endpoints that don't implement all
+// of what webrtcbin expects. The SDP is handled as text, line by line.
+
+// Attributes which SIP endpoints often only set at the session level
+const SESSION_TRANSPORT_ATTRIBUTES: &[&str] = &["ice-ufrag", "ice-pwd", "fingerprint", "setup"];
+
+struct Sdp<'a> {
+    session: Vec<&'a str>,
+    medias: Vec<Vec<&'a str>>,
+}
+
+impl<'a> Sdp<'a> {
+    fn parse(text: &'a str) -> Self {
+        let mut sdp = Sdp {
+            session: vec![],
+            medias: vec![],
+        };
+
+        for line in text.lines().map(str::trim_end).filter(|l| !l.is_empty()) {
+            if line.starts_with("m=") {
+                sdp.medias.push(vec![line]);
+            } else if let Some(media) = sdp.medias.last_mut() {
+                media.push(line);
+            } else {
+                sdp.session.push(line);
+            }
+        }
+
+        sdp
+    }
+}
+
+fn to_text(session: &[&str], medias: &[Vec<String>]) -> String {
+    let mut text = String::new();
+    for line in session.iter().copied().chain(
+        medias
+            .iter()
+            .flat_map(|media| media.iter().map(String::as_str)),
+    ) {
+        text.push_str(line);
+        text.push_str("\r\n");
+    }
+
+    text
+}
+
+fn attribute<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let attr = line.strip_prefix("a=")?;
+    let (key, value) = attr.split_once(':').unwrap_or((attr, ""));
+
+    (key == name).then_some(value)
+}
+
+// The address and port of the candidate webrtcbin would use first for the
+// RTP component of a media, which endpoints not doing ICE send media to
+fn default_candidate(media: &[&str]) -> Option<(String, u16)> {
+    media
+        .iter()
+        .filter_map(|line| attribute(line, "candidate"))
+        .filter_map(|candidate| {
+            let fields = candidate.split_whitespace().collect::<Vec<_>>();
+            match fields[..] {
+                [_, "1", transport, priority, address, port, "typ", ..]
+                    if transport.eq_ignore_ascii_case("udp") && !address.ends_with(".local") =>
+                {
+                    Some((
+                        priority.parse::<u64>().ok()?,
+                        address.to_string(),
+                        port.parse::<u16>().ok()?,
+                    ))
+                }
+                _ => None,
+            }
+        })
+        .max_by_key(|(priority, _, _)| *priority)
+        .map(|(_, address, port)| (address, port))
+}
+
+/// Makes a complete local description usable by endpoints that don't do
+/// ICE: the media ports and connection addresses are the ones of their
+/// default candidate, and trickling is not advertised as all candidates are
+/// included
+pub fn munge_local(text: &str) -> String {
+    let sdp = Sdp::parse(text);
+    let mut session = sdp.session.clone();
+    session.retain(|line| attribute(line, "ice-options") != Some("trickle"));
+
+    let medias = sdp
+        .medias
+        .iter()
+        .map(|media| {
+            let candidate = default_candidate(media);
+            media
+                .iter()
+                .filter(|line| attribute(line, "ice-options") != Some("trickle"))
+                .map(|line| match (&candidate, line) {
+                    (Some((_, port)), line) if line.starts_with("m=") => {
+                        let mut fields = line.splitn(3, ' ').collect::<Vec<_>>();
+                        let port = port.to_string();
+                        if fields.len() == 3 {
+                            fields[1] = &port;
+                        }
+                        fields.join(" ")
+                    }
+                    (Some((address, _)), line) if line.starts_with("c=") => {
+                        let family = if address.contains(':') { "IP6" } else { "IP4" };
+                        format!("c=IN {family} {address}")
+                    }
+                    (_, line) => line.to_string(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    to_text(&session, &medias)
+}
+
+/// Makes a remote description from an endpoint that doesn't do BUNDLE or
+/// only sets the ICE and DTLS parameters at the session level acceptable by
+/// webrtcbin: these parameters are copied to each media and the medias
+/// without identifier get the one of the media of the offer, @offer_mids, at
+/// the same index, or their index
+pub fn munge_remote(text: &str, offer_mids: &[String]) -> String {
+    let sdp = Sdp::parse(text);
+
+    let session_attributes = sdp
+        .session
+        .iter()
+        .filter(|line| {
+            SESSION_TRANSPORT_ATTRIBUTES
+                .iter()
+                .any(|name| attribute(line, name).is_some())
+        })
+        .copied()
+        .collect::<Vec<_>>();
+
+    let medias = sdp
+        .medias
+        .iter()
+        .enumerate()
+        .map(|(index, media)| {
+            let mut lines = media.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+
+            for line in &session_attributes {
+                let name = line[2..].split(':').next().unwrap_or_default();
+                if !media.iter().any(|l| attribute(l, name).is_some()) {
+                    lines.push(line.to_string());
+                }
+            }
+
+            if !media.iter().any(|l| attribute(l, "mid").is_some()) {
+                let mid = offer_mids
+                    .get(index)
+                    .cloned()
+                    .unwrap_or_else(|| index.to_string());
+                lines.push(format!("a=mid:{mid}"));
+            }
+
+            lines
+        })
+        .collect::<Vec<_>>();
+
+    to_text(&sdp.session, &medias)
+}
+
+/// The identifiers of the medias of @text, in order
+pub fn mids(text: &str) -> Vec<String> {
+    Sdp::parse(text)
+        .medias
+        .iter()
+        .enumerate()
+        .map(|(index, media)| {
+            media
+                .iter()
+                .find_map(|line| attribute(line, "mid"))
+                .map_or_else(|| index.to_string(), str::to_string)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCAL: &str = "v=0\r\n\
+        o=- 1 0 IN IP4 0.0.0.0\r\n\
+        s=-\r\n\
+        t=0 0\r\n\
+        a=ice-options:trickle\r\n\
+        a=group:BUNDLE video0\r\n\
+        m=video 9 UDP/TLS/RTP/SAVPF 96\r\n\
+        c=IN IP4 0.0.0.0\r\n\
+        a=mid:video0\r\n\
+        a=candidate:1 1 UDP 2015363327 192.168.1.2 50000 typ host\r\n\
+        a=candidate:2 1 UDP 2015363583 abcd.local 50002 typ host\r\n\
+        a=candidate:3 2 UDP 2015363583 192.168.1.2 50004 typ host\r\n\
+        a=candidate:4 1 TCP 1015363327 192.168.1.2 9 typ host tcptype active\r\n";
+
+    #[test]
+    fn test_munge_local() {
+        let munged = munge_local(LOCAL);
+
+        assert!(munged.contains("m=video 50000 UDP/TLS/RTP/SAVPF 96\r\n"));
+        assert!(munged.contains("c=IN IP4 192.168.1.2\r\n"));
+        assert!(!munged.contains("trickle"));
+        assert!(munged.contains("a=group:BUNDLE video0\r\n"));
+    }
+
+    #[test]
+    fn test_munge_remote() {
+        let remote = "v=0\r\n\
+            o=- 1 0 IN IP4 10.0.0.1\r\n\
+            s=-\r\n\
+            t=0 0\r\n\
+            a=ice-ufrag:abcd\r\n\
+            a=ice-pwd:efgh\r\n\
+            a=fingerprint:sha-256 AB:CD\r\n\
+            m=audio 4000 UDP/TLS/RTP/SAVPF 0\r\n\
+            a=ice-ufrag:ijkl\r\n\
+            m=video 4002 UDP/TLS/RTP/SAVPF 96\r\n\
+            a=mid:1\r\n";
+
+        let munged = munge_remote(remote, &["audio0".to_string()]);
+        let medias = munged.split("m=").skip(1).collect::<Vec<_>>();
+
+        assert!(medias[0].contains("a=ice-ufrag:ijkl\r\n"));
+        assert!(!medias[0].contains("a=ice-ufrag:abcd"));
+        assert!(medias[0].contains("a=ice-pwd:efgh\r\n"));
+        assert!(medias[0].contains("a=fingerprint:sha-256 AB:CD\r\n"));
+        assert!(medias[0].contains("a=mid:audio0\r\n"));
+        assert!(medias[1].contains("a=ice-ufrag:abcd\r\n"));
+        assert!(medias[1].contains("a=mid:1\r\n"));
+        assert_eq!(mids(&munged), ["audio0", "1"]);
+    }
+}
@@ -104,7 +104,7 @@ use crate::RUNTIME;
 use futures::future;
 use futures::prelude::*;
 use gst::ErrorMessage;
-use reqwest::header::HeaderMap;
+use reqwest::header::{HeaderMap, HeaderValue};
 use reqwest::redirect::Policy;
 use std::sync::Mutex;
 use std::time::Duration;
@@ -394,6 +394,229 @@ pub fn build_link_header(url_str: &str) -> Result<String, url::ParseError> {
     Ok(link_str)
 }
 
+/// `Link` headers advertising @stun_server and @turn_servers to the clients of
+/// a WHIP or WHEP server, servers that can't be parsed are skipped
+pub fn ice_server_links(stun_server: Option<&str>, turn_servers: &gst::Array) -> HeaderMap {
+    let mut links = HeaderMap::new();
+
+    let turn_servers = turn_servers
+        .iter()
+        .filter_map(|turn_server| turn_server.get::<String>().ok());
+    for server in stun_server
+        .map(String::from)
+        .into_iter()
+        .chain(turn_servers)
+    {
+        match build_link_header(&server)
+            .map_err(|err| err.to_string())
+            .and_then(|link| HeaderValue::from_str(&link).map_err(|err| err.to_string()))
+        {
+            Ok(link) => {
+                links.append(reqwest::header::LINK, link);
+            }
+            Err(err) => {
+                gst::error!(CAT, "Failed to parse {server:?} : {err}");
+            }
+        }
+    }
+
+    links
+}
+
+/// CORS configuration of the WHIP and WHEP servers, allowing any origin when
+/// @allowed_origins is empty
+pub fn cors(allowed_origins: &[String]) -> warp::filters::cors::Builder {
+    let cors = warp::cors()
+        .allow_methods(["GET", "POST", "PATCH", "DELETE", "OPTIONS"])
+        .allow_headers(["authorization", "content-type", "if-match"])
+        .expose_headers(["location", "link", "etag", "accept-post", "accept-patch"]);
+
+    if allowed_origins.is_empty() {
+        return cors.allow_any_origin();
+    }
+
+    // warp panics on origins with a path or a trailing slash
+    let origins = allowed_origins
+        .iter()
+        .filter_map(|origin| match url::Url::parse(origin) {
+            Ok(url) if url.origin().is_tuple() => Some(url.origin().ascii_serialization()),
+            _ => {
+                gst::warning!(CAT, "Ignoring invalid CORS origin {origin:?}");
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    cors.allow_origins(origins.iter().map(String::as_str))
+}
+
+/// ICE parameters and candidates carried by `application/trickle-ice-sdpfrag`
+/// bodies, as used by WHIP and WHEP to trickle candidates and restart ICE
+/// (RFC 8840)
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IceFragment {
+    pub ice_ufrag: Option<String>,
+    pub ice_pwd: Option<String>,
+    pub media: Vec<IceFragmentMedia>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IceFragmentMedia {
+    pub mid: Option<String>,
+    /// Values of the `a=candidate` attributes, i.e. without the `candidate:`
+    /// prefix used by webrtcbin
+    pub candidates: Vec<String>,
+    pub end_of_candidates: bool,
+}
+
+const ICE_ATTRIBUTES: [&str; 4] = ["ice-ufrag", "ice-pwd", "candidate", "end-of-candidates"];
+
+impl IceFragment {
+    /// Fragment describing the media at `mline_index` of `sdp`, without
+    /// candidates
+    pub fn for_media(sdp: &gst_sdp::SDPMessageRef, mline_index: u32) -> Option<Self> {
+        let media = sdp.media(mline_index)?;
+        let attribute = |key| {
+            media
+                .attribute_val(key)
+                .or_else(|| sdp.attribute_val(key))
+                .map(String::from)
+        };
+
+        Some(Self {
+            ice_ufrag: attribute("ice-ufrag"),
+            ice_pwd: attribute("ice-pwd"),
+            media: vec![IceFragmentMedia {
+                mid: media.attribute_val("mid").map(String::from),
+                ..Default::default()
+            }],
+        })
+    }
+
+    pub fn parse(frag: &str) -> Result<Self, Error> {
+        let mut fragment = Self::default();
+        let mut end_of_candidates = false;
+
+        for line in frag.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if line.starts_with("m=") {
+                fragment.media.push(IceFragmentMedia::default());
+                continue;
+            }
+
+            let Some(attribute) = line.strip_prefix("a=") else {
+                continue;
+            };
+
+            let (key, value) = match attribute.split_once(':') {
+                Some((key, value)) => (key, Some(value)),
+                None => (attribute, None),
+            };
+
+            match (key, value, fragment.media.last_mut()) {
+                ("ice-ufrag", Some(ufrag), _) => fragment.ice_ufrag = Some(ufrag.to_string()),
+                ("ice-pwd", Some(pwd), _) => fragment.ice_pwd = Some(pwd.to_string()),
+                ("mid", Some(mid), Some(media)) => media.mid = Some(mid.to_string()),
+                ("candidate", Some(candidate), Some(media)) => {
+                    media.candidates.push(candidate.to_string())
+                }
+                ("end-of-candidates", None, Some(media)) => media.end_of_candidates = true,
+                ("end-of-candidates", None, None) => end_of_candidates = true,
+                ("mid" | "candidate", _, None) => {
+                    anyhow::bail!("Attribute {key} outside of a media section")
+                }
+                _ => (),
+            }
+        }
+
+        if end_of_candidates {
+            for media in fragment.media.iter_mut() {
+                media.end_of_candidates = true;
+            }
+        }
+
+        Ok(fragment)
+    }
+
+    pub fn to_sdpfrag(&self) -> String {
+        let mut frag = String::new();
+
+        if let Some(ufrag) = &self.ice_ufrag {
+            frag += &format!("a=ice-ufrag:{ufrag}\r\n");
+        }
+        if let Some(pwd) = &self.ice_pwd {
+            frag += &format!("a=ice-pwd:{pwd}\r\n");
+        }
+
+        for media in &self.media {
+            // The content of the m-line is ignored, RFC 8840 section 9
+            frag += "m=audio 9 RTP/AVP 0\r\n";
+            if let Some(mid) = &media.mid {
+                frag += &format!("a=mid:{mid}\r\n");
+            }
+            for candidate in &media.candidates {
+                frag += &format!("a=candidate:{candidate}\r\n");
+            }
+            if media.end_of_candidates {
+                frag += "a=end-of-candidates\r\n";
+            }
+        }
+
+        frag
+    }
+
+    /// Replaces the ICE credentials and candidates of `sdp` with the ones of
+    /// the fragment, media sections being matched by mid or else by index
+    pub fn apply(&self, sdp: &mut gst_sdp::SDPMessageRef) {
+        for idx in (0..sdp.attributes_len()).rev() {
+            if sdp
+                .attribute(idx)
+                .is_some_and(|attr| ICE_ATTRIBUTES.contains(&attr.key()))
+            {
+                let _ = sdp.remove_attribute(idx);
+            }
+        }
+
+        for idx in 0..sdp.medias_len() {
+            let Some(media) = sdp.media_mut(idx) else {
+                continue;
+            };
+
+            let mid = media.attribute_val("mid").map(String::from);
+            let frag_media = match mid {
+                Some(ref mid) => self
+                    .media
+                    .iter()
+                    .find(|media| media.mid.as_ref() == Some(mid)),
+                None => self.media.get(idx as usize),
+            };
+
+            for idx in (0..media.attributes_len()).rev() {
+                if media
+                    .attribute(idx)
+                    .is_some_and(|attr| ICE_ATTRIBUTES.contains(&attr.key()))
+                {
+                    let _ = media.remove_attribute(idx);
+                }
+            }
+
+            if let Some(ufrag) = &self.ice_ufrag {
+                media.add_attribute("ice-ufrag", Some(ufrag));
+            }
+            if let Some(pwd) = &self.ice_pwd {
+                media.add_attribute("ice-pwd", Some(pwd));
+            }
+            if let Some(frag_media) = frag_media {
+                for candidate in &frag_media.candidates {
+                    media.add_attribute("candidate", Some(candidate));
+                }
+                if frag_media.end_of_candidates {
+                    media.add_attribute("end-of-candidates", None);
+                }
+            }
+        }
+    }
+}
+
 /// Wrapper around `gst::ElementFactory::make` with a better error
 /// message
 pub fn make_element(element: &str, name: Option<&str>) -> Result<gst::Element, Error> {
@@ -584,16 +807,41 @@ impl Codec {
     }
 
     fn has_decoder_for_caps(caps: &gst::Caps, decoders: &glib::List<gst::ElementFactory>) -> bool {
-        decoders.iter().any(|factory| {
-            factory.static_pad_templates().iter().any(|template| {
-                let template_caps = template.caps();
-                template.direction() == gst::PadDirection::Sink
-                    && !template_caps.is_any()
-                    && caps.can_intersect(&template_caps)
-            })
+        decoders
+            .iter()
+            .any(|factory| Self::factory_can_decode(caps, factory))
+    }
+
+    fn factory_can_decode(caps: &gst::Caps, factory: &gst::ElementFactory) -> bool {
+        factory.static_pad_templates().iter().any(|template| {
+            let template_caps = template.caps();
+            template.direction() == gst::PadDirection::Sink
+                && !template_caps.is_any()
+                && caps.can_intersect(&template_caps)
         })
     }
 
+    /// Whether @factory is a decoder able to handle streams of this codec
+    pub fn can_be_decoded_by(&self, factory: &gst::ElementFactory) -> bool {
+        factory.has_type(gst::ElementFactoryType::DECODER)
+            && Self::factory_can_decode(&self.caps, factory)
+    }
+
+    /// Lists the decoders available for this codec, sorted by decreasing rank
+    pub fn decoder_factories(&self) -> Vec<gst::ElementFactory> {
+        let mut factories = gst::ElementFactory::factories_with_type(
+            gst::ElementFactoryType::DECODER,
+            gst::Rank::MARGINAL,
+        )
+        .into_iter()
+        .filter(|factory| Self::factory_can_decode(&self.caps, factory))
+        .collect::<Vec<_>>();
+
+        factories.sort_by_key(|factory| std::cmp::Reverse(factory.rank()));
+
+        factories
+    }
+
     pub fn is_video(&self) -> bool {
         matches!(self.stream_type, gst::StreamType::VIDEO)
     }
@@ -831,6 +1079,13 @@ impl Codecs {
             .cloned()
     }
 
+    pub fn find_for_caps(caps: &gst::Caps) -> Option<Codec> {
+        CODECS
+            .iter()
+            .find(|codec| codec.caps.can_intersect(caps))
+            .cloned()
+    }
+
     pub fn video_codecs() -> Vec<Codec> {
         CODECS
             .iter()
@@ -928,10 +1183,55 @@ pub fn cleanup_codec_caps(mut caps: gst::Caps) -> gst::Caps {
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
 pub struct NavigationEvent {
     pub mid: Option<String>,
+    /// Set by clients expecting a reply once the event was handled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
     #[serde(flatten)]
     pub event: gst_video::NavigationEvent,
 }
 
+/// Reply of the producer to a navigation event sent on the navigation data
+/// channel, errors are always reported while acks are only sent for events
+/// with an `id`
+#[derive(Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum NavigationEventReply {
+    Ack { id: u64 },
+    Error { id: Option<u64>, details: String },
+}
+
+pub const RTP_TWCC_URI: &str =
+    "http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01";
+
+/// Returns the URI of an `extmap-<id>` caps field value, which can either be
+/// a plain string or an array of the form `<direction, uri, attributes>`
+pub fn extmap_uri(value: &glib::SendValue) -> Option<String> {
+    if let Ok(uri) = value.get::<String>() {
+        Some(uri)
+    } else if let Ok(array) = value.get::<gst::ArrayRef>() {
+        array
+            .as_slice()
+            .get(1)
+            .and_then(|uri| uri.get::<String>().ok())
+    } else {
+        None
+    }
+}
+
+/// Looks up the ID mapped to the RTP header extension identified by `uri` in
+/// the `extmap-<id>` fields of @s
+pub fn find_extmap_id(s: &gst::StructureRef, uri: &str) -> Option<u32> {
+    s.iter().find_map(|(key, value)| {
+        let id = key.strip_prefix("extmap-")?.parse::<u32>().ok()?;
+
+        if extmap_uri(value).as_deref() == Some(uri) {
+            Some(id)
+        } else {
+            None
+        }
+    })
+}
+
 pub fn find_smallest_available_ext_id(ids: impl IntoIterator<Item = u32>) -> u32 {
     let used_numbers: HashSet<_> = ids.into_iter().collect();
     (1..).find(|&num| !used_numbers.contains(&num)).unwrap()
@@ -954,6 +1254,29 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_find_extmap_id() {
+        gst::init().unwrap();
+
+        let s = gst::Structure::builder("application/x-rtp")
+            .field("extmap-1", "urn:ietf:params:rtp-hdrext:sdes:mid")
+            .field("extmap-3", gst::Array::new(["", RTP_TWCC_URI, ""]))
+            .build();
+
+        assert_eq!(find_extmap_id(&s, RTP_TWCC_URI), Some(3));
+        assert_eq!(
+            find_extmap_id(&s, "urn:ietf:params:rtp-hdrext:sdes:mid"),
+            Some(1)
+        );
+        assert_eq!(
+            find_extmap_id(
+                &s,
+                "http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time"
+            ),
+            None
+        );
+    }
+
     #[test]
     fn test_find_smallest_available_ext_id() -> Result<(), String> {
         [
@@ -966,4 +1289,47 @@ mod tests {
         .into_iter()
         .try_for_each(|(input, expected)| test_find_smallest_available_ext_id_case(input, expected))
     }
+
+    #[test]
+    fn test_ice_fragment() {
+        let frag = "a=ice-ufrag:EsAw\r\n\
+                    a=ice-pwd:P2uYro0UCOQ4zxjKXaWCBui1\r\n\
+                    m=audio 9 RTP/AVP 0\r\n\
+                    a=mid:0\r\n\
+                    a=candidate:1387637174 1 udp 2122260223 192.0.2.1 61764 typ host generation 0 ufrag EsAw network-id 1\r\n\
+                    a=end-of-candidates\r\n";
+
+        let fragment = IceFragment::parse(frag).unwrap();
+        assert_eq!(fragment.ice_ufrag.as_deref(), Some("EsAw"));
+        assert_eq!(
+            fragment.ice_pwd.as_deref(),
+            Some("P2uYro0UCOQ4zxjKXaWCBui1")
+        );
+        assert_eq!(fragment.media.len(), 1);
+        assert_eq!(fragment.media[0].mid.as_deref(), Some("0"));
+        assert_eq!(fragment.media[0].candidates.len(), 1);
+        assert!(fragment.media[0].end_of_candidates);
+
+        assert_eq!(
+            IceFragment::parse(&fragment.to_sdpfrag()).unwrap(),
+            fragment
+        );
+        assert!(IceFragment::parse("a=candidate:1 1 udp 1 192.0.2.1 1 typ host").is_err());
+    }
+
+    #[test]
+    fn test_navigation_event_reply() {
+        let ack = serde_json::to_string(&NavigationEventReply::Ack { id: 3 }).unwrap();
+        assert_eq!(ack, r#"{"type":"ack","id":3}"#);
+
+        let error: NavigationEventReply =
+            serde_json::from_str(r#"{"type":"error","id":null,"details":"oops"}"#).unwrap();
+        assert_eq!(
+            error,
+            NavigationEventReply::Error {
+                id: None,
+                details: "oops".to_string()
+            }
+        );
+    }
 }
@@ -1,6 +1,9 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use crate::utils::{cleanup_codec_caps, is_raw_caps, make_element, Codec, Codecs, NavigationEvent};
+use crate::utils::{
+    cleanup_codec_caps, is_raw_caps, make_element, Codec, Codecs, NavigationEvent,
+    NavigationEventReply, RTP_TWCC_URI,
+};
 use anyhow::Context;
 use gst::glib;
 use gst::prelude::*;
@@ -24,9 +27,14 @@ use super::{
     WebRTCSinkCongestionControl, WebRTCSinkError, WebRTCSinkMitigationMode, WebRTCSinkPad,
 };
 use crate::aws_kvs_signaller::AwsKvsSignaller;
+use crate::data_channel::{self, DataChannelMessage, DATA_CAPS, NAVIGATION_CHANNEL_LABEL};
 use crate::janusvr_signaller::JanusVRSignaller;
-use crate::livekit_signaller::LiveKitSignaller;
+use crate::livekit_signaller::{self, LiveKitSignaller};
+use crate::manual_signaller::ManualSignaller;
+use crate::mqtt_signaller::MqttSignaller;
 use crate::signaller::{prelude::*, Signallable, Signaller, WebRTCSignallerRole};
+use crate::sip_signaller::SipSignaller;
+use crate::whep_signaller::WhepServerSignaller;
 use crate::whip_signaller::WhipClientSignaller;
 use crate::{utils, RUNTIME};
 use std::collections::{BTreeMap, HashSet};
@@ -44,9 +52,6 @@ const GL_MEMORY_FEATURE: &str = "memory:GLMemory";
 const NVMM_MEMORY_FEATURE: &str = "memory:NVMM";
 const D3D11_MEMORY_FEATURE: &str = "memory:D3D11Memory";
 
-const RTP_TWCC_URI: &str =
-    "http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01";
-
 const DEFAULT_STUN_SERVER: Option<&str> = Some("stun://stun.l.google.com:19302");
 const DEFAULT_MIN_BITRATE: u32 = 1000;
 
@@ -88,9 +93,11 @@ struct Settings {
     do_fec: bool,
     do_retransmission: bool,
     enable_data_channel_navigation: bool,
+    allowed_navigation_events: Vec<String>,
     meta: Option<gst::Structure>,
     ice_transport_policy: WebRTCICETransportPolicy,
     signaller: Signallable,
+    data_channels: Vec<gst::Structure>,
 }
 
 #[derive(Debug, Clone)]
@@ -454,46 +461,101 @@ struct State {
     streams: HashMap<String, InputStream>,
     discoveries: HashMap<String, Vec<DiscoveryInfo>>,
     navigation_handler: Option<NavigationEventHandler>,
+    /// Application data channels, by session id and label
+    data_channels: HashMap<String, HashMap<String, WebRTCDataChannel>>,
+    /// Source of the `data` pad, if requested
+    data_src: Option<gst_app::AppSrc>,
     mids: HashMap<String, String>,
     signaller_signals: Option<SignallerSignals>,
     finalizing_sessions: Arc<(Mutex<HashSet<String>>, Condvar)>,
 }
 
-fn create_navigation_event(sink: &super::BaseWebRTCSink, msg: &str) {
-    let event: Result<NavigationEvent, _> = serde_json::from_str(msg);
+/// Handles a message received on the navigation data channel, returning the
+/// reply to send back to the consumer, if any
+fn create_navigation_event(
+    sink: &super::BaseWebRTCSink,
+    msg: &str,
+) -> Option<NavigationEventReply> {
+    let event: NavigationEvent = match serde_json::from_str(msg) {
+        Ok(event) => event,
+        Err(err) => {
+            gst::error!(CAT, obj: sink, "Invalid navigation event {msg:?}: {err}");
+
+            return Some(NavigationEventReply::Error {
+                id: None,
+                details: format!("Invalid navigation event: {err}"),
+            });
+        }
+    };
+
+    let id = event.id;
+    match push_navigation_event(sink, event) {
+        Ok(()) => id.map(|id| NavigationEventReply::Ack { id }),
+        Err(details) => {
+            gst::info!(CAT, obj: sink, "Navigation event {id:?} failed: {details}");
 
-    if let Ok(event) = event {
-        gst::log!(CAT, obj: sink, "Processing navigation event: {:?}", event);
+            Some(NavigationEventReply::Error { id, details })
+        }
+    }
+}
 
+fn push_navigation_event(
+    sink: &super::BaseWebRTCSink,
+    event: NavigationEvent,
+) -> Result<(), String> {
+    gst::log!(CAT, obj: sink, "Processing navigation event: {:?}", event);
+
+    let this = sink.imp();
+    let structure = event.event.structure();
+    let event_type = structure.get::<String>("event").unwrap_or_default();
+
+    {
+        let settings = this.settings.lock().unwrap();
+        if !settings.allowed_navigation_events.is_empty()
+            && !settings
+                .allowed_navigation_events
+                .iter()
+                .any(|allowed| *allowed == event_type)
+        {
+            return Err(format!("Navigation event type {event_type} is not allowed"));
+        }
+    }
+
+    let pads = {
+        let state = this.state.lock().unwrap();
         if let Some(mid) = event.mid {
-            let this = sink.imp();
+            let stream = state
+                .mids
+                .get(&mid)
+                .and_then(|stream_name| state.streams.get(stream_name))
+                .ok_or_else(|| format!("No stream with mid {mid}"))?;
 
-            let state = this.state.lock().unwrap();
-            if let Some(stream_name) = state.mids.get(&mid) {
-                if let Some(stream) = state.streams.get(stream_name) {
-                    let event = gst::event::Navigation::new(event.event.structure());
+            vec![stream.sink_pad.clone()]
+        } else {
+            state
+                .streams
+                .values()
+                .filter(|stream| stream.sink_pad.name().starts_with("video_"))
+                .map(|stream| stream.sink_pad.clone())
+                .collect::<Vec<_>>()
+        }
+    };
 
-                    if !stream.sink_pad.push_event(event.clone()) {
-                        gst::info!(CAT, "Could not send event: {:?}", event);
-                    }
-                }
-            }
+    let event = gst::event::Navigation::new(structure);
+    let mut handled = false;
+    for pad in pads {
+        gst::log!(CAT, obj: sink, "Navigating to: {:?}", event);
+        if pad.push_event(event.clone()) {
+            handled = true;
         } else {
-            let this = sink.imp();
-
-            let state = this.state.lock().unwrap();
-            let event = gst::event::Navigation::new(event.event.structure());
-            state.streams.iter().for_each(|(_, stream)| {
-                if stream.sink_pad.name().starts_with("video_") {
-                    gst::log!(CAT, "Navigating to: {:?}", event);
-                    if !stream.sink_pad.push_event(event.clone()) {
-                        gst::info!(CAT, "Could not send event: {:?}", event);
-                    }
-                }
-            });
+            gst::info!(CAT, "Could not send event: {:?}", event);
         }
+    }
+
+    if handled {
+        Ok(())
     } else {
-        gst::error!(CAT, "Invalid navigation event: {:?}", msg);
+        Err(format!("Navigation event {event_type} was not handled"))
     }
 }
 
@@ -537,9 +599,11 @@ impl Default for Settings {
             do_fec: DEFAULT_DO_FEC,
             do_retransmission: DEFAULT_DO_RETRANSMISSION,
             enable_data_channel_navigation: DEFAULT_ENABLE_DATA_CHANNEL_NAVIGATION,
+            allowed_navigation_events: Vec::new(),
             meta: None,
             ice_transport_policy: DEFAULT_ICE_TRANSPORT_POLICY,
             signaller: signaller.upcast(),
+            data_channels: Vec::new(),
         }
     }
 }
@@ -558,6 +622,8 @@ impl Default for State {
             streams: HashMap::new(),
             discoveries: HashMap::new(),
             navigation_handler: None,
+            data_channels: HashMap::new(),
+            data_src: None,
             mids: HashMap::new(),
             signaller_signals: Default::default(),
             finalizing_sessions: Arc::new((Mutex::new(HashSet::new()), Condvar::new())),
@@ -1176,6 +1242,8 @@ impl State {
     }
 
     fn end_session(&mut self, session_id: &str) -> Option<Session> {
+        self.data_channels.remove(session_id);
+
         if let Some(session) = self.sessions.remove(session_id) {
             let mut session = session.into_inner();
             self.finalize_session(&mut session);
@@ -1532,7 +1600,7 @@ impl NavigationEventHandler {
         let channel = webrtcbin.emit_by_name::<WebRTCDataChannel>(
             "create-data-channel",
             &[
-                &"input",
+                &NAVIGATION_CHANNEL_LABEL,
                 &gst::Structure::builder("config")
                     .field("priority", gst_webrtc::WebRTCPriorityType::High)
                     .build(),
@@ -1543,9 +1611,16 @@ impl NavigationEventHandler {
         Self((
             channel.connect("on-message-string", false, move |values| {
                 if let Some(element) = weak_element.upgrade() {
-                    let _channel = values[0].get::<WebRTCDataChannel>().unwrap();
+                    let channel = values[0].get::<WebRTCDataChannel>().unwrap();
                     let msg = values[1].get::<&str>().unwrap();
-                    create_navigation_event(&element, msg);
+                    if let Some(reply) = create_navigation_event(&element, msg) {
+                        match serde_json::to_string(&reply) {
+                            Ok(reply) => channel.send_string(Some(reply.as_str())),
+                            Err(err) => {
+                                gst::error!(CAT, obj: element, "Could not serialize reply: {err}")
+                            }
+                        }
+                    }
                 }
 
                 None
@@ -2560,6 +2635,18 @@ impl BaseWebRTCSink {
 
         pipeline.add(&webrtcbin).unwrap();
 
+        let element_clone = element.downgrade();
+        let session_id_clone = session_id.clone();
+        webrtcbin.connect("on-data-channel", false, move |values| {
+            if let Some(element) = element_clone.upgrade() {
+                let channel = values[1]
+                    .get::<WebRTCDataChannel>()
+                    .expect("Invalid argument");
+                element.imp().setup_data_channel(&session_id_clone, channel);
+            }
+            None
+        });
+
         let element_clone = element.downgrade();
         let session_id_clone = session_id.clone();
         webrtcbin.connect("on-ice-candidate", false, move |values| {
@@ -2894,6 +2981,7 @@ impl BaseWebRTCSink {
                 }
 
                 let enable_data_channel_navigation = settings_clone.enable_data_channel_navigation;
+                let data_channel_declarations = settings_clone.data_channels.clone();
 
                 drop(settings_clone);
 
@@ -2925,6 +3013,12 @@ impl BaseWebRTCSink {
                         Some(NavigationEventHandler::new(&element, &webrtcbin));
                 }
 
+                for channel in
+                    data_channel::create_data_channels(&webrtcbin, &data_channel_declarations)
+                {
+                    this.setup_data_channel(&session_id, channel);
+                }
+
                 // This is intentionally emitted with the pipeline in the Ready state,
                 // so that application code can create data channels at the correct
                 // moment.
@@ -2951,6 +3045,121 @@ impl BaseWebRTCSink {
         Ok(())
     }
 
+    fn setup_data_channel(&self, session_id: &str, channel: WebRTCDataChannel) {
+        let element_weak = self.obj().downgrade();
+        let session_id_clone = session_id.to_string();
+        data_channel::connect_messages(&channel, move |label, message| {
+            if let Some(element) = element_weak.upgrade() {
+                element
+                    .imp()
+                    .on_data_channel_message(&session_id_clone, label, message);
+            }
+        });
+
+        let mut state = self.state.lock().unwrap();
+        state
+            .data_channels
+            .entry(session_id.to_string())
+            .or_default()
+            .insert(data_channel::label(&channel), channel);
+    }
+
+    fn on_data_channel_message(&self, session_id: &str, label: &str, message: DataChannelMessage) {
+        gst::log!(
+            CAT,
+            imp: self,
+            "Received message on data channel {label} of session {session_id}"
+        );
+
+        let data_src = self.state.lock().unwrap().data_src.clone();
+        if let Some(data_src) = data_src {
+            if let Err(err) = data_src.push_buffer(message.to_buffer(Some(session_id), label)) {
+                gst::debug!(CAT, imp: self, "Failed to push data message: {err}");
+            }
+        }
+
+        match message {
+            DataChannelMessage::String(message) => self.obj().emit_by_name::<()>(
+                "data-channel-message-string",
+                &[&session_id, &label, &message],
+            ),
+            DataChannelMessage::Data(data) => self
+                .obj()
+                .emit_by_name::<()>("data-channel-message-data", &[&session_id, &label, &data]),
+        }
+    }
+
+    /// Sends a message to the consumer of `session_id`, or to all of them
+    fn send_data_channel_message(
+        &self,
+        session_id: Option<&str>,
+        label: &str,
+        message: DataChannelMessage,
+    ) -> bool {
+        let channels = self
+            .state
+            .lock()
+            .unwrap()
+            .data_channels
+            .iter()
+            .filter(|(id, _)| session_id.map_or(true, |session_id| session_id == id.as_str()))
+            .filter_map(|(_, channels)| channels.get(label).cloned())
+            .collect::<Vec<_>>();
+
+        channels
+            .iter()
+            .fold(false, |sent, channel| message.send(channel) || sent)
+    }
+
+    fn request_data_pad(&self, templ: &gst::PadTemplate) -> Option<gst::Pad> {
+        let element = self.obj();
+        let data_src = {
+            let mut state = self.state.lock().unwrap();
+            if state.data_src.is_some() {
+                gst::error!(CAT, imp: self, "The data pad was already requested");
+                return None;
+            }
+
+            let data_src = data_channel::make_data_src();
+            state.data_src = Some(data_src.clone());
+            data_src
+        };
+
+        // Not holding the state as pad-added handlers may call us back
+        let pad = gst::GhostPad::builder_from_template(templ)
+            .name("data")
+            .with_target(&data_src.static_pad("src").unwrap())
+            .unwrap()
+            .build();
+        if let Err(err) = element
+            .add(&data_src)
+            .and_then(|_| pad.set_active(true))
+            .and_then(|_| element.add_pad(&pad))
+            .and_then(|_| data_src.sync_state_with_parent())
+        {
+            gst::error!(CAT, imp: self, "Failed to add the data pad: {err}");
+            self.release_data_pad(pad.upcast_ref());
+            return None;
+        }
+
+        Some(pad.upcast())
+    }
+
+    fn release_data_pad(&self, pad: &gst::Pad) {
+        let element = self.obj();
+        let data_src = self.state.lock().unwrap().data_src.take();
+        if let Some(data_src) = data_src {
+            let _ = data_src.set_state(gst::State::Null);
+            if data_src.parent().is_some() {
+                let _ = element.remove(&data_src);
+            }
+        }
+
+        if pad.parent().is_some() {
+            let _ = element.remove_pad(pad);
+        }
+    }
+
     /// Called by the signaller to remove a consumer
     fn remove_session(
         &self,
@@ -3887,6 +4096,22 @@ impl ObjectImpl for BaseWebRTCSink {
                     .default_value(DEFAULT_ENABLE_DATA_CHANNEL_NAVIGATION)
                     .mutable_ready()
                     .build(),
+                /**
+                 * GstBaseWebRTCSink:allowed-navigation-events:
+                 *
+                 * The types of navigation events consumers are allowed to send
+                 * through the navigation data channel, as found in the `event`
+                 * field of navigation event structures (`key-press`,
+                 * `mouse-move`, `touch-down`, `command`...). Other events are
+                 * rejected with an error reply. All event types are allowed
+                 * when empty.
+                 */
+                gst::ParamSpecArray::builder("allowed-navigation-events")
+                    .nick("Allowed navigation events")
+                    .blurb("Navigation event types consumers may send, all types are allowed when empty")
+                    .element_spec(&glib::ParamSpecString::builder("event-type").build())
+                    .mutable_playing()
+                    .build(),
                 glib::ParamSpecBoxed::builder::<gst::Structure>("meta")
                     .nick("Meta")
                     .blurb("Free form metadata about the producer")
@@ -3900,6 +4125,24 @@ impl ObjectImpl for BaseWebRTCSink {
                     .flags(glib::ParamFlags::READABLE | gst::PARAM_FLAG_MUTABLE_READY)
                     .blurb("The Signallable object to use to handle WebRTC Signalling")
                     .build(),
+                /**
+                 * GstBaseWebRTCSink:data-channels:
+                 *
+                 * Data channels created for every consumer, each structure
+                 * name being the label of a channel and its fields the options
+                 * passed to #webrtcbin::create-data-channel, e.g.
+                 * `<telemetry,ordered=false,max-retransmits=0, control>`
+                 */
+                gst::ParamSpecArray::builder("data-channels")
+                    .nick("Data channels")
+                    .blurb("Data channels to open with every consumer")
+                    .element_spec(&glib::ParamSpecBoxed::builder::<gst::Structure>("data-channel")
+                        .nick("Data channel")
+                        .blurb("Label of the channel as structure name and create-data-channel options as fields")
+                        .build()
+                    )
+                    .mutable_ready()
+                    .build(),
             ]
         });
 
@@ -3963,6 +4206,30 @@ impl ObjectImpl for BaseWebRTCSink {
                 settings.enable_data_channel_navigation =
                     value.get::<bool>().expect("type checked upstream");
             }
+            "allowed-navigation-events" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.allowed_navigation_events = value
+                    .get::<gst::ArrayRef>()
+                    .expect("type checked upstream")
+                    .as_slice()
+                    .iter()
+                    .filter_map(|event_type| event_type.get::<String>().ok())
+                    .collect()
+            }
+            "data-channels" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.data_channels = value
+                    .get::<gst::ArrayRef>()
+                    .expect("type checked upstream")
+                    .as_slice()
+                    .iter()
+                    .map(|declaration| {
+                        declaration
+                            .get::<gst::Structure>()
+                            .expect("type checked upstream")
+                    })
+                    .collect()
+            }
             "meta" => {
                 let mut settings = self.settings.lock().unwrap();
                 settings.meta = value
@@ -4025,6 +4292,14 @@ impl ObjectImpl for BaseWebRTCSink {
                 let settings = self.settings.lock().unwrap();
                 settings.enable_data_channel_navigation.to_value()
             }
+            "allowed-navigation-events" => {
+                let settings = self.settings.lock().unwrap();
+                gst::Array::new(settings.allowed_navigation_events.iter()).to_value()
+            }
+            "data-channels" => {
+                let settings = self.settings.lock().unwrap();
+                gst::Array::new(settings.data_channels.iter()).to_value()
+            }
             "stats" => self.gather_stats().to_value(),
             "meta" => {
                 let settings = self.settings.lock().unwrap();
@@ -4204,6 +4479,110 @@ impl ObjectImpl for BaseWebRTCSink {
                     ])
                     .return_type::<gst::Element>()
                     .build(),
+                /**
+                 * GstBaseWebRTCSink::send-data-channel-string:
+                 * @session_id: (nullable): Identifier of the session, %NULL
+                 *   to send to all sessions
+                 * @label: Label of the data channel
+                 * @message: The message to send
+                 *
+                 * Sends @message on the data channel labelled @label.
+                 *
+                 * Returns: %TRUE if the message was sent to at least one session.
+                 */
+                glib::subclass::Signal::builder("send-data-channel-string")
+                    .param_types([
+                        Option::<String>::static_type(),
+                        String::static_type(),
+                        String::static_type(),
+                    ])
+                    .return_type::<bool>()
+                    .action()
+                    .class_handler(|_, args| {
+                        let element = args[0].get::<super::BaseWebRTCSink>().expect("signal arg");
+                        let session_id = args[1].get::<Option<String>>().expect("signal arg");
+                        let label = args[2].get::<String>().expect("signal arg");
+                        let message = args[3].get::<String>().expect("signal arg");
+
+                        Some(
+                            element
+                                .imp()
+                                .send_data_channel_message(
+                                    session_id.as_deref(),
+                                    &label,
+                                    DataChannelMessage::String(message),
+                                )
+                                .to_value(),
+                        )
+                    })
+                    .build(),
+                /**
+                 * GstBaseWebRTCSink::send-data-channel-data:
+                 * @session_id: (nullable): Identifier of the session, %NULL
+                 *   to send to all sessions
+                 * @label: Label of the data channel
+                 * @data: The binary message to send
+                 *
+                 * Sends @data on the data channel labelled @label.
+                 *
+                 * Returns: %TRUE if the message was sent to at least one session.
+                 */
+                glib::subclass::Signal::builder("send-data-channel-data")
+                    .param_types([
+                        Option::<String>::static_type(),
+                        String::static_type(),
+                        glib::Bytes::static_type(),
+                    ])
+                    .return_type::<bool>()
+                    .action()
+                    .class_handler(|_, args| {
+                        let element = args[0].get::<super::BaseWebRTCSink>().expect("signal arg");
+                        let session_id = args[1].get::<Option<String>>().expect("signal arg");
+                        let label = args[2].get::<String>().expect("signal arg");
+                        let data = args[3].get::<glib::Bytes>().expect("signal arg");
+
+                        Some(
+                            element
+                                .imp()
+                                .send_data_channel_message(
+                                    session_id.as_deref(),
+                                    &label,
+                                    DataChannelMessage::Data(data),
+                                )
+                                .to_value(),
+                        )
+                    })
+                    .build(),
+                /**
+                 * GstBaseWebRTCSink::data-channel-message-string:
+                 * @session_id: Identifier of the session
+                 * @label: Label of the data channel
+                 * @message: The received message
+                 *
+                 * Emitted when a text message is received from a consumer.
+                 */
+                glib::subclass::Signal::builder("data-channel-message-string")
+                    .param_types([
+                        String::static_type(),
+                        String::static_type(),
+                        String::static_type(),
+                    ])
+                    .build(),
+                /**
+                 * GstBaseWebRTCSink::data-channel-message-data:
+                 * @session_id: Identifier of the session
+                 * @label: Label of the data channel
+                 * @data: The received message
+                 *
+                 * Emitted when a binary message is received from a consumer.
+                 */
+                glib::subclass::Signal::builder("data-channel-message-data")
+                    .param_types([
+                        String::static_type(),
+                        String::static_type(),
+                        glib::Bytes::static_type(),
+                    ])
+                    .build(),
             ]
         });
 
@@ -4273,7 +4652,15 @@ impl ElementImpl for BaseWebRTCSink {
             )
             .unwrap();
 
-            vec![video_pad_template, audio_pad_template]
+            let data_pad_template = gst::PadTemplate::new(
+                "data",
+                gst::PadDirection::Src,
+                gst::PadPresence::Request,
+                &DATA_CAPS,
+            )
+            .unwrap();
+
+            vec![video_pad_template, audio_pad_template, data_pad_template]
         });
 
         PAD_TEMPLATES.as_ref()
@@ -4291,6 +4678,10 @@ impl ElementImpl for BaseWebRTCSink {
             return None;
         }
 
+        if templ.direction() == gst::PadDirection::Src {
+            return self.request_data_pad(templ);
+        }
+
         let mut state = self.state.lock().unwrap();
 
         let serial;
@@ -4347,6 +4738,14 @@ impl ElementImpl for BaseWebRTCSink {
         Some(sink_pad.upcast())
     }
 
+    fn release_pad(&self, pad: &gst::Pad) {
+        if pad.direction() == gst::PadDirection::Src {
+            self.release_data_pad(pad);
+        } else {
+            self.parent_release_pad(pad);
+        }
+    }
+
     fn change_state(
         &self,
         transition: gst::StateChange,
@@ -4526,6 +4925,126 @@ impl ObjectSubclass for AwsKvsWebRTCSink {
     type ParentType = super::BaseWebRTCSink;
 }
 
+#[derive(Default)]
+pub struct ManualWebRTCSink {}
+
+impl ObjectImpl for ManualWebRTCSink {
+    fn constructed(&self) {
+        let element = self.obj();
+        let ws = element.upcast_ref::<super::BaseWebRTCSink>().imp();
+
+        let _ = ws.set_signaller(ManualSignaller::new_producer().upcast());
+    }
+}
+
+impl GstObjectImpl for ManualWebRTCSink {}
+
+impl ElementImpl for ManualWebRTCSink {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "ManualWebRTCSink",
+                "Sink/Network/WebRTC",
+                "WebRTC sink exchanging its signalling messages through files",
+                "The GStreamer Rust plugins developers",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+}
+
+impl BinImpl for ManualWebRTCSink {}
+
+impl BaseWebRTCSinkImpl for ManualWebRTCSink {}
+
+#[glib::object_subclass]
+impl ObjectSubclass for ManualWebRTCSink {
+    const NAME: &'static str = "GstManualWebRTCSink";
+    type Type = super::ManualWebRTCSink;
+    type ParentType = super::BaseWebRTCSink;
+}
+
+#[derive(Default)]
+pub struct MqttWebRTCSink {}
+
+impl ObjectImpl for MqttWebRTCSink {
+    fn constructed(&self) {
+        let element = self.obj();
+        let ws = element.upcast_ref::<super::BaseWebRTCSink>().imp();
+
+        let _ = ws.set_signaller(MqttSignaller::new_producer().upcast());
+    }
+}
+
+impl GstObjectImpl for MqttWebRTCSink {}
+
+impl ElementImpl for MqttWebRTCSink {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "MqttWebRTCSink",
+                "Sink/Network/WebRTC",
+                "WebRTC sink with MQTT signaller",
+                "The GStreamer Rust plugins developers",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+}
+
+impl BinImpl for MqttWebRTCSink {}
+
+impl BaseWebRTCSinkImpl for MqttWebRTCSink {}
+
+#[glib::object_subclass]
+impl ObjectSubclass for MqttWebRTCSink {
+    const NAME: &'static str = "GstMqttWebRTCSink";
+    type Type = super::MqttWebRTCSink;
+    type ParentType = super::BaseWebRTCSink;
+}
+
+#[derive(Default)]
+pub struct SipWebRTCSink {}
+
+impl ObjectImpl for SipWebRTCSink {
+    fn constructed(&self) {
+        let element = self.obj();
+        let ws = element.upcast_ref::<super::BaseWebRTCSink>().imp();
+
+        let _ = ws.set_signaller(SipSignaller::new_producer().upcast());
+    }
+}
+
+impl GstObjectImpl for SipWebRTCSink {}
+
+impl ElementImpl for SipWebRTCSink {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "SipWebRTCSink",
+                "Sink/Network/WebRTC",
+                "WebRTC sink with SIP signaller",
+                "The GStreamer Rust plugins developers",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+}
+
+impl BinImpl for SipWebRTCSink {}
+
+impl BaseWebRTCSinkImpl for SipWebRTCSink {}
+
+#[glib::object_subclass]
+impl ObjectSubclass for SipWebRTCSink {
+    const NAME: &'static str = "GstSipWebRTCSink";
+    type Type = super::SipWebRTCSink;
+    type ParentType = super::BaseWebRTCSink;
+}
+
 #[derive(Default)]
 pub struct WhipWebRTCSink {}
 
@@ -4566,15 +5085,88 @@ impl ObjectSubclass for WhipWebRTCSink {
     type ParentType = super::BaseWebRTCSink;
 }
 
+#[derive(Default)]
+pub struct WhepServerSink {}
+
+impl ObjectImpl for WhepServerSink {
+    fn constructed(&self) {
+        let element = self.obj();
+        let ws = element.upcast_ref::<super::BaseWebRTCSink>().imp();
+
+        let _ = ws.set_signaller(WhepServerSignaller::default().upcast());
+
+        let settings = ws.settings.lock().unwrap();
+        element
+            .bind_property("stun-server", &settings.signaller, "stun-server")
+            .build();
+        element
+            .bind_property("turn-servers", &settings.signaller, "turn-servers")
+            .build();
+    }
+}
+
+impl GstObjectImpl for WhepServerSink {}
+
+impl ElementImpl for WhepServerSink {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "WhepServerSink",
+                "Sink/Network/WebRTC",
+                "WebRTC sink with WHEP server signaller",
+                "The GStreamer Rust plugins developers",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+}
+
+impl BinImpl for WhepServerSink {}
+
+impl BaseWebRTCSinkImpl for WhepServerSink {}
+
+#[glib::object_subclass]
+impl ObjectSubclass for WhepServerSink {
+    const NAME: &'static str = "GstWhepServerSink";
+    type Type = super::WhepServerSink;
+    type ParentType = super::BaseWebRTCSink;
+}
+
 #[derive(Default)]
 pub struct LiveKitWebRTCSink {}
 
 impl ObjectImpl for LiveKitWebRTCSink {
+    fn signals() -> &'static [glib::subclass::Signal] {
+        static SIGNALS: Lazy<Vec<glib::subclass::Signal>> = Lazy::new(|| {
+            vec![
+                /**
+                 * GstLiveKitWebRTCSink::send-data:
+                 * @payload: The data to send
+                 * @reliable: Whether to send it on the reliable or the lossy channel
+                 * @topic: (nullable): The topic of the data
+                 * @destination-identities: The identities of the participants
+                 *   to send the data to, an empty list sending it to all of them
+                 *
+                 * Sends user data to the participants of the room, see the
+                 * `send-data` signal of the signaller.
+                 *
+                 * Returns: whether the data was sent
+                 */
+                livekit_signaller::send_data_signal(),
+            ]
+        });
+
+        SIGNALS.as_ref()
+    }
+
     fn constructed(&self) {
         let element = self.obj();
         let ws = element.upcast_ref::<super::BaseWebRTCSink>().imp();
 
-        let _ = ws.set_signaller(LiveKitSignaller::new_producer().upcast());
+        let signaller = LiveKitSignaller::new_producer();
+        signaller.forward_messages(element.upcast_ref());
+        let _ = ws.set_signaller(signaller.upcast());
     }
 }
 
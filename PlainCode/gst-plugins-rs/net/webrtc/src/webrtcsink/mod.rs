@@ -57,10 +57,26 @@ glib::wrapper! {
     pub struct AwsKvsWebRTCSink(ObjectSubclass<imp::AwsKvsWebRTCSink>) @extends BaseWebRTCSink, gst::Bin, gst::Element, gst::Object, @implements gst::ChildProxy, gst_video::Navigation;
 }
 
+glib::wrapper! {
+    pub struct ManualWebRTCSink(ObjectSubclass<imp::ManualWebRTCSink>) @extends BaseWebRTCSink, gst::Bin, gst::Element, gst::Object, @implements gst::ChildProxy, gst_video::Navigation;
+}
+
+glib::wrapper! {
+    pub struct MqttWebRTCSink(ObjectSubclass<imp::MqttWebRTCSink>) @extends BaseWebRTCSink, gst::Bin, gst::Element, gst::Object, @implements gst::ChildProxy, gst_video::Navigation;
+}
+
+glib::wrapper! {
+    pub struct SipWebRTCSink(ObjectSubclass<imp::SipWebRTCSink>) @extends BaseWebRTCSink, gst::Bin, gst::Element, gst::Object, @implements gst::ChildProxy, gst_video::Navigation;
+}
+
 glib::wrapper! {
     pub struct WhipWebRTCSink(ObjectSubclass<imp::WhipWebRTCSink>) @extends BaseWebRTCSink, gst::Bin, gst::Element, gst::Object, @implements gst::ChildProxy, gst_video::Navigation;
 }
 
+glib::wrapper! {
+    pub struct WhepServerSink(ObjectSubclass<imp::WhepServerSink>) @extends BaseWebRTCSink, gst::Bin, gst::Element, gst::Object, @implements gst::ChildProxy, gst_video::Navigation;
+}
+
 glib::wrapper! {
     pub struct LiveKitWebRTCSink(ObjectSubclass<imp::LiveKitWebRTCSink>) @extends BaseWebRTCSink, gst::Bin, gst::Element, gst::Object, @implements gst::ChildProxy, gst_video::Navigation;
 }
@@ -146,12 +162,116 @@ pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
         gst::Rank::NONE,
         AwsKvsWebRTCSink::static_type(),
     )?;
+    /**
+     * element-manualwebrtcsink:
+     *
+     * The `manualwebrtcsink` element exchanges its signalling messages as JSON
+     * lines in the format of the signalling server, without connecting to any:
+     * the messages it receives are read from `signaller::input` and the ones it
+     * sends written to `signaller::output`, the standard input and output by
+     * default. This allows debugging a negotiation by pasting messages by hand,
+     * replaying a recorded one or carrying it over any other channel.
+     *
+     * A session is requested by a `startSession` message, or by an offer of the
+     * consumer, and right away with `signaller::session-id`:
+     *
+     * ``` bash
+     * gst-launch-1.0 videotestsrc ! manualwebrtcsink signaller::session-id=1
+     * ```
+     *
+     * prints the offer of the session, to which the answer is pasted:
+     *
+     * ``` json
+     * {"type":"peer","sessionId":"1","sdp":{"type":"answer","sdp":"v=0\r\n..."}}
+     * ```
+     */
+    gst::Element::register(
+        Some(plugin),
+        "manualwebrtcsink",
+        gst::Rank::NONE,
+        ManualWebRTCSink::static_type(),
+    )?;
+    /**
+     * element-mqttwebrtcsink:
+     *
+     * The `mqttwebrtcsink` element signals through the MQTT broker at
+     * `signaller::broker-uri`, `mqtts://` URIs connecting with TLS. The
+     * producer publishes its retained presence to `signaller::presence-topic-pattern`,
+     * cleared when it stops or, as its last will, when its connection is lost,
+     * and receives the messages of its consumers on `signaller::peer-topic-pattern`.
+     * `{peer-id}` is replaced in both patterns by the ID of the peer,
+     * `signaller::peer-id`.
+     *
+     * The messages are the ones of the signalling server protocol, published
+     * with the `signaller::qos` quality of service.
+     *
+     * ``` bash
+     * gst-launch-1.0 videotestsrc ! mqttwebrtcsink signaller::broker-uri=mqtts://broker:8883 signaller::peer-id=camera1 signaller::username=camera1 signaller::password=XXX
+     * ```
+     */
+    gst::Element::register(
+        Some(plugin),
+        "mqttwebrtcsink",
+        gst::Rank::NONE,
+        MqttWebRTCSink::static_type(),
+    )?;
+    /**
+     * element-sipwebrtcsink:
+     *
+     * The `sipwebrtcsink` element calls `signaller::remote-uri`, or answers
+     * the calls to `signaller::user-uri` when unset, through the SIP proxy at
+     * `signaller::proxy-uri`. It registers with the proxy, answering its
+     * digest authentication challenges with `signaller::username` and
+     * `signaller::password`, unless `signaller::register` is disabled. SIP
+     * messages are sent over UDP unless the proxy URI selects TCP with a
+     * `transport=tcp` parameter or is a `ws://` or `wss://` URL.
+     *
+     * Candidates are not trickled: the descriptions are sent once all local
+     * candidates are gathered, and rewritten for endpoints not implementing
+     * all of WebRTC unless `signaller::sdp-munging` is disabled.
+     *
+     * ``` bash
+     * gst-launch-1.0 videotestsrc ! sipwebrtcsink signaller::proxy-uri=sip:pbx.example.com signaller::user-uri=sip:camera@example.com signaller::password=XXX signaller::remote-uri=sip:alice@example.com
+     * ```
+     */
+    gst::Element::register(
+        Some(plugin),
+        "sipwebrtcsink",
+        gst::Rank::NONE,
+        SipWebRTCSink::static_type(),
+    )?;
     gst::Element::register(
         Some(plugin),
         "whipclientsink",
         gst::Rank::NONE,
         WhipWebRTCSink::static_type(),
     )?;
+    /**
+     * element-whepserversink:
+     *
+     * The `whepserversink` element serves its streams to WHEP players through
+     * an embedded HTTP server listening on `signaller::host-addr`.
+     *
+     * Each POST request on `/whep/endpoint` creates a consumer session
+     * answering the offer of the player, the answer is sent once ICE gathering
+     * is complete along with `Link` headers for the `stun-server` and
+     * `turn-servers`. The resource, `/whep/resource/<id>`, is DELETEd by the
+     * player to end its session.
+     *
+     * Players must authenticate with a bearer token when
+     * `signaller::auth-token` is set, and web players on other origins can be
+     * restricted with `signaller::cors-allowed-origins`.
+     *
+     * ``` bash
+     * gst-launch-1.0 videotestsrc is-live=true ! whepserversink signaller::host-addr=http://0.0.0.0:9090
+     * ```
+     */
+    gst::Element::register(
+        Some(plugin),
+        "whepserversink",
+        gst::Rank::NONE,
+        WhepServerSink::static_type(),
+    )?;
     gst::Element::register(
         Some(plugin),
         "livekitwebrtcsink",
@@ -197,6 +317,26 @@ pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
      * $ gst-launch-1.0 videotestsrc ! janusvrwebrtcsink signaller::room-id=1234 signaller::janus-endpoint=wss://janus.conf.meetecho.com/ws
      * ```
      *
+     * The room can be created when joining it with `signaller::create-room=true`, Janus picking
+     * its ID if `signaller::room-id` is not set, and configured with the `signaller::room-*`
+     * properties:
+     *
+     * ```bash
+     * $ gst-launch-1.0 videotestsrc ! janusvrwebrtcsink signaller::create-room=true signaller::room-bitrate=1000000 signaller::room-video-codecs=vp8
+     * ```
+     *
+     * Setting `signaller::plugin=audiobridge` sends the audio to a room of the
+     * [AudioBridge plugin](https://janus.conf.meetecho.com/docs/audiobridge) instead, mixed
+     * with the one of the other participants:
+     *
+     * ```bash
+     * $ gst-launch-1.0 audiotestsrc ! janusvrwebrtcsink signaller::plugin=audiobridge signaller::room-id=1234
+     * ```
+     *
+     * The Janus session is kept alive every `signaller::keepalive-interval` seconds. When the room
+     * is destroyed, the session times out or Janus hangs up the peer connection, the session ends,
+     * unless `signaller::rejoin=true` is set to join the room again in a new Janus session.
+     *
      * ## Reference links
      *
      * - [Janus REST/WebSockets docs](https://janus.conf.meetecho.com/docs/rest.html)
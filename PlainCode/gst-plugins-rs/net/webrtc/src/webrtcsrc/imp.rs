@@ -2,27 +2,47 @@
 
 use gst::prelude::*;
 
-use crate::livekit_signaller::LiveKitSignaller;
+use crate::aws_kvs_signaller::AwsKvsSignaller;
+use crate::data_channel::{self, DataChannelMessage, DATA_CAPS, NAVIGATION_CHANNEL_LABEL};
+use crate::janusvr_signaller::JanusVRSignaller;
+use crate::livekit_signaller::{self, LiveKitSignaller};
+use crate::manual_signaller::ManualSignaller;
+use crate::mqtt_signaller::MqttSignaller;
 use crate::signaller::{prelude::*, Signallable, Signaller};
-use crate::utils::{Codec, Codecs, NavigationEvent, AUDIO_CAPS, RTP_CAPS, VIDEO_CAPS};
+use crate::sip_signaller::SipSignaller;
+use crate::utils::{
+    extmap_uri, find_extmap_id, Codec, Codecs, NavigationEvent, NavigationEventReply, AUDIO_CAPS,
+    RTP_CAPS, RTP_TWCC_URI, VIDEO_CAPS,
+};
+use crate::webrtcsrc::remb::{self, RembEstimator};
 use crate::webrtcsrc::WebRTCSrcPad;
+use crate::whep_signaller::WhepClientSignaller;
 use crate::whip_signaller::WhipServerSignaller;
+use crate::RUNTIME;
 use anyhow::{Context, Error};
 use gst::glib;
 use gst::subclass::prelude::*;
 use gst_webrtc::WebRTCDataChannel;
 use once_cell::sync::Lazy;
-use std::borrow::BorrowMut;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::str::FromStr;
 use std::sync::atomic::AtomicU16;
 use std::sync::atomic::Ordering;
 use std::sync::Mutex;
+use std::time::Duration;
 use url::Url;
 
 const DEFAULT_STUN_SERVER: Option<&str> = Some("stun://stun.l.google.com:19302");
 const DEFAULT_ENABLE_DATA_CHANNEL_NAVIGATION: bool = false;
 const DEFAULT_DO_RETRANSMISSION: bool = true;
+const DEFAULT_DO_TWCC: bool = true;
+const DEFAULT_DO_REMB: bool = false;
+const DEFAULT_RECONNECT: bool = false;
+/// Interval between GAP events sent while waiting for the producer
+const GAP_INTERVAL: Duration = Duration::from_millis(100);
+/// Navigation events sent continuously, which the producer is not asked to
+/// acknowledge
+const CONTINUOUS_NAVIGATION_EVENTS: &[&str] = &["mouse-move", "mouse-scroll", "touch-motion"];
 
 static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
     gst::DebugCategory::new(
@@ -41,6 +61,16 @@ struct Settings {
     audio_codecs: Vec<Codec>,
     enable_data_channel_navigation: bool,
     do_retransmission: bool,
+    do_twcc: bool,
+    do_remb: bool,
+    decoder_preferences: Option<gst::Structure>,
+    reconnect: bool,
+    video_placeholder: Option<String>,
+    audio_placeholder: Option<String>,
+    data_channels: Vec<gst::Structure>,
+    // Whether the pads of a session are removed once it ended, instead of
+    // being EOSed, for signallers accepting several producers
+    remove_ended_sessions: bool,
 }
 
 #[derive(Default)]
@@ -121,6 +151,49 @@ impl ObjectImpl for BaseWebRTCSrc {
                     .default_value(DEFAULT_DO_RETRANSMISSION)
                     .mutable_ready()
                     .build(),
+                glib::ParamSpecBoolean::builder("do-twcc")
+                    .nick("Enable TWCC feedback")
+                    .blurb("Send transport-wide congestion control feedback to the peer")
+                    .default_value(DEFAULT_DO_TWCC)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecBoolean::builder("do-remb")
+                    .nick("Enable REMB feedback")
+                    .blurb("Send receiver estimated maximum bitrate RTCP feedback")
+                    .default_value(DEFAULT_DO_REMB)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecBoxed::builder::<gst::Structure>("decoder-preferences")
+                    .nick("Decoder preferences")
+                    .blurb("Ordered decoder factory names to try per codec, e.g. decoders,H264=<nvh264dec,avdec_h264>, '*' stands for all other decoders by rank")
+                    .flags(glib::ParamFlags::READWRITE | gst::PARAM_FLAG_MUTABLE_READY)
+                    .build(),
+                glib::ParamSpecBoolean::builder("reconnect")
+                    .nick("Reconnect")
+                    .blurb("Keep the source pads when the session ends and resume once the producer is back")
+                    .default_value(DEFAULT_RECONNECT)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("video-placeholder")
+                    .nick("Video placeholder")
+                    .blurb("Pipeline description of the source feeding raw video pads while reconnecting, e.g. videotestsrc is-live=true pattern=black, GAP events are sent if unset")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("audio-placeholder")
+                    .nick("Audio placeholder")
+                    .blurb("Pipeline description of the source feeding raw audio pads while reconnecting, e.g. audiotestsrc is-live=true wave=silence, GAP events are sent if unset")
+                    .mutable_ready()
+                    .build(),
+                gst::ParamSpecArray::builder("data-channels")
+                    .nick("Data channels")
+                    .blurb("Data channels to open, e.g. <telemetry,ordered=false,max-retransmits=0, control>")
+                    .element_spec(&glib::ParamSpecBoxed::builder::<gst::Structure>("data-channel")
+                        .nick("Data channel")
+                        .blurb("Label of the channel as structure name and create-data-channel options as fields")
+                        .build()
+                    )
+                    .mutable_ready()
+                    .build(),
              ]
         });
 
@@ -183,6 +256,46 @@ impl ObjectImpl for BaseWebRTCSrc {
                 let mut settings = self.settings.lock().unwrap();
                 settings.do_retransmission = value.get::<bool>().unwrap();
             }
+            "do-twcc" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.do_twcc = value.get::<bool>().unwrap();
+            }
+            "do-remb" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.do_remb = value.get::<bool>().unwrap();
+            }
+            "decoder-preferences" => {
+                self.settings.lock().unwrap().decoder_preferences = value
+                    .get::<Option<gst::Structure>>()
+                    .expect("type checked upstream")
+            }
+            "reconnect" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.reconnect = value.get::<bool>().unwrap();
+            }
+            "video-placeholder" => {
+                self.settings.lock().unwrap().video_placeholder = value
+                    .get::<Option<String>>()
+                    .expect("type checked upstream")
+            }
+            "audio-placeholder" => {
+                self.settings.lock().unwrap().audio_placeholder = value
+                    .get::<Option<String>>()
+                    .expect("type checked upstream")
+            }
+            "data-channels" => {
+                self.settings.lock().unwrap().data_channels = value
+                    .get::<gst::ArrayRef>()
+                    .expect("type checked upstream")
+                    .as_slice()
+                    .iter()
+                    .map(|declaration| {
+                        declaration
+                            .get::<gst::Structure>()
+                            .expect("type checked upstream")
+                    })
+                    .collect()
+            }
             _ => unimplemented!(),
         }
     }
@@ -216,6 +329,15 @@ impl ObjectImpl for BaseWebRTCSrc {
                 settings.enable_data_channel_navigation.to_value()
             }
             "do-retransmission" => self.settings.lock().unwrap().do_retransmission.to_value(),
+            "do-twcc" => self.settings.lock().unwrap().do_twcc.to_value(),
+            "do-remb" => self.settings.lock().unwrap().do_remb.to_value(),
+            "decoder-preferences" => self.settings.lock().unwrap().decoder_preferences.to_value(),
+            "reconnect" => self.settings.lock().unwrap().reconnect.to_value(),
+            "video-placeholder" => self.settings.lock().unwrap().video_placeholder.to_value(),
+            "audio-placeholder" => self.settings.lock().unwrap().audio_placeholder.to_value(),
+            "data-channels" => {
+                gst::Array::new(self.settings.lock().unwrap().data_channels.iter()).to_value()
+            }
             name => panic!("{} getter not implemented", name),
         }
     }
@@ -246,6 +368,126 @@ impl ObjectImpl for BaseWebRTCSrc {
                     ])
                     .return_type::<gst::Element>()
                     .build(),
+                /**
+                 * GstBaseWebRTCSrc::send-data-channel-string:
+                 * @label: Label of the data channel
+                 * @message: The message to send
+                 *
+                 * Sends @message on the data channel labelled @label.
+                 *
+                 * Returns: %TRUE if the message was sent.
+                 */
+                glib::subclass::Signal::builder("send-data-channel-string")
+                    .param_types([String::static_type(), String::static_type()])
+                    .return_type::<bool>()
+                    .action()
+                    .class_handler(|_, args| {
+                        let element = args[0].get::<super::BaseWebRTCSrc>().expect("signal arg");
+                        let label = args[1].get::<String>().expect("signal arg");
+                        let message = args[2].get::<String>().expect("signal arg");
+
+                        Some(
+                            element
+                                .imp()
+                                .send_data_channel_message(&label, DataChannelMessage::String(message))
+                                .to_value(),
+                        )
+                    })
+                    .build(),
+                /**
+                 * GstBaseWebRTCSrc::send-data-channel-data:
+                 * @label: Label of the data channel
+                 * @data: The binary message to send
+                 *
+                 * Sends @data on the data channel labelled @label.
+                 *
+                 * Returns: %TRUE if the message was sent.
+                 */
+                glib::subclass::Signal::builder("send-data-channel-data")
+                    .param_types([String::static_type(), glib::Bytes::static_type()])
+                    .return_type::<bool>()
+                    .action()
+                    .class_handler(|_, args| {
+                        let element = args[0].get::<super::BaseWebRTCSrc>().expect("signal arg");
+                        let label = args[1].get::<String>().expect("signal arg");
+                        let data = args[2].get::<glib::Bytes>().expect("signal arg");
+
+                        Some(
+                            element
+                                .imp()
+                                .send_data_channel_message(&label, DataChannelMessage::Data(data))
+                                .to_value(),
+                        )
+                    })
+                    .build(),
+                /**
+                 * GstBaseWebRTCSrc::send-navigation-event:
+                 * @event: The navigation event structure, as built by the
+                 *   #GstNavigation helpers (`key-press`, `mouse-move`,
+                 *   `touch-down`, `command`...)
+                 *
+                 * Sends @event to the producer through the navigation data
+                 * channel.
+                 *
+                 * Only discrete events are acknowledged by the producer,
+                 * continuous `mouse-move`, `mouse-scroll` and `touch-motion`
+                 * events are only replied to when rejected, with an id of 0.
+                 *
+                 * Returns: The id of the event, as found in
+                 * #GstBaseWebRTCSrc::navigation-event-reply, or 0 if it could
+                 * not be sent or is not acknowledged.
+                 */
+                glib::subclass::Signal::builder("send-navigation-event")
+                    .param_types([gst::Structure::static_type()])
+                    .return_type::<u64>()
+                    .action()
+                    .class_handler(|_, args| {
+                        let element = args[0].get::<super::BaseWebRTCSrc>().expect("signal arg");
+                        let structure = args[1].get::<gst::Structure>().expect("signal arg");
+
+                        let id = match gst_video::NavigationEvent::parse(&gst::event::Navigation::new(structure)) {
+                            Ok(event) => element.imp().send_navigation_event(event, None, None),
+                            Err(err) => {
+                                gst::warning!(CAT, obj: element, "Invalid navigation event: {err}");
+                                None
+                            }
+                        };
+
+                        Some(id.unwrap_or(0).to_value())
+                    })
+                    .build(),
+                /**
+                 * GstBaseWebRTCSrc::navigation-event-reply:
+                 * @id: The id of the navigation event, 0 if the producer could
+                 *   not parse it
+                 * @error: (nullable): The reason why the event was rejected
+                 *
+                 * Emitted when the producer acknowledges or rejects a
+                 * navigation event.
+                 */
+                glib::subclass::Signal::builder("navigation-event-reply")
+                    .param_types([u64::static_type(), Option::<String>::static_type()])
+                    .build(),
+                /**
+                 * GstBaseWebRTCSrc::data-channel-message-string:
+                 * @label: Label of the data channel
+                 * @message: The received message
+                 *
+                 * Emitted when a text message is received on a data channel.
+                 */
+                glib::subclass::Signal::builder("data-channel-message-string")
+                    .param_types([String::static_type(), String::static_type()])
+                    .build(),
+                /**
+                 * GstBaseWebRTCSrc::data-channel-message-data:
+                 * @label: Label of the data channel
+                 * @data: The received message
+                 *
+                 * Emitted when a binary message is received on a data channel.
+                 */
+                glib::subclass::Signal::builder("data-channel-message-data")
+                    .param_types([String::static_type(), glib::Bytes::static_type()])
+                    .build(),
             ]
         });
 
@@ -284,6 +526,14 @@ impl Default for Settings {
                 .collect(),
             enable_data_channel_navigation: DEFAULT_ENABLE_DATA_CHANNEL_NAVIGATION,
             do_retransmission: DEFAULT_DO_RETRANSMISSION,
+            do_twcc: DEFAULT_DO_TWCC,
+            do_remb: DEFAULT_DO_REMB,
+            decoder_preferences: None,
+            reconnect: DEFAULT_RECONNECT,
+            video_placeholder: None,
+            audio_placeholder: None,
+            data_channels: Vec::new(),
+            remove_ended_sessions: false,
         }
     }
 }
@@ -293,23 +543,58 @@ struct SignallerSignals {
     error: glib::SignalHandlerId,
     session_started: glib::SignalHandlerId,
     session_ended: glib::SignalHandlerId,
+    session_requested: glib::SignalHandlerId,
     request_meta: glib::SignalHandlerId,
     session_description: glib::SignalHandlerId,
     handle_ice: glib::SignalHandlerId,
 }
 
 impl BaseWebRTCSrc {
-    fn webrtcbin(&self) -> gst::Bin {
-        let state = self.state.lock().unwrap();
-        let webrtcbin = state
-            .webrtcbin
-            .as_ref()
-            .expect("We should never call `.webrtcbin()` when state not > Ready")
-            .clone()
-            .downcast::<gst::Bin>()
-            .unwrap();
+    // Returns the `webrtcbin` of @session_id, creating the session if needed
+    fn session_webrtcbin(&self, session_id: &str) -> Result<gst::Element, Error> {
+        let webrtcbin = self
+            .state
+            .lock()
+            .unwrap()
+            .sessions
+            .get(session_id)
+            .map(|session| session.webrtcbin.clone());
+
+        match webrtcbin {
+            Some(webrtcbin) => Ok(webrtcbin),
+            None => self.create_session(session_id),
+        }
+    }
 
-        webrtcbin
+    fn session_id_for(&self, webrtcbin: &gst::Element) -> Option<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .sessions
+            .iter()
+            .find(|(_, session)| &session.webrtcbin == webrtcbin)
+            .map(|(session_id, _)| session_id.clone())
+    }
+
+    fn with_session<T>(
+        &self,
+        webrtcbin: &gst::Element,
+        f: impl FnOnce(&mut Session) -> T,
+    ) -> Option<T> {
+        self.state
+            .lock()
+            .unwrap()
+            .sessions
+            .values_mut()
+            .find(|session| &session.webrtcbin == webrtcbin)
+            .map(f)
+    }
+
+    // Adds an element plugged downstream of @webrtcbin, so that it goes away
+    // along with its session
+    fn add_session_element(&self, webrtcbin: &gst::Element, element: &gst::Element) {
+        self.obj().add(element).unwrap();
+        self.with_session(webrtcbin, |session| session.elements.push(element.clone()));
     }
 
     fn signaller(&self) -> Signallable {
@@ -318,13 +603,18 @@ impl BaseWebRTCSrc {
 
     // Maps the `webrtcbin` pad to our exposed source pad using the pad stream ID.
     fn get_src_pad_from_webrtcbin_pad(&self, webrtcbin_src: &gst::Pad) -> Option<WebRTCSrcPad> {
+        let session_id = webrtcbin_src
+            .parent_element()
+            .and_then(|webrtcbin| self.session_id_for(&webrtcbin))?;
+
         self.get_stream_id(
+            &session_id,
             Some(webrtcbin_src.property::<gst_webrtc::WebRTCRTPTransceiver>("transceiver")),
             None,
         )
         .and_then(|stream_id| {
             self.obj().iterate_src_pads().into_iter().find_map(|s| {
-                let pad = s.ok()?.downcast::<WebRTCSrcPad>().unwrap();
+                let pad = s.ok()?.downcast::<WebRTCSrcPad>().ok()?;
                 if pad.imp().stream_id() == stream_id {
                     Some(pad)
                 } else {
@@ -334,25 +624,88 @@ impl BaseWebRTCSrc {
         })
     }
 
-    fn send_navigation_event(&self, evt: gst_video::NavigationEvent) {
-        if let Some(data_channel) = &self.state.lock().unwrap().data_channel.borrow_mut() {
-            let nav_event = NavigationEvent {
-                mid: None,
-                event: evt,
-            };
-            match serde_json::to_string(&nav_event).ok() {
-                Some(str) => {
-                    gst::trace!(CAT, imp: self, "Sending navigation event to peer");
+    // Returns the id of the event the producer will refer to in its reply,
+    // if it was sent and is expected to be acknowledged, the event goes to
+    // the producer of @webrtcbin or to all of them
+    fn send_navigation_event(
+        &self,
+        evt: gst_video::NavigationEvent,
+        mid: Option<String>,
+        webrtcbin: Option<&gst::Element>,
+    ) -> Option<u64> {
+        let mut state = self.state.lock().unwrap();
+        let data_channels = state
+            .sessions
+            .values()
+            .filter(|session| webrtcbin.map_or(true, |webrtcbin| &session.webrtcbin == webrtcbin))
+            .filter_map(|session| session.data_channel.clone())
+            .filter(|data_channel| {
+                data_channel.property::<gst_webrtc::WebRTCDataChannelState>("ready-state")
+                    == gst_webrtc::WebRTCDataChannelState::Open
+            })
+            .collect::<Vec<_>>();
+        if data_channels.is_empty() {
+            gst::debug!(
+                CAT,
+                imp: self,
+                "No open navigation data channel, dropping {evt:?}"
+            );
+            return None;
+        }
+
+        let event_type = evt.structure().get::<String>("event").unwrap_or_default();
+        let id = if CONTINUOUS_NAVIGATION_EVENTS.contains(&event_type.as_str()) {
+            None
+        } else {
+            state.navigation_seqnum += 1;
+            Some(state.navigation_seqnum)
+        };
+        drop(state);
+
+        let nav_event = NavigationEvent {
+            mid,
+            id,
+            event: evt,
+        };
+        match serde_json::to_string(&nav_event).ok() {
+            Some(str) => {
+                gst::trace!(CAT, imp: self, "Sending navigation event {event_type} {id:?} to peer");
+                for data_channel in data_channels {
                     data_channel.send_string(Some(str.as_str()));
                 }
-                None => {
-                    gst::error!(CAT, imp: self, "Could not serialize navigation event");
-                }
+
+                id
+            }
+            None => {
+                gst::error!(CAT, imp: self, "Could not serialize navigation event");
+
+                None
             }
         }
     }
 
+    fn on_navigation_reply(&self, msg: &str) {
+        let reply = match serde_json::from_str::<NavigationEventReply>(msg) {
+            Ok(reply) => reply,
+            Err(err) => {
+                gst::warning!(CAT, imp: self, "Invalid navigation reply {msg:?}: {err}");
+                return;
+            }
+        };
+
+        gst::log!(CAT, imp: self, "Navigation reply: {reply:?}");
+
+        let (id, error) = match reply {
+            NavigationEventReply::Ack { id } => (id, None),
+            NavigationEventReply::Error { id, details } => (id.unwrap_or(0), Some(details)),
+        };
+
+        self.obj()
+            .emit_by_name::<()>("navigation-event-reply", &[&id, &error]);
+    }
+
     fn handle_webrtc_src_pad(&self, bin: &gst::Bin, pad: &gst::Pad) {
+        let webrtcbin = pad.parent_element().unwrap();
         let srcpad = self.get_src_pad_from_webrtcbin_pad(pad);
         if let Some(ref srcpad) = srcpad {
             let stream_id = srcpad.imp().stream_id();
@@ -397,10 +750,27 @@ impl BaseWebRTCSrc {
             }))
             .build();
 
+        if self.settings.lock().unwrap().do_remb {
+            pad.add_probe(
+                gst::PadProbeType::BUFFER,
+                glib::clone!(@weak self as this, @weak webrtcbin => @default-panic, move |_pad, info| {
+                    if let Some(buffer) = info.buffer() {
+                        this.with_session(&webrtcbin, |session| {
+                            if let Some(remb) = session.remb.as_mut() {
+                                remb.on_rtp_buffer(buffer);
+                            }
+                        });
+                    }
+
+                    gst::PadProbeReturn::Ok
+                }),
+            );
+        }
+
         if self.settings.lock().unwrap().enable_data_channel_navigation {
             pad.add_probe(
                 gst::PadProbeType::EVENT_UPSTREAM,
-                glib::clone!(@weak self as this => @default-panic, move |_pad, info| {
+                glib::clone!(@weak self as this, @weak webrtcbin => @default-panic, move |pad, info| {
                     let Some(ev) = info.event() else {
                         return gst::PadProbeReturn::Ok;
                     };
@@ -408,7 +778,18 @@ impl BaseWebRTCSrc {
                         return gst::PadProbeReturn::Ok;
                     };
 
-                    this.send_navigation_event (gst_video::NavigationEvent::parse(ev).unwrap());
+                    let event = match gst_video::NavigationEvent::parse(ev) {
+                        Ok(event) => event,
+                        Err(err) => {
+                            gst::warning!(CAT, imp: this, "Dropping invalid navigation event: {err}");
+                            return gst::PadProbeReturn::Drop;
+                        }
+                    };
+
+                    let mid = pad
+                        .property::<Option<gst_webrtc::WebRTCRTPTransceiver>>("transceiver")
+                        .and_then(|transceiver| transceiver.mid().map(String::from));
+                    this.send_navigation_event(event, mid, Some(&webrtcbin));
 
                     gst::PadProbeReturn::Ok
                 }),
@@ -429,11 +810,22 @@ impl BaseWebRTCSrc {
                 &[&producer_id, &srcpad.name(), &srcpad.allowed_caps()],
             );
 
-            if srcpad.imp().needs_decoding() {
+            if srcpad.imp().needs_decoding()
+                && self.settings.lock().unwrap().decoder_preferences.is_some()
+            {
+                gst::debug!(
+                    CAT,
+                    imp: self,
+                    "Decoding with preferred decoders for {}",
+                    srcpad.imp().stream_id()
+                );
+
+                self.setup_preferred_decoding(&webrtcbin, &ghostpad, &srcpad, encoded_filter);
+            } else if srcpad.imp().needs_decoding() {
                 let decodebin = gst::ElementFactory::make("decodebin3")
                     .build()
                     .expect("decodebin3 needs to be present!");
-                self.obj().add(&decodebin).unwrap();
+                self.add_session_element(&webrtcbin, &decodebin);
                 decodebin.sync_state_with_parent().unwrap();
                 decodebin.connect_pad_added(
                     glib::clone!(@weak self as this, @weak srcpad => move |_webrtcbin, pad| {
@@ -455,7 +847,8 @@ impl BaseWebRTCSrc {
                     let parsebin = gst::ElementFactory::make("parsebin")
                         .build()
                         .expect("parsebin needs to be present!");
-                    self.obj().add_many([&parsebin, &encoded_filter]).unwrap();
+                    self.add_session_element(&webrtcbin, &parsebin);
+                    self.add_session_element(&webrtcbin, &encoded_filter);
 
                     parsebin.connect_pad_added(move |_, pad| {
                         pad.link(&filter_sink_pad)
@@ -496,7 +889,7 @@ impl BaseWebRTCSrc {
                         .static_pad("src")
                         .expect("encoded filter must expose a static src pad");
 
-                    self.obj().add(&encoded_filter).unwrap();
+                    self.add_session_element(&webrtcbin, &encoded_filter);
 
                     ghostpad
                         .link(&filter_sink_pad)
@@ -513,7 +906,257 @@ impl BaseWebRTCSrc {
         }
     }
 
-    fn prepare(&self) -> Result<(), Error> {
+    // Decodes using `parsebin ! decoder`, the decoder being picked from the
+    // `decoder-preferences` list for the codec of the stream
+    fn setup_preferred_decoding(
+        &self,
+        webrtcbin: &gst::Element,
+        ghostpad: &gst::GhostPad,
+        srcpad: &WebRTCSrcPad,
+        encoded_filter: Option<gst::Element>,
+    ) {
+        let parsebin = gst::ElementFactory::make("parsebin")
+            .build()
+            .expect("parsebin needs to be present!");
+        self.add_session_element(webrtcbin, &parsebin);
+
+        if let Some(ref encoded_filter) = encoded_filter {
+            self.add_session_element(webrtcbin, encoded_filter);
+        }
+
+        parsebin.connect_pad_added(
+            glib::clone!(@weak self as this, @weak webrtcbin, @weak srcpad => move |_, pad| {
+                let upstream = if let Some(ref encoded_filter) = encoded_filter {
+                    pad.link(&encoded_filter.static_pad("sink").unwrap())
+                        .expect("parsebin ! encoded_filter linking failed");
+                    encoded_filter.sync_state_with_parent().unwrap();
+
+                    encoded_filter.static_pad("src").unwrap()
+                } else {
+                    pad.clone()
+                };
+
+                let caps = pad.current_caps().unwrap_or_else(|| pad.query_caps(None));
+                let candidates = this.preferred_decoders(&caps);
+                if candidates.is_empty() {
+                    gst::debug!(CAT, imp: this, "No decoder preference for {caps:?}, using decodebin3");
+
+                    let decodebin = gst::ElementFactory::make("decodebin3")
+                        .build()
+                        .expect("decodebin3 needs to be present!");
+                    this.add_session_element(&webrtcbin, &decodebin);
+                    decodebin.connect_pad_added(
+                        glib::clone!(@weak srcpad => move |_, pad| {
+                            if pad.direction() == gst::PadDirection::Src {
+                                srcpad.set_target(Some(pad)).unwrap();
+                            }
+                        }),
+                    );
+                    upstream
+                        .link(&decodebin.static_pad("sink").unwrap())
+                        .expect("parsebin ! decodebin3 linking failed");
+                    decodebin.sync_state_with_parent().unwrap();
+
+                    return;
+                }
+
+                if this.plug_next_decoder(&webrtcbin, &srcpad, &upstream, candidates).is_none() {
+                    gst::element_error!(
+                        this.obj(),
+                        gst::StreamError::Decode,
+                        ["None of the preferred decoders could be used for {caps:?}"]
+                    );
+                }
+            }),
+        );
+
+        ghostpad
+            .link(&parsebin.static_pad("sink").unwrap())
+            .expect("webrtcbin ! parsebin linking failed");
+
+        parsebin.sync_state_with_parent().unwrap();
+    }
+
+    // Lists the decoders to try, in order, for streams with @caps
+    fn preferred_decoders(&self, caps: &gst::Caps) -> VecDeque<gst::ElementFactory> {
+        let Some(codec) = Codecs::find_for_caps(caps) else {
+            return VecDeque::new();
+        };
+
+        let settings = self.settings.lock().unwrap();
+        let Some(names) = settings
+            .decoder_preferences
+            .as_ref()
+            .and_then(|prefs| prefs.value(codec.name.as_str()).ok())
+            .map(|value| {
+                if let Ok(array) = value.get::<gst::ArrayRef>() {
+                    array
+                        .iter()
+                        .filter_map(|name| name.get::<String>().ok())
+                        .collect::<Vec<_>>()
+                } else if let Ok(names) = value.get::<String>() {
+                    names
+                        .split(',')
+                        .map(|name| name.trim().to_string())
+                        .collect()
+                } else {
+                    vec![]
+                }
+            })
+        else {
+            return VecDeque::new();
+        };
+        drop(settings);
+
+        let mut factories = VecDeque::new();
+        for name in names {
+            if name == "*" {
+                // All other decoders, by decreasing rank
+                for factory in codec.decoder_factories() {
+                    if !factories.contains(&factory) {
+                        factories.push_back(factory);
+                    }
+                }
+            } else if let Some(factory) = gst::ElementFactory::find(&name) {
+                if !codec.can_be_decoded_by(&factory) {
+                    gst::warning!(CAT, imp: self, "{name} can't decode {} streams, ignoring", codec.name);
+                } else if !factories.contains(&factory) {
+                    factories.push_back(factory);
+                }
+            } else {
+                gst::warning!(CAT, imp: self, "Preferred decoder {name} is not available");
+            }
+        }
+
+        factories
+    }
+
+    // Plugs the first usable decoder in @candidates between @upstream and
+    // @srcpad, returns the decoder that is now in use
+    fn plug_next_decoder(
+        &self,
+        webrtcbin: &gst::Element,
+        srcpad: &WebRTCSrcPad,
+        upstream: &gst::Pad,
+        mut candidates: VecDeque<gst::ElementFactory>,
+    ) -> Option<gst::Element> {
+        let obj = self.obj();
+
+        while let Some(factory) = candidates.pop_front() {
+            let decoder = match factory.create().build() {
+                Ok(decoder) => decoder,
+                Err(err) => {
+                    gst::warning!(CAT, imp: self, "Could not create {}: {err}", factory.name());
+                    continue;
+                }
+            };
+
+            self.add_session_element(webrtcbin, &decoder);
+
+            let sinkpad = decoder.static_pad("sink").unwrap();
+            if let Err(err) = upstream.link(&sinkpad) {
+                gst::warning!(CAT, imp: self, "Could not link to {}: {err:?}", decoder.name());
+                obj.remove(&decoder).unwrap();
+                continue;
+            }
+
+            srcpad
+                .set_target(Some(&decoder.static_pad("src").unwrap()))
+                .unwrap();
+
+            self.state
+                .lock()
+                .unwrap()
+                .decoder_chains
+                .push(DecoderChain {
+                    webrtcbin: webrtcbin.clone(),
+                    srcpad: srcpad.clone(),
+                    upstream: upstream.clone(),
+                    decoder: decoder.clone(),
+                    candidates: candidates.clone(),
+                });
+
+            if let Err(err) = decoder.sync_state_with_parent() {
+                gst::warning!(CAT, imp: self, "Could not start {}: {err}", decoder.name());
+
+                self.state
+                    .lock()
+                    .unwrap()
+                    .decoder_chains
+                    .retain(|chain| chain.decoder != decoder);
+                let _ = upstream.unlink(&sinkpad);
+                let _ = decoder.set_state(gst::State::Null);
+                obj.remove(&decoder).unwrap();
+                continue;
+            }
+
+            gst::info!(CAT, imp: self, "Decoding {} with {}", srcpad.imp().stream_id(), decoder.name());
+
+            return Some(decoder);
+        }
+
+        None
+    }
+
+    // Replaces @failed with the next preferred decoder of its chain
+    fn switch_to_next_decoder(&self, failed: &gst::Element) {
+        let chain = {
+            let mut state = self.state.lock().unwrap();
+            let Some(idx) = state
+                .decoder_chains
+                .iter()
+                .position(|chain| &chain.decoder == failed)
+            else {
+                // Already replaced
+                return;
+            };
+
+            state.decoder_chains.remove(idx)
+        };
+
+        let probe_id = chain
+            .upstream
+            .add_probe(gst::PadProbeType::BLOCK_DOWNSTREAM, |_, _| {
+                gst::PadProbeReturn::Ok
+            });
+
+        let _ = chain.upstream.unlink(&failed.static_pad("sink").unwrap());
+        let _ = failed.set_state(gst::State::Null);
+        let _ = self.obj().remove(failed);
+
+        match self.plug_next_decoder(
+            &chain.webrtcbin,
+            &chain.srcpad,
+            &chain.upstream,
+            chain.candidates,
+        ) {
+            Some(decoder) => {
+                // Make sure the new decoder can start from a keyframe
+                let sinkpad = decoder.static_pad("sink").unwrap();
+                sinkpad.push_event(
+                    gst_video::UpstreamForceKeyUnitEvent::builder()
+                        .all_headers(true)
+                        .build(),
+                );
+            }
+            None => {
+                gst::element_error!(
+                    self.obj(),
+                    gst::StreamError::Decode,
+                    ["No working decoder left for {}", chain.srcpad.name()]
+                );
+            }
+        }
+
+        if let Some(probe_id) = probe_id {
+            chain.upstream.remove_probe(probe_id);
+        }
+    }
+
+    // Creates the `webrtcbin` negotiating @session_id
+    fn create_session(&self, session_id: &str) -> Result<gst::Element, Error> {
+        gst::debug!(CAT, imp: self, "Creating session {session_id}");
+
         let webrtcbin = gst::ElementFactory::make("webrtcbin")
             .property("bundle-policy", gst_webrtc::WebRTCBundlePolicy::MaxBundle)
             .build()
@@ -551,72 +1194,478 @@ impl BaseWebRTCSrc {
         webrtcbin.connect_closure(
             "on-ice-candidate",
             false,
-            glib::closure!(@weak-allow-none self as this => move |
+            glib::closure!(@weak-allow-none self as this, @to-owned session_id => move |
                     _webrtcbin: gst::Bin,
                     sdp_m_line_index: u32,
                     candidate: String| {
-                this.unwrap().on_ice_candidate(sdp_m_line_index, candidate);
+                this.unwrap().on_ice_candidate(&session_id, sdp_m_line_index, candidate);
             }),
         );
 
         webrtcbin.connect_closure(
             "on-data-channel",
             false,
-            glib::closure!(@weak-allow-none self as this => move |
-                    _webrtcbin: gst::Bin,
+            glib::closure!(@weak-allow-none self as this, @to-owned session_id => move |
+                    webrtcbin: gst::Bin,
                     data_channel: glib::Object| {
-                this.unwrap().on_data_channel(data_channel);
+                this.unwrap().on_data_channel(&session_id, webrtcbin.upcast_ref(), data_channel);
             }),
         );
 
+        let do_remb = self.settings.lock().unwrap().do_remb;
+        if do_remb {
+            self.connect_remb(&webrtcbin);
+        }
+
         self.signaller()
-            .emit_by_name::<()>("webrtcbin-ready", &[&"none", &webrtcbin]);
+            .emit_by_name::<()>("webrtcbin-ready", &[&session_id, &webrtcbin]);
 
         bin.add(&webrtcbin).unwrap();
         self.obj().add(&bin).context("Could not add `webrtcbin`")?;
 
-        let mut state = self.state.lock().unwrap();
-        state.webrtcbin.replace(webrtcbin);
-
-        Ok(())
-    }
+        self.state.lock().unwrap().sessions.insert(
+            session_id.to_string(),
+            Session {
+                webrtcbin: webrtcbin.clone(),
+                elements: vec![bin.clone().upcast()],
+                pads: Vec::new(),
+                data_channel: None,
+                data_channels: HashMap::new(),
+                remb: do_remb.then(RembEstimator::default),
+                remb_sigid: None,
+            },
+        );
 
-    fn get_stream_id(
-        &self,
-        transceiver: Option<gst_webrtc::WebRTCRTPTransceiver>,
-        mline: Option<u32>,
-    ) -> Option<String> {
-        let mline = transceiver.map_or(mline, |t| Some(t.mlineindex()));
+        bin.sync_state_with_parent()
+            .context("Could not start `webrtcbin`")?;
 
-        // Same logic as gst_pad_create_stream_id and friends, making a hash of
-        // the URI (session id, if URI doesn't exist) and adding `:<some-id>`, here the ID is the mline of the
-        // stream in the SDP.
-        mline.map(|mline| {
-            let mut cs = glib::Checksum::new(glib::ChecksumType::Sha256).unwrap();
+        Ok(webrtcbin)
+    }
 
-            let data: String = if self
-                .signaller()
-                .has_property("uri", Some(String::static_type()))
-            {
-                self.signaller().property::<Option<String>>("uri").unwrap()
-            } else {
-                // use the session id
-                self.state.lock().unwrap().session_id.clone().unwrap()
-            };
+    // Removes the elements of an ended session, its pads are left to the
+    // caller
+    fn teardown_session(&self, session: Session) {
+        let obj = self.obj();
 
-            cs.update(data.as_bytes());
+        {
+            let mut state = self.state.lock().unwrap();
+            state
+                .decoder_chains
+                .retain(|chain| chain.webrtcbin != session.webrtcbin);
+            if let Some(bin) = session.webrtcbin.parent().and_downcast::<gst::Bin>() {
+                for pad in bin.src_pads() {
+                    state.flow_combiner.remove_pad(&pad);
+                }
+            }
+        }
 
-            format!("{}:{mline}", cs.string().unwrap())
-        })
+        for element in session.elements.iter().rev() {
+            let _ = element.set_state(gst::State::Null);
+            let _ = obj.remove(element);
+        }
     }
 
-    fn unprepare(&self) -> Result<(), Error> {
-        gst::info!(CAT, imp: self, "unpreparing");
-
+    // Appends REMB feedback to the RTCP packets sent by the internal RTP session
+    fn connect_remb(&self, webrtcbin: &gst::Element) {
+        let rtpbin = webrtcbin
+            .dynamic_cast_ref::<gst::ChildProxy>()
+            .unwrap()
+            .child_by_name("rtpbin")
+            .unwrap();
+
+        rtpbin.connect_closure(
+            "on-new-ssrc",
+            true,
+            glib::closure!(@weak-allow-none self as this, @weak-allow-none webrtcbin => move |rtpbin: gst::Object, session_id: u32, _src: u32| {
+                let (Some(this), Some(webrtcbin)) = (this, webrtcbin) else {
+                    return;
+                };
+
+                let mut state = this.state.lock().unwrap();
+                let Some(session) = state.sessions.values_mut().find(|session| session.webrtcbin == webrtcbin) else {
+                    return;
+                };
+                if session.remb_sigid.is_some() {
+                    return;
+                }
+
+                let rtp_session = rtpbin.emit_by_name::<glib::Object>("get-internal-session", &[&session_id]);
+
+                session.remb_sigid = Some(rtp_session.connect_closure(
+                    "on-sending-rtcp",
+                    false,
+                    glib::closure!(@weak-allow-none this, @weak-allow-none webrtcbin => move |rtp_session: glib::Object, buffer: &gst::BufferRef, _early: bool| -> bool {
+                        let (Some(this), Some(webrtcbin)) = (this, webrtcbin) else {
+                            return false;
+                        };
+
+                        let sender_ssrc = rtp_session.property::<u32>("internal-ssrc");
+                        let Some(fci) = this.with_session(&webrtcbin, |session| session.remb.as_mut().and_then(RembEstimator::next_remb_fci)).flatten() else {
+                            return false;
+                        };
+
+                        gst::trace!(CAT, imp: this, "Adding REMB packet to RTCP buffer");
+
+                        // SAFETY: rtpsession emits `on-sending-rtcp` with the
+                        // compound packet it is building, unmapped and only
+                        // referenced by itself, for handlers to add packets
+                        let buffer = unsafe {
+                            gst::BufferRef::from_mut_ptr(buffer.as_ptr() as *mut gst::ffi::GstBuffer)
+                        };
+                        if !remb::add_remb_packet(buffer, sender_ssrc, &fci) {
+                            gst::warning!(CAT, imp: this, "No room left for the REMB packet");
+                        }
+
+                        false
+                    }),
+                ));
+            }),
+        );
+    }
+
+    // Keeps the source pads of the session that just ended for the next
+    // session with the same producer. This runs from the `session-ended`
+    // handler, the elements of the session are torn down asynchronously.
+    fn wait_for_reconnection(&self, session_id: &str) -> Result<(), Error> {
+        gst::info!(
+            CAT,
+            imp: self,
+            "Session {session_id} ended, waiting for the producer to come back"
+        );
+
+        let obj = self.obj();
+        self.clear_disconnected_pads();
+
+        let Some(session) = self.state.lock().unwrap().sessions.remove(session_id) else {
+            return Ok(());
+        };
+
+        let pads = session
+            .pads
+            .iter()
+            .map(|pad| {
+                let caps = pad.current_caps();
+                pad.set_target(None::<&gst::Pad>)?;
+
+                Ok((pad.clone(), caps))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        // GAP events are timestamped with our running time until a
+        // placeholder takes over
+        let segment = gst::FormattedSegment::<gst::ClockTime>::new();
+        let disconnected_pads = pads
+            .iter()
+            .map(|(pad, _)| {
+                pad.push_event(gst::event::Segment::new(&segment));
+
+                DisconnectedPad {
+                    pad: pad.clone(),
+                    placeholder: None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let obj_weak = obj.downgrade();
+        let gap_task = RUNTIME.spawn(async move {
+            let mut interval = tokio::time::interval(GAP_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                let Some(obj) = obj_weak.upgrade() else {
+                    break;
+                };
+                obj.imp().push_gaps();
+            }
+        });
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.disconnected_pads = disconnected_pads;
+            state.gap_task = Some(gap_task);
+        }
+
+        // Changing the state of our children from a signal handler could
+        // deadlock with the signaller or a state change of the element
+        obj.call_async(move |obj| obj.imp().finish_disconnection(session, pads));
+
+        Ok(())
+    }
+
+    // Tears down the elements of a session that ended in reconnect mode and
+    // plugs the placeholders of its pads that are still disconnected
+    fn finish_disconnection(&self, session: Session, pads: Vec<(WebRTCSrcPad, Option<gst::Caps>)>) {
+        self.teardown_session(session);
+
+        for (pad, caps) in pads {
+            let Some(caps) = caps else {
+                continue;
+            };
+            let still_disconnected = self
+                .state
+                .lock()
+                .unwrap()
+                .disconnected_pads
+                .iter()
+                .any(|disconnected| disconnected.pad == pad);
+            if !still_disconnected {
+                continue;
+            }
+            let Some(placeholder) = self.start_placeholder(&pad, &caps) else {
+                continue;
+            };
+
+            let mut state = self.state.lock().unwrap();
+            match state
+                .disconnected_pads
+                .iter_mut()
+                .find(|disconnected| disconnected.pad == pad)
+            {
+                Some(disconnected) => disconnected.placeholder = Some(placeholder),
+                // Reused by a new session in the meantime
+                None => {
+                    drop(state);
+                    let _ = placeholder.set_state(gst::State::Null);
+                    let _ = self.obj().remove(&placeholder);
+                }
+            }
+        }
+    }
+
+    // Plugs the placeholder source configured for the media type of `pad`,
+    // only raw streams in system memory can be replaced
+    fn start_placeholder(&self, pad: &WebRTCSrcPad, caps: &gst::Caps) -> Option<gst::Element> {
+        let s = caps.structure(0)?;
+        if !caps
+            .features(0)
+            .map_or(true, |f| f.contains(gst::CAPS_FEATURE_MEMORY_SYSTEM_MEMORY))
+        {
+            return None;
+        }
+
+        let (description, converters) = {
+            let settings = self.settings.lock().unwrap();
+            if s.name() == "video/x-raw" {
+                (
+                    settings.video_placeholder.clone()?,
+                    "videoconvert ! videoscale",
+                )
+            } else if s.name() == "audio/x-raw" {
+                (
+                    settings.audio_placeholder.clone()?,
+                    "audioconvert ! audioresample",
+                )
+            } else {
+                return None;
+            }
+        };
+
+        let placeholder = match gst::parse::bin_from_description(
+            &format!("{description} ! {converters} ! capsfilter name=placeholder-caps"),
+            true,
+        ) {
+            Ok(placeholder) => placeholder,
+            Err(err) => {
+                gst::warning!(
+                    CAT,
+                    imp: self,
+                    "Failed to create placeholder {description:?}: {err}"
+                );
+                return None;
+            }
+        };
+
+        // A variable framerate would make the placeholder output a single frame
+        let mut caps = caps.clone();
+        if s.get::<gst::Fraction>("framerate").ok() == Some(gst::Fraction::new(0, 1)) {
+            caps.make_mut()
+                .structure_mut(0)
+                .unwrap()
+                .remove_field("framerate");
+        }
+        placeholder
+            .by_name("placeholder-caps")
+            .unwrap()
+            .set_property("caps", &caps);
+
+        let obj = self.obj();
+        obj.add(&placeholder).ok()?;
+        let src = placeholder.static_pad("src").unwrap();
+        if let Err(err) = pad
+            .set_target(Some(&src))
+            .map_err(Error::from)
+            .and_then(|_| placeholder.sync_state_with_parent().map_err(Error::from))
+        {
+            gst::warning!(
+                CAT,
+                imp: self,
+                "Failed to plug placeholder on {}: {err}",
+                pad.name()
+            );
+            let _ = pad.set_target(None::<&gst::Pad>);
+            let _ = placeholder.set_state(gst::State::Null);
+            let _ = obj.remove(&placeholder);
+
+            return None;
+        }
+
+        gst::debug!(
+            CAT,
+            imp: self,
+            "Feeding {} from placeholder {description:?}",
+            pad.name()
+        );
+
+        Some(placeholder.upcast())
+    }
+
+    // Keeps downstream going on the disconnected pads without placeholder
+    fn push_gaps(&self) {
+        let Some(running_time) = self.obj().current_running_time() else {
+            return;
+        };
+
+        let pads = self
+            .state
+            .lock()
+            .unwrap()
+            .disconnected_pads
+            .iter()
+            .filter(|disconnected| disconnected.placeholder.is_none())
+            .map(|disconnected| disconnected.pad.clone())
+            .collect::<Vec<_>>();
+
+        let duration = gst::ClockTime::try_from(GAP_INTERVAL).unwrap();
+        for pad in pads {
+            pad.push_event(
+                gst::event::Gap::builder(running_time)
+                    .duration(duration)
+                    .build(),
+            );
+        }
+    }
+
+    // Hands a pad kept from a previous session over to a stream of the same
+    // media type in the new one
+    fn reuse_src_pad(
+        &self,
+        session_id: &str,
+        caps: &gst::Caps,
+        stream_id: &str,
+        msid: Option<&str>,
+    ) -> Option<WebRTCSrcPad> {
+        let media_type = caps.structure(0)?.get::<&str>("media").ok()?;
+
+        let prefix = format!("{media_type}_");
+        let disconnected = {
+            let mut state = self.state.lock().unwrap();
+            let Some(idx) = state
+                .disconnected_pads
+                .iter()
+                .position(|disconnected| disconnected.pad.name().starts_with(&prefix))
+            else {
+                return None;
+            };
+
+            let disconnected = state.disconnected_pads.remove(idx);
+            if state.disconnected_pads.is_empty() {
+                if let Some(gap_task) = state.gap_task.take() {
+                    gap_task.abort();
+                }
+            }
+
+            disconnected
+        };
+
+        gst::info!(
+            CAT,
+            imp: self,
+            "Reusing {} for {stream_id}",
+            disconnected.pad.name()
+        );
+
+        let pad = disconnected.pad.clone();
+        pad.imp().set_stream_id(stream_id);
+        pad.imp().set_session_id(session_id);
+        pad.imp().set_msid(msid);
+        self.release_disconnected_pad(disconnected);
+
+        Some(pad)
+    }
+
+    fn release_disconnected_pad(&self, disconnected: DisconnectedPad) {
+        let _ = disconnected.pad.set_target(None::<&gst::Pad>);
+
+        if let Some(placeholder) = disconnected.placeholder {
+            let _ = placeholder.set_state(gst::State::Null);
+            let _ = self.obj().remove(&placeholder);
+        }
+    }
+
+    fn clear_disconnected_pads(&self) {
+        let (disconnected_pads, gap_task) = {
+            let mut state = self.state.lock().unwrap();
+            (
+                std::mem::take(&mut state.disconnected_pads),
+                state.gap_task.take(),
+            )
+        };
+
+        if let Some(gap_task) = gap_task {
+            gap_task.abort();
+        }
+
+        for disconnected in disconnected_pads {
+            self.release_disconnected_pad(disconnected);
+        }
+    }
+
+    fn get_stream_id(
+        &self,
+        session_id: &str,
+        transceiver: Option<gst_webrtc::WebRTCRTPTransceiver>,
+        mline: Option<u32>,
+    ) -> Option<String> {
+        let mline = transceiver.map_or(mline, |t| Some(t.mlineindex()));
+
+        // Same logic as gst_pad_create_stream_id and friends, making a hash of
+        // the URI (session id, if URI doesn't exist) and adding `:<some-id>`, here the ID is the mline of the
+        // stream in the SDP.
+        mline.map(|mline| {
+            let mut cs = glib::Checksum::new(glib::ChecksumType::Sha256).unwrap();
+
+            let data: String = if self
+                .signaller()
+                .has_property("uri", Some(String::static_type()))
+            {
+                self.signaller().property::<Option<String>>("uri").unwrap()
+            } else {
+                // use the session id
+                session_id.to_string()
+            };
+
+            cs.update(data.as_bytes());
+
+            format!("{}:{mline}", cs.string().unwrap())
+        })
+    }
+
+    fn unprepare(&self) -> Result<(), Error> {
+        gst::info!(CAT, imp: self, "unpreparing");
+
         let obj = self.obj();
         self.maybe_stop_signaller();
-        self.state.lock().unwrap().session_id = None;
-        for pad in obj.src_pads() {
+        self.clear_disconnected_pads();
+        let sessions = std::mem::take(&mut self.state.lock().unwrap().sessions);
+        for (_, session) in sessions {
+            self.teardown_session(session);
+        }
+        // The `data` pad is a request pad, released by the application
+        for pad in obj
+            .src_pads()
+            .into_iter()
+            .filter(|pad| pad.is::<WebRTCSrcPad>())
+        {
             obj.remove_pad(&pad)
                 .map_err(|err| anyhow::anyhow!("Couldn't remove pad? {err:?}"))?;
         }
@@ -627,6 +1676,63 @@ impl BaseWebRTCSrc {
         Ok(())
     }
 
+    // EOSes the pads of @session_id, which are also removed along with the
+    // session elements when more producers may come and go
+    fn end_session(&self, session_id: &str) {
+        let obj = self.obj();
+        let remove = self.settings.lock().unwrap().remove_ended_sessions;
+
+        let mut state = self.state.lock().unwrap();
+        if !remove && state.sessions.len() <= 1 {
+            drop(state);
+
+            obj.iterate_src_pads().into_iter().for_each(|pad| {
+                if let Err(e) = pad.map(|pad| pad.push_event(gst::event::Eos::new())) {
+                    gst::error!(CAT, "Could not send EOS: {e:?}");
+                }
+            });
+
+            return;
+        }
+
+        let session = if remove {
+            state.sessions.remove(session_id)
+        } else {
+            None
+        };
+        let pads = session
+            .as_ref()
+            .or_else(|| state.sessions.get(session_id))
+            .map(|session| session.pads.clone())
+            .unwrap_or_default();
+        drop(state);
+
+        for pad in pads.iter() {
+            pad.push_event(gst::event::Eos::new());
+        }
+
+        if let Some(session) = session {
+            gst::info!(CAT, imp: self, "Removing session {session_id}");
+
+            for pad in pads {
+                let _ = pad.set_target(None::<&gst::Pad>);
+                let _ = obj.remove_pad(&pad);
+            }
+
+            self.teardown_session(session);
+        }
+    }
+
+    // EOSes and removes @pad, whose stream was removed from its session
+    fn remove_src_pad(&self, webrtcbin: &gst::Element, pad: &WebRTCSrcPad) {
+        gst::info!(CAT, imp: self, "Stream of {} ended, removing it", pad.name());
+
+        self.with_session(webrtcbin, |session| session.pads.retain(|p| p != pad));
+        pad.push_event(gst::event::Eos::new());
+        let _ = pad.set_target(None::<&gst::Pad>);
+        let _ = self.obj().remove_pad(pad);
+    }
+
     fn connect_signaller(&self, signaller: &Signallable) {
         let instance = &*self.obj();
 
@@ -658,26 +1764,57 @@ impl BaseWebRTCSrc {
                         _peer_id: &str| {
                     let imp = instance.imp();
                     gst::info!(CAT, imp: imp, "Session started: {session_id}");
-                    imp.state.lock().unwrap().session_id =
-                        Some(session_id.to_string());
+                    if let Err(err) = imp.session_webrtcbin(session_id) {
+                        gst::element_error!(
+                            instance,
+                            gst::StreamError::Failed,
+                            ["Failed to start session {}: {}", session_id, err]
+                        );
+                    }
                 }),
             ),
 
             session_ended: signaller.connect_closure(
                 "session-ended",
                 false,
-                glib::closure!(@watch instance => move |_signaler: glib::Object, _session_id: &str|{
-                    instance.imp().state.lock().unwrap().session_id = None;
-                    instance.iterate_src_pads().into_iter().for_each(|pad|
-                        { if let Err(e) = pad.map(|pad| pad.push_event(gst::event::Eos::new())) {
-                            gst::error!(CAT, "Could not send EOS: {e:?}");
-                        }}
-                    );
+                glib::closure!(@watch instance => move |_signaler: glib::Object, session_id: &str|{
+                    let imp = instance.imp();
+                    if imp.settings.lock().unwrap().reconnect {
+                        if let Err(err) = imp.wait_for_reconnection(session_id) {
+                            gst::element_error!(
+                                instance,
+                                gst::StreamError::Failed,
+                                ["Failed to wait for the producer: {}", err]
+                            );
+                        }
+
+                        return false;
+                    }
+
+                    imp.end_session(session_id);
 
                     false
                 }),
             ),
 
+            // Signallers for which the consumer makes the offer ask for it
+            // with this signal
+            session_requested: signaller.connect_closure(
+                "session-requested",
+                false,
+                glib::closure!(@watch instance => move |
+                        _signaller: glib::Object,
+                        session_id: &str,
+                        _peer_id: &str,
+                        offer: Option<&gst_webrtc::WebRTCSessionDescription>| {
+                    let imp = instance.imp();
+                    match offer {
+                        Some(offer) => imp.handle_offer(session_id, offer),
+                        None => imp.create_offer(session_id),
+                    }
+                }),
+            ),
+
             request_meta: signaller.connect_closure(
                 "request-meta",
                 false,
@@ -692,11 +1829,16 @@ impl BaseWebRTCSrc {
                 false,
                 glib::closure!(@watch instance => move |
                         _signaller: glib::Object,
-                        _peer_id: &str,
+                        session_id: &str,
                         desc: &gst_webrtc::WebRTCSessionDescription| {
-                    assert_eq!(desc.type_(), gst_webrtc::WebRTCSDPType::Offer);
-
-                    instance.imp().handle_offer(desc);
+                    let imp = instance.imp();
+                    match desc.type_() {
+                        gst_webrtc::WebRTCSDPType::Offer => imp.handle_offer(session_id, desc),
+                        gst_webrtc::WebRTCSDPType::Answer => imp.handle_answer(session_id, desc),
+                        sdp_type => {
+                            gst::warning!(CAT, imp: imp, "Ignoring {sdp_type:?} description");
+                        }
+                    }
                 }),
             ),
 
@@ -708,11 +1850,11 @@ impl BaseWebRTCSrc {
                 false,
                 glib::closure!(@watch instance => move |
                         _signaller: glib::Object,
-                        peer_id: &str,
+                        session_id: &str,
                         sdp_m_line_index: u32,
                         _sdp_mid: Option<String>,
                         candidate: &str| {
-                    instance.imp().handle_ice(peer_id, Some(sdp_m_line_index), None, candidate);
+                    instance.imp().handle_ice(session_id, Some(sdp_m_line_index), None, candidate);
                 }),
             ),
         });
@@ -720,9 +1862,15 @@ impl BaseWebRTCSrc {
         // previous signals are disconnected when dropping the old structure
     }
 
-    // Creates and adds our `WebRTCSrcPad` source pad, returning caps accepted
-    // downstream
-    fn create_and_probe_src_pad(&self, caps: &gst::Caps, stream_id: &str) -> bool {
+    // Creates and adds our `WebRTCSrcPad` source pad, probing whether raw caps
+    // are accepted downstream
+    fn create_and_probe_src_pad(
+        &self,
+        session_id: &str,
+        caps: &gst::Caps,
+        stream_id: &str,
+        msid: Option<&str>,
+    ) -> Option<WebRTCSrcPad> {
         gst::log!(CAT, "Creating pad for {caps:?}, stream: {stream_id}");
 
         let obj = self.obj();
@@ -747,7 +1895,7 @@ impl BaseWebRTCSrc {
         } else {
             gst::info!(CAT, imp: self, "Not an audio or video media {media_type:?}");
 
-            return false;
+            return None;
         };
 
         let caps_with_raw = [caps.clone(), raw_caps.clone()]
@@ -759,6 +1907,8 @@ impl BaseWebRTCSrc {
             .downcast::<WebRTCSrcPad>()
             .unwrap();
         ghost.imp().set_stream_id(stream_id);
+        ghost.imp().set_session_id(session_id);
+        ghost.imp().set_msid(msid);
         obj.add_pad(&ghost)
             .expect("Adding ghost pad should never fail");
 
@@ -769,77 +1919,166 @@ impl BaseWebRTCSrc {
             }
         }
 
-        true
+        Some(ghost)
     }
 
-    fn handle_offer(&self, offer: &gst_webrtc::WebRTCSessionDescription) {
-        gst::log!(CAT, imp: self, "Got offer {}", offer.sdp().to_string());
+    // The RTP caps of the payloads of @media we can receive, keeping only the
+    // feedback and header extensions we handle
+    fn media_caps(&self, media: &gst_sdp::SDPMediaRef) -> gst::Caps {
+        let (codec_names, do_twcc, do_remb) = {
+            let settings = self.settings.lock().unwrap();
+            (
+                settings
+                    .video_codecs
+                    .iter()
+                    .chain(settings.audio_codecs.iter())
+                    .map(|codec| codec.name.clone())
+                    .collect::<HashSet<String>>(),
+                settings.do_twcc,
+                settings.do_remb,
+            )
+        };
+        media
+            .formats()
+            .filter_map(|format| {
+                format.parse::<i32>().ok().and_then(|pt| {
+                    let mut mediacaps = media.caps_from_media(pt)?;
+                    let s = mediacaps.structure(0).unwrap();
+                    if !codec_names.contains(s.get::<&str>("encoding-name").ok()?) {
+                        return None;
+                    }
+
+                    let mut filtered_s = gst::Structure::new_empty("application/x-rtp");
+                    filtered_s.extend(s.iter().filter_map(|(key, value)| {
+                        // Only keep the congestion control related feedback,
+                        // webrtcbin takes care of the rest
+                        let keep = !key.starts_with("rtcp-")
+                            || (do_twcc && key == "rtcp-fb-transport-cc")
+                            || (do_remb && key == "rtcp-fb-goog-remb");
+
+                        if keep {
+                            Some((key, value.to_owned()))
+                        } else {
+                            None
+                        }
+                    }));
+
+                    if media
+                        .attributes_to_caps(mediacaps.get_mut().unwrap())
+                        .is_err()
+                    {
+                        gst::warning!(
+                            CAT,
+                            imp: self,
+                            "Failed to retrieve attributes from media!"
+                        );
+                        return None;
+                    }
+
+                    let s = mediacaps.structure(0).unwrap();
+
+                    filtered_s.extend(s.iter().filter_map(|(key, value)| {
+                        if key.starts_with("extmap-") {
+                            if !do_twcc && extmap_uri(value).as_deref() == Some(RTP_TWCC_URI) {
+                                return None;
+                            }
 
-        let sdp = offer.sdp();
-        let direction = gst_webrtc::WebRTCRTPTransceiverDirection::Recvonly;
-        let webrtcbin = self.webrtcbin();
-        for (i, media) in sdp.medias().enumerate() {
-            let (codec_names, do_retransmission) = {
-                let settings = self.settings.lock().unwrap();
-                (
-                    settings
-                        .video_codecs
-                        .iter()
-                        .chain(settings.audio_codecs.iter())
-                        .map(|codec| codec.name.clone())
-                        .collect::<HashSet<String>>(),
-                    settings.do_retransmission,
-                )
-            };
-            let caps = media
-                .formats()
-                .filter_map(|format| {
-                    format.parse::<i32>().ok().and_then(|pt| {
-                        let mut mediacaps = media.caps_from_media(pt)?;
-                        let s = mediacaps.structure(0).unwrap();
-                        if !codec_names.contains(s.get::<&str>("encoding-name").ok()?) {
-                            return None;
+                            return Some((key, value.to_owned()));
                         }
 
-                        let mut filtered_s = gst::Structure::new_empty("application/x-rtp");
-                        filtered_s.extend(s.iter().filter_map(|(key, value)| {
-                            if key.starts_with("rtcp-") {
-                                None
-                            } else {
-                                Some((key, value.to_owned()))
-                            }
-                        }));
+                        None
+                    }));
 
-                        if media
-                            .attributes_to_caps(mediacaps.get_mut().unwrap())
-                            .is_err()
-                        {
-                            gst::warning!(
+                    if do_twcc {
+                        match find_extmap_id(&filtered_s, RTP_TWCC_URI) {
+                            Some(twcc_id) => gst::debug!(
                                 CAT,
                                 imp: self,
-                                "Failed to retrieve attributes from media!"
-                            );
-                            return None;
+                                "Sending TWCC feedback for pt {pt}, extension ID {twcc_id}"
+                            ),
+                            None => gst::debug!(
+                                CAT,
+                                imp: self,
+                                "Remote peer didn't offer TWCC for pt {pt}"
+                            ),
                         }
+                    }
 
-                        let s = mediacaps.structure(0).unwrap();
-
-                        filtered_s.extend(s.iter().filter_map(|(key, value)| {
-                            if key.starts_with("extmap-") {
-                                return Some((key, value.to_owned()));
-                            }
+                    Some(filtered_s)
+                })
+            })
+            .collect::<gst::Caps>()
+    }
 
-                            None
-                        }));
+    fn handle_offer(&self, session_id: &str, offer: &gst_webrtc::WebRTCSessionDescription) {
+        gst::log!(CAT, imp: self, "Got offer {}", offer.sdp().to_string());
 
-                        Some(filtered_s)
-                    })
+        let sdp = offer.sdp();
+        let direction = gst_webrtc::WebRTCRTPTransceiverDirection::Recvonly;
+        let webrtcbin = match self.session_webrtcbin(session_id) {
+            Ok(webrtcbin) => webrtcbin,
+            Err(err) => {
+                gst::element_error!(
+                    self.obj(),
+                    gst::StreamError::Failed,
+                    ["Failed to start session {}: {}", session_id, err]
+                );
+                return;
+            }
+        };
+        let do_retransmission = self.settings.lock().unwrap().do_retransmission;
+        for (i, media) in sdp.medias().enumerate() {
+            let stream_id = self
+                .get_stream_id(session_id, None, Some(i as u32))
+                .unwrap();
+            let msid = media_msid(media);
+            let inactive = media.port() == 0 || media.attribute_val("inactive").is_some();
+
+            // Producers renegotiating the session (e.g. SFUs adding or
+            // removing tracks) keep the m-lines of the streams already
+            // exposed, unless they were recycled for another stream
+            let existing = self
+                .with_session(&webrtcbin, |session| {
+                    session
+                        .pads
+                        .iter()
+                        .find(|pad| pad.imp().stream_id() == stream_id)
+                        .cloned()
                 })
-                .collect::<gst::Caps>();
+                .flatten();
+            let has_transceiver = existing.is_some();
+            if let Some(pad) = existing {
+                if !inactive && pad.imp().msid() == msid {
+                    continue;
+                }
+
+                self.remove_src_pad(&webrtcbin, &pad);
+            }
+
+            if inactive {
+                continue;
+            }
+
+            let caps = self.media_caps(media);
 
             if !caps.is_empty() {
-                let stream_id = self.get_stream_id(None, Some(i as u32)).unwrap();
-                if self.create_and_probe_src_pad(&caps, &stream_id) {
+                if let Some(pad) = self
+                    .reuse_src_pad(session_id, &caps, &stream_id, msid.as_deref())
+                    .or_else(|| {
+                        self.create_and_probe_src_pad(
+                            session_id,
+                            &caps,
+                            &stream_id,
+                            msid.as_deref(),
+                        )
+                    })
+                {
+                    self.with_session(&webrtcbin, |session| session.pads.push(pad));
+
+                    if has_transceiver {
+                        continue;
+                    }
+
                     gst::info!(
                         CAT,
                         imp: self,
@@ -863,19 +2102,33 @@ impl BaseWebRTCSrc {
 
         webrtcbin.emit_by_name::<()>("set-remote-description", &[&offer, &None::<gst::Promise>]);
 
-        let obj = self.obj();
-        obj.no_more_pads();
+        let declarations = self.settings.lock().unwrap().data_channels.clone();
+        for channel in data_channel::create_data_channels(webrtcbin.upcast_ref(), &declarations) {
+            self.setup_data_channel(session_id, &webrtcbin, channel);
+        }
+
+        // More producers may join later on
+        if !self.settings.lock().unwrap().remove_ended_sessions {
+            self.obj().no_more_pads();
+        }
 
-        let promise =
-            gst::Promise::with_change_func(glib::clone!(@weak self as this => move |reply| {
-                    this.on_answer_created(reply);
+        let session_id = session_id.to_string();
+        let promise = gst::Promise::with_change_func(
+            glib::clone!(@weak self as this, @weak webrtcbin => move |reply| {
+                    this.on_answer_created(&session_id, &webrtcbin, reply);
                 }
-            ));
+            ),
+        );
 
         webrtcbin.emit_by_name::<()>("create-answer", &[&None::<gst::Structure>, &promise]);
     }
 
-    fn on_answer_created(&self, reply: Result<Option<&gst::StructureRef>, gst::PromiseError>) {
+    fn on_answer_created(
+        &self,
+        session_id: &str,
+        webrtcbin: &gst::Element,
+        reply: Result<Option<&gst::StructureRef>, gst::PromiseError>,
+    ) {
         let reply = match reply {
             Ok(Some(reply)) => {
                 if !reply.has_field_with_type(
@@ -925,57 +2178,281 @@ impl BaseWebRTCSrc {
             .get::<gst_webrtc::WebRTCSessionDescription>()
             .expect("Invalid argument");
 
-        self.webrtcbin()
-            .emit_by_name::<()>("set-local-description", &[&answer, &None::<gst::Promise>]);
+        webrtcbin.emit_by_name::<()>("set-local-description", &[&answer, &None::<gst::Promise>]);
+
+        gst::log!(CAT, imp: self, "Sending SDP, {}", answer.sdp().to_string());
+        let signaller = self.signaller();
+        signaller.send_sdp(session_id, &answer);
+    }
+
+    // Makes an offer to receive the streams of the codecs we can handle, for
+    // signallers with which the consumer starts the negotiation
+    fn create_offer(&self, session_id: &str) {
+        gst::info!(CAT, imp: self, "Creating offer for {session_id}");
+
+        let webrtcbin = match self.session_webrtcbin(session_id) {
+            Ok(webrtcbin) => webrtcbin,
+            Err(err) => {
+                gst::element_error!(
+                    self.obj(),
+                    gst::StreamError::Failed,
+                    ["Failed to start session {}: {}", session_id, err]
+                );
+                return;
+            }
+        };
+
+        let (codecs, do_retransmission, do_twcc, do_remb) = {
+            let settings = self.settings.lock().unwrap();
+            (
+                [settings.video_codecs.clone(), settings.audio_codecs.clone()],
+                settings.do_retransmission,
+                settings.do_twcc,
+                settings.do_remb,
+            )
+        };
+
+        // Payload types must be unique across the bundled medias
+        let mut payloads = 96..128;
+        for codecs in codecs {
+            let caps = codecs
+                .iter()
+                .zip(&mut payloads)
+                .map(|(codec, pt)| offer_caps(codec, pt, do_twcc, do_remb))
+                .collect::<gst::Caps>();
+            if caps.is_empty() {
+                continue;
+            }
+
+            gst::info!(CAT, imp: self, "Adding transceiver with caps: {caps:#?}");
+            let transceiver = webrtcbin.emit_by_name::<gst_webrtc::WebRTCRTPTransceiver>(
+                "add-transceiver",
+                &[&gst_webrtc::WebRTCRTPTransceiverDirection::Recvonly, &caps],
+            );
+
+            transceiver.set_property("do-nack", do_retransmission);
+            transceiver.set_property("fec-type", gst_webrtc::WebRTCFECType::UlpRed);
+        }
+
+        let declarations = self.settings.lock().unwrap().data_channels.clone();
+        for channel in data_channel::create_data_channels(webrtcbin.upcast_ref(), &declarations) {
+            self.setup_data_channel(session_id, &webrtcbin, channel);
+        }
+
+        let session_id = session_id.to_string();
+        let promise = gst::Promise::with_change_func(
+            glib::clone!(@weak self as this, @weak webrtcbin => move |reply| {
+                    this.on_offer_created(&session_id, &webrtcbin, reply);
+                }
+            ),
+        );
+
+        webrtcbin.emit_by_name::<()>("create-offer", &[&None::<gst::Structure>, &promise]);
+    }
 
-        let session_id = {
-            let state = self.state.lock().unwrap();
-            match &state.session_id {
-                Some(id) => id.to_owned(),
-                _ => {
+    fn on_offer_created(
+        &self,
+        session_id: &str,
+        webrtcbin: &gst::Element,
+        reply: Result<Option<&gst::StructureRef>, gst::PromiseError>,
+    ) {
+        let offer = match reply {
+            Ok(Some(reply)) => match reply.value("offer").map(|offer| {
+                offer
+                    .get::<gst_webrtc::WebRTCSessionDescription>()
+                    .expect("Invalid argument")
+            }) {
+                Ok(offer) => offer,
+                Err(_) => {
                     gst::element_error!(
                         self.obj(),
-                        gst::StreamError::Failed,
-                        ["Signalling error, no session started while requesting to send an SDP offer"]
+                        gst::LibraryError::Failed,
+                        ["create-offer::Promise returned with error: {:?}", reply]
                     );
-
                     return;
                 }
+            },
+            Ok(None) => {
+                gst::element_error!(
+                    self.obj(),
+                    gst::StreamError::Failed,
+                    ["create-offer::Promise returned with no reply"]
+                );
+
+                return;
+            }
+            Err(err) => {
+                gst::element_error!(
+                    self.obj(),
+                    gst::LibraryError::Failed,
+                    ["create-offer::Promise returned with error {:?}", err]
+                );
+
+                return;
             }
         };
 
-        gst::log!(CAT, imp: self, "Sending SDP, {}", answer.sdp().to_string());
-        let signaller = self.signaller();
-        signaller.send_sdp(&session_id, &answer);
+        webrtcbin.emit_by_name::<()>("set-local-description", &[&offer, &None::<gst::Promise>]);
+
+        gst::log!(CAT, imp: self, "Sending SDP, {}", offer.sdp().to_string());
+        self.signaller().send_sdp(session_id, &offer);
+    }
+
+    // Exposes a pad for each media accepted by the answer to our offer
+    fn handle_answer(&self, session_id: &str, answer: &gst_webrtc::WebRTCSessionDescription) {
+        gst::log!(CAT, imp: self, "Got answer {}", answer.sdp().to_string());
+
+        let Some(webrtcbin) = self
+            .state
+            .lock()
+            .unwrap()
+            .sessions
+            .get(session_id)
+            .map(|session| session.webrtcbin.clone())
+        else {
+            gst::warning!(CAT, imp: self, "No session {session_id}, dropping answer");
+            return;
+        };
+
+        for (i, media) in answer.sdp().medias().enumerate() {
+            // A zero port means the media was rejected
+            if media.port() == 0 {
+                continue;
+            }
+
+            let caps = self.media_caps(media);
+            if caps.is_empty() {
+                gst::info!(
+                    CAT,
+                    "Not using media: {media:#?} as it doesn't match our codec restrictions"
+                );
+                continue;
+            }
+
+            let stream_id = self
+                .get_stream_id(session_id, None, Some(i as u32))
+                .unwrap();
+            let msid = media_msid(media);
+            if let Some(pad) = self
+                .reuse_src_pad(session_id, &caps, &stream_id, msid.as_deref())
+                .or_else(|| {
+                    self.create_and_probe_src_pad(session_id, &caps, &stream_id, msid.as_deref())
+                })
+            {
+                self.with_session(&webrtcbin, |session| session.pads.push(pad));
+            }
+        }
+
+        webrtcbin.emit_by_name::<()>("set-remote-description", &[&answer, &None::<gst::Promise>]);
+
+        if !self.settings.lock().unwrap().remove_ended_sessions {
+            self.obj().no_more_pads();
+        }
     }
 
-    fn on_data_channel(&self, data_channel: glib::Object) {
+    fn on_data_channel(
+        &self,
+        session_id: &str,
+        webrtcbin: &gst::Element,
+        data_channel: glib::Object,
+    ) {
         gst::info!(CAT, imp: self, "Received data channel {data_channel:?}");
-        let mut state = self.state.lock().unwrap();
-        state.data_channel = data_channel.dynamic_cast::<WebRTCDataChannel>().ok();
+        let Ok(data_channel) = data_channel.dynamic_cast::<WebRTCDataChannel>() else {
+            return;
+        };
+
+        if data_channel::label(&data_channel) == NAVIGATION_CHANNEL_LABEL {
+            data_channel.connect_closure(
+                "on-message-string",
+                false,
+                glib::closure!(@weak-allow-none self as this => move |
+                        _channel: WebRTCDataChannel,
+                        msg: Option<String>| {
+                    if let (Some(this), Some(msg)) = (this, msg) {
+                        this.on_navigation_reply(&msg);
+                    }
+                }),
+            );
+
+            self.with_session(webrtcbin, |session| {
+                session.data_channel = Some(data_channel)
+            });
+        } else {
+            self.setup_data_channel(session_id, webrtcbin, data_channel);
+        }
     }
 
-    fn on_ice_candidate(&self, sdp_m_line_index: u32, candidate: String) {
-        let signaller = self.signaller();
-        let session_id = match self.state.lock().unwrap().session_id.as_ref() {
-            Some(id) => id.to_string(),
-            _ => {
-                gst::element_error!(
-                        self.obj(),
-                        gst::StreamError::Failed,
-                        ["Signalling error, no session started while requesting to propose ice candidates"]
-                    );
+    fn setup_data_channel(
+        &self,
+        session_id: &str,
+        webrtcbin: &gst::Element,
+        channel: WebRTCDataChannel,
+    ) {
+        let obj_weak = self.obj().downgrade();
+        let session_id = session_id.to_string();
+        data_channel::connect_messages(&channel, move |label, message| {
+            if let Some(obj) = obj_weak.upgrade() {
+                obj.imp()
+                    .on_data_channel_message(&session_id, label, message);
+            }
+        });
 
-                return;
+        self.with_session(webrtcbin, |session| {
+            session
+                .data_channels
+                .insert(data_channel::label(&channel), channel)
+        });
+    }
+
+    fn on_data_channel_message(&self, session_id: &str, label: &str, message: DataChannelMessage) {
+        gst::log!(CAT, imp: self, "Received message on data channel {label}");
+
+        let data_src = self.state.lock().unwrap().data_src.clone();
+        if let Some(data_src) = data_src {
+            if let Err(err) = data_src.push_buffer(message.to_buffer(Some(session_id), label)) {
+                gst::debug!(CAT, imp: self, "Failed to push data message: {err}");
             }
-        };
-        signaller.add_ice(&session_id, &candidate, sdp_m_line_index, None::<String>);
+        }
+
+        match message {
+            DataChannelMessage::String(message) => self
+                .obj()
+                .emit_by_name::<()>("data-channel-message-string", &[&label, &message]),
+            DataChannelMessage::Data(data) => self
+                .obj()
+                .emit_by_name::<()>("data-channel-message-data", &[&label, &data]),
+        }
+    }
+
+    // Sends @message on the channels labelled @label of all sessions
+    fn send_data_channel_message(&self, label: &str, message: DataChannelMessage) -> bool {
+        let channels = self
+            .state
+            .lock()
+            .unwrap()
+            .sessions
+            .values()
+            .filter_map(|session| session.data_channels.get(label).cloned())
+            .collect::<Vec<_>>();
+        if channels.is_empty() {
+            gst::warning!(CAT, imp: self, "No data channel labelled {label}");
+            return false;
+        }
+
+        channels
+            .iter()
+            .fold(false, |sent, channel| message.send(channel) || sent)
+    }
+
+    fn on_ice_candidate(&self, session_id: &str, sdp_m_line_index: u32, candidate: String) {
+        let signaller = self.signaller();
+        signaller.add_ice(session_id, &candidate, sdp_m_line_index, None::<String>);
     }
 
     /// Called by the signaller with an ice candidate
     fn handle_ice(
         &self,
-        peer_id: &str,
+        session_id: &str,
         sdp_m_line_index: Option<u32>,
         _sdp_mid: Option<String>,
         candidate: &str,
@@ -987,10 +2464,21 @@ impl BaseWebRTCSrc {
                 return;
             }
         };
-        gst::log!(CAT, imp: self, "Got ice from {peer_id}: {candidate}");
+        gst::log!(CAT, imp: self, "Got ice for {session_id}: {candidate}");
+
+        let Some(webrtcbin) = self
+            .state
+            .lock()
+            .unwrap()
+            .sessions
+            .get(session_id)
+            .map(|session| session.webrtcbin.clone())
+        else {
+            gst::warning!(CAT, imp: self, "No session {session_id}, dropping candidate");
+            return;
+        };
 
-        self.webrtcbin()
-            .emit_by_name::<()>("add-ice-candidate", &[&sdp_m_line_index, &candidate]);
+        webrtcbin.emit_by_name::<()>("add-ice-candidate", &[&sdp_m_line_index, &candidate]);
     }
 
     fn maybe_start_signaller(&self) {
@@ -999,7 +2487,14 @@ impl BaseWebRTCSrc {
         if state.signaller_state == SignallerState::Stopped
             && obj.current_state() >= gst::State::Paused
         {
-            self.signaller().start();
+            let signaller = self.signaller();
+            if self.settings.lock().unwrap().reconnect
+                && signaller.has_property("wait-for-producer", Some(bool::static_type()))
+            {
+                signaller.set_property("wait-for-producer", true);
+            }
+
+            signaller.start();
 
             gst::info!(CAT, imp: self, "Started signaller");
             state.signaller_state = SignallerState::Started;
@@ -1064,28 +2559,78 @@ impl ElementImpl for BaseWebRTCSrc {
                     WebRTCSrcPad::static_type(),
                 )
                 .unwrap(),
+                gst::PadTemplate::new(
+                    "data",
+                    gst::PadDirection::Src,
+                    gst::PadPresence::Request,
+                    &DATA_CAPS,
+                )
+                .unwrap(),
             ]
         });
 
         PAD_TEMPLATES.as_ref()
     }
 
+    fn request_new_pad(
+        &self,
+        templ: &gst::PadTemplate,
+        _name: Option<&str>,
+        _caps: Option<&gst::Caps>,
+    ) -> Option<gst::Pad> {
+        let obj = self.obj();
+        let data_src = {
+            let mut state = self.state.lock().unwrap();
+            if state.data_src.is_some() {
+                gst::error!(CAT, imp: self, "The data pad was already requested");
+                return None;
+            }
+
+            let data_src = data_channel::make_data_src();
+            state.data_src = Some(data_src.clone());
+            data_src
+        };
+
+        // Not holding the state as pad-added handlers may call us back
+        let pad = gst::GhostPad::builder_from_template(templ)
+            .name("data")
+            .with_target(&data_src.static_pad("src").unwrap())
+            .unwrap()
+            .build();
+        if let Err(err) = obj
+            .add(&data_src)
+            .and_then(|_| pad.set_active(true))
+            .and_then(|_| obj.add_pad(&pad))
+            .and_then(|_| data_src.sync_state_with_parent())
+        {
+            gst::error!(CAT, imp: self, "Failed to add the data pad: {err}");
+            self.release_pad(pad.upcast_ref());
+            return None;
+        }
+
+        Some(pad.upcast())
+    }
+
+    fn release_pad(&self, pad: &gst::Pad) {
+        let obj = self.obj();
+        let data_src = self.state.lock().unwrap().data_src.take();
+        if let Some(data_src) = data_src {
+            let _ = data_src.set_state(gst::State::Null);
+            if data_src.parent().is_some() {
+                let _ = obj.remove(&data_src);
+            }
+        }
+
+        if pad.parent().is_some() {
+            let _ = obj.remove_pad(pad);
+        }
+    }
+
     fn change_state(
         &self,
         transition: gst::StateChange,
     ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
         let obj = &*self.obj();
-        if let gst::StateChange::NullToReady = transition {
-            if let Err(err) = self.prepare() {
-                gst::element_error!(
-                    obj,
-                    gst::StreamError::Failed,
-                    ["Failed to prepare: {}", err]
-                );
-                return Err(gst::StateChangeError);
-            }
-        }
-
         let mut ret = self.parent_change_state(transition);
 
         match transition {
@@ -1116,10 +2661,16 @@ impl ElementImpl for BaseWebRTCSrc {
 
     fn send_event(&self, event: gst::Event) -> bool {
         match event.view() {
-            gst::EventView::Navigation(ev) => {
-                self.send_navigation_event(gst_video::NavigationEvent::parse(ev).unwrap());
-                true
-            }
+            gst::EventView::Navigation(ev) => match gst_video::NavigationEvent::parse(ev) {
+                Ok(event) => {
+                    self.send_navigation_event(event, None, None);
+                    true
+                }
+                Err(err) => {
+                    gst::warning!(CAT, imp: self, "Dropping invalid navigation event: {err}");
+                    false
+                }
+            },
             _ => true,
         }
     }
@@ -1127,7 +2678,44 @@ impl ElementImpl for BaseWebRTCSrc {
 
 impl GstObjectImpl for BaseWebRTCSrc {}
 
-impl BinImpl for BaseWebRTCSrc {}
+impl BinImpl for BaseWebRTCSrc {
+    fn handle_message(&self, msg: gst::Message) {
+        if let gst::MessageView::Error(err) = msg.view() {
+            let failed = msg.src().and_then(|src| {
+                let state = self.state.lock().unwrap();
+                state
+                    .decoder_chains
+                    .iter()
+                    .find(|chain| {
+                        src == chain.decoder.upcast_ref::<gst::Object>()
+                            || src.has_as_ancestor(&chain.decoder)
+                    })
+                    .filter(|chain| !chain.candidates.is_empty())
+                    .map(|chain| chain.decoder.clone())
+            });
+
+            if let Some(failed) = failed {
+                gst::element_warning!(
+                    self.obj(),
+                    gst::StreamError::Decode,
+                    [
+                        "Decoder {} failed, falling back to the next one",
+                        failed.name()
+                    ],
+                    ["{}", err.error()]
+                );
+
+                self.obj().call_async(move |obj| {
+                    obj.imp().switch_to_next_decoder(&failed);
+                });
+
+                return;
+            }
+        }
+
+        self.parent_handle_message(msg)
+    }
+}
 
 impl ChildProxyImpl for BaseWebRTCSrc {
     fn child_by_index(&self, index: u32) -> Option<glib::Object> {
@@ -1153,6 +2741,47 @@ impl ChildProxyImpl for BaseWebRTCSrc {
     }
 }
 
+// The RTP caps we offer to receive @codec with, using payload type @pt
+fn offer_caps(codec: &Codec, pt: u8, do_twcc: bool, do_remb: bool) -> gst::Structure {
+    let (media, clock_rate) = if codec.is_video() {
+        ("video", 90000)
+    } else {
+        ("audio", 48000)
+    };
+
+    let mut s = gst::Structure::builder("application/x-rtp")
+        .field("media", media)
+        .field("encoding-name", codec.name.as_str())
+        .field("payload", pt as i32)
+        .field("clock-rate", clock_rate)
+        .build();
+
+    match codec.name.as_str() {
+        "OPUS" => s.set("encoding-params", "2"),
+        "H264" => s.set("packetization-mode", "1"),
+        _ => (),
+    }
+
+    if do_twcc {
+        s.set("extmap-1", RTP_TWCC_URI);
+        s.set("rtcp-fb-transport-cc", true);
+    }
+
+    if do_remb {
+        s.set("rtcp-fb-goog-remb", true);
+    }
+
+    s
+}
+
+// The stream identifier of the `a=msid` attribute of @media
+fn media_msid(media: &gst_sdp::SDPMediaRef) -> Option<String> {
+    media
+        .attribute_val("msid")
+        .and_then(|msid| msid.split_whitespace().next())
+        .map(String::from)
+}
+
 #[derive(PartialEq)]
 enum SignallerState {
     Started,
@@ -1160,23 +2789,58 @@ enum SignallerState {
 }
 
 struct State {
-    session_id: Option<String>,
     signaller_state: SignallerState,
-    webrtcbin: Option<gst::Element>,
+    sessions: HashMap<String, Session>,
     flow_combiner: gst_base::UniqueFlowCombiner,
     signaller_signals: Option<SignallerSignals>,
+    navigation_seqnum: u64,
+    data_src: Option<gst_app::AppSrc>,
+    decoder_chains: Vec<DecoderChain>,
+    disconnected_pads: Vec<DisconnectedPad>,
+    gap_task: Option<tokio::task::JoinHandle<()>>,
+}
+
+// A session with a producer, negotiated by its own `webrtcbin`
+struct Session {
+    webrtcbin: gst::Element,
+    // The bin wrapping `webrtcbin` and the elements plugged downstream of it
+    elements: Vec<gst::Element>,
+    pads: Vec<WebRTCSrcPad>,
     data_channel: Option<WebRTCDataChannel>,
+    data_channels: HashMap<String, WebRTCDataChannel>,
+    remb: Option<RembEstimator>,
+    remb_sigid: Option<glib::SignalHandlerId>,
+}
+
+// A decoder plugged according to `decoder-preferences`, along with the
+// remaining decoders to fall back to
+struct DecoderChain {
+    webrtcbin: gst::Element,
+    srcpad: WebRTCSrcPad,
+    upstream: gst::Pad,
+    decoder: gst::Element,
+    candidates: VecDeque<gst::ElementFactory>,
+}
+
+// A source pad waiting for the producer to come back, fed by a placeholder
+// source or GAP events in the meantime
+struct DisconnectedPad {
+    pad: WebRTCSrcPad,
+    placeholder: Option<gst::Element>,
 }
 
 impl Default for State {
     fn default() -> Self {
         Self {
             signaller_state: SignallerState::Stopped,
-            session_id: None,
-            webrtcbin: None,
+            sessions: HashMap::new(),
             flow_combiner: Default::default(),
             signaller_signals: Default::default(),
-            data_channel: None,
+            navigation_seqnum: 0,
+            data_src: None,
+            decoder_chains: Vec::new(),
+            disconnected_pads: Vec::new(),
+            gap_task: None,
         }
     }
 }
@@ -1266,7 +2930,9 @@ impl ObjectImpl for WhipServerSrc {
 
         let _ = ws.set_signaller(WhipServerSignaller::default().upcast());
 
-        let settings = ws.settings.lock().unwrap();
+        let mut settings = ws.settings.lock().unwrap();
+        // Each publisher gets its own session and pads
+        settings.remove_ended_sessions = true;
         element
             .bind_property("stun-server", &settings.signaller, "stun-server")
             .build();
@@ -1308,12 +2974,50 @@ impl ObjectSubclass for WhipServerSrc {
 pub struct LiveKitWebRTCSrc;
 
 impl ObjectImpl for LiveKitWebRTCSrc {
+    fn signals() -> &'static [glib::subclass::Signal] {
+        static SIGNALS: Lazy<Vec<glib::subclass::Signal>> = Lazy::new(|| {
+            vec![
+                /**
+                 * GstLiveKitWebRTCSrc::send-data:
+                 * @payload: The data to send
+                 * @reliable: Whether to send it on the reliable or the lossy channel
+                 * @topic: (nullable): The topic of the data
+                 * @destination-identities: The identities of the participants
+                 *   to send the data to, an empty list sending it to all of them
+                 *
+                 * Sends user data to the participants of the room, see the
+                 * `send-data` signal of the signaller.
+                 *
+                 * Returns: whether the data was sent
+                 */
+                livekit_signaller::send_data_signal(),
+            ]
+        });
+
+        SIGNALS.as_ref()
+    }
+
     fn constructed(&self) {
         self.parent_constructed();
         let element = self.obj();
         let ws = element.upcast_ref::<super::BaseWebRTCSrc>().imp();
 
-        let _ = ws.set_signaller(LiveKitSignaller::new_consumer().upcast());
+        let signaller = LiveKitSignaller::new_consumer();
+        signaller.forward_messages(element.upcast_ref());
+
+        // Describe the participant and track of each pad
+        element.connect_pad_added(glib::clone!(@weak signaller => move |_, pad| {
+            let Some(pad) = pad.downcast_ref::<WebRTCSrcPad>() else {
+                return;
+            };
+
+            if let Some(info) = pad.imp().msid().and_then(|msid| signaller.track_info(&msid)) {
+                gst::debug!(CAT, obj: pad, "Stream info: {info}");
+                pad.imp().set_stream_info(Some(info));
+            }
+        }));
+
+        let _ = ws.set_signaller(signaller.upcast());
     }
 }
 
@@ -1344,3 +3048,249 @@ impl ObjectSubclass for LiveKitWebRTCSrc {
     type Type = super::LiveKitWebRTCSrc;
     type ParentType = super::BaseWebRTCSrc;
 }
+
+#[derive(Default)]
+pub struct WhepClientSrc;
+
+impl ObjectImpl for WhepClientSrc {
+    fn constructed(&self) {
+        self.parent_constructed();
+        let element = self.obj();
+        let ws = element.upcast_ref::<super::BaseWebRTCSrc>().imp();
+
+        let _ = ws.set_signaller(WhepClientSignaller::default().upcast());
+    }
+}
+
+impl GstObjectImpl for WhepClientSrc {}
+
+impl BinImpl for WhepClientSrc {}
+
+impl ElementImpl for WhepClientSrc {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "WhepClientSrc",
+                "Source/Network/WebRTC",
+                "WebRTC source element using WHEP Client as the signaller",
+                "The GStreamer Rust plugins developers",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+}
+
+impl BaseWebRTCSrcImpl for WhepClientSrc {}
+
+#[glib::object_subclass]
+impl ObjectSubclass for WhepClientSrc {
+    const NAME: &'static str = "GstWhepClientSrc";
+    type Type = super::WhepClientSrc;
+    type ParentType = super::BaseWebRTCSrc;
+}
+
+#[derive(Default)]
+pub struct JanusVRWebRTCSrc;
+
+impl ObjectImpl for JanusVRWebRTCSrc {
+    fn constructed(&self) {
+        self.parent_constructed();
+        let element = self.obj();
+        let ws = element.upcast_ref::<super::BaseWebRTCSrc>().imp();
+
+        let _ = ws.set_signaller(JanusVRSignaller::new_consumer().upcast());
+    }
+}
+
+impl GstObjectImpl for JanusVRWebRTCSrc {}
+
+impl BinImpl for JanusVRWebRTCSrc {}
+
+impl ElementImpl for JanusVRWebRTCSrc {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "JanusVRWebRTCSrc",
+                "Source/Network/WebRTC",
+                "WebRTC source with Janus Video Room signaller",
+                "The GStreamer Rust plugins developers",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+}
+
+impl BaseWebRTCSrcImpl for JanusVRWebRTCSrc {}
+
+#[glib::object_subclass]
+impl ObjectSubclass for JanusVRWebRTCSrc {
+    const NAME: &'static str = "GstJanusVRWebRTCSrc";
+    type Type = super::JanusVRWebRTCSrc;
+    type ParentType = super::BaseWebRTCSrc;
+}
+
+#[derive(Default)]
+pub struct ManualWebRTCSrc;
+
+impl ObjectImpl for ManualWebRTCSrc {
+    fn constructed(&self) {
+        self.parent_constructed();
+        let element = self.obj();
+        let ws = element.upcast_ref::<super::BaseWebRTCSrc>().imp();
+
+        let _ = ws.set_signaller(ManualSignaller::new_consumer().upcast());
+    }
+}
+
+impl GstObjectImpl for ManualWebRTCSrc {}
+
+impl BinImpl for ManualWebRTCSrc {}
+
+impl ElementImpl for ManualWebRTCSrc {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "ManualWebRTCSrc",
+                "Source/Network/WebRTC",
+                "WebRTC source exchanging its signalling messages through files",
+                "The GStreamer Rust plugins developers",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+}
+
+impl BaseWebRTCSrcImpl for ManualWebRTCSrc {}
+
+#[glib::object_subclass]
+impl ObjectSubclass for ManualWebRTCSrc {
+    const NAME: &'static str = "GstManualWebRTCSrc";
+    type Type = super::ManualWebRTCSrc;
+    type ParentType = super::BaseWebRTCSrc;
+}
+
+#[derive(Default)]
+pub struct MqttWebRTCSrc;
+
+impl ObjectImpl for MqttWebRTCSrc {
+    fn constructed(&self) {
+        self.parent_constructed();
+        let element = self.obj();
+        let ws = element.upcast_ref::<super::BaseWebRTCSrc>().imp();
+
+        let _ = ws.set_signaller(MqttSignaller::new_consumer().upcast());
+    }
+}
+
+impl GstObjectImpl for MqttWebRTCSrc {}
+
+impl BinImpl for MqttWebRTCSrc {}
+
+impl ElementImpl for MqttWebRTCSrc {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "MqttWebRTCSrc",
+                "Source/Network/WebRTC",
+                "WebRTC source with MQTT signaller",
+                "The GStreamer Rust plugins developers",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+}
+
+impl BaseWebRTCSrcImpl for MqttWebRTCSrc {}
+
+#[glib::object_subclass]
+impl ObjectSubclass for MqttWebRTCSrc {
+    const NAME: &'static str = "GstMqttWebRTCSrc";
+    type Type = super::MqttWebRTCSrc;
+    type ParentType = super::BaseWebRTCSrc;
+}
+
+#[derive(Default)]
+pub struct SipWebRTCSrc;
+
+impl ObjectImpl for SipWebRTCSrc {
+    fn constructed(&self) {
+        self.parent_constructed();
+        let element = self.obj();
+        let ws = element.upcast_ref::<super::BaseWebRTCSrc>().imp();
+
+        let _ = ws.set_signaller(SipSignaller::new_consumer().upcast());
+    }
+}
+
+impl GstObjectImpl for SipWebRTCSrc {}
+
+impl BinImpl for SipWebRTCSrc {}
+
+impl ElementImpl for SipWebRTCSrc {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "SipWebRTCSrc",
+                "Source/Network/WebRTC",
+                "WebRTC source with SIP signaller",
+                "The GStreamer Rust plugins developers",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+}
+
+impl BaseWebRTCSrcImpl for SipWebRTCSrc {}
+
+#[glib::object_subclass]
+impl ObjectSubclass for SipWebRTCSrc {
+    const NAME: &'static str = "GstSipWebRTCSrc";
+    type Type = super::SipWebRTCSrc;
+    type ParentType = super::BaseWebRTCSrc;
+}
+
+#[derive(Default)]
+pub struct AwsKvsWebRTCSrc;
+
+impl ObjectImpl for AwsKvsWebRTCSrc {
+    fn constructed(&self) {
+        self.parent_constructed();
+        let element = self.obj();
+        let ws = element.upcast_ref::<super::BaseWebRTCSrc>().imp();
+
+        let _ = ws.set_signaller(AwsKvsSignaller::new_consumer().upcast());
+    }
+}
+
+impl GstObjectImpl for AwsKvsWebRTCSrc {}
+
+impl BinImpl for AwsKvsWebRTCSrc {}
+
+impl ElementImpl for AwsKvsWebRTCSrc {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "AwsKvsWebRTCSrc",
+                "Source/Network/WebRTC",
+                "WebRTC source with AWS Kinesis Video Streams signaller",
+                "The GStreamer Rust plugins developers",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+}
+
+impl BaseWebRTCSrcImpl for AwsKvsWebRTCSrc {}
+
+#[glib::object_subclass]
+impl ObjectSubclass for AwsKvsWebRTCSrc {
+    const NAME: &'static str = "GstAwsKvsWebRTCSrc";
+    type Type = super::AwsKvsWebRTCSrc;
+    type ParentType = super::BaseWebRTCSrc;
+}
@@ -31,11 +31,60 @@
  * in `decodebinX` but for the case where a `videoconvert` is placed after a `video_XX` pad,
  * decoding will happen inside `webrtcsrc`.
  *
+ * By default `decodebin3` picks the decoder, the `decoder-preferences` property
+ * can be used to pick between hardware and software decoders instead, for
+ * example:
+ *
+ * ``` bash
+ * gst-launch-1.0 webrtcsrc decoder-preferences="decoders,H264=<nvh264dec,avdec_h264>,VP8=<vavp8dec,*>" ! videoconvert ! autovideosink
+ * ```
+ *
+ * Decoders are tried in order, `*` standing for all the other decoders of the
+ * codec by decreasing rank. If a decoder fails to start or posts an error,
+ * `webrtcsrc` posts a warning and falls back to the next one, an error is only
+ * posted once no decoder is left.
+ *
+ * ## Congestion control feedback
+ *
+ * `webrtcsrc` answers offers advertising the transport-wide congestion control
+ * RTP header extension and RTCP feedback (`transport-cc`) so that the remote
+ * sender receives TWCC feedback for every received stream, this can be disabled
+ * with the `do-twcc` property.
+ *
+ * Senders that rely on receiver-side estimation (`goog-remb`) can additionally
+ * be served by setting `do-remb=true`, in which case `webrtcsrc` computes a
+ * simple estimate based on the incoming bitrate and packet loss and sends it
+ * in REMB RTCP messages.
+ *
+ * ## Reconnecting
+ *
+ * By default `webrtcsrc` sends EOS on all its pads once the session ends. With
+ * `reconnect=true` the source pads are kept instead and GAP events are pushed on
+ * them until a new session is started, so that long running pipelines (e.g.
+ * recorders) survive the producer going away. The default signaller then waits
+ * for the producer to be registered again (see its `wait-for-producer`
+ * property) before asking for a new session with it. Streams of the new
+ * session are sent on the existing pads with the same media type, starting a
+ * new segment.
+ *
+ * When the pads output raw streams, placeholder sources can be used instead of
+ * GAP events, for example:
+ *
+ * ``` bash
+ * gst-launch-1.0 webrtcsrc reconnect=true video-placeholder="videotestsrc is-live=true pattern=black" \
+ *     audio-placeholder="audiotestsrc is-live=true wave=silence" signaller::producer-peer-id=<webrtcsink-peer-id> \
+ *     name=src src.video_0 ! videoconvert ! autovideosink src.audio_0 ! audioconvert ! autoaudiosink
+ * ```
+ *
  * Since: 0.10
  */
 mod imp;
 mod pad;
+mod remb;
+#[cfg(test)]
+mod tests;
 
+use crate::janusvr_signaller::JanusVRPlugin;
 use crate::signaller::Signallable;
 use crate::signaller::WebRTCSignallerRole;
 use gst::prelude::*;
@@ -57,6 +106,30 @@ glib::wrapper! {
     pub struct LiveKitWebRTCSrc(ObjectSubclass<imp::LiveKitWebRTCSrc>) @extends BaseWebRTCSrc, gst::Bin, gst::Element, gst::Object, gst::ChildProxy;
 }
 
+glib::wrapper! {
+    pub struct WhepClientSrc(ObjectSubclass<imp::WhepClientSrc>) @extends BaseWebRTCSrc, gst::Bin, gst::Element, gst::Object, gst::ChildProxy;
+}
+
+glib::wrapper! {
+    pub struct JanusVRWebRTCSrc(ObjectSubclass<imp::JanusVRWebRTCSrc>) @extends BaseWebRTCSrc, gst::Bin, gst::Element, gst::Object, gst::ChildProxy;
+}
+
+glib::wrapper! {
+    pub struct AwsKvsWebRTCSrc(ObjectSubclass<imp::AwsKvsWebRTCSrc>) @extends BaseWebRTCSrc, gst::Bin, gst::Element, gst::Object, gst::ChildProxy;
+}
+
+glib::wrapper! {
+    pub struct ManualWebRTCSrc(ObjectSubclass<imp::ManualWebRTCSrc>) @extends BaseWebRTCSrc, gst::Bin, gst::Element, gst::Object, gst::ChildProxy;
+}
+
+glib::wrapper! {
+    pub struct MqttWebRTCSrc(ObjectSubclass<imp::MqttWebRTCSrc>) @extends BaseWebRTCSrc, gst::Bin, gst::Element, gst::Object, gst::ChildProxy;
+}
+
+glib::wrapper! {
+    pub struct SipWebRTCSrc(ObjectSubclass<imp::SipWebRTCSrc>) @extends BaseWebRTCSrc, gst::Bin, gst::Element, gst::Object, gst::ChildProxy;
+}
+
 glib::wrapper! {
     pub struct WebRTCSrcPad(ObjectSubclass<pad::WebRTCSrcPad>) @extends gst::GhostPad, gst::ProxyPad, gst::Pad, gst::Object;
 }
@@ -64,6 +137,7 @@ glib::wrapper! {
 pub fn register(plugin: Option<&gst::Plugin>) -> Result<(), glib::BoolError> {
     BaseWebRTCSrc::static_type().mark_as_plugin_api(gst::PluginAPIFlags::empty());
     WebRTCSignallerRole::static_type().mark_as_plugin_api(gst::PluginAPIFlags::empty());
+    JanusVRPlugin::static_type().mark_as_plugin_api(gst::PluginAPIFlags::empty());
     WebRTCSrcPad::static_type().mark_as_plugin_api(gst::PluginAPIFlags::empty());
     Signallable::static_type().mark_as_plugin_api(gst::PluginAPIFlags::empty());
     gst::Element::register(
@@ -73,6 +147,24 @@ pub fn register(plugin: Option<&gst::Plugin>) -> Result<(), glib::BoolError> {
         WebRTCSrc::static_type(),
     )?;
 
+    /**
+     * element-whipserversrc:
+     *
+     * The `whipserversrc` element is a WHIP server receiving the streams of
+     * any number of concurrent publishers.
+     *
+     * Each publisher gets its own WHIP resource and its own set of source
+     * pads, the `session-id` property of the pads being the id of the
+     * resource. When a publisher deletes its resource, EOS is sent on its pads
+     * and they are removed, the other publishers are not affected.
+     *
+     * By default any publisher is accepted, from any origin. Publishers can
+     * be authenticated with bearer tokens, either listed in
+     * `signaller::auth-tokens` or checked by the application from the
+     * `validate-token` signal of the signaller, and the origins of web
+     * publishers restricted with `signaller::cors-allowed-origins`. The server is served over
+     * HTTPS when `signaller::tls-certificate` and `signaller::tls-key` are set.
+     */
     gst::Element::register(
         plugin,
         "whipserversrc",
@@ -80,6 +172,30 @@ pub fn register(plugin: Option<&gst::Plugin>) -> Result<(), glib::BoolError> {
         WhipServerSrc::static_type(),
     )?;
 
+    /**
+     * element-whepclientsrc:
+     *
+     * The `whepclientsrc` element plays the streams of a WHEP server.
+     *
+     * By default the element POSTs its own offer to the `signaller::whep-endpoint`
+     * once ICE gathering is complete, receiving the streams of any of its
+     * `video-codecs` and `audio-codecs`. With `signaller::server-offer=true`, it
+     * POSTs an empty body instead and sends its answer to the offer of the
+     * server with a PATCH request on the WHEP resource. The resource is
+     * DELETEd when the element stops.
+     *
+     * ``` bash
+     * gst-launch-1.0 whepclientsrc signaller::whep-endpoint="http://127.0.0.1:8190/whep/endpoint" \
+     *     ! videoconvert ! autovideosink
+     * ```
+     */
+    gst::Element::register(
+        plugin,
+        "whepclientsrc",
+        gst::Rank::PRIMARY,
+        WhepClientSrc::static_type(),
+    )?;
+
     /**
      * element-livekitwebrtcsrc:
      *
@@ -93,6 +209,13 @@ pub fn register(plugin: Option<&gst::Plugin>) -> Result<(), glib::BoolError> {
      * `signaller::excluded-producer-peer-ids=<a,b,c>` to ignore peers `a`, `b`,
      * and `c` while subscribing to all other members of the room.
      *
+     * Each subscribed track gets its own source pad, added and removed as
+     * participants publish and unpublish tracks or join and leave the room.
+     * The `stream-info` property of the pads describes the participant and
+     * the track of the pad. The `set-track-subscription` and
+     * `set-track-quality` action signals of the signaller change the
+     * subscriptions and the simulcast layers received while playing.
+     *
      * ## Sample Pipeline
      *
      * First, start the livekit server with the `--dev` flag to enable the test credentials.
@@ -135,5 +258,133 @@ pub fn register(plugin: Option<&gst::Plugin>) -> Result<(), glib::BoolError> {
         LiveKitWebRTCSrc::static_type(),
     )?;
 
+    /**
+     * element-janusvrwebrtcsrc:
+     *
+     * The `janusvrwebrtcsrc` element plays the streams published in a room
+     * of the [Video Room plugin](https://janus.conf.meetecho.com/docs/videoroom)
+     * of the [Janus Gateway](https://github.com/meetecho/janus-gateway).
+     *
+     * The signaller joins the room to follow its publishers and subscribes to
+     * them with a second handle, the offers of Janus being answered by the
+     * element. By default all the publishers of the room are subscribed to,
+     * the streams of the publishers joining later on being added to the
+     * session. `signaller::producer-peer-id` restricts the subscription to
+     * the publisher with that feed ID or display name.
+     *
+     * ``` bash
+     * gst-launch-1.0 janusvrwebrtcsrc signaller::room-id=1234 ! videoconvert ! autovideosink
+     * ```
+     *
+     * With `signaller::plugin=streaming`, the mountpoint with the ID set in
+     * `signaller::room-id` of the [Streaming plugin](https://janus.conf.meetecho.com/docs/streaming)
+     * is watched instead:
+     *
+     * ``` bash
+     * gst-launch-1.0 janusvrwebrtcsrc signaller::plugin=streaming signaller::room-id=1 ! videoconvert ! autovideosink
+     * ```
+     *
+     * With `signaller::plugin=audiobridge`, the element joins the room of the
+     * [AudioBridge plugin](https://janus.conf.meetecho.com/docs/audiobridge)
+     * muted and plays the mix of its participants, Janus generating the offer.
+     */
+    gst::Element::register(
+        plugin,
+        "janusvrwebrtcsrc",
+        gst::Rank::NONE,
+        JanusVRWebRTCSrc::static_type(),
+    )?;
+
+    /**
+     * element-awskvswebrtcsrc:
+     *
+     * The `awskvswebrtcsrc` element connects as viewer to an [AWS Kinesis Video Streams]
+     * signalling channel and plays the streams of its master, e.g. `awskvswebrtcsink`.
+     * The element makes the offer, the client ID of the viewer can be set with
+     * `signaller::client-id`.
+     *
+     * ``` bash
+     * AWS_ACCESS_KEY_ID="XXX" AWS_SECRET_ACCESS_KEY="XXX" gst-launch-1.0 awskvswebrtcsrc signaller::channel-name="XXX" ! videoconvert ! autovideosink
+     * ```
+     *
+     * [AWS Kinesis Video Streams]: https://docs.aws.amazon.com/kinesisvideostreams-webrtc-dg/latest/devguide/what-is-kvswebrtc.html
+     */
+    gst::Element::register(
+        plugin,
+        "awskvswebrtcsrc",
+        gst::Rank::NONE,
+        AwsKvsWebRTCSrc::static_type(),
+    )?;
+    /**
+     * element-manualwebrtcsrc:
+     *
+     * The `manualwebrtcsrc` element exchanges its signalling messages as JSON
+     * lines in the format of the signalling server: the messages it receives are
+     * read from `signaller::input` and the ones it sends written to
+     * `signaller::output`, the standard input and output by default. Inputs can
+     * be files, named pipes or pasted by hand, blank lines and lines starting
+     * with `#` are skipped.
+     *
+     * A session starts with the offer of the producer:
+     *
+     * ``` json
+     * {"type":"peer","sessionId":"1","sdp":{"type":"offer","sdp":"v=0\r\n..."}}
+     * ```
+     *
+     * to which the element answers on its output. Two elements can be
+     * connected by having each one read the output of the other, e.g. through
+     * named pipes:
+     *
+     * ``` bash
+     * mkfifo offer answer
+     * gst-launch-1.0 videotestsrc ! manualwebrtcsink signaller::session-id=1 signaller::output=offer signaller::input=answer
+     * gst-launch-1.0 manualwebrtcsrc signaller::input=offer signaller::output=answer ! videoconvert ! autovideosink
+     * ```
+     */
+    gst::Element::register(
+        plugin,
+        "manualwebrtcsrc",
+        gst::Rank::NONE,
+        ManualWebRTCSrc::static_type(),
+    )?;
+    /**
+     * element-mqttwebrtcsrc:
+     *
+     * The `mqttwebrtcsrc` element plays the streams of a producer, such as
+     * `mqttwebrtcsink`, signalling through the MQTT broker at
+     * `signaller::broker-uri`. It starts a session with `signaller::producer-peer-id`,
+     * or the first producer available if unset, as soon as its retained
+     * presence is received.
+     *
+     * ``` bash
+     * gst-launch-1.0 mqttwebrtcsrc signaller::broker-uri=mqtts://broker:8883 signaller::producer-peer-id=camera1 ! videoconvert ! autovideosink
+     * ```
+     */
+    gst::Element::register(
+        plugin,
+        "mqttwebrtcsrc",
+        gst::Rank::NONE,
+        MqttWebRTCSrc::static_type(),
+    )?;
+    /**
+     * element-sipwebrtcsrc:
+     *
+     * The `sipwebrtcsrc` element plays the streams of a SIP call, calling
+     * `signaller::remote-uri`, or answering the calls to `signaller::user-uri`
+     * when unset, through the SIP proxy at `signaller::proxy-uri`. The SIP
+     * endpoint has to support DTLS-SRTP and ICE, or at least ICE-lite, as
+     * required by webrtcbin.
+     *
+     * ``` bash
+     * gst-launch-1.0 sipwebrtcsrc signaller::proxy-uri=sip:pbx.example.com signaller::user-uri=sip:monitor@example.com signaller::password=XXX ! videoconvert ! autovideosink
+     * ```
+     */
+    gst::Element::register(
+        plugin,
+        "sipwebrtcsrc",
+        gst::Rank::NONE,
+        SipWebRTCSrc::static_type(),
+    )?;
+
     Ok(())
 }
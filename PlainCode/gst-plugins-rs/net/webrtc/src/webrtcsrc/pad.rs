@@ -1,7 +1,9 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use gst::glib;
+use gst::prelude::*;
 use gst::subclass::prelude::*;
+use once_cell::sync::Lazy;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::Mutex;
@@ -10,6 +12,9 @@ use std::sync::Mutex;
 pub struct WebRTCSrcPad {
     needs_raw: AtomicBool,
     stream_id: Mutex<Option<String>>,
+    session_id: Mutex<Option<String>>,
+    msid: Mutex<Option<String>>,
+    stream_info: Mutex<Option<gst::Structure>>,
 }
 
 impl WebRTCSrcPad {
@@ -29,6 +34,23 @@ impl WebRTCSrcPad {
         let stream_id = self.stream_id.lock().unwrap();
         stream_id.as_ref().unwrap().clone()
     }
+
+    pub fn set_session_id(&self, session_id: &str) {
+        *self.session_id.lock().unwrap() = Some(session_id.to_string());
+    }
+
+    pub fn set_msid(&self, msid: Option<&str>) {
+        *self.msid.lock().unwrap() = msid.map(String::from);
+    }
+
+    pub fn msid(&self) -> Option<String> {
+        self.msid.lock().unwrap().clone()
+    }
+
+    pub fn set_stream_info(&self, stream_info: Option<gst::Structure>) {
+        *self.stream_info.lock().unwrap() = stream_info;
+        self.obj().notify("stream-info");
+    }
 }
 
 #[glib::object_subclass]
@@ -38,7 +60,40 @@ impl ObjectSubclass for WebRTCSrcPad {
     type ParentType = gst::GhostPad;
 }
 
-impl ObjectImpl for WebRTCSrcPad {}
+impl ObjectImpl for WebRTCSrcPad {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPS: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+            vec![
+                glib::ParamSpecString::builder("session-id")
+                    .nick("Session ID")
+                    .blurb("Identifier of the session with the producer of the stream")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecString::builder("msid")
+                    .nick("MSID")
+                    .blurb("Media stream identifier of the stream in the SDP of the producer")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecBoxed::builder::<gst::Structure>("stream-info")
+                    .nick("Stream Info")
+                    .blurb("Information about the stream provided by the signaller, if any")
+                    .read_only()
+                    .build(),
+            ]
+        });
+
+        PROPS.as_ref()
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        match pspec.name() {
+            "session-id" => self.session_id.lock().unwrap().to_value(),
+            "msid" => self.msid.lock().unwrap().to_value(),
+            "stream-info" => self.stream_info.lock().unwrap().to_value(),
+            _ => unimplemented!(),
+        }
+    }
+}
 impl GstObjectImpl for WebRTCSrcPad {}
 impl PadImpl for WebRTCSrcPad {}
 impl ProxyPadImpl for WebRTCSrcPad {}
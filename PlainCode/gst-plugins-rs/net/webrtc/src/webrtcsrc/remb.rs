@@ -0,0 +1,348 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use gst::glib;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
+    gst::DebugCategory::new(
+        "webrtcsrc-remb",
+        gst::DebugColorFlags::empty(),
+        Some("WebRTC src REMB estimator"),
+    )
+});
+
+/// Minimum bitrate we ever advertise to the sender
+const MIN_BITRATE: u32 = 100_000;
+/// Starting estimate, matches webrtcsink's default start bitrate
+const START_BITRATE: u32 = 2_048_000;
+/// Maximum bitrate we ever advertise to the sender
+const MAX_BITRATE: u32 = 50_000_000;
+/// Minimum duration of an observation window
+const UPDATE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Sequence number tracking for a remote SSRC
+#[derive(Debug)]
+struct SsrcStats {
+    /// Highest extended sequence number seen so far
+    max_seqnum: Option<u64>,
+    /// Extended sequence number at the start of the current window
+    base_seqnum: Option<u64>,
+    received: u64,
+}
+
+impl SsrcStats {
+    fn new() -> Self {
+        Self {
+            max_seqnum: None,
+            base_seqnum: None,
+            received: 0,
+        }
+    }
+
+    fn update(&mut self, seqnum: u16) {
+        let extended = match self.max_seqnum {
+            None => seqnum as u64,
+            Some(max) => {
+                // Pick the extended sequence number closest to the last one seen
+                let cycle = max & !0xffff;
+                [cycle.wrapping_sub(0x10000), cycle, cycle + 0x10000]
+                    .into_iter()
+                    .map(|cycle| cycle | seqnum as u64)
+                    .min_by_key(|ext| ext.abs_diff(max))
+                    .unwrap()
+            }
+        };
+
+        if self.base_seqnum.is_none() {
+            self.base_seqnum = Some(extended);
+        }
+
+        self.max_seqnum = Some(self.max_seqnum.map_or(extended, |max| max.max(extended)));
+        self.received += 1;
+    }
+
+    /// Returns (expected, received) packet counts for the window and resets it
+    fn reset(&mut self) -> (u64, u64) {
+        let expected = match (self.base_seqnum, self.max_seqnum) {
+            (Some(base), Some(max)) => max - base + 1,
+            _ => 0,
+        };
+        let received = self.received;
+
+        self.base_seqnum = self.max_seqnum.map(|max| max + 1);
+        self.received = 0;
+
+        (expected, received)
+    }
+}
+
+/// Simple receiver-side bandwidth estimator, based on the incoming bitrate
+/// and the observed packet loss, its output is sent to the remote peer
+/// through REMB RTCP feedback messages.
+#[derive(Debug)]
+pub struct RembEstimator {
+    ssrcs: HashMap<u32, SsrcStats>,
+    bytes: u64,
+    window_start: Option<Instant>,
+    estimate: u32,
+}
+
+impl Default for RembEstimator {
+    fn default() -> Self {
+        Self {
+            ssrcs: HashMap::new(),
+            bytes: 0,
+            window_start: None,
+            estimate: START_BITRATE,
+        }
+    }
+}
+
+impl RembEstimator {
+    /// Accounts for a received RTP packet
+    pub fn on_rtp_buffer(&mut self, buffer: &gst::BufferRef) {
+        let Ok(rtp_buffer) = gst_rtp::RTPBuffer::from_buffer_readable(buffer) else {
+            return;
+        };
+
+        self.window_start.get_or_insert_with(Instant::now);
+        self.bytes += buffer.size() as u64;
+        self.ssrcs
+            .entry(rtp_buffer.ssrc())
+            .or_insert_with(SsrcStats::new)
+            .update(rtp_buffer.seq());
+    }
+
+    /// Updates the estimate if the current observation window is long enough,
+    /// returning the feedback control information of the REMB packet to send,
+    /// if any
+    pub fn next_remb_fci(&mut self) -> Option<Vec<u8>> {
+        let elapsed = self.window_start?.elapsed();
+
+        if elapsed < UPDATE_INTERVAL || self.ssrcs.is_empty() {
+            return None;
+        }
+
+        let (expected, received) = self
+            .ssrcs
+            .values_mut()
+            .map(SsrcStats::reset)
+            .fold((0, 0), |(e, r), (expected, received)| {
+                (e + expected, r + received)
+            });
+
+        let incoming_bitrate = (self.bytes * 8) as f64 / elapsed.as_secs_f64();
+        let loss = if expected > 0 {
+            expected.saturating_sub(received) as f64 / expected as f64
+        } else {
+            0.
+        };
+
+        let estimate = if loss > 0.10 {
+            // Significant loss, back off below what we currently receive
+            incoming_bitrate * (1. - 0.5 * loss)
+        } else if loss < 0.02 {
+            // No congestion, probe for more but never stray too far from
+            // what we actually receive
+            (self.estimate as f64 * 1.08).min(incoming_bitrate.max(MIN_BITRATE as f64) * 1.5)
+        } else {
+            self.estimate as f64
+        };
+
+        self.estimate = (estimate as u32).clamp(MIN_BITRATE, MAX_BITRATE);
+
+        gst::trace!(
+            CAT,
+            "incoming bitrate: {incoming_bitrate:.0}, loss: {loss:.3}, estimate: {}",
+            self.estimate
+        );
+
+        self.bytes = 0;
+        self.window_start = Some(Instant::now());
+
+        let ssrcs = self.ssrcs.keys().copied().collect::<Vec<_>>();
+
+        Some(build_remb_fci(self.estimate, &ssrcs))
+    }
+}
+
+/// Serializes the feedback control information of a REMB RTCP packet as
+/// described in https://datatracker.ietf.org/doc/html/draft-alvestrand-rmcat-remb-03
+pub fn build_remb_fci(bitrate: u32, ssrcs: &[u32]) -> Vec<u8> {
+    let ssrcs = &ssrcs[..ssrcs.len().min(u8::MAX as usize)];
+
+    let mut mantissa = bitrate;
+    let mut exp = 0u32;
+    while mantissa > 0x3ffff {
+        mantissa >>= 1;
+        exp += 1;
+    }
+
+    let mut fci = Vec::with_capacity((2 + ssrcs.len()) * 4);
+    fci.extend_from_slice(b"REMB");
+    fci.push(ssrcs.len() as u8);
+    fci.extend_from_slice(&((exp << 18) | mantissa).to_be_bytes()[1..]);
+    for ssrc in ssrcs {
+        fci.extend_from_slice(&ssrc.to_be_bytes());
+    }
+
+    fci
+}
+
+/// Appends a REMB packet, an application layer feedback message carrying
+/// @fci, to the compound RTCP packet in @buffer
+pub fn add_remb_packet(buffer: &mut gst::BufferRef, sender_ssrc: u32, fci: &[u8]) -> bool {
+    // SAFETY: the RTCP buffer and packet are initialized by the map and
+    // add_packet calls, and the FCI is only written once sized for @fci
+    unsafe {
+        let mut rtcp = std::mem::zeroed::<gst_rtp::ffi::GstRTCPBuffer>();
+        if gst_rtp::ffi::gst_rtcp_buffer_map(
+            buffer.as_mut_ptr(),
+            gst::ffi::GST_MAP_READWRITE,
+            &mut rtcp,
+        ) == glib::ffi::GFALSE
+        {
+            return false;
+        }
+
+        let mut packet = std::mem::zeroed::<gst_rtp::ffi::GstRTCPPacket>();
+        let mut added = gst_rtp::ffi::gst_rtcp_buffer_add_packet(
+            &mut rtcp,
+            gst_rtp::ffi::GST_RTCP_TYPE_PSFB,
+            &mut packet,
+        ) != glib::ffi::GFALSE;
+
+        if added {
+            gst_rtp::ffi::gst_rtcp_packet_fb_set_type(
+                &mut packet,
+                gst_rtp::ffi::GST_RTCP_PSFB_TYPE_AFB,
+            );
+            gst_rtp::ffi::gst_rtcp_packet_fb_set_sender_ssrc(&mut packet, sender_ssrc);
+            // SSRC of media source, unused
+            gst_rtp::ffi::gst_rtcp_packet_fb_set_media_ssrc(&mut packet, 0);
+
+            added = gst_rtp::ffi::gst_rtcp_packet_fb_set_fci_length(
+                &mut packet,
+                (fci.len() / 4) as u16,
+            ) != glib::ffi::GFALSE;
+            if added {
+                std::ptr::copy_nonoverlapping(
+                    fci.as_ptr(),
+                    gst_rtp::ffi::gst_rtcp_packet_fb_get_fci(&mut packet),
+                    fci.len(),
+                );
+            } else {
+                // Not enough room left in the buffer
+                gst_rtp::ffi::gst_rtcp_packet_remove(&mut packet);
+            }
+        }
+
+        gst_rtp::ffi::gst_rtcp_buffer_unmap(&mut rtcp);
+
+        added
+    }
+}
+
+/// Returns the sender SSRC and feedback control information of the first
+/// REMB packet of the compound RTCP packet in @buffer, if any
+#[cfg(test)]
+pub fn find_remb_packet(buffer: &gst::BufferRef) -> Option<(u32, Vec<u8>)> {
+    // SAFETY: the buffer is only mapped for reading, and the FCI is read
+    // within the length reported for the packet
+    unsafe {
+        let mut rtcp = std::mem::zeroed::<gst_rtp::ffi::GstRTCPBuffer>();
+        if gst_rtp::ffi::gst_rtcp_buffer_map(
+            buffer.as_ptr() as *mut gst::ffi::GstBuffer,
+            gst::ffi::GST_MAP_READ,
+            &mut rtcp,
+        ) == glib::ffi::GFALSE
+        {
+            return None;
+        }
+
+        let mut packet = std::mem::zeroed::<gst_rtp::ffi::GstRTCPPacket>();
+        let mut more = gst_rtp::ffi::gst_rtcp_buffer_get_first_packet(&mut rtcp, &mut packet)
+            != glib::ffi::GFALSE;
+        let mut remb = None;
+        while more {
+            if gst_rtp::ffi::gst_rtcp_packet_get_type(&mut packet)
+                == gst_rtp::ffi::GST_RTCP_TYPE_PSFB
+                && gst_rtp::ffi::gst_rtcp_packet_fb_get_type(&mut packet)
+                    == gst_rtp::ffi::GST_RTCP_PSFB_TYPE_AFB
+            {
+                let len = gst_rtp::ffi::gst_rtcp_packet_fb_get_fci_length(&mut packet) as usize * 4;
+                let fci = std::slice::from_raw_parts(
+                    gst_rtp::ffi::gst_rtcp_packet_fb_get_fci(&mut packet),
+                    len,
+                );
+                if fci.starts_with(b"REMB") {
+                    remb = Some((
+                        gst_rtp::ffi::gst_rtcp_packet_fb_get_sender_ssrc(&mut packet),
+                        fci.to_vec(),
+                    ));
+                    break;
+                }
+            }
+
+            more = gst_rtp::ffi::gst_rtcp_packet_move_to_next(&mut packet) != glib::ffi::GFALSE;
+        }
+
+        gst_rtp::ffi::gst_rtcp_buffer_unmap(&mut rtcp);
+
+        remb
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gst::glib::translate::FromGlibPtrFull;
+
+    #[test]
+    fn test_build_remb_fci() {
+        let fci = build_remb_fci(1_000_000, &[0xaabbccdd]);
+
+        assert_eq!(fci.len(), 12);
+        assert_eq!(&fci[0..4], b"REMB");
+        assert_eq!(fci[4], 1);
+
+        let exp = (fci[5] >> 2) as u32;
+        let mantissa = (((fci[5] & 0x3) as u32) << 16) | ((fci[6] as u32) << 8) | fci[7] as u32;
+        assert_eq!(mantissa << exp, 1_000_000 & !((1 << exp) - 1));
+        assert_eq!(&fci[8..12], &[0xaa, 0xbb, 0xcc, 0xdd]);
+    }
+
+    #[test]
+    fn test_add_remb_packet() {
+        gst::init().unwrap();
+
+        // A compound packet starting with an empty receiver report, as sent
+        // by rtpsession
+        let mut buffer = unsafe {
+            let buffer = gst_rtp::ffi::gst_rtcp_buffer_new(1200);
+            let mut rtcp = std::mem::zeroed::<gst_rtp::ffi::GstRTCPBuffer>();
+            let mut packet = std::mem::zeroed::<gst_rtp::ffi::GstRTCPPacket>();
+            gst_rtp::ffi::gst_rtcp_buffer_map(buffer, gst::ffi::GST_MAP_READWRITE, &mut rtcp);
+            gst_rtp::ffi::gst_rtcp_buffer_add_packet(
+                &mut rtcp,
+                gst_rtp::ffi::GST_RTCP_TYPE_RR,
+                &mut packet,
+            );
+            gst_rtp::ffi::gst_rtcp_buffer_unmap(&mut rtcp);
+            gst::Buffer::from_glib_full(buffer)
+        };
+
+        let fci = build_remb_fci(1_000_000, &[0xaabbccdd]);
+        assert!(add_remb_packet(buffer.get_mut().unwrap(), 0x01020304, &fci));
+
+        // The RR and the 24 bytes of the REMB packet
+        assert_eq!(buffer.size(), 8 + 24);
+        let map = buffer.map_readable().unwrap();
+        assert_eq!(&map[8..12], &[0x8f, 206, 0, 5]);
+        drop(map);
+
+        assert_eq!(find_remb_packet(&buffer), Some((0x01020304, fci)));
+    }
+}
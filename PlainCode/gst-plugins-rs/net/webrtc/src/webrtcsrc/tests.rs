@@ -0,0 +1,737 @@
+// SPDX-License-Identifier: MPL-2.0
+
+// Element level tests, streaming from `webrtcsink` to `webrtcsrc` over
+// loopback
+
+use super::WebRTCSrcPad;
+use crate::data_channel::{self, NAVIGATION_CHANNEL_LABEL};
+use crate::local_signaller::LocalSignaller;
+use crate::signaller::Signallable;
+use crate::webrtcsink::BaseWebRTCSink;
+use gst::glib;
+use gst::prelude::*;
+use std::collections::HashSet;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+// What happened to the source pads of the consumer
+#[derive(Debug)]
+enum PadEvent {
+    // The first buffer of a pad of the session
+    Buffer(String),
+    // A pad of the session was removed
+    Removed(String),
+}
+
+fn init() {
+    gst::init().unwrap();
+    super::register(None).unwrap();
+}
+
+fn session_id(pad: &gst::Pad) -> String {
+    pad.property::<Option<String>>("session-id").unwrap()
+}
+
+// A live `videotestsrc` producer streaming through a `webrtcsink` using
+// @signaller, both elements are named after their factory
+fn producer(signaller: impl IsA<Signallable>) -> gst::Pipeline {
+    let sink = BaseWebRTCSink::with_signaller(signaller.upcast());
+    sink.set_property("name", "webrtcsink");
+    sink.set_property("stun-server", None::<String>);
+
+    let pipeline = gst::Pipeline::new();
+    let videotestsrc = gst::ElementFactory::make("videotestsrc")
+        .name("videotestsrc")
+        .property("is-live", true)
+        .build()
+        .unwrap();
+    pipeline
+        .add_many([&videotestsrc, sink.upcast_ref::<gst::Element>()])
+        .unwrap();
+    videotestsrc.link(&sink).unwrap();
+
+    pipeline
+}
+
+// Plays the pads of @src into fakesinks, reporting the first buffer of each
+// pad and its removal to the returned receiver
+fn consumer(src: &gst::Element) -> (gst::Pipeline, mpsc::Receiver<PadEvent>) {
+    consumer_with_sink(src, "fakesink async=false")
+}
+
+// Same as `consumer()`, linking the pads to a new @sink bin description
+fn consumer_with_sink(
+    src: &gst::Element,
+    sink: &'static str,
+) -> (gst::Pipeline, mpsc::Receiver<PadEvent>) {
+    src.set_property("stun-server", None::<String>);
+
+    let pipeline = gst::Pipeline::new();
+    pipeline.add(src).unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    let tx = Arc::new(Mutex::new(tx));
+    let removed_tx = tx.clone();
+    let weak_pipeline = pipeline.downgrade();
+    src.connect_pad_added(move |_, pad| {
+        if !pad.is::<WebRTCSrcPad>() {
+            return;
+        }
+        let Some(pipeline) = weak_pipeline.upgrade() else {
+            return;
+        };
+        let sink = gst::parse::bin_from_description(sink, true).unwrap();
+        pipeline.add(&sink).unwrap();
+        sink.sync_state_with_parent().unwrap();
+        pad.link(&sink.static_pad("sink").unwrap()).unwrap();
+
+        let tx = tx.lock().unwrap().clone();
+        let session_id = session_id(pad);
+        pad.add_probe(gst::PadProbeType::BUFFER, move |_, _| {
+            let _ = tx.send(PadEvent::Buffer(session_id.clone()));
+            gst::PadProbeReturn::Remove
+        });
+    });
+
+    src.connect_pad_removed(move |_, pad| {
+        if pad.is::<WebRTCSrcPad>() {
+            let event = PadEvent::Removed(session_id(pad));
+            let _ = removed_tx.lock().unwrap().send(event);
+        }
+    });
+
+    (pipeline, rx)
+}
+
+fn next_event(rx: &mpsc::Receiver<PadEvent>) -> PadEvent {
+    rx.recv_timeout(TIMEOUT).unwrap()
+}
+
+#[test]
+fn test_whip_publishers() {
+    init();
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let host_addr = format!("http://127.0.0.1:{port}");
+
+    let src = gst::ElementFactory::make("whipserversrc").build().unwrap();
+    let signaller = src.property::<glib::Object>("signaller");
+    signaller.set_property("host-addr", &host_addr);
+
+    let (tx, ready_rx) = mpsc::channel();
+    let tx = Mutex::new(tx);
+    signaller.connect("webrtcbin-ready", false, move |args| {
+        let _ = tx.lock().unwrap().send(args[1].get::<String>().unwrap());
+        None
+    });
+
+    let (consumer, rx) = consumer(&src);
+    consumer.set_state(gst::State::Playing).unwrap();
+
+    let publishers = (0..2)
+        .map(|_| {
+            let whip = glib::Object::builder::<crate::whip_signaller::WhipClientSignaller>()
+                .property("whip-endpoint", format!("{host_addr}/whip/endpoint"))
+                .build();
+            let publisher = producer(whip);
+            publisher.set_state(gst::State::Playing).unwrap();
+            publisher
+        })
+        .collect::<Vec<_>>();
+
+    // Each publisher streams on pads of its own session
+    let mut sessions = HashSet::new();
+    while sessions.len() < 2 {
+        match next_event(&rx) {
+            PadEvent::Buffer(session_id) => sessions.insert(session_id),
+            event => panic!("unexpected {event:?}"),
+        };
+    }
+
+    // webrtcbin-ready is emitted for each session, with its id
+    let ready = ready_rx.try_iter().collect::<HashSet<_>>();
+    assert_eq!(ready, sessions);
+
+    // Stopping a publisher deletes its resource, removing its pads only
+    publishers[0].set_state(gst::State::Null).unwrap();
+    let PadEvent::Removed(ended) = next_event(&rx) else {
+        panic!("expected a pad removal");
+    };
+    assert!(sessions.contains(&ended));
+
+    let remaining = src
+        .src_pads()
+        .into_iter()
+        .filter(|pad| pad.is::<WebRTCSrcPad>())
+        .map(|pad| session_id(&pad))
+        .collect::<HashSet<_>>();
+    let expected = sessions
+        .iter()
+        .filter(|session_id| **session_id != ended)
+        .cloned()
+        .collect::<HashSet<_>>();
+    assert_eq!(remaining, expected);
+
+    for publisher in publishers {
+        publisher.set_state(gst::State::Null).unwrap();
+    }
+    consumer.set_state(gst::State::Null).unwrap();
+}
+
+// A `webrtcsrc` consuming the producer of the @channel local channel
+fn local_src(channel: &str) -> gst::Element {
+    gst::ElementFactory::make("webrtcsrc")
+        .property("signaller", LocalSignaller::new_consumer(channel))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_remb() {
+    init();
+
+    let src = local_src("test-remb");
+    src.set_property("do-remb", true);
+
+    // Inspect the RTCP packets once our REMB packets were added
+    let (tx, remb_rx) = mpsc::channel();
+    let tx = Mutex::new(tx);
+    let signaller = src.property::<glib::Object>("signaller");
+    signaller.connect("webrtcbin-ready", false, move |args| {
+        let webrtcbin = args[2].get::<gst::Element>().unwrap();
+        let rtpbin = webrtcbin
+            .dynamic_cast_ref::<gst::ChildProxy>()
+            .unwrap()
+            .child_by_name("rtpbin")
+            .unwrap();
+        let tx = tx.lock().unwrap().clone();
+        rtpbin.connect("on-new-ssrc", true, move |args| {
+            let rtpbin = args[0].get::<gst::Element>().unwrap();
+            let session = args[1].get::<u32>().unwrap();
+            let rtp_session =
+                rtpbin.emit_by_name::<glib::Object>("get-internal-session", &[&session]);
+            let tx = Mutex::new(tx.clone());
+            rtp_session.connect("on-sending-rtcp", true, move |args| {
+                let buffer = args[1].get::<gst::Buffer>().unwrap();
+                if let Some(remb) = super::remb::find_remb_packet(&buffer) {
+                    let _ = tx.lock().unwrap().send(remb);
+                }
+                Some(false.to_value())
+            });
+            None
+        });
+        None
+    });
+
+    let producer = producer(LocalSignaller::new_producer("test-remb"));
+    let (consumer, _rx) = consumer(&src);
+    consumer.set_state(gst::State::Playing).unwrap();
+    producer.set_state(gst::State::Playing).unwrap();
+
+    let (_sender_ssrc, fci) = remb_rx.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(&fci[..4], b"REMB");
+    // The estimate covers the SSRCs of the video stream
+    assert!(fci[4] >= 1);
+
+    producer.set_state(gst::State::Null).unwrap();
+    consumer.set_state(gst::State::Null).unwrap();
+}
+
+fn src_pads(src: &gst::Element) -> Vec<gst::Pad> {
+    src.src_pads()
+        .into_iter()
+        .filter(|pad| pad.is::<WebRTCSrcPad>())
+        .collect()
+}
+
+#[test]
+fn test_reconnect() {
+    init();
+
+    let src = local_src("test-reconnect");
+    src.set_property("reconnect", true);
+    let (consumer, rx) = consumer(&src);
+    consumer.set_state(gst::State::Playing).unwrap();
+
+    let first = producer(LocalSignaller::new_producer("test-reconnect"));
+    first.set_state(gst::State::Playing).unwrap();
+    let PadEvent::Buffer(first_session) = next_event(&rx) else {
+        panic!("expected a buffer");
+    };
+    let pads = src_pads(&src);
+
+    // The pads are kept while the producer is away
+    first.set_state(gst::State::Null).unwrap();
+    let second = producer(LocalSignaller::new_producer("test-reconnect"));
+    second.set_state(gst::State::Playing).unwrap();
+
+    let start = std::time::Instant::now();
+    while pads.iter().any(|pad| session_id(pad) == first_session) {
+        assert!(start.elapsed() < TIMEOUT, "pads not reused");
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    assert_eq!(src_pads(&src), pads);
+
+    // And stream from the new session
+    let (tx, buffer_rx) = mpsc::channel();
+    let tx = Mutex::new(tx);
+    pads[0].add_probe(gst::PadProbeType::BUFFER, move |_, _| {
+        let _ = tx.lock().unwrap().send(());
+        gst::PadProbeReturn::Remove
+    });
+    buffer_rx.recv_timeout(TIMEOUT).unwrap();
+    assert!(rx.try_recv().is_err());
+
+    second.set_state(gst::State::Null).unwrap();
+    consumer.set_state(gst::State::Null).unwrap();
+}
+
+mod failing_decoder {
+    use gst::glib;
+    use gst::prelude::*;
+    use gst::subclass::prelude::*;
+    use once_cell::sync::Lazy;
+
+    // A VP8 decoder posting an error for every buffer
+    #[derive(Default)]
+    pub struct FailingDecoderImp;
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for FailingDecoderImp {
+        const NAME: &'static str = "GstWebRTCSrcTestFailingDecoder";
+        type Type = FailingDecoder;
+        type ParentType = gst::Element;
+    }
+
+    impl ObjectImpl for FailingDecoderImp {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            let sinkpad = gst::Pad::builder_from_template(&obj.pad_template("sink").unwrap())
+                .chain_function(|_pad, parent, _buffer| {
+                    FailingDecoderImp::catch_panic_pad_function(
+                        parent,
+                        || Err(gst::FlowError::Error),
+                        |imp| {
+                            gst::element_imp_error!(
+                                imp,
+                                gst::StreamError::Decode,
+                                ["Failing on purpose"]
+                            );
+                            Ok(gst::FlowSuccess::Ok)
+                        },
+                    )
+                })
+                .build();
+            let srcpad = gst::Pad::from_template(&obj.pad_template("src").unwrap());
+            obj.add_pad(&sinkpad).unwrap();
+            obj.add_pad(&srcpad).unwrap();
+        }
+    }
+
+    impl GstObjectImpl for FailingDecoderImp {}
+
+    impl ElementImpl for FailingDecoderImp {
+        fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+            static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
+                gst::subclass::ElementMetadata::new(
+                    "Failing VP8 decoder",
+                    "Codec/Decoder/Video",
+                    "Posts an error for every buffer",
+                    "The GStreamer Rust plugins developers",
+                )
+            });
+
+            Some(&*ELEMENT_METADATA)
+        }
+
+        fn pad_templates() -> &'static [gst::PadTemplate] {
+            static PAD_TEMPLATES: Lazy<Vec<gst::PadTemplate>> = Lazy::new(|| {
+                vec![
+                    gst::PadTemplate::new(
+                        "sink",
+                        gst::PadDirection::Sink,
+                        gst::PadPresence::Always,
+                        &gst::Caps::builder("video/x-vp8").build(),
+                    )
+                    .unwrap(),
+                    gst::PadTemplate::new(
+                        "src",
+                        gst::PadDirection::Src,
+                        gst::PadPresence::Always,
+                        &gst::Caps::builder("video/x-raw").build(),
+                    )
+                    .unwrap(),
+                ]
+            });
+
+            PAD_TEMPLATES.as_ref()
+        }
+    }
+
+    glib::wrapper! {
+        pub struct FailingDecoder(ObjectSubclass<FailingDecoderImp>) @extends gst::Element, gst::Object;
+    }
+}
+
+#[test]
+fn test_decoder_fallback() {
+    init();
+    gst::Element::register(
+        None,
+        "failingvp8dec",
+        gst::Rank::NONE,
+        failing_decoder::FailingDecoder::static_type(),
+    )
+    .unwrap();
+
+    let src = local_src("test-decoder-fallback");
+    src.set_property(
+        "decoder-preferences",
+        gst::Structure::builder("decoders")
+            .field("VP8", gst::Array::new(["failingvp8dec", "vp8dec"]))
+            .build(),
+    );
+
+    let producer = producer(LocalSignaller::new_producer("test-decoder-fallback"));
+    producer
+        .by_name("webrtcsink")
+        .unwrap()
+        .set_property("video-caps", gst::Caps::builder("video/x-vp8").build());
+    let (consumer, rx) =
+        consumer_with_sink(&src, "capsfilter caps=video/x-raw ! fakesink async=false");
+    consumer.set_state(gst::State::Playing).unwrap();
+    producer.set_state(gst::State::Playing).unwrap();
+
+    // The failing decoder never outputs anything
+    let PadEvent::Buffer(_) = next_event(&rx) else {
+        panic!("expected a buffer");
+    };
+
+    let factories = src
+        .downcast_ref::<gst::Bin>()
+        .unwrap()
+        .iterate_recurse()
+        .into_iter()
+        .filter_map(|element| element.ok()?.factory())
+        .map(|factory| factory.name().to_string())
+        .collect::<Vec<_>>();
+    assert!(factories.iter().any(|name| name == "vp8dec"));
+    assert!(!factories.iter().any(|name| name == "failingvp8dec"));
+
+    // The failure was reported as a warning, not an error
+    let mut fell_back = false;
+    for msg in consumer.bus().unwrap().iter() {
+        match msg.view() {
+            gst::MessageView::Warning(warning) => {
+                fell_back |= warning.error().to_string().contains("falling back");
+            }
+            gst::MessageView::Error(err) => panic!("unexpected error {err:?}"),
+            _ => (),
+        }
+    }
+    assert!(fell_back);
+
+    producer.set_state(gst::State::Null).unwrap();
+    consumer.set_state(gst::State::Null).unwrap();
+}
+
+#[test]
+fn test_data_pads() {
+    init();
+
+    let sink =
+        BaseWebRTCSink::with_signaller(LocalSignaller::new_producer("test-data-pads").upcast());
+    for element in [sink.upcast::<gst::Element>(), local_src("test-data-pads")] {
+        let bin = element.downcast_ref::<gst::Bin>().unwrap();
+        let n_children = bin.children().len();
+        let pad = element.request_pad_simple("data").unwrap();
+        assert!(pad.is_active());
+        assert!(element.request_pad_simple("data").is_none());
+
+        // The pad can be requested again once released
+        element.release_request_pad(&pad);
+        assert!(element.static_pad("data").is_none());
+        assert_eq!(bin.children().len(), n_children);
+        let pad = element.request_pad_simple("data").unwrap();
+        element.release_request_pad(&pad);
+    }
+}
+
+// Retries @send until the data channel it sends on is open
+fn send_when_open(send: impl Fn() -> bool) {
+    let start = std::time::Instant::now();
+    while !send() {
+        assert!(start.elapsed() < TIMEOUT, "data channel not opened");
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+// Forwards the label and content of the text messages received by @element,
+// whose `data-channel-message-string` signal has the message as last argument
+fn string_messages(element: &gst::Element) -> mpsc::Receiver<(String, String)> {
+    let (tx, rx) = mpsc::channel();
+    let tx = Mutex::new(tx);
+    element.connect("data-channel-message-string", false, move |args| {
+        let n_args = args.len();
+        let label = args[n_args - 2].get::<String>().unwrap();
+        let message = args[n_args - 1].get::<String>().unwrap();
+        let _ = tx.lock().unwrap().send((label, message));
+        None
+    });
+
+    rx
+}
+
+#[test]
+fn test_data_channels() {
+    init();
+
+    let producer = producer(LocalSignaller::new_producer("test-data-channels"));
+    let sink = producer.by_name("webrtcsink").unwrap();
+    sink.set_property(
+        "data-channels",
+        gst::Array::new([gst::Structure::new_empty("telemetry")]),
+    );
+    let (tx, sink_rx) = mpsc::channel();
+    let tx = Mutex::new(tx);
+    sink.connect("data-channel-message-data", false, move |args| {
+        let session_id = args[1].get::<String>().unwrap();
+        let label = args[2].get::<String>().unwrap();
+        let data = args[3].get::<glib::Bytes>().unwrap();
+        let _ = tx.lock().unwrap().send((session_id, label, data.to_vec()));
+        None
+    });
+
+    // Two consumers, the first one also opening its own channel and
+    // outputting the messages it receives on its data pad
+    let consumers = (0..2)
+        .map(|i| {
+            let src = local_src("test-data-channels");
+            if i == 0 {
+                src.set_property(
+                    "data-channels",
+                    gst::Array::new([gst::Structure::new_empty("control")]),
+                );
+            }
+            let messages = string_messages(&src);
+            let (pipeline, rx) = consumer(&src);
+            (src, messages, pipeline, rx)
+        })
+        .collect::<Vec<_>>();
+
+    let (first, first_messages, first_pipeline, _) = &consumers[0];
+    let (second, second_messages, _, _) = &consumers[1];
+
+    let appsink = gst_app::AppSink::builder().sync(false).build();
+    first_pipeline.add(&appsink).unwrap();
+    first
+        .request_pad_simple("data")
+        .unwrap()
+        .link(&appsink.static_pad("sink").unwrap())
+        .unwrap();
+
+    for (_, _, pipeline, _) in &consumers {
+        pipeline.set_state(gst::State::Playing).unwrap();
+    }
+    producer.set_state(gst::State::Playing).unwrap();
+
+    let session_ids = consumers
+        .iter()
+        .map(|(_, _, _, rx)| match next_event(rx) {
+            PadEvent::Buffer(session_id) => session_id,
+            event => panic!("unexpected {event:?}"),
+        })
+        .collect::<Vec<_>>();
+
+    // Messages sent to one session only reach its consumer
+    for (session_id, message) in session_ids.iter().zip(["to-first", "to-second"]) {
+        send_when_open(|| {
+            sink.emit_by_name::<bool>(
+                "send-data-channel-string",
+                &[&Some(session_id), &"telemetry", &message],
+            )
+        });
+    }
+
+    // And broadcast ones reach all of them
+    assert!(sink.emit_by_name::<bool>(
+        "send-data-channel-string",
+        &[&None::<String>, &"telemetry", &"to-all"],
+    ));
+
+    for (messages, expected) in [(first_messages, "to-first"), (second_messages, "to-second")] {
+        for expected in [expected, "to-all"] {
+            assert_eq!(
+                messages.recv_timeout(TIMEOUT).unwrap(),
+                ("telemetry".to_string(), expected.to_string())
+            );
+        }
+    }
+    assert!(second_messages.try_recv().is_err());
+
+    // The data pad outputs the messages along with their meta
+    for expected in ["to-first", "to-all"] {
+        let sample = appsink.try_pull_sample(TIMEOUT).unwrap();
+        let buffer = sample.buffer().unwrap();
+        assert_eq!(&*buffer.map_readable().unwrap(), expected.as_bytes());
+
+        let meta = gst::meta::CustomMeta::from_buffer(buffer, "GstWebRTCDataChannelMeta").unwrap();
+        let s = meta.structure();
+        assert_eq!(s.get::<&str>("label").unwrap(), "telemetry");
+        assert_eq!(
+            s.get::<Option<&str>>("session-id").unwrap(),
+            Some(session_ids[0].as_str())
+        );
+        assert!(s.get::<bool>("is-string").unwrap());
+    }
+
+    // Consumers send on their own channels
+    send_when_open(|| {
+        first.emit_by_name::<bool>(
+            "send-data-channel-data",
+            &[&"control", &glib::Bytes::from_static(b"\x01\x02")],
+        )
+    });
+    assert_eq!(
+        sink_rx.recv_timeout(TIMEOUT).unwrap(),
+        (session_ids[0].clone(), "control".to_string(), vec![1, 2])
+    );
+    // Not on the channels of others
+    assert!(!second.emit_by_name::<bool>(
+        "send-data-channel-data",
+        &[&"control", &glib::Bytes::from_static(b"\x03")],
+    ));
+
+    producer.set_state(gst::State::Null).unwrap();
+    for (_, _, pipeline, _) in consumers {
+        pipeline.set_state(gst::State::Null).unwrap();
+    }
+}
+
+// Notifies the returned receiver once the navigation data channel of @src
+// is open
+fn navigation_channel_opened(src: &gst::Element) -> mpsc::Receiver<()> {
+    let (tx, rx) = mpsc::channel();
+    let tx = Arc::new(Mutex::new(tx));
+    let signaller = src.property::<glib::Object>("signaller");
+    signaller.connect("webrtcbin-ready", false, move |args| {
+        let webrtcbin = args[2].get::<gst::Element>().unwrap();
+        let tx = tx.clone();
+        webrtcbin.connect("on-data-channel", false, move |args| {
+            let channel = args[1].get::<gst_webrtc::WebRTCDataChannel>().unwrap();
+            if data_channel::label(&channel) != NAVIGATION_CHANNEL_LABEL {
+                return None;
+            }
+
+            let is_open = |channel: &gst_webrtc::WebRTCDataChannel| {
+                channel.property::<gst_webrtc::WebRTCDataChannelState>("ready-state")
+                    == gst_webrtc::WebRTCDataChannelState::Open
+            };
+            let notify_tx = tx.clone();
+            channel.connect_notify(Some("ready-state"), move |channel, _| {
+                if is_open(channel) {
+                    let _ = notify_tx.lock().unwrap().send(());
+                }
+            });
+            if is_open(&channel) {
+                let _ = tx.lock().unwrap().send(());
+            }
+
+            None
+        });
+        None
+    });
+
+    rx
+}
+
+#[test]
+fn test_navigation_events() {
+    init();
+
+    let producer = producer(LocalSignaller::new_producer("test-navigation-events"));
+    let sink = producer.by_name("webrtcsink").unwrap();
+    sink.set_property("enable-data-channel-navigation", true);
+    sink.set_property(
+        "allowed-navigation-events",
+        gst::Array::new(["key-press", "mouse-move"]),
+    );
+
+    // Navigation events reaching the source are handled
+    let (tx, events_rx) = mpsc::channel();
+    let tx = Mutex::new(tx);
+    producer
+        .by_name("videotestsrc")
+        .unwrap()
+        .static_pad("src")
+        .unwrap()
+        .add_probe(gst::PadProbeType::EVENT_UPSTREAM, move |_, info| {
+            let Some(event) = info.event() else {
+                return gst::PadProbeReturn::Ok;
+            };
+            if event.type_() != gst::EventType::Navigation {
+                return gst::PadProbeReturn::Ok;
+            }
+
+            let event_type = event
+                .structure()
+                .and_then(|s| s.get::<String>("event").ok())
+                .unwrap();
+            let _ = tx.lock().unwrap().send(event_type);
+            gst::PadProbeReturn::Handled
+        });
+
+    let src = local_src("test-navigation-events");
+    src.set_property("enable-data-channel-navigation", true);
+    let opened_rx = navigation_channel_opened(&src);
+    let (tx, replies_rx) = mpsc::channel();
+    let tx = Mutex::new(tx);
+    src.connect("navigation-event-reply", false, move |args| {
+        let id = args[1].get::<u64>().unwrap();
+        let error = args[2].get::<Option<String>>().unwrap();
+        let _ = tx.lock().unwrap().send((id, error));
+        None
+    });
+
+    let (consumer, _rx) = consumer(&src);
+    consumer.set_state(gst::State::Playing).unwrap();
+    producer.set_state(gst::State::Playing).unwrap();
+
+    let send = |event: gst_video::NavigationEvent| {
+        src.emit_by_name::<u64>("send-navigation-event", &[&event.structure()])
+    };
+
+    opened_rx.recv_timeout(TIMEOUT).unwrap();
+
+    // Allowed discrete events are acknowledged
+    let id = send(gst_video::NavigationEvent::new_key_press("a"));
+    assert_ne!(id, 0);
+    assert_eq!(replies_rx.recv_timeout(TIMEOUT).unwrap(), (id, None));
+    assert_eq!(events_rx.recv_timeout(TIMEOUT).unwrap(), "key-press");
+
+    // Continuous ones are not
+    assert_eq!(send(gst_video::NavigationEvent::new_mouse_move(1., 1.)), 0);
+    assert_eq!(events_rx.recv_timeout(TIMEOUT).unwrap(), "mouse-move");
+
+    // Others are rejected, the reply coming next as mouse-move was not
+    // acknowledged
+    let id = send(gst_video::NavigationEvent::new_mouse_button_press(
+        1, 1., 1.,
+    ));
+    assert_ne!(id, 0);
+    let (reply_id, error) = replies_rx.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(reply_id, id);
+    assert!(error.unwrap().contains("not allowed"));
+    assert!(events_rx.try_recv().is_err());
+
+    producer.set_state(gst::State::Null).unwrap();
+    consumer.set_state(gst::State::Null).unwrap();
+}
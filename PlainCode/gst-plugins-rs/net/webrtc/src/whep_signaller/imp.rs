@@ -0,0 +1,1645 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::signaller::{Signallable, SignallableImpl};
+use crate::utils::{
+    build_reqwest_client, cors, ice_server_links, parse_redirect_location, set_ice_servers, wait,
+    wait_async, WaitError,
+};
+use crate::RUNTIME;
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+use gst::ErrorMessage;
+use gst_sdp::SDPMessage;
+use gst_webrtc::{WebRTCICEGatheringState, WebRTCSDPType, WebRTCSessionDescription};
+use once_cell::sync::Lazy;
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::StatusCode;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use core::time::Duration;
+use futures::channel::oneshot;
+use std::net::SocketAddr;
+use url::Url;
+use warp::{
+    http,
+    hyper::{
+        header::{AUTHORIZATION, CONTENT_TYPE, LOCATION},
+        Body,
+    },
+    Filter, Reply,
+};
+
+static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
+    gst::DebugCategory::new(
+        "webrtc-whep-signaller",
+        gst::DebugColorFlags::empty(),
+        Some("WebRTC WHEP signaller"),
+    )
+});
+
+const MAX_REDIRECTS: u8 = 10;
+const DEFAULT_TIMEOUT: u32 = 15;
+const DEFAULT_SERVER_OFFER: bool = false;
+const CONTENT_SDP: &str = "application/sdp";
+
+#[derive(Clone)]
+struct WhepClientSettings {
+    whep_endpoint: Option<String>,
+    use_link_headers: bool,
+    auth_token: Option<String>,
+    timeout: u32,
+    server_offer: bool,
+}
+
+impl Default for WhepClientSettings {
+    fn default() -> Self {
+        Self {
+            whep_endpoint: None,
+            use_link_headers: false,
+            auth_token: None,
+            timeout: DEFAULT_TIMEOUT,
+            server_offer: DEFAULT_SERVER_OFFER,
+        }
+    }
+}
+
+/// The WHEP resource created by the server in response to the POST request,
+/// along with the SDP answer or offer it returned
+#[derive(Debug)]
+struct WhepResource {
+    url: reqwest::Url,
+    headers: HeaderMap,
+    sdp: SDPMessage,
+}
+
+#[derive(Default)]
+pub struct WhepClient {
+    settings: Mutex<WhepClientSettings>,
+    resource_url: Mutex<Option<reqwest::Url>>,
+    canceller: Mutex<Option<futures::future::AbortHandle>>,
+    webrtcbin: Mutex<Option<glib::WeakRef<gst::Element>>>,
+    /// Link headers of the OPTIONS response, applied to the webrtcbin of the
+    /// session before it gathers candidates for our offer
+    link_headers: Mutex<Option<HeaderMap>>,
+}
+
+impl WhepClient {
+    fn raise_error(&self, msg: String) {
+        self.obj()
+            .emit_by_name::<()>("error", &[&format!("Error: {msg}")]);
+    }
+
+    fn handle_future_error(&self, err: WaitError) {
+        match err {
+            WaitError::FutureAborted => {
+                gst::warning!(CAT, imp: self, "Future aborted")
+            }
+            WaitError::FutureError(err) => self.raise_error(err.to_string()),
+        };
+    }
+
+    fn webrtcbin(&self) -> Option<gst::Element> {
+        self.webrtcbin
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|webrtcbin| webrtcbin.upgrade())
+    }
+
+    fn endpoint(&self) -> Result<reqwest::Url, String> {
+        let settings = self.settings.lock().unwrap();
+        let Some(endpoint) = settings.whep_endpoint.as_ref() else {
+            return Err("WHEP endpoint URL must be set".to_string());
+        };
+
+        reqwest::Url::parse(endpoint).map_err(|err| format!("Invalid WHEP endpoint URL: {err}"))
+    }
+
+    // Creates the WHEP resource by POSTing @offer, or an empty body to get the
+    // offer of the server, and hands the SDP returned by the server to the
+    // consumer
+    async fn create_resource(&self, offer: Option<WebRTCSessionDescription>) {
+        let endpoint = match self.endpoint() {
+            Ok(endpoint) => endpoint,
+            Err(err) => {
+                self.raise_error(err);
+                return;
+            }
+        };
+
+        let offer_text = match offer.as_ref().map(|offer| offer.sdp().as_text()) {
+            Some(Ok(text)) => Some(text),
+            Some(Err(err)) => {
+                self.raise_error(format!("Could not serialize offer: {err}"));
+                return;
+            }
+            None => None,
+        };
+
+        gst::debug!(CAT, imp: self, "Sending offer SDP: {offer_text:?}");
+
+        let (auth_token, timeout, use_link_headers) = {
+            let settings = self.settings.lock().unwrap();
+            (
+                settings.auth_token.clone(),
+                settings.timeout,
+                settings.use_link_headers,
+            )
+        };
+
+        let resource = match wait_async(
+            &self.canceller,
+            post(endpoint, auth_token, offer_text),
+            timeout,
+        )
+        .await
+        {
+            Ok(Ok(resource)) => resource,
+            Ok(Err(err)) => {
+                self.raise_error(err.to_string());
+                return;
+            }
+            Err(err) => {
+                self.handle_future_error(err);
+                return;
+            }
+        };
+
+        gst::debug!(CAT, imp: self, "WHEP resource: {}", resource.url);
+        *self.resource_url.lock().unwrap() = Some(resource.url);
+
+        let obj = self.obj();
+        if offer.is_none() {
+            // Makes the consumer create its webrtcbin, which our
+            // `webrtcbin-ready` handler keeps track of
+            obj.emit_by_name::<()>("session-started", &[&"unique", &"unique"]);
+        }
+
+        // When we made the offer, the candidates were already gathered and
+        // the ICE servers came from the OPTIONS response instead
+        if use_link_headers && offer.is_none() {
+            let Some(webrtcbin) = self.webrtcbin() else {
+                self.raise_error("No webrtcbin to set the ICE servers on".to_string());
+                return;
+            };
+
+            if let Err(err) = set_ice_servers(&webrtcbin, &resource.headers) {
+                self.raise_error(err.to_string());
+                return;
+            }
+        }
+
+        let sdp_type = if offer.is_some() {
+            WebRTCSDPType::Answer
+        } else {
+            WebRTCSDPType::Offer
+        };
+        let desc = WebRTCSessionDescription::new(sdp_type, resource.sdp);
+        obj.emit_by_name::<()>("session-description", &[&"unique", &desc]);
+    }
+
+    // Sends our answer to the offer of the server with a PATCH request on the
+    // WHEP resource
+    async fn send_answer(&self, answer: WebRTCSessionDescription) {
+        let Some(url) = self.resource_url.lock().unwrap().clone() else {
+            self.raise_error("No WHEP resource to send the answer to".to_string());
+            return;
+        };
+
+        let answer = match answer.sdp().as_text() {
+            Ok(text) => text,
+            Err(err) => {
+                self.raise_error(format!("Could not serialize answer: {err}"));
+                return;
+            }
+        };
+
+        gst::debug!(CAT, imp: self, "Sending answer SDP: {answer:?}");
+
+        let (auth_token, timeout) = {
+            let settings = self.settings.lock().unwrap();
+            (settings.auth_token.clone(), settings.timeout)
+        };
+
+        match wait_async(
+            &self.canceller,
+            patch_answer(url, auth_token, answer),
+            timeout,
+        )
+        .await
+        {
+            Ok(Ok(())) => gst::debug!(CAT, imp: self, "Answer accepted by the server"),
+            Ok(Err(err)) => self.raise_error(err.to_string()),
+            Err(err) => self.handle_future_error(err),
+        }
+    }
+
+    fn terminate_session(&self) {
+        let Some(url) = self.resource_url.lock().unwrap().take() else {
+            return;
+        };
+
+        let (auth_token, timeout) = {
+            let settings = self.settings.lock().unwrap();
+            (settings.auth_token.clone(), settings.timeout)
+        };
+
+        gst::debug!(CAT, imp: self, "DELETE request on {url}");
+        match wait(&self.canceller, delete_resource(url, auth_token), timeout) {
+            Ok(status) => {
+                gst::debug!(CAT, imp: self, "Response to DELETE : {status}");
+            }
+            Err(WaitError::FutureAborted) => {
+                gst::warning!(CAT, imp: self, "DELETE request aborted")
+            }
+            Err(WaitError::FutureError(err)) => {
+                gst::error!(CAT, imp: self, "Error on DELETE request : {err}")
+            }
+        };
+    }
+
+    // Gets the ICE servers from the Link headers of the response to an
+    // OPTIONS request before requesting the session, as those of the POST
+    // response arrive once our offer is complete, too late to be used
+    async fn request_session(&self) {
+        let endpoint = match self.endpoint() {
+            Ok(endpoint) => endpoint,
+            Err(err) => {
+                self.raise_error(err);
+                return;
+            }
+        };
+
+        let (auth_token, timeout) = {
+            let settings = self.settings.lock().unwrap();
+            (settings.auth_token.clone(), settings.timeout)
+        };
+
+        match wait_async(&self.canceller, options(endpoint, auth_token), timeout).await {
+            Ok(Ok(headers)) => *self.link_headers.lock().unwrap() = Some(headers),
+            Ok(Err(err)) => {
+                self.raise_error(err.to_string());
+                return;
+            }
+            Err(err) => {
+                self.handle_future_error(err);
+                return;
+            }
+        }
+
+        self.obj().emit_by_name::<()>(
+            "session-requested",
+            &[&"unique", &"unique", &None::<WebRTCSessionDescription>],
+        );
+    }
+
+    fn on_webrtcbin_ready(&self, webrtcbin: &gst::Element) {
+        *self.webrtcbin.lock().unwrap() = Some(webrtcbin.downgrade());
+
+        if let Some(headers) = self.link_headers.lock().unwrap().as_ref() {
+            if let Err(err) = set_ice_servers(webrtcbin, headers) {
+                self.raise_error(err.to_string());
+            }
+        }
+
+        let obj_weak = self.obj().downgrade();
+        webrtcbin.connect_notify(Some("ice-gathering-state"), move |webrtcbin, _pspec| {
+            let Some(obj) = obj_weak.upgrade() else {
+                return;
+            };
+
+            let state = webrtcbin.property::<WebRTCICEGatheringState>("ice-gathering-state");
+
+            match state {
+                WebRTCICEGatheringState::Gathering => {
+                    gst::info!(CAT, obj: obj, "ICE gathering started");
+                }
+                WebRTCICEGatheringState::Complete => {
+                    gst::info!(CAT, obj: obj, "ICE gathering complete");
+
+                    // Candidates are not trickled, the local description is
+                    // sent once all of them are part of it
+                    let Some(desc) =
+                        webrtcbin.property::<Option<WebRTCSessionDescription>>("local-description")
+                    else {
+                        obj.imp()
+                            .raise_error("Local description is not set".to_string());
+                        return;
+                    };
+
+                    RUNTIME.spawn(async move {
+                        if desc.type_() == WebRTCSDPType::Offer {
+                            obj.imp().create_resource(Some(desc)).await
+                        } else {
+                            obj.imp().send_answer(desc).await
+                        }
+                    });
+                }
+                _ => (),
+            }
+        });
+    }
+}
+
+impl SignallableImpl for WhepClient {
+    fn start(&self) {
+        if let Err(err) = self.endpoint() {
+            self.raise_error(err);
+            return;
+        }
+
+        let (server_offer, use_link_headers) = {
+            let settings = self.settings.lock().unwrap();
+            (settings.server_offer, settings.use_link_headers)
+        };
+
+        if server_offer {
+            let this = self.obj().clone();
+            RUNTIME.spawn(async move { this.imp().create_resource(None).await });
+        } else if use_link_headers {
+            let this = self.obj().clone();
+            RUNTIME.spawn(async move { this.imp().request_session().await });
+        } else {
+            self.obj().emit_by_name::<()>(
+                "session-requested",
+                &[&"unique", &"unique", &None::<WebRTCSessionDescription>],
+            );
+        }
+    }
+
+    fn stop(&self) {
+        // Interrupt requests in progress, if any
+        if let Some(canceller) = self.canceller.lock().unwrap().take() {
+            canceller.abort();
+        }
+
+        // Release server-side resources
+        self.terminate_session();
+        *self.webrtcbin.lock().unwrap() = None;
+        *self.link_headers.lock().unwrap() = None;
+    }
+
+    fn send_sdp(&self, _session_id: &str, _sdp: &WebRTCSessionDescription) {
+        // The local description is sent once ICE gathering is complete
+    }
+
+    fn add_ice(
+        &self,
+        _session_id: &str,
+        _candidate: &str,
+        _sdp_m_line_index: u32,
+        _sdp_mid: Option<String>,
+    ) {
+        // Candidates are sent as part of the local description
+    }
+
+    fn end_session(&self, session_id: &str) {
+        assert_eq!(session_id, "unique");
+
+        if let Some(canceller) = self.canceller.lock().unwrap().take() {
+            canceller.abort();
+        }
+
+        self.terminate_session();
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for WhepClient {
+    const NAME: &'static str = "GstWhepClientSignaller";
+    type Type = super::WhepClientSignaller;
+    type ParentType = glib::Object;
+    type Interfaces = (Signallable,);
+}
+
+impl ObjectImpl for WhepClient {
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        self.obj().connect_closure(
+            "webrtcbin-ready",
+            false,
+            glib::closure!(|signaller: &super::WhepClientSignaller,
+                            _session_id: &str,
+                            webrtcbin: &gst::Element| {
+                signaller.imp().on_webrtcbin_ready(webrtcbin);
+            }),
+        );
+    }
+
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+            vec![glib::ParamSpecString::builder("whep-endpoint")
+                    .nick("WHEP Endpoint")
+                    .blurb("The WHEP server endpoint to POST SDP offer to.
+                        e.g.: https://example.com/whep/endpoint/room1234")
+                    .mutable_ready()
+                    .build(),
+
+                glib::ParamSpecBoolean::builder("use-link-headers")
+                    .nick("Use Link Headers")
+                    .blurb("Use link headers to configure ice-servers from the WHEP server response to an OPTIONS request,
+                        or to the POST request with server-offer. If set to TRUE and the WHEP server returns valid ice-servers,
+                        this property overrides the ice-servers values set using the stun-server and turn-server properties.")
+                    .mutable_ready()
+                    .build(),
+
+                glib::ParamSpecString::builder("auth-token")
+                    .nick("Authorization Token")
+                    .blurb("Authentication token to use, will be sent in the HTTP Header as 'Bearer <auth-token>'")
+                    .mutable_ready()
+                    .build(),
+
+                glib::ParamSpecUInt::builder("timeout")
+                    .nick("Timeout")
+                    .blurb("Value in seconds to timeout WHEP endpoint requests (0 = No timeout).")
+                    .maximum(3600)
+                    .default_value(DEFAULT_TIMEOUT)
+                    .build(),
+
+                glib::ParamSpecBoolean::builder("server-offer")
+                    .nick("Server Offer")
+                    .blurb("POST an empty body to get the SDP offer from the WHEP server instead of sending ours,
+                        the answer is then sent with a PATCH request on the WHEP resource.")
+                    .default_value(DEFAULT_SERVER_OFFER)
+                    .mutable_ready()
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        match pspec.name() {
+            "whep-endpoint" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.whep_endpoint = value.get().unwrap();
+            }
+            "use-link-headers" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.use_link_headers = value.get().unwrap();
+            }
+            "auth-token" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.auth_token = value.get().unwrap();
+            }
+            "timeout" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.timeout = value.get().unwrap();
+            }
+            "server-offer" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.server_offer = value.get().unwrap();
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        match pspec.name() {
+            "whep-endpoint" => {
+                let settings = self.settings.lock().unwrap();
+                settings.whep_endpoint.to_value()
+            }
+            "use-link-headers" => {
+                let settings = self.settings.lock().unwrap();
+                settings.use_link_headers.to_value()
+            }
+            "auth-token" => {
+                let settings = self.settings.lock().unwrap();
+                settings.auth_token.to_value()
+            }
+            "timeout" => {
+                let settings = self.settings.lock().unwrap();
+                settings.timeout.to_value()
+            }
+            "server-offer" => {
+                let settings = self.settings.lock().unwrap();
+                settings.server_offer.to_value()
+            }
+            _ => unimplemented!(),
+        }
+    }
+}
+
+fn auth_headers(auth_token: Option<&str>) -> Result<HeaderMap, ErrorMessage> {
+    let mut headermap = HeaderMap::new();
+
+    if let Some(token) = auth_token {
+        let bearer_token = HeaderValue::from_str(&format!("Bearer {token}")).map_err(|err| {
+            gst::error_msg!(gst::ResourceError::Failed, ["Invalid auth token: {}", err])
+        })?;
+        headermap.insert(reqwest::header::AUTHORIZATION, bearer_token);
+    }
+
+    Ok(headermap)
+}
+
+/// POSTs @offer to @endpoint, or an empty body when the server is expected to
+/// make the offer, following redirects
+async fn post(
+    mut endpoint: reqwest::Url,
+    auth_token: Option<String>,
+    offer: Option<String>,
+) -> Result<WhepResource, ErrorMessage> {
+    // Default policy for redirect does not share the auth token to new location
+    let client = build_reqwest_client(reqwest::redirect::Policy::none());
+    let headermap = auth_headers(auth_token.as_deref())?;
+
+    for _ in 0..=MAX_REDIRECTS {
+        gst::debug!(CAT, "POST request on {endpoint}");
+
+        let request = client.post(endpoint.clone()).headers(headermap.clone());
+        let request = match offer {
+            Some(ref offer) => request
+                .header(reqwest::header::CONTENT_TYPE, CONTENT_SDP)
+                .body(offer.clone()),
+            None => request.header(reqwest::header::ACCEPT, CONTENT_SDP),
+        };
+
+        let resp = request.send().await.map_err(|err| {
+            gst::error_msg!(
+                gst::ResourceError::Failed,
+                ["POST request failed {}: {:?}", endpoint, err]
+            )
+        })?;
+
+        let status = resp.status();
+        gst::debug!(CAT, "response status: {status}");
+
+        if status.is_redirection() {
+            endpoint = parse_redirect_location(resp.headers(), &endpoint)?;
+            continue;
+        }
+
+        if status != StatusCode::OK && status != StatusCode::CREATED {
+            let body = resp
+                .bytes()
+                .await
+                .map(|body| body.escape_ascii().to_string())
+                .unwrap_or_default();
+
+            return Err(gst::error_msg!(
+                gst::ResourceError::Failed,
+                ["Unexpected response: {} - {}", status.as_str(), body]
+            ));
+        }
+
+        // The resource URL is usually relative to the endpoint
+        let url = parse_redirect_location(resp.headers(), &endpoint)?;
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await.map_err(|err| {
+            gst::error_msg!(
+                gst::ResourceError::Failed,
+                ["Failed to read response: {:?}", err]
+            )
+        })?;
+        let sdp = SDPMessage::parse_buffer(&body).map_err(|err| {
+            gst::error_msg!(gst::ResourceError::Failed, ["Could not parse SDP: {}", err])
+        })?;
+
+        return Ok(WhepResource { url, headers, sdp });
+    }
+
+    Err(gst::error_msg!(
+        gst::ResourceError::Failed,
+        ["Too many redirects. Unable to connect."]
+    ))
+}
+
+/// Sends an OPTIONS request to @endpoint, the ICE servers to use being
+/// returned in the Link headers of the response
+async fn options(
+    endpoint: reqwest::Url,
+    auth_token: Option<String>,
+) -> Result<HeaderMap, ErrorMessage> {
+    let client = build_reqwest_client(reqwest::redirect::Policy::default());
+    let headermap = auth_headers(auth_token.as_deref())?;
+
+    gst::debug!(CAT, "OPTIONS request on {endpoint}");
+
+    let resp = client
+        .request(reqwest::Method::OPTIONS, endpoint.clone())
+        .headers(headermap)
+        .send()
+        .await
+        .map_err(|err| {
+            gst::error_msg!(
+                gst::ResourceError::Failed,
+                ["OPTIONS request failed {}: {:?}", endpoint, err]
+            )
+        })?;
+
+    let status = resp.status();
+    gst::debug!(CAT, "response status: {status}");
+
+    if !status.is_success() {
+        return Err(gst::error_msg!(
+            gst::ResourceError::Failed,
+            ["Unexpected response: {}", status.as_str()]
+        ));
+    }
+
+    Ok(resp.headers().clone())
+}
+
+async fn patch_answer(
+    url: reqwest::Url,
+    auth_token: Option<String>,
+    answer: String,
+) -> Result<(), ErrorMessage> {
+    // Redirects are only supported for the initial POST request
+    let client = build_reqwest_client(reqwest::redirect::Policy::none());
+
+    let resp = client
+        .patch(url.clone())
+        .headers(auth_headers(auth_token.as_deref())?)
+        .header(reqwest::header::CONTENT_TYPE, CONTENT_SDP)
+        .body(answer)
+        .send()
+        .await
+        .map_err(|err| {
+            gst::error_msg!(
+                gst::ResourceError::Failed,
+                ["PATCH request failed {}: {:?}", url, err]
+            )
+        })?;
+
+    match resp.status() {
+        s if s.is_success() => Ok(()),
+        s => Err(gst::error_msg!(
+            gst::ResourceError::Failed,
+            ["Unexpected response to the answer: {}", s.as_str()]
+        )),
+    }
+}
+
+async fn delete_resource(
+    url: reqwest::Url,
+    auth_token: Option<String>,
+) -> Result<StatusCode, ErrorMessage> {
+    let client = build_reqwest_client(reqwest::redirect::Policy::default());
+
+    client
+        .delete(url.clone())
+        .headers(auth_headers(auth_token.as_deref())?)
+        .send()
+        .await
+        .map(|resp| resp.status())
+        .map_err(|err| {
+            gst::error_msg!(
+                gst::ResourceError::Failed,
+                ["DELETE request failed {}: {:?}", url, err]
+            )
+        })
+}
+
+// WHEP server implementation
+
+const ROOT: &str = "whep";
+const ENDPOINT_PATH: &str = "endpoint";
+const RESOURCE_PATH: &str = "resource";
+const DEFAULT_HOST_ADDR: &str = "http://127.0.0.1:9090";
+const DEFAULT_STUN_SERVER: Option<&str> = Some("stun://stun.l.google.com:19302");
+
+struct WhepServerSettings {
+    host_addr: Url,
+    stun_server: Option<String>,
+    turn_servers: gst::Array,
+    timeout: u32,
+    auth_token: Option<String>,
+    cors_allowed_origins: gst::Array,
+}
+
+impl Default for WhepServerSettings {
+    fn default() -> Self {
+        Self {
+            host_addr: Url::parse(DEFAULT_HOST_ADDR).unwrap(),
+            stun_server: DEFAULT_STUN_SERVER.map(String::from),
+            turn_servers: gst::Array::new(Vec::new() as Vec<glib::SendValue>),
+            timeout: DEFAULT_TIMEOUT,
+            auth_token: None,
+            cors_allowed_origins: gst::Array::new(Vec::new() as Vec<glib::SendValue>),
+        }
+    }
+}
+
+#[derive(Default)]
+struct WhepServerTask {
+    shutdown_signal: Option<tokio::sync::oneshot::Sender<()>>,
+    handle: Option<tokio::task::JoinHandle<()>>,
+}
+
+/// A WHEP resource, created for each player
+struct WhepServerSession {
+    // Hands the answer, holding all the candidates, to the POST request
+    answer: Option<oneshot::Sender<Option<SDPMessage>>>,
+}
+
+#[derive(Default)]
+pub struct WhepServer {
+    settings: Mutex<WhepServerSettings>,
+    task: Mutex<WhepServerTask>,
+    // Keyed by resource id, which is also the id of the webrtcsink session
+    sessions: Mutex<HashMap<String, WhepServerSession>>,
+}
+
+impl WhepServer {
+    fn on_webrtcbin_ready(&self, session_id: &str, webrtcbin: &gst::Element) {
+        let obj_weak = self.obj().downgrade();
+        let session_id = session_id.to_string();
+        webrtcbin.connect_notify(Some("ice-gathering-state"), move |webrtcbin, _pspec| {
+            let Some(obj) = obj_weak.upgrade() else {
+                return;
+            };
+
+            let state = webrtcbin.property::<WebRTCICEGatheringState>("ice-gathering-state");
+
+            match state {
+                WebRTCICEGatheringState::Gathering => {
+                    gst::info!(CAT, obj: obj, "ICE gathering started");
+                }
+                WebRTCICEGatheringState::Complete => {
+                    gst::info!(CAT, obj: obj, "ICE gathering complete for {session_id}");
+
+                    let answer = webrtcbin
+                        .property::<Option<WebRTCSessionDescription>>("local-description")
+                        .map(|answer| answer.sdp());
+                    let sender = obj
+                        .imp()
+                        .sessions
+                        .lock()
+                        .unwrap()
+                        .get_mut(&session_id)
+                        .and_then(|session| session.answer.take());
+                    if let Some(sender) = sender {
+                        let _ = sender.send(answer);
+                    }
+                }
+                _ => (),
+            }
+        });
+    }
+
+    fn authorized(&self, authorization: Option<&str>) -> bool {
+        let settings = self.settings.lock().unwrap();
+        let Some(token) = settings.auth_token.as_deref() else {
+            return true;
+        };
+
+        authorization.and_then(|value| value.strip_prefix("Bearer ")) == Some(token)
+    }
+
+    fn ice_server_links(&self) -> HeaderMap {
+        let settings = self.settings.lock().unwrap();
+        ice_server_links(settings.stun_server.as_deref(), &settings.turn_servers)
+    }
+
+    // Forgets about a resource whose negotiation failed, webrtcsink removes
+    // its session
+    fn abort_session(&self, session_id: &str) {
+        self.sessions.lock().unwrap().remove(session_id);
+        self.obj()
+            .emit_by_name::<bool>("session-ended", &[&session_id]);
+    }
+
+    fn error_response(status: http::StatusCode, msg: &str) -> http::Response<Body> {
+        http::Response::builder()
+            .status(status)
+            .body(Body::from(msg.to_string()))
+            .unwrap()
+    }
+
+    fn unauthorized_response() -> http::Response<Body> {
+        let mut res = Self::error_response(
+            http::StatusCode::UNAUTHORIZED,
+            "Missing or invalid bearer token",
+        );
+        res.headers_mut().insert(
+            http::header::WWW_AUTHENTICATE,
+            HeaderValue::from_static("Bearer"),
+        );
+
+        res
+    }
+
+    async fn options_handler(
+        &self,
+        authorization: Option<String>,
+    ) -> Result<http::Response<Body>, warp::Rejection> {
+        let mut res = http::Response::builder()
+            .status(http::StatusCode::NO_CONTENT)
+            .header("Accept-Post", CONTENT_SDP)
+            .body(Body::empty())
+            .unwrap();
+
+        // The TURN credentials are only given to authorized players
+        if self.authorized(authorization.as_deref()) {
+            res.headers_mut().extend(self.ice_server_links());
+        }
+
+        Ok(res)
+    }
+
+    async fn post_handler(
+        &self,
+        authorization: Option<String>,
+        body: warp::hyper::body::Bytes,
+    ) -> Result<http::Response<Body>, warp::Rejection> {
+        if !self.authorized(authorization.as_deref()) {
+            gst::warning!(CAT, imp: self, "Rejecting unauthorized POST request");
+            return Ok(Self::unauthorized_response());
+        }
+
+        let offer_sdp = match SDPMessage::parse_buffer(body.as_ref()) {
+            Ok(offer_sdp) => offer_sdp,
+            Err(err) => {
+                gst::error!(CAT, imp: self, "Could not parse offer SDP: {err}");
+                return Ok(Self::error_response(
+                    http::StatusCode::BAD_REQUEST,
+                    "Invalid SDP offer",
+                ));
+            }
+        };
+
+        // Each player gets its own resource and webrtcsink session
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = oneshot::channel();
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(session_id.clone(), WhepServerSession { answer: Some(tx) });
+
+        gst::info!(CAT, imp: self, "New player, resource {session_id}");
+
+        let offer = WebRTCSessionDescription::new(WebRTCSDPType::Offer, offer_sdp);
+        self.obj().emit_by_name::<()>(
+            "session-requested",
+            &[&session_id, &session_id, &Some(offer)],
+        );
+
+        let timeout = self.settings.lock().unwrap().timeout;
+        let answer = if timeout == 0 {
+            rx.await
+                .map_err(|_| http::StatusCode::INTERNAL_SERVER_ERROR)
+        } else {
+            match tokio::time::timeout(Duration::from_secs(timeout.into()), rx).await {
+                Ok(answer) => answer.map_err(|_| http::StatusCode::INTERNAL_SERVER_ERROR),
+                Err(_) => Err(http::StatusCode::REQUEST_TIMEOUT),
+            }
+        };
+
+        let answer = match answer {
+            Ok(Some(answer)) => answer.as_text().ok(),
+            Ok(None) => None,
+            Err(status) => {
+                gst::error!(CAT, imp: self, "No answer for {session_id}: {status}");
+                self.abort_session(&session_id);
+                return Ok(Self::error_response(status, "Failed to create the answer"));
+            }
+        };
+
+        let Some(answer) = answer else {
+            gst::error!(CAT, imp: self, "SDP answer for {session_id} is empty");
+            self.abort_session(&session_id);
+            return Ok(Self::error_response(
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                "SDP answer is empty",
+            ));
+        };
+
+        gst::debug!(CAT, imp: self, "Answer for {session_id}: {answer}");
+
+        let resource_url = format!("/{ROOT}/{RESOURCE_PATH}/{session_id}");
+        let mut res = http::Response::builder()
+            .status(http::StatusCode::CREATED)
+            .header(CONTENT_TYPE, CONTENT_SDP)
+            .header(LOCATION, resource_url)
+            .body(Body::from(answer))
+            .unwrap();
+        res.headers_mut().extend(self.ice_server_links());
+
+        Ok(res)
+    }
+
+    async fn delete_handler(
+        &self,
+        id: String,
+        authorization: Option<String>,
+    ) -> Result<http::Response<Body>, warp::Rejection> {
+        if !self.authorized(authorization.as_deref()) {
+            gst::warning!(CAT, imp: self, "Rejecting unauthorized DELETE request");
+            return Ok(Self::unauthorized_response());
+        }
+
+        if self.sessions.lock().unwrap().remove(&id).is_none() {
+            gst::error!(CAT, imp: self, "DELETE requested for unknown resource {id}");
+            return Ok(Self::error_response(
+                http::StatusCode::NOT_FOUND,
+                "Unknown resource",
+            ));
+        }
+
+        gst::info!(CAT, imp: self, "Ending session {id}");
+        self.obj().emit_by_name::<bool>("session-ended", &[&id]);
+
+        Ok(warp::reply::reply().into_response())
+    }
+
+    fn serve(&self) -> Option<WhepServerTask> {
+        let (addr, cors_allowed_origins) = {
+            let settings = self.settings.lock().unwrap();
+            (
+                settings.host_addr.socket_addrs(|| None),
+                settings
+                    .cors_allowed_origins
+                    .iter()
+                    .filter_map(|origin| origin.get::<String>().ok())
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        let addr: SocketAddr = match addr {
+            Ok(addrs) if !addrs.is_empty() => addrs[0],
+            res => {
+                gst::error!(CAT, imp: self, "error getting addr from uri {res:?}");
+                self.obj().emit_by_name::<()>(
+                    "error",
+                    &[&format!("Unable to start WHEP Server: {res:?}")],
+                );
+                return None;
+            }
+        };
+        gst::info!(CAT, imp: self, "using {addr:?} as address");
+
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+
+        let prefix = warp::path(ROOT);
+
+        let self_weak = self.downgrade();
+
+        // POST /endpoint
+        let post_filter = warp::post()
+            .and(warp::path(ENDPOINT_PATH))
+            .and(warp::path::end())
+            .and(warp::header::exact(CONTENT_TYPE.as_str(), CONTENT_SDP))
+            .and(warp::header::optional::<String>(AUTHORIZATION.as_str()))
+            .and(warp::body::bytes())
+            .and_then(move |authorization, body| {
+                let s = self_weak.upgrade();
+                async {
+                    let self_ = s.expect("Need to have the ObjectRef");
+                    self_.post_handler(authorization, body).await
+                }
+            });
+
+        let self_weak = self.downgrade();
+
+        // OPTIONS /endpoint
+        let options_filter = warp::options()
+            .and(warp::path(ENDPOINT_PATH))
+            .and(warp::path::end())
+            .and(warp::header::optional::<String>(AUTHORIZATION.as_str()))
+            .and_then(move |authorization| {
+                let s = self_weak.upgrade();
+                async {
+                    let self_ = s.expect("Need to have the ObjectRef");
+                    self_.options_handler(authorization).await
+                }
+            });
+
+        let self_weak = self.downgrade();
+
+        // DELETE /resource/:id
+        let delete_filter = warp::delete()
+            .and(warp::path(RESOURCE_PATH))
+            .and(warp::path::param::<String>())
+            .and(warp::path::end())
+            .and(warp::header::optional::<String>(AUTHORIZATION.as_str()))
+            .and_then(move |id, authorization| {
+                let s = self_weak.upgrade();
+                async {
+                    let self_ = s.expect("Need to have the ObjectRef");
+                    self_.delete_handler(id, authorization).await
+                }
+            });
+
+        let api = prefix
+            .and(post_filter)
+            .or(prefix.and(options_filter))
+            .or(prefix.and(delete_filter))
+            .with(cors(&cors_allowed_origins));
+
+        let s = warp::serve(api);
+        let handle = RUNTIME.spawn(async move {
+            let (_, server) = s.bind_with_graceful_shutdown(addr, async move {
+                match rx.await {
+                    Ok(_) => gst::debug!(CAT, "Server shut down signal received"),
+                    Err(e) => gst::error!(CAT, "{e:?}: Sender dropped"),
+                }
+            });
+
+            server.await;
+            gst::debug!(CAT, "Stopped the server task...");
+        });
+
+        gst::debug!(CAT, imp: self, "Started the server...");
+        Some(WhepServerTask {
+            shutdown_signal: Some(tx),
+            handle: Some(handle),
+        })
+    }
+}
+
+impl SignallableImpl for WhepServer {
+    fn start(&self) {
+        gst::info!(CAT, imp: self, "starting the WHEP server");
+        if let Some(task) = self.serve() {
+            *self.task.lock().unwrap() = task;
+        }
+    }
+
+    fn stop(&self) {
+        let task = std::mem::take(&mut *self.task.lock().unwrap());
+
+        if let Some(tx) = task.shutdown_signal {
+            if tx.send(()).is_err() {
+                gst::error!(CAT, imp: self, "Failed to send shutdown signal. Receiver dropped");
+            }
+        }
+
+        if let Some(handle) = task.handle {
+            gst::debug!(CAT, imp: self, "Await server handle to join");
+            RUNTIME.block_on(async {
+                if let Err(e) = handle.await {
+                    gst::error!(CAT, imp: self, "Failed to join server handle: {e:?}");
+                };
+            });
+        }
+
+        self.sessions.lock().unwrap().clear();
+
+        gst::info!(CAT, imp: self, "stopped the WHEP server");
+    }
+
+    fn send_sdp(&self, _session_id: &str, _sdp: &WebRTCSessionDescription) {
+        // The answer is sent once ICE gathering is complete
+    }
+
+    fn add_ice(
+        &self,
+        _session_id: &str,
+        _candidate: &str,
+        _sdp_m_line_index: u32,
+        _sdp_mid: Option<String>,
+    ) {
+        // Candidates are sent as part of the answer
+    }
+
+    fn end_session(&self, session_id: &str) {
+        // The player gets a 404 for its resource from now on
+        self.sessions.lock().unwrap().remove(session_id);
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for WhepServer {
+    const NAME: &'static str = "GstWhepServerSignaller";
+    type Type = super::WhepServerSignaller;
+    type ParentType = glib::Object;
+    type Interfaces = (Signallable,);
+}
+
+impl ObjectImpl for WhepServer {
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        self.obj().connect_closure(
+            "webrtcbin-ready",
+            false,
+            glib::closure!(|signaller: &super::WhepServerSignaller,
+                            session_id: &str,
+                            webrtcbin: &gst::Element| {
+                signaller.imp().on_webrtcbin_ready(session_id, webrtcbin);
+            }),
+        );
+    }
+
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+            vec![
+                glib::ParamSpecString::builder("host-addr")
+                    .nick("Host address")
+                    .blurb("The host address of the WHEP endpoint e.g., http://127.0.0.1:9090")
+                    .default_value(DEFAULT_HOST_ADDR)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("stun-server")
+                    .nick("STUN Server")
+                    .blurb("The STUN server of the form stun://hostname:port")
+                    .default_value(DEFAULT_STUN_SERVER)
+                    .build(),
+                gst::ParamSpecArray::builder("turn-servers")
+                    .nick("List of TURN Servers to use")
+                    .blurb("The TURN servers of the form <\"turn(s)://username:password@host:port\", \"turn(s)://username1:password1@host1:port1\">")
+                    .element_spec(&glib::ParamSpecString::builder("turn-server")
+                        .nick("TURN Server")
+                        .blurb("The TURN server of the form turn(s)://username:password@host:port.")
+                        .build()
+                    )
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("timeout")
+                    .nick("Timeout")
+                    .blurb("Value in seconds to wait for the SDP answer before failing the POST request (0 = No timeout).")
+                    .maximum(3600)
+                    .default_value(DEFAULT_TIMEOUT)
+                    .build(),
+                glib::ParamSpecString::builder("auth-token")
+                    .nick("Authorization Token")
+                    .blurb("Token players must send in the HTTP Header as 'Bearer <auth-token>', any player is accepted if not set")
+                    .mutable_ready()
+                    .build(),
+                gst::ParamSpecArray::builder("cors-allowed-origins")
+                    .nick("CORS allowed origins")
+                    .blurb("Origins of the web players allowed to use the server, e.g. <\"https://example.com\">. Any origin is allowed if empty")
+                    .element_spec(&glib::ParamSpecString::builder("origin")
+                        .nick("Origin")
+                        .blurb("An origin of the form scheme://host[:port]")
+                        .build()
+                    )
+                    .mutable_ready()
+                    .build(),
+            ]
+        });
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let mut settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "host-addr" => {
+                let host_addr = value.get::<&str>().expect("type checked upstream");
+                match Url::parse(host_addr) {
+                    Ok(host_addr) => settings.host_addr = host_addr,
+                    Err(e) => {
+                        gst::error!(CAT, "Couldn't set the host address as {e:?}, fallback to the default value {DEFAULT_HOST_ADDR:?}");
+                    }
+                }
+            }
+            "stun-server" => {
+                settings.stun_server = value
+                    .get::<Option<String>>()
+                    .expect("type checked upstream")
+            }
+            "turn-servers" => {
+                settings.turn_servers = value.get::<gst::Array>().expect("type checked upstream")
+            }
+            "timeout" => settings.timeout = value.get().unwrap(),
+            "auth-token" => settings.auth_token = value.get().unwrap(),
+            "cors-allowed-origins" => {
+                settings.cors_allowed_origins =
+                    value.get::<gst::Array>().expect("type checked upstream")
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "host-addr" => settings.host_addr.to_string().to_value(),
+            "stun-server" => settings.stun_server.to_value(),
+            "turn-servers" => settings.turn_servers.to_value(),
+            "timeout" => settings.timeout.to_value(),
+            "auth-token" => settings.auth_token.to_value(),
+            "cors-allowed-origins" => settings.cors_allowed_origins.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signaller::SignallableExt;
+    use crate::whep_signaller::WhepServerSignaller;
+    use std::sync::Arc;
+    use warp::hyper::body::Bytes;
+
+    const OFFER: &str = "v=0\r\no=- 0 0 IN IP4 127.0.0.1\r\ns=-\r\nt=0 0\r\n\
+        m=video 9 UDP/TLS/RTP/SAVPF 96\r\na=rtpmap:96 VP8/90000\r\na=recvonly\r\n";
+    const ANSWER: &str = "v=0\r\no=- 1 0 IN IP4 127.0.0.1\r\ns=-\r\nt=0 0\r\n\
+        m=video 9 UDP/TLS/RTP/SAVPF 96\r\na=rtpmap:96 VP8/90000\r\na=sendonly\r\n";
+    const SERVER_OFFER: &str = "v=0\r\no=- 2 0 IN IP4 127.0.0.1\r\ns=-\r\nt=0 0\r\n\
+        m=video 9 UDP/TLS/RTP/SAVPF 96\r\na=rtpmap:96 VP8/90000\r\na=sendonly\r\n";
+    const ICE_SERVER: &str = "<stun:stun.example.net>; rel=\"ice-server\"";
+
+    #[derive(Default)]
+    struct Requests {
+        auth: Vec<Option<String>>,
+        answers: Vec<String>,
+        deleted: bool,
+    }
+
+    fn sdp_response(status: http::StatusCode, sdp: &str) -> http::Response<String> {
+        http::Response::builder()
+            .status(status)
+            .header("location", "/whep/resource/1")
+            .header("link", ICE_SERVER)
+            .header("content-type", CONTENT_SDP)
+            .body(sdp.to_string())
+            .unwrap()
+    }
+
+    // Stand-in WHEP server, answering offers and making one when the POST
+    // body is empty
+    fn serve(requests: Arc<Mutex<Requests>>) -> SocketAddr {
+        let endpoint = warp::post()
+            .and(warp::path!("whep" / "endpoint"))
+            .and(warp::header::optional::<String>("authorization"))
+            .and(warp::body::bytes())
+            .map({
+                let requests = requests.clone();
+                move |auth: Option<String>, body: Bytes| {
+                    requests.lock().unwrap().auth.push(auth);
+
+                    if body.is_empty() {
+                        sdp_response(http::StatusCode::CREATED, SERVER_OFFER)
+                    } else if body.as_ref() == OFFER.as_bytes() {
+                        sdp_response(http::StatusCode::CREATED, ANSWER)
+                    } else {
+                        http::Response::builder()
+                            .status(http::StatusCode::BAD_REQUEST)
+                            .body(String::new())
+                            .unwrap()
+                    }
+                }
+            });
+
+        let options = warp::options()
+            .and(warp::path!("whep" / "endpoint"))
+            .map(|| {
+                http::Response::builder()
+                    .status(http::StatusCode::NO_CONTENT)
+                    .header("link", ICE_SERVER)
+                    .body(String::new())
+                    .unwrap()
+            });
+
+        let redirect = warp::post().and(warp::path!("redirect")).map(|| {
+            http::Response::builder()
+                .status(http::StatusCode::TEMPORARY_REDIRECT)
+                .header("location", "/whep/endpoint")
+                .body(String::new())
+                .unwrap()
+        });
+
+        let patch = warp::patch()
+            .and(warp::path!("whep" / "resource" / "1"))
+            .and(warp::body::bytes())
+            .map({
+                let requests = requests.clone();
+                move |body: Bytes| {
+                    requests
+                        .lock()
+                        .unwrap()
+                        .answers
+                        .push(String::from_utf8_lossy(&body).to_string());
+                    http::StatusCode::NO_CONTENT
+                }
+            });
+
+        let delete = warp::delete()
+            .and(warp::path!("whep" / "resource" / "1"))
+            .map(move || {
+                requests.lock().unwrap().deleted = true;
+                http::StatusCode::OK
+            });
+
+        let _guard = RUNTIME.enter();
+        let (addr, server) = warp::serve(endpoint.or(options).or(redirect).or(patch).or(delete))
+            .bind_ephemeral(([127, 0, 0, 1], 0));
+        RUNTIME.spawn(server);
+
+        addr
+    }
+
+    fn url(addr: SocketAddr, path: &str) -> reqwest::Url {
+        reqwest::Url::parse(&format!("http://{addr}{path}")).unwrap()
+    }
+
+    #[test]
+    fn test_post_offer_and_delete() {
+        gst::init().unwrap();
+
+        let requests = Arc::new(Mutex::new(Requests::default()));
+        let addr = serve(requests.clone());
+
+        let resource = RUNTIME
+            .block_on(post(
+                url(addr, "/whep/endpoint"),
+                Some("secret".to_string()),
+                Some(OFFER.to_string()),
+            ))
+            .unwrap();
+
+        assert_eq!(resource.url, url(addr, "/whep/resource/1"));
+        assert_eq!(
+            resource.sdp.as_text().unwrap(),
+            SDPMessage::parse_buffer(ANSWER.as_bytes())
+                .unwrap()
+                .as_text()
+                .unwrap()
+        );
+        assert_eq!(
+            resource.headers.get("link").unwrap().to_str().unwrap(),
+            ICE_SERVER
+        );
+        assert_eq!(
+            requests.lock().unwrap().auth,
+            vec![Some("Bearer secret".to_string())]
+        );
+
+        let status = RUNTIME
+            .block_on(delete_resource(resource.url, None))
+            .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert!(requests.lock().unwrap().deleted);
+    }
+
+    #[test]
+    fn test_options_link_headers() {
+        gst::init().unwrap();
+
+        let requests = Arc::new(Mutex::new(Requests::default()));
+        let addr = serve(requests);
+
+        let headers = RUNTIME
+            .block_on(options(url(addr, "/whep/endpoint"), None))
+            .unwrap();
+        assert_eq!(headers.get("link").unwrap().to_str().unwrap(), ICE_SERVER);
+
+        let webrtcbin = gst::ElementFactory::make("webrtcbin").build().unwrap();
+        set_ice_servers(&webrtcbin, &headers).unwrap();
+        assert!(webrtcbin
+            .property::<Option<String>>("stun-server")
+            .is_some_and(|server| server.starts_with("stun://stun.example.net")));
+    }
+
+    #[test]
+    fn test_post_follows_redirects() {
+        gst::init().unwrap();
+
+        let requests = Arc::new(Mutex::new(Requests::default()));
+        let addr = serve(requests.clone());
+
+        let resource = RUNTIME
+            .block_on(post(
+                url(addr, "/redirect"),
+                Some("secret".to_string()),
+                Some(OFFER.to_string()),
+            ))
+            .unwrap();
+
+        assert_eq!(resource.url, url(addr, "/whep/resource/1"));
+        // The token is sent to the new location as well
+        assert_eq!(
+            requests.lock().unwrap().auth,
+            vec![Some("Bearer secret".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_server_offer() {
+        gst::init().unwrap();
+
+        let requests = Arc::new(Mutex::new(Requests::default()));
+        let addr = serve(requests.clone());
+
+        let resource = RUNTIME
+            .block_on(post(url(addr, "/whep/endpoint"), None, None))
+            .unwrap();
+        assert_eq!(
+            resource.sdp.as_text().unwrap(),
+            SDPMessage::parse_buffer(SERVER_OFFER.as_bytes())
+                .unwrap()
+                .as_text()
+                .unwrap()
+        );
+
+        RUNTIME
+            .block_on(patch_answer(resource.url.clone(), None, OFFER.to_string()))
+            .unwrap();
+        assert_eq!(requests.lock().unwrap().answers, vec![OFFER.to_string()]);
+    }
+
+    #[test]
+    fn test_post_rejected() {
+        gst::init().unwrap();
+
+        let requests = Arc::new(Mutex::new(Requests::default()));
+        let addr = serve(requests);
+
+        assert!(RUNTIME
+            .block_on(post(
+                url(addr, "/whep/endpoint"),
+                None,
+                Some("not an offer".to_string()),
+            ))
+            .is_err());
+    }
+
+    fn start_server(
+        auth_token: Option<&str>,
+        cors_allowed_origins: &[&str],
+    ) -> (WhepServerSignaller, reqwest::Url) {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let host_addr = format!("http://127.0.0.1:{port}");
+
+        let signaller = glib::Object::builder::<WhepServerSignaller>()
+            .property("host-addr", &host_addr)
+            .property("stun-server", "stun://stun.example.net:3478")
+            .property("auth-token", auth_token)
+            .property("timeout", 1u32)
+            .property(
+                "cors-allowed-origins",
+                gst::Array::new(cors_allowed_origins.iter().copied()),
+            )
+            .build();
+        signaller.start();
+
+        (signaller, reqwest::Url::parse(&host_addr).unwrap())
+    }
+
+    fn request(
+        method: reqwest::Method,
+        url: reqwest::Url,
+        headers: &[(&'static str, &str)],
+        body: &str,
+    ) -> reqwest::Response {
+        let mut request = reqwest::Client::new()
+            .request(method, url)
+            .body(body.to_string());
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+
+        RUNTIME.block_on(request.send()).unwrap()
+    }
+
+    #[test]
+    fn test_server_options() {
+        gst::init().unwrap();
+
+        let (signaller, host) = start_server(None, &[]);
+
+        let resp = request(
+            reqwest::Method::OPTIONS,
+            host.join("/whep/endpoint").unwrap(),
+            &[],
+            "",
+        );
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert_eq!(resp.headers().get("accept-post").unwrap(), CONTENT_SDP);
+        assert_eq!(
+            resp.headers().get("link").unwrap(),
+            "<stun:stun.example.net:3478>; rel=\"ice-server\""
+        );
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_server_auth() {
+        gst::init().unwrap();
+
+        let (signaller, host) = start_server(Some("secret"), &[]);
+        let endpoint = host.join("/whep/endpoint").unwrap();
+        let resource = host.join("/whep/resource/unknown").unwrap();
+
+        let resp = request(
+            reqwest::Method::POST,
+            endpoint.clone(),
+            &[("content-type", CONTENT_SDP)],
+            OFFER,
+        );
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let resp = request(
+            reqwest::Method::POST,
+            endpoint.clone(),
+            &[
+                ("content-type", CONTENT_SDP),
+                ("authorization", "Bearer wrong"),
+            ],
+            OFFER,
+        );
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        // The ICE servers are only advertised to authorized players
+        let resp = request(reqwest::Method::OPTIONS, endpoint, &[], "");
+        assert!(resp.headers().get("link").is_none());
+
+        let resp = request(reqwest::Method::DELETE, resource.clone(), &[], "");
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let resp = request(
+            reqwest::Method::DELETE,
+            resource,
+            &[("authorization", "Bearer secret")],
+            "",
+        );
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_server_cors() {
+        gst::init().unwrap();
+
+        let (signaller, host) = start_server(None, &["https://player.example.com/"]);
+        let endpoint = host.join("/whep/endpoint").unwrap();
+
+        let resp = request(
+            reqwest::Method::OPTIONS,
+            endpoint.clone(),
+            &[
+                ("origin", "https://player.example.com"),
+                ("access-control-request-method", "POST"),
+            ],
+            "",
+        );
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers().get("access-control-allow-origin").unwrap(),
+            "https://player.example.com"
+        );
+
+        let resp = request(
+            reqwest::Method::OPTIONS,
+            endpoint,
+            &[
+                ("origin", "https://other.example.com"),
+                ("access-control-request-method", "POST"),
+            ],
+            "",
+        );
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_server_answer_timeout() {
+        gst::init().unwrap();
+
+        let (signaller, host) = start_server(None, &[]);
+
+        let requested = Arc::new(Mutex::new(None));
+        let ended = Arc::new(Mutex::new(None));
+        signaller.connect("session-requested", false, {
+            let requested = requested.clone();
+            move |args| {
+                let session_id = args[1].get::<String>().unwrap();
+                let offer = args[3]
+                    .get::<Option<WebRTCSessionDescription>>()
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(offer.type_(), WebRTCSDPType::Offer);
+                *requested.lock().unwrap() = Some(session_id);
+                None
+            }
+        });
+        signaller.connect("session-ended", false, {
+            let ended = ended.clone();
+            move |args| {
+                *ended.lock().unwrap() = Some(args[1].get::<String>().unwrap());
+                Some(false.to_value())
+            }
+        });
+
+        // No webrtcsink answers the offer
+        let resp = request(
+            reqwest::Method::POST,
+            host.join("/whep/endpoint").unwrap(),
+            &[("content-type", CONTENT_SDP)],
+            OFFER,
+        );
+        assert_eq!(resp.status(), StatusCode::REQUEST_TIMEOUT);
+
+        let requested = requested.lock().unwrap().clone();
+        assert!(requested.is_some());
+        assert_eq!(requested, *ended.lock().unwrap());
+
+        signaller.stop();
+    }
+}
@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::signaller::Signallable;
+use gst::glib;
+
+mod imp;
+
+glib::wrapper! {
+    pub struct WhepClientSignaller(ObjectSubclass<imp::WhepClient>) @implements Signallable;
+}
+
+glib::wrapper! {
+    pub struct WhepServerSignaller(ObjectSubclass<imp::WhepServer>) @implements Signallable;
+}
+
+impl Default for WhepClientSignaller {
+    fn default() -> Self {
+        glib::Object::new()
+    }
+}
+
+impl Default for WhepServerSignaller {
+    fn default() -> Self {
+        glib::Object::new()
+    }
+}
@@ -2,8 +2,8 @@
 
 use crate::signaller::{Signallable, SignallableImpl};
 use crate::utils::{
-    build_link_header, build_reqwest_client, parse_redirect_location, set_ice_servers, wait,
-    wait_async, WaitError,
+    build_reqwest_client, cors, ice_server_links, parse_redirect_location, set_ice_servers, wait,
+    wait_async, IceFragment, WaitError,
 };
 use crate::RUNTIME;
 use async_recursion::async_recursion;
@@ -16,16 +16,18 @@ use once_cell::sync::Lazy;
 use reqwest::header::HeaderMap;
 use reqwest::header::HeaderValue;
 use reqwest::StatusCode;
+use std::collections::HashMap;
 use std::sync::Mutex;
 
 use core::time::Duration;
-use crossbeam_channel::unbounded;
+use futures::channel::mpsc;
+use futures::StreamExt;
 use std::net::SocketAddr;
 use url::Url;
 use warp::{
     http,
     hyper::{
-        header::{CONTENT_TYPE, LINK},
+        header::{AUTHORIZATION, CONTENT_TYPE},
         Body,
     },
     Filter, Reply,
@@ -41,6 +43,13 @@ static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
 
 const MAX_REDIRECTS: u8 = 10;
 const DEFAULT_TIMEOUT: u32 = 15;
+const DEFAULT_TRICKLE_ICE: bool = false;
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+// How long a disconnected ICE connection is given to recover on its own
+// before restarting ICE
+const ICE_RESTART_DELAY: Duration = Duration::from_secs(5);
 
 const ROOT: &str = "whip";
 const ENDPOINT_PATH: &str = "endpoint";
@@ -54,8 +63,13 @@ const CONTENT_TRICKLE_ICE: &str = "application/trickle-ice-sdpfrag";
 #[derive(Debug)]
 enum WhipClientState {
     Stopped,
-    Post { redirects: u8 },
-    Running { whip_resource_url: String },
+    Post {
+        redirects: u8,
+    },
+    Running {
+        whip_resource_url: String,
+        etag: Option<String>,
+    },
 }
 
 impl Default for WhipClientState {
@@ -67,27 +81,107 @@ impl Default for WhipClientState {
 #[derive(Clone)]
 struct WhipClientSettings {
     whip_endpoint: Option<String>,
+    whip_endpoints: gst::Array,
     use_link_headers: bool,
     auth_token: Option<String>,
     timeout: u32,
+    trickle_ice: bool,
+    max_retries: u32,
 }
 
 impl Default for WhipClientSettings {
     fn default() -> Self {
         Self {
             whip_endpoint: None,
+            whip_endpoints: gst::Array::new(Vec::new() as Vec<glib::SendValue>),
             use_link_headers: false,
             auth_token: None,
             timeout: DEFAULT_TIMEOUT,
+            trickle_ice: DEFAULT_TRICKLE_ICE,
+            max_retries: DEFAULT_MAX_RETRIES,
+        }
+    }
+}
+
+impl WhipClientSettings {
+    /// The endpoints to publish to, by order of priority
+    fn endpoints(&self) -> Result<Vec<reqwest::Url>, String> {
+        let endpoints = if self.whip_endpoints.is_empty() {
+            self.whip_endpoint.iter().cloned().collect::<Vec<_>>()
+        } else {
+            self.whip_endpoints
+                .iter()
+                .filter_map(|endpoint| endpoint.get::<String>().ok())
+                .collect()
+        };
+
+        if endpoints.is_empty() {
+            return Err("WHIP endpoint URL must be set".to_string());
+        }
+
+        endpoints
+            .iter()
+            .map(|endpoint| {
+                reqwest::Url::parse(endpoint)
+                    .map_err(|err| format!("Invalid WHIP endpoint {endpoint:?}: {err}"))
+            })
+            .collect()
+    }
+}
+
+/// Failure of the POST request to an endpoint
+#[derive(Debug)]
+enum PostError {
+    /// Server errors, timeouts and connection failures, the request is
+    /// retried on the same endpoint after a delay
+    Transient {
+        msg: String,
+        retry_after: Option<Duration>,
+    },
+    /// The endpoint rejected the offer or replied with an invalid answer
+    Fatal(String),
+}
+
+impl std::fmt::Display for PostError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PostError::Transient { msg, .. } | PostError::Fatal(msg) => f.write_str(msg),
         }
     }
 }
 
+/// Requests sent with PATCH on the resource URL
+#[derive(Debug)]
+enum PatchRequest {
+    Candidate {
+        sdp_m_line_index: u32,
+        candidate: String,
+    },
+    EndOfCandidates,
+    IceRestart,
+}
+
+/// PATCH requests are sent one at a time from a single task so that each of
+/// them uses the ETag returned by the previous one
+#[derive(Default)]
+struct PatchQueue {
+    sender: Option<mpsc::UnboundedSender<PatchRequest>>,
+    // Taken by the task once the resource URL is known
+    receiver: Option<mpsc::UnboundedReceiver<PatchRequest>>,
+    task: Option<tokio::task::JoinHandle<()>>,
+    restarting_ice: bool,
+}
+
 #[derive(Default)]
 pub struct WhipClient {
     state: Mutex<WhipClientState>,
     settings: Mutex<WhipClientSettings>,
     canceller: Mutex<Option<futures::future::AbortHandle>>,
+    webrtcbin: Mutex<Option<glib::WeakRef<gst::Element>>>,
+    patches: Mutex<PatchQueue>,
+    patch_canceller: Mutex<Option<futures::future::AbortHandle>>,
+    // Index of the endpoint in use, or to try first
+    endpoint_index: Mutex<usize>,
 }
 
 impl WhipClient {
@@ -96,33 +190,7 @@ impl WhipClient {
             .emit_by_name::<()>("error", &[&format!("Error: {msg}")]);
     }
 
-    fn handle_future_error(&self, err: WaitError) {
-        match err {
-            WaitError::FutureAborted => {
-                gst::warning!(CAT, imp: self, "Future aborted")
-            }
-            WaitError::FutureError(err) => self.raise_error(err.to_string()),
-        };
-    }
-
-    async fn send_offer(&self, webrtcbin: &gst::Element) {
-        {
-            let mut state = self.state.lock().unwrap();
-            *state = WhipClientState::Post { redirects: 0 };
-            drop(state);
-        }
-
-        let local_desc =
-            webrtcbin.property::<Option<WebRTCSessionDescription>>("local-description");
-
-        let offer_sdp = match local_desc {
-            None => {
-                self.raise_error("Local description is not set".to_string());
-                return;
-            }
-            Some(offer) => offer,
-        };
-
+    async fn send_offer(&self, webrtcbin: &gst::Element, offer_sdp: WebRTCSessionDescription) {
         gst::debug!(
             CAT,
             imp: self,
@@ -130,24 +198,81 @@ impl WhipClient {
             offer_sdp.sdp().as_text()
         );
 
-        let timeout;
-        let endpoint;
-        {
+        let (endpoints, timeout, max_retries) = {
             let settings = self.settings.lock().unwrap();
-            timeout = settings.timeout;
-            endpoint =
-                reqwest::Url::parse(settings.whip_endpoint.as_ref().unwrap().as_str()).unwrap();
-            drop(settings);
+            match settings.endpoints() {
+                Ok(endpoints) => (endpoints, settings.timeout, settings.max_retries),
+                Err(err) => {
+                    drop(settings);
+                    self.raise_error(err);
+                    return;
+                }
+            }
+        };
+
+        // Starting from the current endpoint, every endpoint is tried once
+        let first = *self.endpoint_index.lock().unwrap();
+        let mut last_error = None;
+        for index in (0..endpoints.len()).map(|i| (first + i) % endpoints.len()) {
+            *self.endpoint_index.lock().unwrap() = index;
+            let endpoint = &endpoints[index];
+
+            let mut retries = 0;
+            let err = loop {
+                *self.state.lock().unwrap() = WhipClientState::Post { redirects: 0 };
+
+                let err = match wait_async(
+                    &self.canceller,
+                    self.do_post(offer_sdp.clone(), webrtcbin, endpoint.clone()),
+                    timeout,
+                )
+                .await
+                {
+                    Ok(Ok(())) => return,
+                    Ok(Err(err)) => err,
+                    Err(WaitError::FutureAborted) => {
+                        gst::warning!(CAT, imp: self, "Future aborted");
+                        return;
+                    }
+                    Err(WaitError::FutureError(err)) => PostError::Transient {
+                        msg: err.to_string(),
+                        retry_after: None,
+                    },
+                };
+
+                let PostError::Transient { retry_after, .. } = err else {
+                    break err;
+                };
+                if retries >= max_retries {
+                    break err;
+                }
+
+                let delay = retry_after
+                    .unwrap_or_else(|| RETRY_BASE_DELAY * 2u32.saturating_pow(retries))
+                    .min(RETRY_MAX_DELAY);
+                retries += 1;
+
+                gst::warning!(
+                    CAT,
+                    imp: self,
+                    "POST to {endpoint} failed: {err}, retry {retries}/{max_retries} in {delay:?}"
+                );
+
+                if let Err(WaitError::FutureAborted) =
+                    wait_async(&self.canceller, tokio::time::sleep(delay), 0).await
+                {
+                    gst::warning!(CAT, imp: self, "Future aborted");
+                    return;
+                }
+            };
+
+            gst::warning!(CAT, imp: self, "Giving up on endpoint {endpoint}: {err}");
+            last_error = Some(err);
         }
 
-        if let Err(e) = wait_async(
-            &self.canceller,
-            self.do_post(offer_sdp, webrtcbin, endpoint),
-            timeout,
-        )
-        .await
-        {
-            self.handle_future_error(e);
+        *self.state.lock().unwrap() = WhipClientState::Stopped;
+        if let Some(err) = last_error {
+            self.raise_error(format!("Failed to publish to any WHIP endpoint: {err}"));
         }
     }
 
@@ -157,7 +282,7 @@ impl WhipClient {
         offer: gst_webrtc::WebRTCSessionDescription,
         webrtcbin: &gst::Element,
         endpoint: reqwest::Url,
-    ) {
+    ) -> Result<(), PostError> {
         let auth_token;
 
         {
@@ -174,8 +299,9 @@ impl WhipClient {
             redirects = match *state {
                 WhipClientState::Post { redirects } => redirects,
                 _ => {
-                    self.raise_error("Trying to do POST in unexpected state".to_string());
-                    return;
+                    return Err(PostError::Fatal(
+                        "Trying to do POST in unexpected state".to_string(),
+                    ));
                 }
             };
             drop(state);
@@ -214,10 +340,13 @@ impl WhipClient {
 
         match res {
             Ok(resp) => {
-                self.parse_endpoint_response(offer, resp, redirects, webrtcbin)
+                self.parse_endpoint_response(offer, resp, redirects, webrtcbin, endpoint)
                     .await
             }
-            Err(err) => self.raise_error(err.to_string()),
+            Err(err) => Err(PostError::Transient {
+                msg: err.to_string(),
+                retry_after: None,
+            }),
         }
     }
 
@@ -227,19 +356,11 @@ impl WhipClient {
         resp: reqwest::Response,
         redirects: u8,
         webrtcbin: &gst::Element,
-    ) {
+        endpoint: reqwest::Url,
+    ) -> Result<(), PostError> {
         gst::debug!(CAT, imp: self, "Parsing endpoint response");
 
-        let endpoint;
-        let use_link_headers;
-
-        {
-            let settings = self.settings.lock().unwrap();
-            endpoint =
-                reqwest::Url::parse(settings.whip_endpoint.as_ref().unwrap().as_str()).unwrap();
-            use_link_headers = settings.use_link_headers;
-            drop(settings);
-        }
+        let use_link_headers = self.settings.lock().unwrap().use_link_headers;
 
         gst::debug!(CAT, "response status: {}", resp.status());
 
@@ -247,8 +368,7 @@ impl WhipClient {
             StatusCode::OK | StatusCode::CREATED => {
                 if use_link_headers {
                     if let Err(e) = set_ice_servers(webrtcbin, resp.headers()) {
-                        self.raise_error(e.to_string());
-                        return;
+                        return Err(PostError::Fatal(e.to_string()));
                     };
                 }
 
@@ -261,31 +381,56 @@ impl WhipClient {
                 let location = match resp.headers().get(reqwest::header::LOCATION) {
                     Some(location) => location,
                     None => {
-                        self.raise_error(
+                        return Err(PostError::Fatal(
                             "Location header field should be present for WHIP resource URL"
                                 .to_string(),
-                        );
-                        return;
+                        ));
                     }
                 };
 
                 let location = match location.to_str() {
                     Ok(loc) => loc,
                     Err(e) => {
-                        self.raise_error(format!("Failed to convert location to string: {e}"));
-                        return;
+                        return Err(PostError::Fatal(format!(
+                            "Failed to convert location to string: {e}"
+                        )));
                     }
                 };
 
-                let url = reqwest::Url::parse(endpoint.as_str()).unwrap();
-
                 gst::debug!(CAT, imp: self, "WHIP resource: {:?}", location);
 
-                let url = match url.join(location) {
+                let url = match endpoint.join(location) {
                     Ok(joined_url) => joined_url,
                     Err(err) => {
-                        self.raise_error(format!("URL join operation failed: {err:?}"));
-                        return;
+                        return Err(PostError::Fatal(format!(
+                            "URL join operation failed: {err:?}"
+                        )));
+                    }
+                };
+
+                let etag = resp
+                    .headers()
+                    .get(reqwest::header::ETAG)
+                    .and_then(|etag| etag.to_str().ok())
+                    .map(String::from);
+
+                let answer = match resp.bytes().await {
+                    Ok(ans_bytes) => match gst_sdp::SDPMessage::parse_buffer(&ans_bytes) {
+                        Ok(ans_sdp) => gst_webrtc::WebRTCSessionDescription::new(
+                            gst_webrtc::WebRTCSDPType::Answer,
+                            ans_sdp,
+                        ),
+                        Err(err) => {
+                            return Err(PostError::Fatal(format!(
+                                "Could not parse answer SDP: {err}"
+                            )));
+                        }
+                    },
+                    Err(err) => {
+                        return Err(PostError::Transient {
+                            msg: err.to_string(),
+                            retry_after: None,
+                        })
                     }
                 };
 
@@ -294,31 +439,23 @@ impl WhipClient {
                     *state = match *state {
                         WhipClientState::Post { redirects: _r } => WhipClientState::Running {
                             whip_resource_url: url.to_string(),
+                            etag,
                         },
                         _ => {
-                            self.raise_error("Expected to be in POST state".to_string());
-                            return;
+                            return Err(PostError::Fatal(
+                                "Expected to be in POST state".to_string(),
+                            ));
                         }
                     };
                     drop(state);
                 }
 
-                match resp.bytes().await {
-                    Ok(ans_bytes) => match gst_sdp::SDPMessage::parse_buffer(&ans_bytes) {
-                        Ok(ans_sdp) => {
-                            let answer = gst_webrtc::WebRTCSessionDescription::new(
-                                gst_webrtc::WebRTCSDPType::Answer,
-                                ans_sdp,
-                            );
-                            self.obj()
-                                .emit_by_name::<()>("session-description", &[&"unique", &answer]);
-                        }
-                        Err(err) => {
-                            self.raise_error(format!("Could not parse answer SDP: {err}"));
-                        }
-                    },
-                    Err(err) => self.raise_error(err.to_string()),
-                }
+                self.start_patch_task();
+
+                self.obj()
+                    .emit_by_name::<()>("session-description", &[&"unique", &answer]);
+
+                Ok(())
             }
 
             s if s.is_redirection() => {
@@ -342,10 +479,9 @@ impl WhipClient {
                                      * POST request may support redirection.
                                      */
                                     WhipClientState::Running { .. } => {
-                                        self.raise_error(
+                                        return Err(PostError::Fatal(
                                             "Unexpected redirection in RUNNING state".to_string(),
-                                        );
-                                        return;
+                                        ));
                                     }
                                     WhipClientState::Stopped => unreachable!(),
                                 };
@@ -361,24 +497,320 @@ impl WhipClient {
 
                             self.do_post(offer, webrtcbin, redirect_url).await
                         }
-                        Err(e) => self.raise_error(e.to_string()),
+                        Err(e) => Err(PostError::Fatal(e.to_string())),
                     }
                 } else {
-                    self.raise_error("Too many redirects. Unable to connect.".to_string());
+                    Err(PostError::Fatal(
+                        "Too many redirects. Unable to connect.".to_string(),
+                    ))
                 }
             }
 
             s => {
-                match resp.bytes().await {
-                    Ok(r) => {
-                        let res = r.escape_ascii().to_string();
+                let retry_after = resp
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().parse::<u64>().ok())
+                    .map(Duration::from_secs);
+
+                let msg = match resp.bytes().await {
+                    Ok(r) => format!("Unexpected response: {} - {}", s.as_str(), r.escape_ascii()),
+                    Err(err) => err.to_string(),
+                };
+
+                if s.is_server_error() || s == StatusCode::TOO_MANY_REQUESTS {
+                    Err(PostError::Transient { msg, retry_after })
+                } else {
+                    Err(PostError::Fatal(msg))
+                }
+            }
+        }
+    }
+
+    fn queue_patch(&self, request: PatchRequest) {
+        let mut patches = self.patches.lock().unwrap();
+
+        if matches!(request, PatchRequest::IceRestart) {
+            if patches.restarting_ice {
+                return;
+            }
+            patches.restarting_ice = true;
+        }
+
+        if let Some(sender) = &patches.sender {
+            let _ = sender.unbounded_send(request);
+        }
+    }
+
+    fn start_patch_task(&self) {
+        let mut patches = self.patches.lock().unwrap();
+        let Some(mut receiver) = patches.receiver.take() else {
+            return;
+        };
+
+        let this_weak = self.obj().downgrade();
+        patches.task = Some(RUNTIME.spawn(async move {
+            // Cleared when the server doesn't support trickle ICE
+            let mut trickle = true;
 
-                        // FIXME: Check and handle 'Retry-After' header in case of server error
-                        self.raise_error(format!("Unexpected response: {} - {}", s.as_str(), res));
+            while let Some(request) = receiver.next().await {
+                let Some(this) = this_weak.upgrade() else {
+                    break;
+                };
+
+                match request {
+                    PatchRequest::IceRestart => {
+                        this.imp().restart_ice().await;
+                        this.imp().patches.lock().unwrap().restarting_ice = false;
                     }
-                    Err(err) => self.raise_error(err.to_string()),
+                    request if trickle => trickle = this.imp().trickle_candidates(request).await,
+                    _ => (),
+                }
+            }
+        }));
+    }
+
+    fn stop_patch_task(&self) {
+        if let Some(canceller) = &*self.patch_canceller.lock().unwrap() {
+            canceller.abort();
+        }
+
+        let mut patches = self.patches.lock().unwrap();
+        if let Some(task) = patches.task.take() {
+            task.abort();
+        }
+        *patches = PatchQueue::default();
+    }
+
+    fn webrtcbin(&self) -> Option<gst::Element> {
+        self.webrtcbin
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|webrtcbin| webrtcbin.upgrade())
+    }
+
+    async fn patch(
+        &self,
+        body: String,
+        if_match: Option<String>,
+    ) -> Result<reqwest::Response, WaitError> {
+        let (resource_url, etag) = match *self.state.lock().unwrap() {
+            WhipClientState::Running {
+                ref whip_resource_url,
+                ref etag,
+            } => (whip_resource_url.clone(), etag.clone()),
+            _ => {
+                return Err(WaitError::FutureError(gst::error_msg!(
+                    gst::ResourceError::Failed,
+                    ["PATCH requested in unexpected state"]
+                )))
+            }
+        };
+
+        let (auth_token, timeout) = {
+            let settings = self.settings.lock().unwrap();
+            (settings.auth_token.clone(), settings.timeout)
+        };
+
+        let mut headermap = HeaderMap::new();
+        headermap.insert(
+            reqwest::header::CONTENT_TYPE,
+            HeaderValue::from_static(CONTENT_TRICKLE_ICE),
+        );
+
+        if let Some(etag) = if_match.or(etag) {
+            if let Ok(etag) = HeaderValue::from_str(&etag) {
+                headermap.insert(reqwest::header::IF_MATCH, etag);
+            }
+        }
+
+        if let Some(token) = auth_token.as_ref() {
+            let bearer_token = "Bearer ".to_owned() + token;
+            headermap.insert(
+                reqwest::header::AUTHORIZATION,
+                HeaderValue::from_str(bearer_token.as_str())
+                    .expect("Failed to set auth token to header"),
+            );
+        }
+
+        gst::trace!(CAT, imp: self, "PATCH request on {resource_url}: {body}");
+
+        let client = build_reqwest_client(reqwest::redirect::Policy::none());
+        let future = client
+            .patch(resource_url)
+            .headers(headermap)
+            .body(body)
+            .send();
+
+        match wait_async(&self.patch_canceller, future, timeout).await? {
+            Ok(resp) => Ok(resp),
+            Err(err) => Err(WaitError::FutureError(gst::error_msg!(
+                gst::ResourceError::Failed,
+                ["PATCH request failed: {err}"]
+            ))),
+        }
+    }
+
+    fn update_etag(&self, headers: &HeaderMap) {
+        let Some(new_etag) = headers
+            .get(reqwest::header::ETAG)
+            .and_then(|etag| etag.to_str().ok())
+        else {
+            return;
+        };
+
+        if let WhipClientState::Running { ref mut etag, .. } = *self.state.lock().unwrap() {
+            *etag = Some(new_etag.to_string());
+        }
+    }
+
+    // Returns false if the server does not support trickle ICE
+    async fn trickle_candidates(&self, request: PatchRequest) -> bool {
+        let Some(sdp) = self.webrtcbin().and_then(|webrtcbin| {
+            webrtcbin
+                .property::<Option<WebRTCSessionDescription>>("local-description")
+                .map(|desc| desc.sdp())
+        }) else {
+            gst::warning!(CAT, imp: self, "No local description, can't send {request:?}");
+            return true;
+        };
+
+        let fragment = match request {
+            PatchRequest::Candidate {
+                sdp_m_line_index,
+                candidate,
+            } => IceFragment::for_media(&sdp, sdp_m_line_index).map(|mut fragment| {
+                let candidate = candidate.strip_prefix("candidate:").unwrap_or(&candidate);
+                fragment.media[0].candidates.push(candidate.to_string());
+                fragment
+            }),
+            PatchRequest::EndOfCandidates => all_media_fragment(&sdp).map(|mut fragment| {
+                for media in fragment.media.iter_mut() {
+                    media.end_of_candidates = true;
                 }
+                fragment
+            }),
+            PatchRequest::IceRestart => unreachable!(),
+        };
+
+        let Some(fragment) = fragment else {
+            gst::warning!(CAT, imp: self, "No media to send candidates for");
+            return true;
+        };
+
+        match self.patch(fragment.to_sdpfrag(), None).await {
+            Ok(resp) => match resp.status() {
+                StatusCode::NO_CONTENT | StatusCode::OK => {
+                    self.update_etag(resp.headers());
+                    true
+                }
+                StatusCode::METHOD_NOT_ALLOWED
+                | StatusCode::NOT_IMPLEMENTED
+                | StatusCode::UNSUPPORTED_MEDIA_TYPE => {
+                    gst::warning!(
+                        CAT,
+                        imp: self,
+                        "Server does not support trickle ICE ({}), not sending more candidates",
+                        resp.status()
+                    );
+                    false
+                }
+                status => {
+                    gst::warning!(CAT, imp: self, "Unexpected response to PATCH: {status}");
+                    true
+                }
+            },
+            Err(WaitError::FutureAborted) => true,
+            Err(WaitError::FutureError(err)) => {
+                gst::warning!(CAT, imp: self, "Failed to send candidates: {err}");
+                true
+            }
+        }
+    }
+
+    // Failures are not fatal, the connection failing over to the next endpoint
+    // if ICE does not recover
+    async fn restart_ice(&self) {
+        let Some(webrtcbin) = self.webrtcbin() else {
+            return;
+        };
+
+        gst::info!(CAT, imp: self, "Restarting ICE");
+
+        let options = gst::Structure::builder("options")
+            .field("ice-restart", true)
+            .build();
+        let offer = promise_reply(&webrtcbin, "create-offer", &options)
+            .await
+            .and_then(|reply| reply.get::<WebRTCSessionDescription>("offer").ok());
+
+        let Some(offer) = offer else {
+            gst::warning!(CAT, imp: self, "Failed to create ICE restart offer");
+            return;
+        };
+
+        webrtcbin.emit_by_name::<()>("set-local-description", &[&offer, &None::<gst::Promise>]);
+
+        let Some(fragment) = all_media_fragment(&offer.sdp()) else {
+            gst::warning!(CAT, imp: self, "ICE restart offer has no media");
+            return;
+        };
+
+        // ICE restart requests must use a wildcard If-Match as per the WHIP specification
+        let resp = match self
+            .patch(fragment.to_sdpfrag(), Some("*".to_string()))
+            .await
+        {
+            Ok(resp) => resp,
+            Err(err) => {
+                gst::warning!(CAT, imp: self, "ICE restart failed: {err:?}");
+                return;
+            }
+        };
+
+        if resp.status() != StatusCode::OK {
+            gst::warning!(
+                CAT,
+                imp: self,
+                "ICE restart rejected by the server: {}",
+                resp.status()
+            );
+            return;
+        }
+
+        self.update_etag(resp.headers());
+
+        let answer = match resp.text().await {
+            Ok(body) => IceFragment::parse(&body),
+            Err(err) => Err(err.into()),
+        };
+
+        let answer = match answer {
+            Ok(answer) => answer,
+            Err(err) => {
+                gst::warning!(CAT, imp: self, "Invalid ICE restart answer: {err}");
+                return;
             }
+        };
+
+        let Some(remote_desc) =
+            webrtcbin.property::<Option<WebRTCSessionDescription>>("remote-description")
+        else {
+            gst::warning!(CAT, imp: self, "No remote description to restart ICE with");
+            return;
+        };
+
+        let mut sdp = remote_desc.sdp();
+        answer.apply(&mut sdp);
+
+        gst::debug!(CAT, imp: self, "ICE restart answer: {:?}", sdp.as_text());
+
+        let answer = WebRTCSessionDescription::new(gst_webrtc::WebRTCSDPType::Answer, sdp);
+        let reply = promise_reply(&webrtcbin, "set-remote-description", &answer).await;
+        if let Some(err) = reply_error(reply.as_ref()) {
+            gst::warning!(CAT, imp: self, "ICE restart answer rejected: {err}");
         }
     }
 
@@ -390,6 +822,7 @@ impl WhipClient {
         let resource_url = match *state {
             WhipClientState::Running {
                 whip_resource_url: ref resource_url,
+                ..
             } => resource_url.clone(),
             _ => {
                 self.raise_error("Terminated in unexpected state".to_string());
@@ -440,21 +873,81 @@ impl WhipClient {
             },
         };
     }
+
+    fn reset_patch_queue(&self) {
+        let (sender, receiver) = mpsc::unbounded();
+        let mut patches = self.patches.lock().unwrap();
+        patches.sender = Some(sender);
+        patches.receiver = Some(receiver);
+    }
+
+    // Whether the session is being ended because its connection failed
+    fn connection_failed(&self) -> bool {
+        self.webrtcbin().map_or(false, |webrtcbin| {
+            webrtcbin.property::<gst_webrtc::WebRTCICEConnectionState>("ice-connection-state")
+                == gst_webrtc::WebRTCICEConnectionState::Failed
+                || webrtcbin.property::<gst_webrtc::WebRTCPeerConnectionState>("connection-state")
+                    == gst_webrtc::WebRTCPeerConnectionState::Failed
+        })
+    }
+
+    // Requests a new session to publish to the next endpoint, the upstream
+    // pipeline of webrtcsink keeps running meanwhile
+    fn fail_over(&self) {
+        let Ok(endpoints) = self.settings.lock().unwrap().endpoints() else {
+            return;
+        };
+
+        {
+            let mut index = self.endpoint_index.lock().unwrap();
+            *index = (*index + 1) % endpoints.len();
+            gst::warning!(
+                CAT,
+                imp: self,
+                "Connection failed, failing over to {}",
+                endpoints[*index]
+            );
+        }
+
+        *self.state.lock().unwrap() = WhipClientState::Stopped;
+        *self.webrtcbin.lock().unwrap() = None;
+        self.reset_patch_queue();
+
+        // Not from the streaming thread of the webrtcbin being removed
+        let this = self.obj().clone();
+        RUNTIME.spawn(async move {
+            this.emit_by_name::<()>(
+                "session-requested",
+                &[
+                    &"unique",
+                    &"unique",
+                    &None::<gst_webrtc::WebRTCSessionDescription>,
+                ],
+            );
+        });
+    }
 }
 
 impl SignallableImpl for WhipClient {
     fn start(&self) {
-        if self.settings.lock().unwrap().whip_endpoint.is_none() {
-            self.raise_error("WHIP endpoint URL must be set".to_string());
+        if let Err(err) = self.settings.lock().unwrap().endpoints() {
+            self.raise_error(err);
             return;
         }
 
+        let trickle_ice = self.settings.lock().unwrap().trickle_ice;
+
+        *self.endpoint_index.lock().unwrap() = 0;
+        self.reset_patch_queue();
+
         self.obj().connect_closure(
             "webrtcbin-ready",
             false,
-            glib::closure!(|signaller: &super::WhipClientSignaller,
-                            _consumer_identifier: &str,
-                            webrtcbin: &gst::Element| {
+            glib::closure!(move |signaller: &super::WhipClientSignaller,
+                                 _consumer_identifier: &str,
+                                 webrtcbin: &gst::Element| {
+                *signaller.imp().webrtcbin.lock().unwrap() = Some(webrtcbin.downgrade());
+
                 let obj_weak = signaller.downgrade();
                 webrtcbin.connect_notify(Some("ice-gathering-state"), move |webrtcbin, _pspec| {
                     let Some(obj) = obj_weak.upgrade() else {
@@ -468,30 +961,87 @@ impl SignallableImpl for WhipClient {
                         WebRTCICEGatheringState::Gathering => {
                             gst::info!(CAT, obj: obj, "ICE gathering started");
                         }
+                        WebRTCICEGatheringState::Complete if trickle_ice => {
+                            gst::info!(CAT, obj: obj, "ICE gathering complete");
+
+                            obj.imp().queue_patch(PatchRequest::EndOfCandidates);
+                        }
                         WebRTCICEGatheringState::Complete => {
                             gst::info!(CAT, obj: obj, "ICE gathering complete");
 
+                            let Some(offer) = webrtcbin
+                                .property::<Option<WebRTCSessionDescription>>("local-description")
+                            else {
+                                obj.imp()
+                                    .raise_error("Local description is not set".to_string());
+                                return;
+                            };
+
                             let webrtcbin = webrtcbin.clone();
 
                             RUNTIME.spawn(async move {
                                 /* Note that we check for a valid WHIP endpoint in change_state */
-                                obj.imp().send_offer(&webrtcbin).await
+                                obj.imp().send_offer(&webrtcbin, offer).await
                             });
                         }
                         _ => (),
                     }
                 });
-            }),
-        );
 
-        self.obj().emit_by_name::<()>(
-            "session-requested",
-            &[
-                &"unique",
-                &"unique",
-                &None::<gst_webrtc::WebRTCSessionDescription>,
-            ],
-        );
+                let obj_weak = signaller.downgrade();
+                webrtcbin.connect_notify(Some("ice-connection-state"), move |webrtcbin, _pspec| {
+                    let Some(obj) = obj_weak.upgrade() else {
+                        return;
+                    };
+
+                    // Ignore the webrtcbin of a session that was failed over
+                    if obj.imp().webrtcbin().as_ref() != Some(webrtcbin) {
+                        return;
+                    }
+
+                    let state = webrtcbin
+                        .property::<gst_webrtc::WebRTCICEConnectionState>("ice-connection-state");
+
+                    if state != gst_webrtc::WebRTCICEConnectionState::Disconnected {
+                        return;
+                    }
+
+                    gst::info!(CAT, obj: obj, "ICE connection lost");
+
+                    // Disconnections are often transient, a failed connection
+                    // is failed over to the next endpoint instead
+                    let obj_weak = obj.downgrade();
+                    let webrtcbin_weak = webrtcbin.downgrade();
+                    RUNTIME.spawn(async move {
+                        tokio::time::sleep(ICE_RESTART_DELAY).await;
+
+                        let (Some(obj), Some(webrtcbin)) =
+                            (obj_weak.upgrade(), webrtcbin_weak.upgrade())
+                        else {
+                            return;
+                        };
+
+                        if obj.imp().webrtcbin().as_ref() == Some(&webrtcbin)
+                            && webrtcbin.property::<gst_webrtc::WebRTCICEConnectionState>(
+                                "ice-connection-state",
+                            ) == gst_webrtc::WebRTCICEConnectionState::Disconnected
+                        {
+                            gst::info!(CAT, obj: obj, "ICE connection did not recover");
+                            obj.imp().queue_patch(PatchRequest::IceRestart);
+                        }
+                    });
+                });
+            }),
+        );
+
+        self.obj().emit_by_name::<()>(
+            "session-requested",
+            &[
+                &"unique",
+                &"unique",
+                &None::<gst_webrtc::WebRTCSessionDescription>,
+            ],
+        );
     }
 
     fn stop(&self) {
@@ -499,6 +1049,7 @@ impl SignallableImpl for WhipClient {
         if let Some(canceller) = &*self.canceller.lock().unwrap() {
             canceller.abort();
         }
+        self.stop_patch_task();
 
         let state = self.state.lock().unwrap();
         if let WhipClientState::Running { .. } = *state {
@@ -507,19 +1058,62 @@ impl SignallableImpl for WhipClient {
         }
     }
 
+    fn send_sdp(&self, _session_id: &str, sdp: &WebRTCSessionDescription) {
+        // Without trickle ICE, the offer is sent once all candidates are gathered
+        if !self.settings.lock().unwrap().trickle_ice
+            || sdp.type_() != gst_webrtc::WebRTCSDPType::Offer
+        {
+            return;
+        }
+
+        let Some(webrtcbin) = self.webrtcbin() else {
+            self.raise_error("No webrtcbin to send the offer for".to_string());
+            return;
+        };
+
+        let this = self.obj().clone();
+        let offer = sdp.clone();
+        RUNTIME.spawn(async move { this.imp().send_offer(&webrtcbin, offer).await });
+    }
+
+    fn add_ice(
+        &self,
+        _session_id: &str,
+        candidate: &str,
+        sdp_m_line_index: u32,
+        _sdp_mid: Option<String>,
+    ) {
+        if !self.settings.lock().unwrap().trickle_ice {
+            return;
+        }
+
+        self.queue_patch(PatchRequest::Candidate {
+            sdp_m_line_index,
+            candidate: candidate.to_string(),
+        });
+    }
+
     fn end_session(&self, session_id: &str) {
         assert_eq!(session_id, "unique");
 
+        // webrtcsink ends sessions whose connection failed
+        let failed = self.connection_failed();
+
         // Interrupt requests in progress, if any
         if let Some(canceller) = &*self.canceller.lock().unwrap() {
             canceller.abort();
         }
+        self.stop_patch_task();
 
         let state = self.state.lock().unwrap();
         if let WhipClientState::Running { .. } = *state {
             // Release server-side resources
             drop(state);
             self.terminate_session();
+
+            if failed {
+                self.fail_over();
+            }
         }
     }
 }
@@ -542,6 +1136,26 @@ impl ObjectImpl for WhipClient {
                     .mutable_ready()
                     .build(),
 
+                gst::ParamSpecArray::builder("whip-endpoints")
+                    .nick("WHIP Endpoints")
+                    .blurb("WHIP server endpoints by order of priority, used instead of whip-endpoint if not empty.
+                        The next endpoint is used when publishing to the current one fails or its connection is lost")
+                    .element_spec(&glib::ParamSpecString::builder("whip-endpoint")
+                        .nick("WHIP Endpoint")
+                        .blurb("A WHIP server endpoint to POST SDP offer to")
+                        .build()
+                    )
+                    .mutable_ready()
+                    .build(),
+
+                glib::ParamSpecUInt::builder("max-retries")
+                    .nick("Maximum Retries")
+                    .blurb("Number of times a POST request failing with a server error or a timeout is retried,
+                        with an exponential backoff, before failing over to the next endpoint")
+                    .default_value(DEFAULT_MAX_RETRIES)
+                    .mutable_ready()
+                    .build(),
+
                 glib::ParamSpecBoolean::builder("use-link-headers")
                     .nick("Use Link Headers")
                     .blurb("Use link headers to configure ice-servers from the WHIP server response to the POST request.
@@ -562,6 +1176,13 @@ impl ObjectImpl for WhipClient {
                     .maximum(3600)
                     .default_value(DEFAULT_TIMEOUT)
                     .build(),
+
+                glib::ParamSpecBoolean::builder("trickle-ice")
+                    .nick("Trickle ICE")
+                    .blurb("POST the offer without waiting for ICE gathering to complete and send the candidates with PATCH requests on the WHIP resource as they are gathered.")
+                    .default_value(DEFAULT_TRICKLE_ICE)
+                    .mutable_ready()
+                    .build(),
             ]
         });
 
@@ -586,6 +1207,18 @@ impl ObjectImpl for WhipClient {
                 let mut settings = self.settings.lock().unwrap();
                 settings.timeout = value.get().unwrap();
             }
+            "trickle-ice" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.trickle_ice = value.get().unwrap();
+            }
+            "whip-endpoints" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.whip_endpoints = value.get::<gst::Array>().expect("type checked upstream");
+            }
+            "max-retries" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.max_retries = value.get().unwrap();
+            }
             _ => unimplemented!(),
         }
     }
@@ -608,26 +1241,70 @@ impl ObjectImpl for WhipClient {
                 let settings = self.settings.lock().unwrap();
                 settings.timeout.to_value()
             }
+            "trickle-ice" => {
+                let settings = self.settings.lock().unwrap();
+                settings.trickle_ice.to_value()
+            }
+            "whip-endpoints" => {
+                let settings = self.settings.lock().unwrap();
+                settings.whip_endpoints.to_value()
+            }
+            "max-retries" => {
+                let settings = self.settings.lock().unwrap();
+                settings.max_retries.to_value()
+            }
             _ => unimplemented!(),
         }
     }
 }
 
+/// Emits `signal` on `webrtcbin` with `arg` and a promise, returning the
+/// reply of the promise
+async fn promise_reply(
+    webrtcbin: &gst::Element,
+    signal: &str,
+    arg: &dyn ToValue,
+) -> Option<gst::Structure> {
+    let (tx, rx) = futures::channel::oneshot::channel();
+    let promise = gst::Promise::with_change_func(move |reply| {
+        let _ = tx.send(reply.ok().flatten().map(|reply| reply.to_owned()));
+    });
+
+    webrtcbin.emit_by_name::<()>(signal, &[arg, &promise]);
+
+    rx.await.ok().flatten()
+}
+
+/// The error set by webrtcbin in the reply of a promise, if any
+fn reply_error(reply: Option<&gst::Structure>) -> Option<glib::Error> {
+    reply.and_then(|reply| reply.get::<glib::Error>("error").ok())
+}
+
+fn new_etag() -> String {
+    format!("\"{}\"", uuid::Uuid::new_v4())
+}
+
+/// Fragment with the ICE credentials of `sdp` and all its media sections
+fn all_media_fragment(sdp: &gst_sdp::SDPMessageRef) -> Option<IceFragment> {
+    let fragment = IceFragment::for_media(sdp, 0)?;
+
+    Some(IceFragment {
+        media: (0..sdp.medias_len())
+            .filter_map(|idx| IceFragment::for_media(sdp, idx))
+            .flat_map(|fragment| fragment.media)
+            .collect(),
+        ..fragment
+    })
+}
+
 // WHIP server implementation
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum WhipServerState {
-    Idle,
     Negotiating,
     Ready,
 }
 
-impl Default for WhipServerState {
-    fn default() -> Self {
-        Self::Idle
-    }
-}
-
 struct WhipServerSettings {
     stun_server: Option<String>,
     turn_servers: gst::Array,
@@ -636,7 +1313,10 @@ struct WhipServerSettings {
     timeout: u32,
     shutdown_signal: Option<tokio::sync::oneshot::Sender<()>>,
     server_handle: Option<tokio::task::JoinHandle<()>>,
-    sdp_answer: Option<crossbeam_channel::Sender<Option<SDPMessage>>>,
+    auth_tokens: gst::Array,
+    cors_allowed_origins: gst::Array,
+    tls_certificate: Option<String>,
+    tls_key: Option<String>,
 }
 
 impl Default for WhipServerSettings {
@@ -649,21 +1329,39 @@ impl Default for WhipServerSettings {
             timeout: DEFAULT_TIMEOUT,
             shutdown_signal: None,
             server_handle: None,
-            sdp_answer: None,
+            auth_tokens: gst::Array::new(Vec::new() as Vec<glib::SendValue>),
+            cors_allowed_origins: gst::Array::new(Vec::new() as Vec<glib::SendValue>),
+            tls_certificate: None,
+            tls_key: None,
         }
     }
 }
 
+/// A WHIP resource, created for each publisher
+struct WhipServerSession {
+    state: WhipServerState,
+    webrtcbin: Option<glib::WeakRef<gst::Element>>,
+    // Hands the answer, holding the candidates gathered so far, to the POST
+    // request
+    sdp_answer: Option<tokio::sync::oneshot::Sender<Option<SDPMessage>>>,
+    etag: Option<String>,
+    // Set once the answer, holding the candidates gathered so far, was sent
+    answered: bool,
+    // Local candidates to send in the response to the next PATCH request
+    pending_candidates: Vec<(u32, String)>,
+}
+
 pub struct WhipServer {
-    state: Mutex<WhipServerState>,
     settings: Mutex<WhipServerSettings>,
+    // Keyed by resource id, which is also the id of the webrtcsrc session
+    sessions: Mutex<HashMap<String, WhipServerSession>>,
 }
 
 impl Default for WhipServer {
     fn default() -> Self {
         Self {
             settings: Mutex::new(WhipServerSettings::default()),
-            state: Mutex::new(WhipServerState::default()),
+            sessions: Mutex::new(HashMap::new()),
         }
     }
 }
@@ -671,9 +1369,14 @@ impl Default for WhipServer {
 impl WhipServer {
     pub fn on_webrtcbin_ready(&self) -> RustClosure {
         glib::closure!(|signaller: &super::WhipServerSignaller,
-                        _producer_identifier: &str,
+                        session_id: &str,
                         webrtcbin: &gst::Element| {
+            if let Some(session) = signaller.imp().sessions.lock().unwrap().get_mut(session_id) {
+                session.webrtcbin = Some(webrtcbin.downgrade());
+            }
+
             let obj_weak = signaller.downgrade();
+            let session_id = session_id.to_string();
             webrtcbin.connect_notify(Some("ice-gathering-state"), move |webrtcbin, _pspec| {
                 let obj = match obj_weak.upgrade() {
                     Some(obj) => obj,
@@ -687,9 +1390,9 @@ impl WhipServer {
                         gst::info!(CAT, obj: obj, "ICE gathering started");
                     }
                     WebRTCICEGatheringState::Complete => {
-                        gst::info!(CAT, obj: obj, "ICE gathering complete");
+                        gst::info!(CAT, obj: obj, "ICE gathering complete for {session_id}");
                         let ans: Option<gst_sdp::SDPMessage>;
-                        let settings = obj.imp().settings.lock().unwrap();
+                        let mut sessions = obj.imp().sessions.lock().unwrap();
                         if let Some(answer_sdp) = webrtcbin
                             .property::<Option<WebRTCSessionDescription>>("local-description")
                         {
@@ -697,8 +1400,11 @@ impl WhipServer {
                         } else {
                             ans = None;
                         }
-                        if let Some(tx) = &settings.sdp_answer {
-                            tx.send(ans).unwrap()
+                        if let Some(sdp_answer) = sessions
+                            .get_mut(&session_id)
+                            .and_then(|session| session.sdp_answer.take())
+                        {
+                            let _ = sdp_answer.send(ans);
                         }
                     }
                     _ => (),
@@ -707,210 +1413,398 @@ impl WhipServer {
         })
     }
 
-    async fn patch_handler(&self, _id: String) -> Result<impl warp::Reply, warp::Rejection> {
-        // FIXME: implement ICE Trickle and ICE restart
-        // emit signal `handle-ice` to for ICE trickle
-        let reply = warp::reply::reply();
-        let res = warp::reply::with_status(reply, http::StatusCode::NOT_IMPLEMENTED);
-        Ok(res.into_response())
+    fn webrtcbin(&self, session_id: &str) -> Option<gst::Element> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .and_then(|session| session.webrtcbin.as_ref())
+            .and_then(|webrtcbin| webrtcbin.upgrade())
+    }
 
-        //FIXME: add state checking once ICE trickle is implemented
+    // Forgets about a resource whose negotiation failed, webrtcsrc tears its
+    // session down
+    fn abort_session(&self, session_id: &str) {
+        self.sessions.lock().unwrap().remove(session_id);
+        self.obj()
+            .emit_by_name::<bool>("session-ended", &[&session_id]);
     }
 
-    async fn delete_handler(&self, _id: String) -> Result<impl warp::Reply, warp::Rejection> {
-        let mut state = self.state.lock().unwrap();
-        match *state {
-            WhipServerState::Ready => {
-                // FIXME: session-ended will make webrtcsrc send EOS
-                // and producer-removed is not handled
-                // Need to address the usecase where when the client terminates
-                // the webrtcsrc should be running without sending EOS and reset
-                // for next client connection like a usual server
+    fn error_response(status: http::StatusCode, msg: &str) -> http::Response<Body> {
+        http::Response::builder()
+            .status(status)
+            .body(Body::from(msg.to_string()))
+            .unwrap()
+    }
 
-                self.obj().emit_by_name::<bool>("session-ended", &[&ROOT]);
+    // Whether the bearer token of @authorization may use the server, as
+    // decided by the `validate-token` signal
+    fn authorized(&self, authorization: Option<&str>) -> bool {
+        let token = authorization.and_then(|value| value.strip_prefix("Bearer "));
+        self.obj().emit_by_name::<bool>("validate-token", &[&token])
+    }
 
-                gst::info!(CAT, imp:self, "Ending session");
-                *state = WhipServerState::Idle;
-                Ok(warp::reply::reply().into_response())
-            }
-            _ => {
-                gst::error!(CAT, imp: self, "DELETE requested in {state:?} state. Can't Proceed");
-                let res = http::Response::builder()
-                    .status(http::StatusCode::CONFLICT)
-                    .body(Body::from(String::from("Session not Ready")))
-                    .unwrap();
-                Ok(res)
-            }
+    // Default `validate-token` handler, checking @token against `auth-tokens`
+    fn validate_token(&self, token: Option<&str>) -> bool {
+        let settings = self.settings.lock().unwrap();
+        if settings.auth_tokens.is_empty() {
+            return true;
         }
+
+        token.map_or(false, |token| {
+            settings
+                .auth_tokens
+                .iter()
+                .any(|allowed| allowed.get::<&str>().ok() == Some(token))
+        })
     }
 
-    async fn options_handler(&self) -> Result<impl warp::Reply, warp::Rejection> {
+    fn unauthorized_response() -> http::Response<Body> {
+        let mut res = Self::error_response(
+            http::StatusCode::UNAUTHORIZED,
+            "Missing or invalid bearer token",
+        );
+        res.headers_mut().insert(
+            http::header::WWW_AUTHENTICATE,
+            HeaderValue::from_static("Bearer"),
+        );
+
+        res
+    }
+
+    fn ice_server_links(&self) -> HeaderMap {
         let settings = self.settings.lock().unwrap();
-        let peer_id = settings.producer_peer_id.clone().unwrap();
-        drop(settings);
+        ice_server_links(settings.stun_server.as_deref(), &settings.turn_servers)
+    }
 
-        let mut state = self.state.lock().unwrap();
-        match *state {
-            WhipServerState::Idle => {
-                self.obj()
-                    .emit_by_name::<()>("session-started", &[&ROOT, &peer_id]);
-                *state = WhipServerState::Negotiating
+    async fn patch_handler(
+        &self,
+        id: String,
+        authorization: Option<String>,
+        if_match: Option<String>,
+        body: warp::hyper::body::Bytes,
+    ) -> Result<http::Response<Body>, warp::Rejection> {
+        if !self.authorized(authorization.as_deref()) {
+            gst::warning!(CAT, imp: self, "Rejecting unauthorized PATCH request");
+            return Ok(Self::unauthorized_response());
+        }
+
+        let (state, etag) = match self.sessions.lock().unwrap().get(&id) {
+            Some(session) => (session.state, session.etag.clone()),
+            None => {
+                return Ok(Self::error_response(
+                    http::StatusCode::NOT_FOUND,
+                    "Unknown resource",
+                ));
             }
-            WhipServerState::Ready => {
-                gst::error!(CAT, imp: self, "OPTIONS requested in {state:?} state. Can't proceed");
-                let res = http::Response::builder()
-                    .status(http::StatusCode::CONFLICT)
-                    .body(Body::from(String::from("Session active already")))
-                    .unwrap();
-                return Ok(res);
+        };
+
+        if !matches!(state, WhipServerState::Ready) {
+            gst::error!(CAT, imp: self, "PATCH requested for {id} in {state:?} state. Can't proceed");
+            return Ok(Self::error_response(
+                http::StatusCode::CONFLICT,
+                "Session not Ready",
+            ));
+        }
+
+        let fragment = match std::str::from_utf8(body.as_ref())
+            .map_err(anyhow::Error::from)
+            .and_then(IceFragment::parse)
+        {
+            Ok(fragment) => fragment,
+            Err(err) => {
+                gst::error!(CAT, imp: self, "Could not parse sdpfrag: {err}");
+                return Ok(Self::error_response(
+                    http::StatusCode::BAD_REQUEST,
+                    &format!("Invalid sdpfrag: {err}"),
+                ));
             }
-            _ => {}
         };
-        drop(state);
 
-        let mut links = HeaderMap::new();
-        let settings = self.settings.lock().unwrap();
-        match &settings.stun_server {
-            Some(stun) => match build_link_header(stun.as_str()) {
-                Ok(stun_link) => {
-                    links.append(LINK, HeaderValue::from_str(stun_link.as_str()).unwrap());
-                }
-                Err(e) => {
-                    gst::error!(CAT, imp: self, "Failed to parse {stun:?} : {e:?}");
-                }
-            },
-            None => {}
+        let Some(webrtcbin) = self.webrtcbin(&id) else {
+            return Ok(Self::error_response(
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                "No webrtcbin",
+            ));
+        };
+
+        match if_match.as_deref() {
+            // ICE restarts use a wildcard If-Match
+            Some("*") => Ok(self.restart_ice(&id, &webrtcbin, fragment).await),
+            Some(if_match) if Some(if_match) != etag.as_deref() => {
+                gst::warning!(CAT, imp: self, "PATCH with outdated ETag {if_match}");
+                Ok(Self::error_response(
+                    http::StatusCode::PRECONDITION_FAILED,
+                    "ETag mismatch",
+                ))
+            }
+            _ => Ok(self.trickle_candidates(&id, &webrtcbin, fragment)),
         }
+    }
 
-        if !settings.turn_servers.is_empty() {
-            for turn_server in settings.turn_servers.iter() {
-                if let Ok(turn) = turn_server.get::<String>() {
-                    gst::debug!(CAT, imp: self, "turn server: {}",turn.as_str());
-                    match build_link_header(turn.as_str()) {
-                        Ok(turn_link) => {
-                            links.append(LINK, HeaderValue::from_str(turn_link.as_str()).unwrap());
-                        }
-                        Err(e) => {
-                            gst::error!(CAT, imp: self, "Failed to parse {turn_server:?} : {e:?}");
-                        }
+    fn trickle_candidates(
+        &self,
+        session_id: &str,
+        webrtcbin: &gst::Element,
+        fragment: IceFragment,
+    ) -> http::Response<Body> {
+        let Some(offer) =
+            webrtcbin.property::<Option<WebRTCSessionDescription>>("remote-description")
+        else {
+            return Self::error_response(http::StatusCode::CONFLICT, "No offer");
+        };
+        let offer = offer.sdp();
+
+        for (idx, media) in fragment.media.iter().enumerate() {
+            let sdp_m_line_index = match media.mid {
+                Some(ref mid) => (0..offer.medias_len()).find(|idx| {
+                    offer
+                        .media(*idx)
+                        .and_then(|media| media.attribute_val("mid"))
+                        == Some(mid.as_str())
+                }),
+                None => Some(idx as u32),
+            };
+
+            let Some(sdp_m_line_index) = sdp_m_line_index else {
+                gst::warning!(CAT, imp: self, "Unknown media {:?} in sdpfrag", media.mid);
+                continue;
+            };
+
+            for candidate in &media.candidates {
+                gst::debug!(CAT, imp: self, "Remote candidate for {sdp_m_line_index}: {candidate}");
+                self.obj().emit_by_name::<()>(
+                    "handle-ice",
+                    &[
+                        &session_id,
+                        &sdp_m_line_index,
+                        &media.mid,
+                        &format!("candidate:{candidate}"),
+                    ],
+                );
+            }
+        }
+
+        let (pending_candidates, etag) = match self.sessions.lock().unwrap().get_mut(session_id) {
+            Some(session) => (
+                std::mem::take(&mut session.pending_candidates),
+                session.etag.clone().unwrap_or_default(),
+            ),
+            None => return Self::error_response(http::StatusCode::NOT_FOUND, "Unknown resource"),
+        };
+
+        let local_fragment = webrtcbin
+            .property::<Option<WebRTCSessionDescription>>("local-description")
+            .and_then(|answer| all_media_fragment(&answer.sdp()));
+
+        match local_fragment {
+            Some(mut local_fragment) if !pending_candidates.is_empty() => {
+                for (sdp_m_line_index, candidate) in pending_candidates {
+                    if let Some(media) = local_fragment.media.get_mut(sdp_m_line_index as usize) {
+                        media.candidates.push(candidate);
                     }
-                } else {
-                    gst::debug!(CAT, imp: self, "Failed to get String value of {turn_server:?}");
                 }
+
+                http::Response::builder()
+                    .status(http::StatusCode::OK)
+                    .header(CONTENT_TYPE, CONTENT_TRICKLE_ICE)
+                    .header(http::header::ETAG, etag)
+                    .body(Body::from(local_fragment.to_sdpfrag()))
+                    .unwrap()
             }
+            _ => http::Response::builder()
+                .status(http::StatusCode::NO_CONTENT)
+                .header(http::header::ETAG, etag)
+                .body(Body::empty())
+                .unwrap(),
         }
+    }
 
+    async fn restart_ice(
+        &self,
+        session_id: &str,
+        webrtcbin: &gst::Element,
+        fragment: IceFragment,
+    ) -> http::Response<Body> {
+        let Some(offer) =
+            webrtcbin.property::<Option<WebRTCSessionDescription>>("remote-description")
+        else {
+            return Self::error_response(http::StatusCode::CONFLICT, "No offer");
+        };
+
+        gst::info!(CAT, imp: self, "Restarting ICE for {session_id}");
+
+        let mut sdp = offer.sdp();
+        fragment.apply(&mut sdp);
+        let offer = WebRTCSessionDescription::new(gst_webrtc::WebRTCSDPType::Offer, sdp);
+
+        let reply = promise_reply(webrtcbin, "set-remote-description", &offer).await;
+        if let Some(err) = reply_error(reply.as_ref()) {
+            gst::error!(CAT, imp: self, "ICE restart offer rejected: {err}");
+            return Self::error_response(
+                http::StatusCode::BAD_REQUEST,
+                &format!("Invalid ICE restart: {err}"),
+            );
+        }
+
+        let answer = promise_reply(webrtcbin, "create-answer", &None::<gst::Structure>)
+            .await
+            .and_then(|reply| reply.get::<WebRTCSessionDescription>("answer").ok());
+
+        let Some(answer) = answer else {
+            gst::error!(CAT, imp: self, "Failed to create ICE restart answer");
+            return Self::error_response(
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to restart ICE",
+            );
+        };
+
+        webrtcbin.emit_by_name::<()>("set-local-description", &[&answer, &None::<gst::Promise>]);
+
+        let Some(local_fragment) = all_media_fragment(&answer.sdp()) else {
+            return Self::error_response(
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                "Answer has no media",
+            );
+        };
+
+        // Candidates gathered from now on are sent in the responses to the
+        // next PATCH requests
+        let etag = new_etag();
+        if let Some(session) = self.sessions.lock().unwrap().get_mut(session_id) {
+            session.etag = Some(etag.clone());
+            session.pending_candidates.clear();
+        }
+
+        http::Response::builder()
+            .status(http::StatusCode::OK)
+            .header(CONTENT_TYPE, CONTENT_TRICKLE_ICE)
+            .header(http::header::ETAG, etag)
+            .body(Body::from(local_fragment.to_sdpfrag()))
+            .unwrap()
+    }
+
+    async fn delete_handler(
+        &self,
+        id: String,
+        authorization: Option<String>,
+    ) -> Result<http::Response<Body>, warp::Rejection> {
+        if !self.authorized(authorization.as_deref()) {
+            gst::warning!(CAT, imp: self, "Rejecting unauthorized DELETE request");
+            return Ok(Self::unauthorized_response());
+        }
+
+        // Only this publisher goes away, the server keeps accepting new ones
+        if self.sessions.lock().unwrap().remove(&id).is_none() {
+            gst::error!(CAT, imp: self, "DELETE requested for unknown resource {id}");
+            return Ok(Self::error_response(
+                http::StatusCode::NOT_FOUND,
+                "Unknown resource",
+            ));
+        }
+
+        gst::info!(CAT, imp: self, "Ending session {id}");
+        self.obj().emit_by_name::<bool>("session-ended", &[&id]);
+
+        Ok(warp::reply::reply().into_response())
+    }
+
+    async fn options_handler(
+        &self,
+        authorization: Option<String>,
+    ) -> Result<impl warp::Reply, warp::Rejection> {
         let mut res = http::Response::builder()
-            .header("Access-Post", "application/sdp")
+            .header("Accept-Post", "application/sdp")
             .body(Body::empty())
             .unwrap();
 
-        let headers = res.headers_mut();
-        headers.extend(links);
+        // The TURN credentials are only given to authorized clients
+        if self.authorized(authorization.as_deref()) {
+            res.headers_mut().extend(self.ice_server_links());
+        }
 
         Ok(res)
     }
 
     async fn post_handler(
         &self,
+        authorization: Option<String>,
         body: warp::hyper::body::Bytes,
     ) -> Result<http::Response<warp::hyper::Body>, warp::Rejection> {
-        let mut settings = self.settings.lock().unwrap();
+        if !self.authorized(authorization.as_deref()) {
+            gst::warning!(CAT, imp: self, "Rejecting unauthorized POST request");
+            return Ok(Self::unauthorized_response());
+        }
+
+        let settings = self.settings.lock().unwrap();
         let peer_id = settings.producer_peer_id.clone().unwrap();
         let wait_timeout = settings.timeout;
-        let (tx, rx) = unbounded::<Option<SDPMessage>>();
-        settings.sdp_answer = Some(tx);
         drop(settings);
 
-        let mut state = self.state.lock().unwrap();
-        match *state {
-            WhipServerState::Idle => {
-                self.obj()
-                    .emit_by_name::<()>("session-started", &[&ROOT, &peer_id]);
-                *state = WhipServerState::Negotiating
-            }
-            WhipServerState::Ready => {
-                gst::error!(CAT, imp: self, "POST requested in {state:?} state. Can't Proceed");
-                let res = http::Response::builder()
-                    .status(http::StatusCode::CONFLICT)
-                    .body(Body::from(String::from("Session active already")))
-                    .unwrap();
-                return Ok(res);
-            }
-            _ => {}
-        };
-        drop(state);
-
-        match gst_sdp::SDPMessage::parse_buffer(body.as_ref()) {
-            Ok(offer_sdp) => {
-                let offer = gst_webrtc::WebRTCSessionDescription::new(
-                    gst_webrtc::WebRTCSDPType::Offer,
-                    offer_sdp,
-                );
-
-                self.obj()
-                    .emit_by_name::<()>("session-description", &[&"unique", &offer]);
-            }
+        let offer_sdp = match gst_sdp::SDPMessage::parse_buffer(body.as_ref()) {
+            Ok(offer_sdp) => offer_sdp,
             Err(err) => {
                 gst::error!(CAT, imp: self, "Could not parse offer SDP: {err}");
                 let reply = warp::reply::reply();
                 let res = warp::reply::with_status(reply, http::StatusCode::NOT_ACCEPTABLE);
                 return Ok(res.into_response());
             }
-        }
+        };
 
-        // We don't want to wait infinitely for the ice gathering to complete.
-        let answer = match rx.recv_timeout(Duration::from_secs(wait_timeout as u64)) {
-            Ok(a) => a,
-            Err(e) => {
-                let reply = warp::reply::reply();
-                let res;
-                if e.is_timeout() {
-                    res = warp::reply::with_status(reply, http::StatusCode::REQUEST_TIMEOUT);
-                    gst::error!(CAT, imp: self, "Timedout waiting for SDP answer");
-                } else {
-                    res = warp::reply::with_status(reply, http::StatusCode::INTERNAL_SERVER_ERROR);
-                    gst::error!(CAT, imp: self, "Channel got disconnected");
-                }
-                return Ok(res.into_response());
+        // Each publisher gets its own resource and webrtcsrc session
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = tokio::sync::oneshot::channel::<Option<SDPMessage>>();
+        self.sessions.lock().unwrap().insert(
+            session_id.clone(),
+            WhipServerSession {
+                state: WhipServerState::Negotiating,
+                webrtcbin: None,
+                sdp_answer: Some(tx),
+                etag: None,
+                answered: false,
+                pending_candidates: Vec::new(),
+            },
+        );
+
+        gst::info!(CAT, imp: self, "New publisher, resource {session_id}");
+
+        let offer =
+            gst_webrtc::WebRTCSessionDescription::new(gst_webrtc::WebRTCSDPType::Offer, offer_sdp);
+        self.obj()
+            .emit_by_name::<()>("session-started", &[&session_id, &peer_id]);
+        self.obj()
+            .emit_by_name::<()>("session-description", &[&session_id, &offer]);
+
+        // We don't want to wait infinitely for the ice gathering to complete,
+        // nor to block the runtime other publishers are served from meanwhile
+        let answer = if wait_timeout == 0 {
+            rx.await
+                .map_err(|_| http::StatusCode::INTERNAL_SERVER_ERROR)
+        } else {
+            match tokio::time::timeout(Duration::from_secs(wait_timeout.into()), rx).await {
+                Ok(answer) => answer.map_err(|_| http::StatusCode::INTERNAL_SERVER_ERROR),
+                Err(_) => Err(http::StatusCode::REQUEST_TIMEOUT),
             }
         };
 
-        let settings = self.settings.lock().unwrap();
-        let mut links = HeaderMap::new();
-
-        match &settings.stun_server {
-            Some(stun) => match build_link_header(stun.as_str()) {
-                Ok(stun_link) => {
-                    links.append(LINK, HeaderValue::from_str(stun_link.as_str()).unwrap());
-                }
-                Err(e) => {
-                    gst::error!(CAT, imp: self, "Failed to parse {stun:?} : {e:?}");
-                }
-            },
-            None => {}
-        }
+        let answer = match answer {
+            Ok(answer) => answer,
+            Err(status) => {
+                self.abort_session(&session_id);
 
-        if !settings.turn_servers.is_empty() {
-            for turn_server in settings.turn_servers.iter() {
-                if let Ok(turn) = turn_server.get::<String>() {
-                    gst::debug!(CAT, imp: self, "turn server: {}",turn.as_str());
-                    match build_link_header(turn.as_str()) {
-                        Ok(turn_link) => {
-                            links.append(LINK, HeaderValue::from_str(turn_link.as_str()).unwrap());
-                        }
-                        Err(e) => {
-                            gst::error!(CAT, imp: self, "Failed to  parse {turn_server:?} : {e:?}");
-                        }
-                    }
+                if status == http::StatusCode::REQUEST_TIMEOUT {
+                    gst::error!(CAT, imp: self, "Timedout waiting for SDP answer");
                 } else {
-                    gst::error!(CAT, imp: self, "Failed to get String value of {turn_server:?}");
+                    gst::error!(CAT, imp: self, "Channel got disconnected");
                 }
+                let reply = warp::reply::reply();
+                return Ok(warp::reply::with_status(reply, status).into_response());
             }
-        }
+        };
 
         // Note: including the ETag in the original "201 Created" response is only REQUIRED
         // if the WHIP resource supports ICE restarts and OPTIONAL otherwise.
+        let etag = new_etag();
 
         let ans_text: Result<String, String>;
         if let Some(sdp) = answer {
@@ -932,6 +1826,8 @@ impl WhipServer {
 
         // If ans_text is an error. Send error code and error string in the response
         if let Err(e) = ans_text {
+            self.abort_session(&session_id);
+
             let res = http::Response::builder()
                 .status(http::StatusCode::INTERNAL_SERVER_ERROR)
                 .body(Body::from(e))
@@ -939,27 +1835,54 @@ impl WhipServer {
             return Ok(res);
         }
 
-        drop(settings);
-
         // Got SDP answer, send answer in the response
-        let resource_url = "/".to_owned() + ROOT + "/" + RESOURCE_PATH + "/" + &peer_id;
+        let resource_url = "/".to_owned() + ROOT + "/" + RESOURCE_PATH + "/" + &session_id;
         let mut res = http::Response::builder()
             .status(StatusCode::CREATED)
             .header(CONTENT_TYPE, "application/sdp")
             .header("location", resource_url)
+            .header(http::header::ETAG, etag.clone())
             .body(Body::from(ans_text.unwrap()))
             .unwrap();
 
-        let headers = res.headers_mut();
-        headers.extend(links);
+        if let Some(session) = self.sessions.lock().unwrap().get_mut(&session_id) {
+            session.state = WhipServerState::Ready;
+            session.etag = Some(etag);
+            session.answered = true;
+            session.pending_candidates.clear();
+        }
 
-        let mut state = self.state.lock().unwrap();
-        *state = WhipServerState::Ready;
-        drop(state);
+        res.headers_mut().extend(self.ice_server_links());
 
         Ok(res)
     }
 
+    // Certificate and key of the HTTPS server, if configured
+    fn tls_identity(&self) -> Result<Option<(Vec<u8>, Vec<u8>)>, String> {
+        let settings = self.settings.lock().unwrap();
+        let (certificate, key) = match (&settings.tls_certificate, &settings.tls_key) {
+            (Some(certificate), Some(key)) => (certificate, key),
+            (None, None) => {
+                if settings.host_addr.scheme() == "https" {
+                    gst::warning!(
+                        CAT,
+                        imp: self,
+                        "No tls-certificate and tls-key set, serving plain HTTP on {}",
+                        settings.host_addr
+                    );
+                }
+                return Ok(None);
+            }
+            _ => return Err("Both tls-certificate and tls-key need to be set".to_string()),
+        };
+
+        let certificate = std::fs::read(certificate)
+            .map_err(|e| format!("Failed to read TLS certificate {certificate:?}: {e}"))?;
+        let key = std::fs::read(key).map_err(|e| format!("Failed to read TLS key {key:?}: {e}"))?;
+
+        Ok(Some((certificate, key)))
+    }
+
     fn serve(&self) -> Option<tokio::task::JoinHandle<()>> {
         let mut settings = self.settings.lock().unwrap();
         let addr: SocketAddr;
@@ -977,10 +1900,26 @@ impl WhipServer {
             }
         }
 
-        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
-        settings.shutdown_signal = Some(tx);
+        let cors_allowed_origins = settings
+            .cors_allowed_origins
+            .iter()
+            .filter_map(|origin| origin.get::<String>().ok())
+            .collect::<Vec<_>>();
         drop(settings);
 
+        let tls_identity = match self.tls_identity() {
+            Ok(tls_identity) => tls_identity,
+            Err(e) => {
+                gst::error!(CAT, imp: self, "{e}");
+                self.obj()
+                    .emit_by_name::<()>("error", &[&format!("Unable to start WHIP Server: {e}")]);
+                return None;
+            }
+        };
+
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        self.settings.lock().unwrap().shutdown_signal = Some(tx);
+
         let prefix = warp::path(ROOT);
 
         let self_weak = self.downgrade();
@@ -990,12 +1929,13 @@ impl WhipServer {
             .and(warp::path(ENDPOINT_PATH))
             .and(warp::path::end())
             .and(warp::header::exact(CONTENT_TYPE.as_str(), CONTENT_SDP))
+            .and(warp::header::optional::<String>(AUTHORIZATION.as_str()))
             .and(warp::body::bytes())
-            .and_then(move |body| {
+            .and_then(move |authorization, body| {
                 let s = self_weak.upgrade();
                 async {
                     let self_ = s.expect("Need to have the ObjectRef");
-                    self_.post_handler(body).await
+                    self_.post_handler(authorization, body).await
                 }
             });
 
@@ -1005,11 +1945,12 @@ impl WhipServer {
         let options_filter = warp::options()
             .and(warp::path(ENDPOINT_PATH))
             .and(warp::path::end())
-            .and_then(move || {
+            .and(warp::header::optional::<String>(AUTHORIZATION.as_str()))
+            .and_then(move |authorization| {
                 let s = self_weak.upgrade();
                 async {
                     let self_ = s.expect("Need to have the ObjectRef");
-                    self_.options_handler().await
+                    self_.options_handler(authorization).await
                 }
             });
 
@@ -1024,11 +1965,14 @@ impl WhipServer {
                 CONTENT_TYPE.as_str(),
                 CONTENT_TRICKLE_ICE,
             ))
-            .and_then(move |id| {
+            .and(warp::header::optional::<String>(AUTHORIZATION.as_str()))
+            .and(warp::header::optional::<String>("if-match"))
+            .and(warp::body::bytes())
+            .and_then(move |id, authorization, if_match, body| {
                 let s = self_weak.upgrade();
                 async {
                     let self_ = s.expect("Need to have the ObjectRef");
-                    self_.patch_handler(id).await
+                    self_.patch_handler(id, authorization, if_match, body).await
                 }
             });
 
@@ -1039,11 +1983,12 @@ impl WhipServer {
             .and(warp::path(RESOURCE_PATH))
             .and(warp::path::param::<String>())
             .and(warp::path::end())
-            .and_then(move |id| {
+            .and(warp::header::optional::<String>(AUTHORIZATION.as_str()))
+            .and_then(move |id, authorization| {
                 let s = self_weak.upgrade();
                 async {
                     let self_ = s.expect("Need to have the ObjectRef");
-                    self_.delete_handler(id).await
+                    self_.delete_handler(id, authorization).await
                 }
             });
 
@@ -1051,18 +1996,32 @@ impl WhipServer {
             .and(post_filter)
             .or(prefix.and(options_filter))
             .or(prefix.and(patch_filter))
-            .or(prefix.and(delete_filter));
+            .or(prefix.and(delete_filter))
+            .with(cors(&cors_allowed_origins));
 
         let s = warp::serve(api);
+        let shutdown = async move {
+            match rx.await {
+                Ok(_) => gst::debug!(CAT, "Server shut down signal received"),
+                Err(e) => gst::error!(CAT, "{e:?}: Sender dropped"),
+            }
+        };
         let jh = RUNTIME.spawn(async move {
-            let (_, server) = s.bind_with_graceful_shutdown(addr, async move {
-                match rx.await {
-                    Ok(_) => gst::debug!(CAT, "Server shut down signal received"),
-                    Err(e) => gst::error!(CAT, "{e:?}: Sender dropped"),
+            match tls_identity {
+                Some((certificate, key)) => {
+                    let (_, server) = s
+                        .tls()
+                        .cert(certificate)
+                        .key(key)
+                        .bind_with_graceful_shutdown(addr, shutdown);
+                    server.await;
                 }
-            });
+                None => {
+                    let (_, server) = s.bind_with_graceful_shutdown(addr, shutdown);
+                    server.await;
+                }
+            }
 
-            server.await;
             gst::debug!(CAT, "Stopped the server task...");
         });
 
@@ -1109,11 +2068,35 @@ impl SignallableImpl for WhipServer {
             };
         });
 
+        self.sessions.lock().unwrap().clear();
+
         gst::info!(CAT, imp: self, "stopped the WHIP server");
     }
 
-    fn end_session(&self, _session_id: &str) {
-        //FIXME: send any events to the client
+    fn add_ice(
+        &self,
+        session_id: &str,
+        candidate: &str,
+        sdp_m_line_index: u32,
+        _sdp_mid: Option<String>,
+    ) {
+        let mut sessions = self.sessions.lock().unwrap();
+        let Some(session) = sessions.get_mut(session_id) else {
+            return;
+        };
+
+        // Candidates gathered before the answer was sent are part of it
+        if session.answered {
+            let candidate = candidate.strip_prefix("candidate:").unwrap_or(candidate);
+            session
+                .pending_candidates
+                .push((sdp_m_line_index, candidate.to_string()));
+        }
+    }
+
+    fn end_session(&self, session_id: &str) {
+        // The publisher gets a 404 for its resource from now on
+        self.sessions.lock().unwrap().remove(session_id);
     }
 }
 
@@ -1161,11 +2144,77 @@ impl ObjectImpl for WhipServer {
                     .maximum(3600)
                     .default_value(DEFAULT_TIMEOUT)
                     .build(),
+                gst::ParamSpecArray::builder("auth-tokens")
+                    .nick("Authorization Tokens")
+                    .blurb("Tokens publishers may send in the HTTP Header as 'Bearer <token>'. Empty by default, which accepts any publisher. \
+                        Applications can instead validate tokens with the validate-token signal")
+                    .element_spec(&glib::ParamSpecString::builder("auth-token")
+                        .nick("Authorization Token")
+                        .blurb("A bearer token accepted by the server")
+                        .build()
+                    )
+                    .mutable_ready()
+                    .build(),
+                gst::ParamSpecArray::builder("cors-allowed-origins")
+                    .nick("CORS allowed origins")
+                    .blurb("Origins of the web publishers allowed to use the server, e.g. <\"https://example.com\">. Empty by default, which allows any origin")
+                    .element_spec(&glib::ParamSpecString::builder("origin")
+                        .nick("Origin")
+                        .blurb("An origin of the form scheme://host[:port]")
+                        .build()
+                    )
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("tls-certificate")
+                    .nick("TLS Certificate")
+                    .blurb("Path to the PEM certificate chain to serve HTTPS with, along with tls-key")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("tls-key")
+                    .nick("TLS Key")
+                    .blurb("Path to the PEM private key of tls-certificate")
+                    .mutable_ready()
+                    .build(),
             ]
         });
         PROPERTIES.as_ref()
     }
 
+    fn signals() -> &'static [glib::subclass::Signal] {
+        static SIGNALS: Lazy<Vec<glib::subclass::Signal>> = Lazy::new(|| {
+            vec![
+                /**
+                 * GstWhipServerSignaller::validate-token:
+                 * @token: The bearer token of the request, if any
+                 *
+                 * Emitted for every request to decide whether its bearer token
+                 * grants access to the server. The default handler accepts the
+                 * tokens listed in `auth-tokens`, or any request if that list
+                 * is empty.
+                 *
+                 * Returns: %TRUE if the request is authorized
+                 */
+                glib::subclass::Signal::builder("validate-token")
+                    .param_types([Option::<String>::static_type()])
+                    .return_type::<bool>()
+                    .run_last()
+                    .class_handler(|_token, args| {
+                        let this = args[0].get::<super::WhipServerSignaller>().unwrap();
+                        let token = args[1].get::<Option<&str>>().unwrap();
+
+                        Some(this.imp().validate_token(token).to_value())
+                    })
+                    .accumulator(move |_hint, output, input| {
+                        *output = input.clone();
+                        false
+                    })
+                    .build(),
+            ]
+        });
+
+        SIGNALS.as_ref()
+    }
+
     fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
         match pspec.name() {
             "host-addr" => {
@@ -1189,6 +2238,23 @@ impl ObjectImpl for WhipServer {
                 let mut settings = self.settings.lock().unwrap();
                 settings.timeout = value.get().unwrap();
             }
+            "auth-tokens" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.auth_tokens = value.get::<gst::Array>().expect("type checked upstream")
+            }
+            "cors-allowed-origins" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.cors_allowed_origins =
+                    value.get::<gst::Array>().expect("type checked upstream")
+            }
+            "tls-certificate" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.tls_certificate = value.get().unwrap();
+            }
+            "tls-key" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.tls_key = value.get().unwrap();
+            }
             _ => unimplemented!(),
         }
     }
@@ -1201,7 +2267,560 @@ impl ObjectImpl for WhipServer {
             "turn-servers" => settings.turn_servers.to_value(),
             "producer-peer-id" => settings.producer_peer_id.to_value(),
             "timeout" => settings.timeout.to_value(),
+            "auth-tokens" => settings.auth_tokens.to_value(),
+            "cors-allowed-origins" => settings.cors_allowed_origins.to_value(),
+            "tls-certificate" => settings.tls_certificate.to_value(),
+            "tls-key" => settings.tls_key.to_value(),
             _ => unimplemented!(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signaller::SignallableExt;
+    use crate::whip_signaller::{WhipClientSignaller, WhipServerSignaller};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const OFFER: &str = "v=0\r\no=- 0 0 IN IP4 127.0.0.1\r\ns=-\r\nt=0 0\r\n\
+        m=video 9 UDP/TLS/RTP/SAVPF 96\r\na=rtpmap:96 VP8/90000\r\na=sendonly\r\n";
+    const ANSWER: &str = "v=0\r\no=- 1 0 IN IP4 127.0.0.1\r\ns=-\r\nt=0 0\r\n\
+        m=video 9 UDP/TLS/RTP/SAVPF 96\r\na=rtpmap:96 VP8/90000\r\na=recvonly\r\n";
+
+    // Stand-in WHIP endpoint, failing with @status until it got @failures
+    // requests, counted in @requests
+    fn serve_endpoint(
+        status: http::StatusCode,
+        failures: usize,
+        requests: Arc<AtomicUsize>,
+    ) -> SocketAddr {
+        let endpoint = warp::post()
+            .and(warp::path!("whip" / "endpoint"))
+            .map(move || {
+                if requests.fetch_add(1, Ordering::SeqCst) < failures {
+                    return http::Response::builder()
+                        .status(status)
+                        .header("retry-after", "0")
+                        .body(String::new())
+                        .unwrap();
+                }
+
+                http::Response::builder()
+                    .status(http::StatusCode::CREATED)
+                    .header("location", "/whip/resource/1")
+                    .header("content-type", CONTENT_SDP)
+                    .body(ANSWER.to_string())
+                    .unwrap()
+            });
+
+        let _guard = RUNTIME.enter();
+        let (addr, server) = warp::serve(endpoint).bind_ephemeral(([127, 0, 0, 1], 0));
+        RUNTIME.spawn(server);
+
+        addr
+    }
+
+    fn publish(endpoints: &[String], max_retries: u32) -> WhipClientSignaller {
+        let client = glib::Object::builder::<WhipClientSignaller>()
+            .property("whip-endpoints", gst::Array::new(endpoints))
+            .property("max-retries", max_retries)
+            .build();
+
+        let offer = WebRTCSessionDescription::new(
+            gst_webrtc::WebRTCSDPType::Offer,
+            SDPMessage::parse_buffer(OFFER.as_bytes()).unwrap(),
+        );
+        // The webrtcbin is only used with use-link-headers
+        let webrtcbin = gst::Bin::new().upcast::<gst::Element>();
+        RUNTIME.block_on(client.imp().send_offer(&webrtcbin, offer));
+
+        client
+    }
+
+    fn resource_url(client: &WhipClientSignaller) -> Option<String> {
+        match *client.imp().state.lock().unwrap() {
+            WhipClientState::Running {
+                ref whip_resource_url,
+                ..
+            } => Some(whip_resource_url.clone()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_client_retry() {
+        gst::init().unwrap();
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let addr = serve_endpoint(http::StatusCode::SERVICE_UNAVAILABLE, 2, requests.clone());
+
+        let client = publish(&[format!("http://{addr}/whip/endpoint")], 2);
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            resource_url(&client),
+            Some(format!("http://{addr}/whip/resource/1"))
+        );
+    }
+
+    #[test]
+    fn test_client_failover() {
+        gst::init().unwrap();
+
+        let primary_requests = Arc::new(AtomicUsize::new(0));
+        let primary = serve_endpoint(
+            http::StatusCode::SERVICE_UNAVAILABLE,
+            usize::MAX,
+            primary_requests.clone(),
+        );
+        let rejecting_requests = Arc::new(AtomicUsize::new(0));
+        let rejecting = serve_endpoint(
+            http::StatusCode::FORBIDDEN,
+            usize::MAX,
+            rejecting_requests.clone(),
+        );
+        let backup_requests = Arc::new(AtomicUsize::new(0));
+        let backup = serve_endpoint(http::StatusCode::OK, 0, backup_requests.clone());
+
+        let client = publish(
+            &[
+                format!("http://{primary}/whip/endpoint"),
+                format!("http://{rejecting}/whip/endpoint"),
+                format!("http://{backup}/whip/endpoint"),
+            ],
+            1,
+        );
+
+        // Server errors are retried, rejections are not
+        assert_eq!(primary_requests.load(Ordering::SeqCst), 2);
+        assert_eq!(rejecting_requests.load(Ordering::SeqCst), 1);
+        assert_eq!(backup_requests.load(Ordering::SeqCst), 1);
+        assert_eq!(*client.imp().endpoint_index.lock().unwrap(), 2);
+        assert_eq!(
+            resource_url(&client),
+            Some(format!("http://{backup}/whip/resource/1"))
+        );
+    }
+
+    fn start_server(
+        auth_tokens: &[&str],
+        cors_allowed_origins: &[&str],
+    ) -> (WhipServerSignaller, reqwest::Url) {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let host_addr = format!("http://127.0.0.1:{port}");
+
+        let signaller = glib::Object::builder::<WhipServerSignaller>()
+            .property("host-addr", &host_addr)
+            .property("stun-server", "stun://stun.example.net:3478")
+            .property("auth-tokens", gst::Array::new(auth_tokens.iter().copied()))
+            .property(
+                "cors-allowed-origins",
+                gst::Array::new(cors_allowed_origins.iter().copied()),
+            )
+            .build();
+        signaller.start();
+
+        (signaller, reqwest::Url::parse(&host_addr).unwrap())
+    }
+
+    fn request(
+        method: reqwest::Method,
+        url: reqwest::Url,
+        headers: &[(&'static str, &str)],
+    ) -> reqwest::Response {
+        let mut request = reqwest::Client::new().request(method, url);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+
+        RUNTIME.block_on(request.send()).unwrap()
+    }
+
+    #[test]
+    fn test_server_auth_tokens() {
+        gst::init().unwrap();
+
+        let (signaller, host) = start_server(&["first", "second"], &[]);
+        let endpoint = host.join("/whip/endpoint").unwrap();
+        let resource = host.join("/whip/resource/unknown").unwrap();
+
+        let resp = request(
+            reqwest::Method::POST,
+            endpoint.clone(),
+            &[("content-type", CONTENT_SDP)],
+        );
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(resp.headers().get("www-authenticate").unwrap(), "Bearer");
+
+        // The ICE servers are only advertised to authorized publishers
+        let resp = request(
+            reqwest::Method::OPTIONS,
+            endpoint.clone(),
+            &[("authorization", "Bearer wrong")],
+        );
+        assert!(resp.headers().get("link").is_none());
+
+        let resp = request(
+            reqwest::Method::OPTIONS,
+            endpoint,
+            &[("authorization", "Bearer second")],
+        );
+        assert_eq!(
+            resp.headers().get("link").unwrap(),
+            "<stun:stun.example.net:3478>; rel=\"ice-server\""
+        );
+        assert_eq!(resp.headers().get("accept-post").unwrap(), CONTENT_SDP);
+
+        let resp = request(reqwest::Method::DELETE, resource.clone(), &[]);
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let resp = request(
+            reqwest::Method::DELETE,
+            resource,
+            &[("authorization", "Bearer first")],
+        );
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_server_validate_token() {
+        gst::init().unwrap();
+
+        let (signaller, host) = start_server(&["static"], &[]);
+        signaller.connect("validate-token", false, |args| {
+            let token = args[1].get::<Option<&str>>().unwrap();
+            Some((token == Some("dynamic")).to_value())
+        });
+        let resource = host.join("/whip/resource/unknown").unwrap();
+
+        // The application handler takes precedence over auth-tokens
+        let resp = request(
+            reqwest::Method::DELETE,
+            resource.clone(),
+            &[("authorization", "Bearer static")],
+        );
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let resp = request(
+            reqwest::Method::DELETE,
+            resource,
+            &[("authorization", "Bearer dynamic")],
+        );
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_server_defaults() {
+        gst::init().unwrap();
+
+        // Any publisher is accepted, from any origin
+        let (signaller, host) = start_server(&[], &[]);
+
+        let resp = request(
+            reqwest::Method::OPTIONS,
+            host.join("/whip/endpoint").unwrap(),
+            &[
+                ("origin", "https://publisher.example.com"),
+                ("access-control-request-method", "POST"),
+            ],
+        );
+        assert!(resp.status().is_success());
+
+        let resp = request(
+            reqwest::Method::DELETE,
+            host.join("/whip/resource/unknown").unwrap(),
+            &[],
+        );
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_server_pending_publishers() {
+        gst::init().unwrap();
+
+        let (signaller, host) = start_server(&[], &[]);
+        signaller.set_property("timeout", 2u32);
+        let endpoint = host.join("/whip/endpoint").unwrap();
+
+        // No webrtcsrc answers the offers, waiting for them doesn't keep
+        // other requests from being served
+        let post = || {
+            reqwest::Client::new()
+                .post(endpoint.clone())
+                .header("content-type", CONTENT_SDP)
+                .body(OFFER)
+                .send()
+        };
+        let (first, second, options) = RUNTIME.block_on(async {
+            futures::join!(post(), post(), async {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                let resp = reqwest::Client::new()
+                    .request(reqwest::Method::OPTIONS, endpoint.clone())
+                    .send()
+                    .await;
+                (resp, signaller.imp().sessions.lock().unwrap().len())
+            })
+        });
+
+        let (options, pending) = options;
+        assert!(options.unwrap().status().is_success());
+        assert_eq!(pending, 2);
+        assert_eq!(first.unwrap().status(), StatusCode::REQUEST_TIMEOUT);
+        assert_eq!(second.unwrap().status(), StatusCode::REQUEST_TIMEOUT);
+        assert!(signaller.imp().sessions.lock().unwrap().is_empty());
+
+        signaller.stop();
+    }
+
+    #[test]
+    fn test_server_cors() {
+        gst::init().unwrap();
+
+        let (signaller, host) = start_server(&[], &["https://publisher.example.com"]);
+        let endpoint = host.join("/whip/endpoint").unwrap();
+
+        let resp = request(
+            reqwest::Method::OPTIONS,
+            endpoint.clone(),
+            &[
+                ("origin", "https://publisher.example.com"),
+                ("access-control-request-method", "POST"),
+            ],
+        );
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers().get("access-control-allow-origin").unwrap(),
+            "https://publisher.example.com"
+        );
+
+        let resp = request(
+            reqwest::Method::OPTIONS,
+            endpoint,
+            &[
+                ("origin", "https://elsewhere.example.com"),
+                ("access-control-request-method", "POST"),
+            ],
+        );
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        signaller.stop();
+    }
+
+    const ETAG: &str = "\"1\"";
+
+    fn description(
+        webrtcbin: &gst::Element,
+        signal: &str,
+        options: Option<gst::Structure>,
+    ) -> WebRTCSessionDescription {
+        let reply = RUNTIME
+            .block_on(promise_reply(webrtcbin, signal, &options))
+            .unwrap();
+        let field = if signal == "create-offer" {
+            "offer"
+        } else {
+            "answer"
+        };
+
+        reply.get::<WebRTCSessionDescription>(field).unwrap()
+    }
+
+    fn set_description(webrtcbin: &gst::Element, signal: &str, desc: &WebRTCSessionDescription) {
+        let reply = RUNTIME.block_on(promise_reply(webrtcbin, signal, desc));
+        assert!(reply_error(reply.as_ref()).is_none());
+    }
+
+    // Negotiates a session between a publisher webrtcbin and the one of the
+    // server, as webrtcsrc does, @session_id being ready for PATCH requests
+    fn negotiated_session(
+        signaller: &WhipServerSignaller,
+        session_id: &str,
+    ) -> (gst::Element, gst::Element) {
+        let publisher = gst::ElementFactory::make("webrtcbin").build().unwrap();
+        publisher.emit_by_name::<glib::Object>(
+            "add-transceiver",
+            &[
+                &gst_webrtc::WebRTCRTPTransceiverDirection::Sendonly,
+                &gst::Caps::builder("application/x-rtp")
+                    .field("media", "video")
+                    .field("encoding-name", "VP8")
+                    .field("payload", 96i32)
+                    .field("clock-rate", 90000i32)
+                    .build(),
+            ],
+        );
+        let server = gst::ElementFactory::make("webrtcbin").build().unwrap();
+        for webrtcbin in [&publisher, &server] {
+            webrtcbin.set_state(gst::State::Ready).unwrap();
+        }
+
+        let offer = description(&publisher, "create-offer", None);
+        set_description(&publisher, "set-local-description", &offer);
+        set_description(&server, "set-remote-description", &offer);
+        let answer = description(&server, "create-answer", None);
+        set_description(&server, "set-local-description", &answer);
+        set_description(&publisher, "set-remote-description", &answer);
+
+        signaller.imp().sessions.lock().unwrap().insert(
+            session_id.to_string(),
+            WhipServerSession {
+                state: WhipServerState::Ready,
+                webrtcbin: Some(server.downgrade()),
+                sdp_answer: None,
+                etag: Some(ETAG.to_string()),
+                answered: true,
+                pending_candidates: Vec::new(),
+            },
+        );
+
+        (publisher, server)
+    }
+
+    fn patch(
+        signaller: &WhipServerSignaller,
+        session_id: &str,
+        if_match: Option<&str>,
+        body: String,
+    ) -> http::Response<Body> {
+        RUNTIME
+            .block_on(signaller.imp().patch_handler(
+                session_id.to_string(),
+                None,
+                if_match.map(String::from),
+                body.into(),
+            ))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_server_trickle_patch() {
+        gst::init().unwrap();
+
+        let signaller = glib::Object::builder::<WhipServerSignaller>().build();
+        let (publisher, server) = negotiated_session(&signaller, "session1");
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let tx = Mutex::new(tx);
+        signaller.connect("handle-ice", false, move |args| {
+            let _ = tx.lock().unwrap().send((
+                args[1].get::<String>().unwrap(),
+                args[2].get::<u32>().unwrap(),
+                args[4].get::<String>().unwrap(),
+            ));
+            None
+        });
+
+        let offer = publisher
+            .property::<Option<WebRTCSessionDescription>>("local-description")
+            .unwrap();
+        let mut fragment = all_media_fragment(&offer.sdp()).unwrap();
+        fragment.media[0]
+            .candidates
+            .push("1 1 UDP 2122252543 127.0.0.1 5000 typ host".to_string());
+
+        let resp = patch(&signaller, "session1", Some(ETAG), fragment.to_sdpfrag());
+        assert_eq!(resp.status(), http::StatusCode::NO_CONTENT);
+        assert_eq!(resp.headers().get(http::header::ETAG).unwrap(), ETAG);
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(10)).unwrap(),
+            (
+                "session1".to_string(),
+                0,
+                "candidate:1 1 UDP 2122252543 127.0.0.1 5000 typ host".to_string()
+            )
+        );
+
+        let resp = patch(&signaller, "unknown", Some(ETAG), fragment.to_sdpfrag());
+        assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+
+        let resp = patch(&signaller, "session1", None, "not a fragment".to_string());
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+        for webrtcbin in [publisher, server] {
+            webrtcbin.set_state(gst::State::Null).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_server_etag_mismatch() {
+        gst::init().unwrap();
+
+        let signaller = glib::Object::builder::<WhipServerSignaller>().build();
+        let (publisher, server) = negotiated_session(&signaller, "session1");
+
+        let offer = publisher
+            .property::<Option<WebRTCSessionDescription>>("local-description")
+            .unwrap();
+        let fragment = all_media_fragment(&offer.sdp()).unwrap();
+
+        let resp = patch(
+            &signaller,
+            "session1",
+            Some("\"outdated\""),
+            fragment.to_sdpfrag(),
+        );
+        assert_eq!(resp.status(), http::StatusCode::PRECONDITION_FAILED);
+
+        for webrtcbin in [publisher, server] {
+            webrtcbin.set_state(gst::State::Null).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_server_ice_restart_patch() {
+        gst::init().unwrap();
+
+        let signaller = glib::Object::builder::<WhipServerSignaller>().build();
+        let (publisher, server) = negotiated_session(&signaller, "session1");
+        let answer = server
+            .property::<Option<WebRTCSessionDescription>>("local-description")
+            .unwrap();
+
+        let options = gst::Structure::builder("options")
+            .field("ice-restart", true)
+            .build();
+        let offer = description(&publisher, "create-offer", Some(options));
+        let fragment = all_media_fragment(&offer.sdp()).unwrap();
+
+        let resp = patch(&signaller, "session1", Some("*"), fragment.to_sdpfrag());
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            CONTENT_TRICKLE_ICE
+        );
+        // A new entity is created along with the new ICE credentials
+        let etag = resp.headers().get(http::header::ETAG).unwrap().clone();
+        assert_ne!(etag, ETAG);
+        assert_eq!(
+            signaller.imp().sessions.lock().unwrap()["session1"]
+                .etag
+                .as_deref(),
+            etag.to_str().ok()
+        );
+
+        let body = RUNTIME
+            .block_on(warp::hyper::body::to_bytes(resp.into_body()))
+            .unwrap();
+        let restart_answer = IceFragment::parse(std::str::from_utf8(&body).unwrap()).unwrap();
+        assert!(restart_answer.ice_ufrag.is_some());
+        assert_ne!(
+            restart_answer.ice_ufrag,
+            all_media_fragment(&answer.sdp()).unwrap().ice_ufrag
+        );
+
+        for webrtcbin in [publisher, server] {
+            webrtcbin.set_state(gst::State::Null).unwrap();
+        }
+    }
+}